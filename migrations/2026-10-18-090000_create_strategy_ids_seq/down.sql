@@ -0,0 +1,8 @@
+ALTER TABLE volumestrategyinstances ALTER COLUMN id SET DEFAULT nextval('volumestrategyinstances_id_seq');
+ALTER TABLE snipingstrategyinstances ALTER COLUMN id SET DEFAULT nextval('snipingstrategyinstances_id_seq');
+
+-- the own sequences continue after the ids drawn from the shared one
+SELECT setval('volumestrategyinstances_id_seq', (SELECT COALESCE(MAX(id), 0) + 1 FROM volumestrategyinstances), false);
+SELECT setval('snipingstrategyinstances_id_seq', (SELECT COALESCE(MAX(id), 0) + 1 FROM snipingstrategyinstances), false);
+
+DROP SEQUENCE strategy_ids_seq;
@@ -0,0 +1,11 @@
+-- The manager keys the running strategies of every kind by id, the stored ones draw it from one sequence not to
+-- collide, the others get negative ids
+CREATE SEQUENCE strategy_ids_seq;
+SELECT setval('strategy_ids_seq', GREATEST(
+    (SELECT COALESCE(MAX(id), 0) FROM volumestrategyinstances),
+    (SELECT COALESCE(MAX(id), 0) FROM snipingstrategyinstances),
+    1
+));
+
+ALTER TABLE volumestrategyinstances ALTER COLUMN id SET DEFAULT nextval('strategy_ids_seq');
+ALTER TABLE snipingstrategyinstances ALTER COLUMN id SET DEFAULT nextval('strategy_ids_seq');
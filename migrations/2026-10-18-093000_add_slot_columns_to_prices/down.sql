@@ -0,0 +1,3 @@
+ALTER TABLE prices
+    DROP COLUMN slot,
+    DROP COLUMN tx_index;
@@ -0,0 +1,3 @@
+ALTER TABLE prices
+    ADD COLUMN slot  BIGINT,
+    ADD COLUMN tx_index  BIGINT;
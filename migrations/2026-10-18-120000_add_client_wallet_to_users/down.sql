@@ -0,0 +1,15 @@
+-- The users registered through the API have no telegram account, reverting would delete them with their strategies,
+-- deposit wallets and subscriptions: it's refused while there are any
+DO $$
+BEGIN
+    IF EXISTS (SELECT 1 FROM users WHERE chat_id IS NULL OR tg_name IS NULL) THEN
+        RAISE EXCEPTION 'users has API users without a telegram account, they would be deleted';
+    END IF;
+END
+$$;
+
+ALTER TABLE users
+    ALTER COLUMN chat_id SET NOT NULL,
+    ALTER COLUMN tg_name SET NOT NULL;
+ALTER TABLE users
+    DROP COLUMN client_wallet;
@@ -0,0 +1,6 @@
+-- Users registered through the HTTP API log in with their own wallet and have no telegram account
+ALTER TABLE users
+    ADD COLUMN client_wallet TEXT UNIQUE;
+ALTER TABLE users
+    ALTER COLUMN chat_id DROP NOT NULL,
+    ALTER COLUMN tg_name DROP NOT NULL;
@@ -0,0 +1,10 @@
+DROP INDEX IF EXISTS bot_events_wallets_idx;
+DROP INDEX IF EXISTS bot_events_pool_idx;
+ALTER TABLE bot_events
+    DROP CONSTRAINT bot_events_pkey;
+ALTER TABLE bot_events
+    DROP COLUMN id,
+    DROP COLUMN pool,
+    DROP COLUMN wallets;
+ALTER TABLE bot_events
+    ADD PRIMARY KEY (timestamp);
@@ -0,0 +1,11 @@
+-- Events are looked up by the pool and the wallets they concern, and several can share a timestamp
+ALTER TABLE bot_events
+    ADD COLUMN id      BIGSERIAL,
+    ADD COLUMN pool    TEXT,
+    ADD COLUMN wallets TEXT[] NOT NULL DEFAULT '{}';
+ALTER TABLE bot_events
+    DROP CONSTRAINT bot_events_pkey;
+ALTER TABLE bot_events
+    ADD PRIMARY KEY (id, timestamp);
+CREATE INDEX bot_events_pool_idx ON bot_events (pool, timestamp DESC);
+CREATE INDEX bot_events_wallets_idx ON bot_events USING GIN (wallets);
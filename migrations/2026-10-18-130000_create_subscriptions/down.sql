@@ -0,0 +1,2 @@
+DROP TABLE subscription_payments;
+DROP TABLE subscriptions;
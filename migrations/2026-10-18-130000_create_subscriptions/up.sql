@@ -0,0 +1,28 @@
+-- Plan of a user for a token, paid every period from the deposit wallet
+CREATE TABLE subscriptions
+(
+    id            SERIAL PRIMARY KEY,
+    user_id       INT4        NOT NULL REFERENCES users (id),
+    token_mint    TEXT        NOT NULL,
+    plan          INT4        NOT NULL DEFAULT 0,
+    status        TEXT        NOT NULL DEFAULT 'active',
+    subscribed_at TIMESTAMPTZ NOT NULL,
+    paid_until    TIMESTAMPTZ,
+    grace_until   TIMESTAMPTZ,
+    UNIQUE (user_id, token_mint)
+);
+
+CREATE TABLE subscription_payments
+(
+    id              SERIAL PRIMARY KEY,
+    subscription_id INT4        NOT NULL REFERENCES subscriptions (id),
+    action_uuid     TEXT        NOT NULL UNIQUE,
+    plan            INT4        NOT NULL,
+    amount_sol      FLOAT8      NOT NULL,
+    status          TEXT        NOT NULL DEFAULT 'pending',
+    tx_hash         TEXT,
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT now(),
+    paid_at         TIMESTAMPTZ
+);
+
+CREATE INDEX subscription_payments_subscription_idx ON subscription_payments (subscription_id, created_at DESC);
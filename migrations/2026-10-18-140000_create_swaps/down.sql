@@ -0,0 +1,2 @@
+DROP MATERIALIZED VIEW swaps_daily;
+DROP TABLE swaps;
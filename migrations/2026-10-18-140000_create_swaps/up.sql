@@ -0,0 +1,42 @@
+-- Swaps of the target pools, the swaps of the strategies are matched by their signatures
+CREATE TABLE swaps (
+    id BIGSERIAL,
+    created_at TIMESTAMPTZ NOT NULL,
+    pool VARCHAR NOT NULL,
+    signature VARCHAR NOT NULL,
+    is_buy BOOLEAN NOT NULL,
+    base_amount DOUBLE PRECISION NOT NULL,
+    quote_amount DOUBLE PRECISION NOT NULL,
+    price DOUBLE PRECISION NOT NULL,
+    -- reserves after the swap
+    base_reserve DOUBLE PRECISION NOT NULL,
+    quote_reserve DOUBLE PRECISION NOT NULL,
+    slot BIGINT NOT NULL,
+    tx_index BIGINT,
+    PRIMARY KEY (id, created_at)
+);
+
+SELECT create_hypertable('swaps', 'created_at');
+
+CREATE INDEX swaps_signature_idx ON swaps (signature, created_at DESC);
+CREATE INDEX swaps_pool_idx ON swaps (pool, created_at DESC);
+
+-- Daily candles of the pools, the last day is aggregated on the fly
+CREATE MATERIALIZED VIEW swaps_daily
+WITH (timescaledb.continuous, timescaledb.materialized_only = false) AS
+SELECT
+    time_bucket(INTERVAL '1 day', created_at) AS day,
+    pool,
+    count(*) AS trade_count,
+    sum(quote_amount) AS volume,
+    first(price, created_at) AS open_price,
+    last(price, created_at) AS close_price,
+    avg(quote_reserve) AS liquidity
+FROM swaps
+GROUP BY day, pool
+WITH NO DATA;
+
+SELECT add_continuous_aggregate_policy('swaps_daily',
+    start_offset => INTERVAL '3 days',
+    end_offset => INTERVAL '1 hour',
+    schedule_interval => INTERVAL '1 hour');
@@ -0,0 +1,5 @@
+DROP INDEX depositswithdrawals_action_uuid_idx;
+DROP INDEX depositswithdrawals_signature_idx;
+ALTER TABLE depositswithdrawals
+    DROP COLUMN action_uuid;
+DROP TABLE deposit_wallets;
@@ -0,0 +1,15 @@
+-- A wallet per user receiving the deposits, swept to the trading wallet of the user once confirmed
+CREATE TABLE deposit_wallets
+(
+    id          SERIAL PRIMARY KEY,
+    user_id     INT4        NOT NULL UNIQUE REFERENCES users (id),
+    wallet      TEXT        NOT NULL UNIQUE,
+    private_key TEXT        NOT NULL,
+    created_at  TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+-- the action sweeping the deposit, its transaction is `signature_fee`
+ALTER TABLE depositswithdrawals
+    ADD COLUMN action_uuid TEXT;
+CREATE UNIQUE INDEX depositswithdrawals_signature_idx ON depositswithdrawals (signature);
+CREATE INDEX depositswithdrawals_action_uuid_idx ON depositswithdrawals (action_uuid);
@@ -0,0 +1,4 @@
+DELETE FROM depositswithdrawals WHERE signature IS NULL;
+ALTER TABLE depositswithdrawals
+    DROP COLUMN destination,
+    ALTER COLUMN signature SET NOT NULL;
@@ -0,0 +1,4 @@
+-- A requested withdrawal has no transaction until it's sent
+ALTER TABLE depositswithdrawals
+    ALTER COLUMN signature DROP NOT NULL,
+    ADD COLUMN destination TEXT;
@@ -0,0 +1,16 @@
+-- The keys of the derived wallets are only in their seeds, they can't be dropped while there are any:
+-- recover the funds of these wallets first (`solana-bot recover-funds`) and delete the seeds
+DO $$
+BEGIN
+    IF EXISTS (SELECT 1 FROM agent_seeds) THEN
+        RAISE EXCEPTION 'agent_seeds is not empty, the derived agent wallets would lose their keys';
+    END IF;
+END
+$$;
+
+DELETE FROM traders WHERE private_key IS NULL;
+ALTER TABLE traders
+    DROP COLUMN derivation_index,
+    ALTER COLUMN private_key SET NOT NULL;
+
+DROP TABLE agent_seeds;
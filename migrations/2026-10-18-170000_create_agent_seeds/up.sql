@@ -0,0 +1,11 @@
+-- Agent wallets of a volume strategy are derived from its seed, their keys aren't stored
+CREATE TABLE agent_seeds (
+    strategy_instance_id INT4 PRIMARY KEY REFERENCES volumestrategyinstances (id),
+    seed TEXT NOT NULL,
+    next_index INT4 NOT NULL DEFAULT 0,
+    created TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+ALTER TABLE traders
+    ALTER COLUMN private_key DROP NOT NULL,
+    ADD COLUMN derivation_index INT4;
@@ -0,0 +1,4 @@
+ALTER TABLE volumestrategyinstances
+    DROP COLUMN daily_volume_sol,
+    DROP COLUMN budget_days,
+    DROP COLUMN volume_traded_sol;
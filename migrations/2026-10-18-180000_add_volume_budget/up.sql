@@ -0,0 +1,5 @@
+-- Budget mode: the tranches are sized and spaced to trade `daily_volume_sol` a day for `budget_days` days
+ALTER TABLE volumestrategyinstances
+    ADD COLUMN daily_volume_sol FLOAT8,
+    ADD COLUMN budget_days INT4,
+    ADD COLUMN volume_traded_sol FLOAT8 NOT NULL DEFAULT 0;
@@ -0,0 +1,4 @@
+ALTER TABLE volumestrategyinstances
+    DROP COLUMN max_inventory_sol,
+    DROP COLUMN max_price_drift_pct,
+    DROP COLUMN reference_price;
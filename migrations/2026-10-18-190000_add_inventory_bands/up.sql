@@ -0,0 +1,5 @@
+-- The sells of a tranche keep the token inventory and the price drift from `reference_price` within the bands
+ALTER TABLE volumestrategyinstances
+    ADD COLUMN max_inventory_sol FLOAT8,
+    ADD COLUMN max_price_drift_pct FLOAT8,
+    ADD COLUMN reference_price FLOAT8;
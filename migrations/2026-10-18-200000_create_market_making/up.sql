@@ -0,0 +1,16 @@
+-- Market making around a fair price from the tick indicators, buys below it and sells above it
+CREATE TABLE marketmakingstrategyinstances (
+    -- drawn from the sequence shared by the strategy kinds
+    id INT4 PRIMARY KEY DEFAULT nextval('strategy_ids_seq'),
+    user_id INT4 NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    target_pool TEXT NOT NULL,
+    started_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    completed_at TIMESTAMPTZ,
+    indicator_period INT4 NOT NULL,
+    spread_pct FLOAT8 NOT NULL,
+    ladder_levels INT4 NOT NULL,
+    order_size_sol FLOAT8 NOT NULL,
+    max_inventory_sol FLOAT8 NOT NULL,
+    bought_sol FLOAT8 NOT NULL DEFAULT 0,
+    sold_sol FLOAT8 NOT NULL DEFAULT 0
+);
@@ -0,0 +1,2 @@
+ALTER TABLE volumestrategyinstances
+    DROP COLUMN volume_counted_until;
@@ -0,0 +1,3 @@
+-- The swaps recorded before it are in `volume_traded_sol`, counting from `started_at` when not set
+ALTER TABLE volumestrategyinstances
+    ADD COLUMN volume_counted_until TIMESTAMPTZ;
@@ -15,6 +15,8 @@ use yata::prelude::{Candle, IndicatorConfig, Method};
 
 #[derive(Debug, Clone, Serialize)]
 pub struct EveryTickIndicatorsValue {
+    /// Length of the indicators, in ticks
+    pub length: PeriodType,
     pub t_tema: ValueType,
     pub t_ema: ValueType,
     pub t_rsi: IndicatorResult,
@@ -23,6 +25,7 @@ pub struct EveryTickIndicatorsValue {
 
 #[derive(Debug, Clone, Serialize)]
 pub struct EveryTickIndicators {
+    pub length: PeriodType,
     pub t_tema: TEMA,
     pub t_ema: EMA,
     pub t_rsi: RelativeStrengthIndexInstance,
@@ -39,6 +42,7 @@ impl EveryTickIndicators {
     pub fn new(length: &PeriodType, price: &ValueType, volume: &ValueType) -> Self {
         let first_candle = Candle::from(&(*price, *price, *price, *price, *volume));
         Self {
+            length: *length,
             t_tema: TEMA::new(*length, price).unwrap(),
             t_ema: EMA::new(*length, price).unwrap(),
             t_rsi: RelativeStrengthIndex {
@@ -61,6 +65,7 @@ impl EveryTickIndicators {
     pub fn next(&mut self, price: &ValueType, volume: &ValueType) -> EveryTickIndicatorsValue {
         let next_tick_candle = Candle::from(&(*price, *price, *price, *price, *volume));
         EveryTickIndicatorsValue {
+            length: self.length,
             t_tema: self.t_tema.next(price),
             t_ema: self.t_ema.next(price),
             t_rsi: self.t_rsi.next(&next_tick_candle),
@@ -0,0 +1,5 @@
+pub mod queries;
+mod stats;
+
+pub use queries::get_strategy_stats;
+pub use stats::*;
@@ -0,0 +1,166 @@
+use crate::analytics::stats::{compute_stats, DailyPoolStats, StrategyStats};
+use crate::config::constants::{ACTION_EXPIRY_S, STATS_MAX_ACTIONS};
+use crate::storage::persistent::DbPool;
+use crate::types::actions::SolanaActionRecord;
+use crate::types::bot_user::Trader;
+use crate::types::engine::StrategyId;
+use crate::types::pool::SwapRecord;
+use crate::types::volume_strategy::VolumeStrategyInstance;
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Duration, Utc};
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashMap;
+
+// the continuous aggregate over `swaps`, views aren't in the generated schema
+diesel::table! {
+    swaps_daily (pool, day) {
+        day -> Timestamptz,
+        pool -> Varchar,
+        trade_count -> Int8,
+        volume -> Nullable<Float8>,
+        open_price -> Nullable<Float8>,
+        close_price -> Nullable<Float8>,
+        liquidity -> Nullable<Float8>,
+    }
+}
+
+/// The agent wallets the strategy trades from.
+pub async fn get_strategy_wallets(db_pool: &DbPool, strategy_id: StrategyId) -> Result<Vec<Pubkey>> {
+    use crate::schema::traders;
+    let mut conn = db_pool.get().await?;
+    Ok(traders::table
+        .filter(traders::strategy_instance_id.eq(strategy_id))
+        .select(traders::all_columns)
+        .load::<Trader>(&mut conn)
+        .await?
+        .into_iter()
+        .map(|trader| trader.wallet)
+        .collect())
+}
+
+/// Latest actions signed or paid by any of the wallets, created in the range if given.
+pub async fn get_actions(
+    db_pool: &DbPool,
+    wallets: &[String],
+    action_uuid: Option<&str>,
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+    limit: i64,
+) -> Result<Vec<SolanaActionRecord>> {
+    use crate::schema::solana_actions;
+    let mut conn = db_pool.get().await?;
+    let mut query = solana_actions::table
+        .filter(
+            solana_actions::sniper
+                .eq_any(wallets)
+                .or(solana_actions::fee_payer.eq_any(wallets)),
+        )
+        .into_boxed();
+    if let Some(action_uuid) = action_uuid {
+        query = query.filter(solana_actions::uuid.eq(action_uuid.to_string()));
+    }
+    if let Some(from) = from {
+        query = query.filter(solana_actions::created_at.ge(from));
+    }
+    if let Some(to) = to {
+        query = query.filter(solana_actions::created_at.lt(to));
+    }
+    Ok(query
+        .order(solana_actions::created_at.desc())
+        .limit(limit)
+        .select(SolanaActionRecord::as_select())
+        .load(&mut conn)
+        .await?)
+}
+
+/// Recorded swaps of the transactions, by signature.
+pub async fn get_swaps(db_pool: &DbPool, signatures: &[String]) -> Result<HashMap<String, SwapRecord>> {
+    use crate::schema::swaps;
+    let mut conn = db_pool.get().await?;
+    Ok(swaps::table
+        .filter(swaps::signature.eq_any(signatures))
+        .select(SwapRecord::as_select())
+        .load(&mut conn)
+        .await?
+        .into_iter()
+        .map(|swap| (swap.signature.clone(), swap))
+        .collect())
+}
+
+/// SOL bought and sold by the agents of the strategy in the swaps recorded in the range. The swaps are matched with
+/// the actions that can still land, so a swap recorded after the range of its action is counted with the next range.
+pub async fn get_volume_traded(db_pool: &DbPool, strategy_id: StrategyId, from: DateTime<Utc>, to: DateTime<Utc>) -> Result<f64> {
+    let wallets: Vec<String> = get_strategy_wallets(db_pool, strategy_id)
+        .await?
+        .iter()
+        .map(|wallet| wallet.to_string())
+        .collect();
+    let actions_from = from - Duration::seconds(ACTION_EXPIRY_S as i64);
+    let actions = get_actions(db_pool, &wallets, None, Some(actions_from), Some(to), STATS_MAX_ACTIONS).await?;
+    let signatures: Vec<String> = actions.iter().filter_map(|action| action.tx_hash.clone()).collect();
+    Ok(get_swaps(db_pool, &signatures)
+        .await?
+        .values()
+        .filter(|swap| swap.created_at >= from && swap.created_at < to)
+        .map(|swap| swap.quote_amount)
+        .sum())
+}
+
+pub async fn get_daily_pool_stats(
+    db_pool: &DbPool,
+    pool: &Pubkey,
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+) -> Result<Vec<DailyPoolStats>> {
+    let mut conn = db_pool.get().await?;
+    let mut query = swaps_daily::table
+        .filter(swaps_daily::pool.eq(pool.to_string()))
+        .into_boxed();
+    if let Some(from) = from {
+        query = query.filter(swaps_daily::day.ge(from));
+    }
+    if let Some(to) = to {
+        query = query.filter(swaps_daily::day.lt(to));
+    }
+    Ok(query
+        .order(swaps_daily::day.asc())
+        .select(DailyPoolStats::as_select())
+        .load(&mut conn)
+        .await?)
+}
+
+/// Effectiveness of the strategy over the actions created in the range, the whole history by default.
+pub async fn get_strategy_stats(
+    db_pool: &DbPool,
+    strategy_id: StrategyId,
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+) -> Result<StrategyStats> {
+    let strategy = {
+        use crate::schema::volumestrategyinstances::dsl::*;
+        let mut conn = db_pool.get().await?;
+        volumestrategyinstances
+            .filter(id.eq(strategy_id))
+            .select(VolumeStrategyInstance::as_select())
+            .first(&mut conn)
+            .await
+            .optional()?
+            .ok_or_else(|| anyhow!("Strategy {} not found", strategy_id))?
+    };
+    let wallets: Vec<String> = get_strategy_wallets(db_pool, strategy.id)
+        .await?
+        .iter()
+        .map(|wallet| wallet.to_string())
+        .collect();
+    let actions = get_actions(db_pool, &wallets, None, from, to, STATS_MAX_ACTIONS).await?;
+    let signatures: Vec<String> = actions.iter().filter_map(|action| action.tx_hash.clone()).collect();
+    let swaps = get_swaps(db_pool, &signatures).await?;
+    // without a start the days start with the first action
+    let from = from
+        .or(actions.iter().map(|action| action.created_at).min())
+        .map(|from| from.date_naive().and_hms_opt(0, 0, 0).unwrap().and_utc());
+    let days = get_daily_pool_stats(db_pool, &strategy.target_pool, from, to).await?;
+    Ok(compute_stats(&actions, &swaps, &days))
+}
@@ -0,0 +1,353 @@
+use crate::config::constants::RAYDIUM_SWAP_FEE;
+use crate::types::actions::{Amount, SolanaActionPayload, SolanaActionRecord, SwapMethod};
+use crate::types::pool::SwapRecord;
+use crate::utils::serdealizers::JsonbWrapper;
+use chrono::{DateTime, NaiveDate, Utc};
+use diesel::pg::Pg;
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use solana_sdk::native_token::lamports_to_sol;
+use std::collections::{BTreeMap, HashMap};
+
+/// How the `ActionExecutionStatus::Success` of an action is stored.
+pub const SUCCESS_STATUS: &str = "Success";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VolumeLevel {
+    pub date: String,
+    pub volume: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PnlLevel {
+    pub date: String,
+    pub pnl: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Level {
+    pub date: String,
+    pub level: f64,
+}
+
+/// Effectiveness of a strategy as in api.md, amounts are in SOL and rates in percent. The metrics
+/// that need the swaps of the strategy are `null` until one of them is recorded.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StrategyStats {
+    pub trade_count: i64,
+    pub average_trade_size: f64,
+    pub volume_generated: f64,
+    /// Share of the sells that made a profit over the average cost of the tokens.
+    pub win_rate: Option<f64>,
+    /// Average SOL in the pool.
+    pub liquidity: Option<f64>,
+    /// Average distance of the execution prices from the pool price before the swaps.
+    pub spread: Option<f64>,
+    /// Average move of the pool price caused by the swaps.
+    pub market_impact: Option<f64>,
+    /// Share of the sent swaps that succeeded.
+    pub execution_efficiency: Option<f64>,
+    /// SOL gained plus the tokens gained at the last price, after the costs.
+    pub pnl: Option<f64>,
+    /// Profits of the winning sells over the losses of the losing ones.
+    pub profit_factor: Option<f64>,
+    /// Transaction fees and the pool fees.
+    pub cost_of_trades: f64,
+    pub volume_levels: Vec<VolumeLevel>,
+    pub pnl_levels: Vec<PnlLevel>,
+    /// Tokens and SOL gained since the start of the range at the end of every day.
+    pub inventory_levels: Vec<Level>,
+    pub sol_levels: Vec<Level>,
+    /// Standard deviation of the daily closing prices relative to their mean.
+    pub price_deviation: Option<f64>,
+}
+
+/// A day of the `swaps_daily` continuous aggregate.
+#[derive(Debug, Clone, Queryable, Selectable)]
+#[diesel(table_name = crate::analytics::queries::swaps_daily)]
+#[diesel(check_for_backend(Pg))]
+pub struct DailyPoolStats {
+    pub day: DateTime<Utc>,
+    pub pool: String,
+    pub trade_count: i64,
+    pub volume: Option<f64>,
+    pub open_price: Option<f64>,
+    pub close_price: Option<f64>,
+    pub liquidity: Option<f64>,
+}
+
+/// A swap of the strategy matched with the recorded swap of its transaction.
+#[derive(Debug, Clone)]
+pub struct Trade {
+    pub date: NaiveDate,
+    pub is_buy: bool,
+    pub tokens: f64,
+    pub sol: f64,
+    pub fee_sol: f64,
+    pub price_before: Option<f64>,
+    pub price_after: Option<f64>,
+}
+
+impl Trade {
+    fn from_swap(action: &SolanaActionRecord, swap: &SwapRecord) -> Self {
+        Self {
+            date: action.created_at.date_naive(),
+            is_buy: swap.is_buy,
+            tokens: swap.base_amount,
+            sol: swap.quote_amount,
+            fee_sol: lamports_to_sol(action.fee.unwrap_or(0).max(0) as u64) + swap.quote_amount * RAYDIUM_SWAP_FEE,
+            price_before: swap.price_before(),
+            price_after: swap.price_after(),
+        }
+    }
+
+    fn execution_price(&self) -> Option<f64> {
+        (self.tokens > 0.0).then(|| self.sol / self.tokens)
+    }
+}
+
+// SOL traded by a swap without a recorded swap: the exact amount of a buy, for a sell the SOL
+// balance change once recorded
+fn swap_volume_lamports(action: &SolanaActionRecord, swap_method: SwapMethod, amount_in: &Amount) -> u64 {
+    match (swap_method, amount_in) {
+        (SwapMethod::BuyTokensForExactSol, Amount::Exact(lamports) | Amount::ExactWithFees(lamports)) => *lamports,
+        _ => {
+            let sol = |balance: &Option<JsonbWrapper<serde_json::Value>>| {
+                balance.as_ref().and_then(|balance| balance.0["sol"].as_u64())
+            };
+            match (sol(&action.balance_before), sol(&action.balance_after)) {
+                (Some(before), Some(after)) => after.abs_diff(before) + action.fee.unwrap_or(0) as u64,
+                _ => 0,
+            }
+        }
+    }
+}
+
+fn average(values: &[f64]) -> Option<f64> {
+    (!values.is_empty()).then(|| values.iter().sum::<f64>() / values.len() as f64)
+}
+
+fn relative_change(from: f64, to: f64) -> f64 {
+    100.0 * (to - from).abs() / from
+}
+
+/// Aggregates the swaps of the strategy wallets, the transfers between them are not trades.
+/// `swaps` are the recorded swaps by signature, `days` the daily stats of the target pool.
+pub fn compute_stats(
+    actions: &[SolanaActionRecord],
+    swaps: &HashMap<String, SwapRecord>,
+    days: &[DailyPoolStats],
+) -> StrategyStats {
+    let mut actions: Vec<_> = actions.iter().collect();
+    actions.sort_by_key(|action| action.created_at);
+    let mut trades = vec![];
+    let mut sent_swaps = 0;
+    let mut successful_swaps = 0;
+    // the swaps that weren't recorded only add to the volume and the costs
+    let mut unmatched_trades = 0;
+    let mut unmatched_volume_lamports = 0u64;
+    let mut unmatched_cost_lamports = 0i64;
+    let mut daily_unmatched_volume: BTreeMap<NaiveDate, u64> = BTreeMap::new();
+    for action in actions {
+        let Ok(payloads) = serde_json::from_value::<Vec<SolanaActionPayload>>(action.action_payload.0.clone()) else {
+            continue;
+        };
+        let swap_payloads: Vec<_> = payloads
+            .iter()
+            .filter_map(|payload| match payload {
+                SolanaActionPayload::SolanaSwapActionPayload(swap) => Some(swap),
+                _ => None,
+            })
+            .collect();
+        if swap_payloads.is_empty() || action.sent_at.is_none() {
+            continue;
+        }
+        sent_swaps += 1;
+        if action.status.as_deref() != Some(SUCCESS_STATUS) {
+            unmatched_cost_lamports += action.fee.unwrap_or(0);
+            continue;
+        }
+        successful_swaps += 1;
+        match action.tx_hash.as_ref().and_then(|tx_hash| swaps.get(tx_hash)) {
+            Some(swap) => trades.push(Trade::from_swap(action, swap)),
+            None => {
+                unmatched_cost_lamports += action.fee.unwrap_or(0);
+                for swap in swap_payloads {
+                    let volume = swap_volume_lamports(action, swap.swap_method, &swap.amount_in);
+                    unmatched_trades += 1;
+                    unmatched_volume_lamports += volume;
+                    *daily_unmatched_volume.entry(action.created_at.date_naive()).or_default() += volume;
+                }
+            }
+        }
+    }
+    let mut stats = compute_trade_stats(&trades, days);
+    stats.trade_count += unmatched_trades;
+    stats.volume_generated += lamports_to_sol(unmatched_volume_lamports);
+    stats.cost_of_trades += lamports_to_sol(unmatched_cost_lamports.max(0) as u64);
+    if stats.trade_count > 0 {
+        stats.average_trade_size = stats.volume_generated / stats.trade_count as f64;
+    }
+    if sent_swaps > 0 {
+        stats.execution_efficiency = Some(100.0 * successful_swaps as f64 / sent_swaps as f64);
+    }
+    if !daily_unmatched_volume.is_empty() {
+        let mut daily_volume: BTreeMap<String, f64> = stats
+            .volume_levels
+            .drain(..)
+            .map(|level| (level.date, level.volume))
+            .collect();
+        for (date, volume) in daily_unmatched_volume {
+            *daily_volume.entry(date.to_string()).or_default() += lamports_to_sol(volume);
+        }
+        stats.volume_levels = daily_volume
+            .into_iter()
+            .map(|(date, volume)| VolumeLevel { date, volume })
+            .collect();
+    }
+    stats
+}
+
+/// Metrics of the trades in chronological order, the sells are valued at the average cost of the
+/// tokens bought before them, or at the pool price for the tokens held before the range.
+pub fn compute_trade_stats(trades: &[Trade], days: &[DailyPoolStats]) -> StrategyStats {
+    let mut stats = StrategyStats::default();
+    let mut spreads = vec![];
+    let mut impacts = vec![];
+    let (mut held_tokens, mut held_cost) = (0.0, 0.0);
+    let (mut wins, mut sells) = (0, 0);
+    let (mut gross_profit, mut gross_loss) = (0.0, 0.0);
+    // per day: volume, SOL and tokens gained, last price
+    let mut daily: BTreeMap<NaiveDate, (f64, f64, f64, Option<f64>)> = BTreeMap::new();
+    for trade in trades {
+        stats.trade_count += 1;
+        stats.volume_generated += trade.sol;
+        stats.cost_of_trades += trade.fee_sol;
+        if let (Some(before), Some(execution)) = (trade.price_before, trade.execution_price()) {
+            spreads.push(relative_change(before, execution));
+        }
+        if let (Some(before), Some(after)) = (trade.price_before, trade.price_after) {
+            impacts.push(relative_change(before, after));
+        }
+        let day = daily.entry(trade.date).or_default();
+        day.0 += trade.sol;
+        day.3 = trade.price_after.or(day.3);
+        if trade.is_buy {
+            day.1 -= trade.sol + trade.fee_sol;
+            day.2 += trade.tokens;
+            held_tokens += trade.tokens;
+            held_cost += trade.sol + trade.fee_sol;
+            continue;
+        }
+        day.1 += trade.sol - trade.fee_sol;
+        day.2 -= trade.tokens;
+        let sold_held = trade.tokens.min(held_tokens);
+        let held_cost_sold = if held_tokens > 0.0 { held_cost * sold_held / held_tokens } else { 0.0 };
+        let outside_price = trade.price_before.or(trade.execution_price()).unwrap_or(0.0);
+        let cost = held_cost_sold + (trade.tokens - sold_held) * outside_price;
+        held_tokens -= sold_held;
+        held_cost -= held_cost_sold;
+        let profit = trade.sol - trade.fee_sol - cost;
+        sells += 1;
+        if profit > 0.0 {
+            wins += 1;
+            gross_profit += profit;
+        } else {
+            gross_loss -= profit;
+        }
+    }
+    for day in days {
+        if let Some(close_price) = day.close_price {
+            daily.entry(day.day.date_naive()).or_default().3 = Some(close_price);
+        }
+    }
+
+    if stats.trade_count > 0 {
+        stats.average_trade_size = stats.volume_generated / stats.trade_count as f64;
+    }
+    stats.spread = average(&spreads);
+    stats.market_impact = average(&impacts);
+    if sells > 0 {
+        stats.win_rate = Some(100.0 * wins as f64 / sells as f64);
+    }
+    if gross_loss > 0.0 {
+        stats.profit_factor = Some(gross_profit / gross_loss);
+    }
+    let liquidity: Vec<f64> = days.iter().filter_map(|day| day.liquidity).collect();
+    stats.liquidity = average(&liquidity);
+    let closes: Vec<f64> = days.iter().filter_map(|day| day.close_price).collect();
+    if closes.len() > 1 && let Some(mean) = average(&closes) {
+        let variance = closes.iter().map(|close| (close - mean).powi(2)).sum::<f64>() / closes.len() as f64;
+        stats.price_deviation = Some(100.0 * variance.sqrt() / mean);
+    }
+
+    let (mut sol, mut tokens, mut price) = (0.0, 0.0, None);
+    for (date, (volume, day_sol, day_tokens, day_price)) in daily {
+        sol += day_sol;
+        tokens += day_tokens;
+        price = day_price.or(price);
+        let date = date.to_string();
+        if volume > 0.0 {
+            stats.volume_levels.push(VolumeLevel { date: date.clone(), volume });
+        }
+        if let Some(price) = price && stats.trade_count > 0 {
+            stats.pnl_levels.push(PnlLevel { date: date.clone(), pnl: sol + tokens * price });
+        }
+        stats.inventory_levels.push(Level { date: date.clone(), level: tokens });
+        stats.sol_levels.push(Level { date, level: sol });
+    }
+    if stats.trade_count > 0 {
+        stats.pnl = price.map(|price| sol + tokens * price);
+    }
+    stats
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trade(date: NaiveDate, is_buy: bool, tokens: f64, sol: f64, price_before: f64, price_after: f64) -> Trade {
+        Trade {
+            date,
+            is_buy,
+            tokens,
+            sol,
+            fee_sol: 0.0,
+            price_before: Some(price_before),
+            price_after: Some(price_after),
+        }
+    }
+
+    #[test]
+    fn test_compute_trade_stats() {
+        let first_day = NaiveDate::from_ymd_opt(2024, 7, 1).unwrap();
+        let second_day = NaiveDate::from_ymd_opt(2024, 7, 2).unwrap();
+        let trades = vec![
+            // 100 tokens for 1 SOL, the price moves from 0.0099 to 0.0101
+            trade(first_day, true, 100.0, 1.0, 0.0099, 0.0101),
+            // half of them sold with a profit
+            trade(first_day, false, 50.0, 0.6, 0.0121, 0.0119),
+            // the rest and 50 more held before with a loss
+            trade(second_day, false, 100.0, 0.8, 0.0081, 0.0079),
+        ];
+        let stats = compute_trade_stats(&trades, &[]);
+        assert_eq!(stats.trade_count, 3);
+        assert!((stats.volume_generated - 2.4).abs() < 1e-9);
+        assert_eq!(stats.win_rate, Some(50.0));
+        // profit 0.6 - 0.5, loss 0.5 + 50 * 0.0081 - 0.8
+        assert!((stats.profit_factor.unwrap() - 0.1 / 0.105).abs() < 1e-9);
+        // 0.4 SOL gained for 50 tokens less, valued at the last price
+        assert!((stats.pnl.unwrap() - (0.4 - 50.0 * 0.0079)).abs() < 1e-9);
+        assert_eq!(stats.volume_levels.len(), 2);
+        assert_eq!(stats.inventory_levels.last().unwrap().level, -50.0);
+        assert!(stats.market_impact.unwrap() > 0.0);
+    }
+
+    #[test]
+    fn test_no_trades() {
+        let stats = compute_stats(&[], &HashMap::new(), &[]);
+        assert_eq!(stats.trade_count, 0);
+        assert!(stats.pnl.is_none());
+        assert!(stats.execution_efficiency.is_none());
+    }
+}
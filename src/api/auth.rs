@@ -0,0 +1,309 @@
+use crate::api::error::{ApiError, ApiResult};
+use crate::api::queries;
+use crate::api::types::SignedRequest;
+use crate::api::ApiState;
+use crate::config::constants::{API_AUTH_MESSAGE_MAX_AGE_S, API_SESSION_EXPIRES_S};
+use crate::storage::cache;
+use crate::types::bot_user::{BotUser, NewBotUser};
+use axum::async_trait;
+use axum::extract::{FromRequestParts, Query};
+use axum::http::header::AUTHORIZATION;
+use axum::http::request::Parts;
+use base64::Engine;
+use chrono::{DateTime, Duration, Utc};
+use serde::Deserialize;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use std::str::FromStr;
+
+const WALLET_FIELD: &str = "Wallet";
+const NONCE_FIELD: &str = "Nonce";
+const ISSUED_AT_FIELD: &str = "Issued At";
+const DESTINATION_FIELD: &str = "Destination";
+const AMOUNT_FIELD: &str = "Amount";
+// the amount of a withdrawal of the whole balance
+const ALL_AMOUNT: &str = "all";
+
+// the first line of the message, a message signed for a call isn't accepted by another
+pub const LOGIN_ACTION: &str = "Login";
+pub const REGISTER_ACTION: &str = "Register";
+pub const SUBSCRIBE_ACTION: &str = "Subscribe";
+pub const PAYMENTS_ACTION: &str = "Payments";
+pub const WITHDRAW_ACTION: &str = "Withdraw";
+pub const CONFIRM_WITHDRAWAL_ACTION: &str = "Confirm withdrawal";
+pub const START_ACTION: &str = "Start";
+pub const STOP_ACTION: &str = "Stop";
+pub const START_STRATEGY_ACTION: &str = "Start strategy";
+
+/// The message a wallet signs to authenticate a call, the first line says what it is for.
+pub fn auth_message(action: &str, wallet: &Pubkey, nonce: &str, issued_at: DateTime<Utc>) -> String {
+    format!(
+        "{action}\n{WALLET_FIELD}: {wallet}\n{NONCE_FIELD}: {nonce}\n{ISSUED_AT_FIELD}: {}",
+        issued_at.to_rfc3339()
+    )
+}
+
+/// A withdrawal signs its destination and its amount as well, they can't be changed in transit.
+pub fn withdrawal_message(
+    wallet: &Pubkey,
+    destination: &str,
+    amount_sol: Option<f64>,
+    nonce: &str,
+    issued_at: DateTime<Utc>,
+) -> String {
+    let amount = amount_sol.map_or(ALL_AMOUNT.to_string(), |amount_sol| amount_sol.to_string());
+    format!(
+        "{}\n{DESTINATION_FIELD}: {destination}\n{AMOUNT_FIELD}: {amount}",
+        auth_message(WITHDRAW_ACTION, wallet, nonce, issued_at)
+    )
+}
+
+fn message_field<'a>(message: &'a str, name: &str) -> Option<&'a str> {
+    message
+        .lines()
+        .find_map(|line| line.strip_prefix(name)?.strip_prefix(':').map(str::trim))
+        .filter(|value| !value.is_empty())
+}
+
+/// The fields of a signed message that make it single use, and the action it was signed for.
+#[derive(Debug, Clone, PartialEq)]
+struct AuthMessage {
+    action: String,
+    wallet: String,
+    nonce: String,
+    issued_at: DateTime<Utc>,
+}
+
+impl AuthMessage {
+    fn parse(message: &str) -> ApiResult<Self> {
+        let field = |name: &str| {
+            message_field(message, name).ok_or_else(|| ApiError::BadRequest(format!("The message has no {name}")))
+        };
+        let nonce = field(NONCE_FIELD)?;
+        if nonce.len() < 8 || nonce.len() > 64 {
+            return Err(ApiError::BadRequest("The nonce must be 8 to 64 characters long".to_string()));
+        }
+        let issued_at = DateTime::parse_from_rfc3339(field(ISSUED_AT_FIELD)?)
+            .map_err(|_| ApiError::BadRequest(format!("{ISSUED_AT_FIELD} must be a RFC 3339 timestamp")))?
+            .with_timezone(&Utc);
+        Ok(Self {
+            action: message.lines().next().unwrap_or_default().trim().to_string(),
+            wallet: field(WALLET_FIELD)?.to_string(),
+            nonce: nonce.to_string(),
+            issued_at,
+        })
+    }
+
+    fn is_fresh(&self, now: DateTime<Utc>) -> bool {
+        (now - self.issued_at).abs() <= Duration::seconds(API_AUTH_MESSAGE_MAX_AGE_S)
+    }
+}
+
+// wallets return the raw signature bytes, base58 as solana signatures or base64
+fn decode_signature(encoded: &str) -> ApiResult<Vec<u8>> {
+    if let Ok(signature) = Signature::from_str(encoded) {
+        return Ok(signature.as_ref().to_vec());
+    }
+    base64::engine::general_purpose::STANDARD
+        .decode(encoded)
+        .ok()
+        .filter(|bytes| bytes.len() == 64)
+        .ok_or_else(|| ApiError::BadRequest("Invalid signature encoding, base58 or base64 expected".to_string()))
+}
+
+/// Checks the ed25519 signature of the message by the wallet, returns the wallet.
+pub fn verify_signature(request: &SignedRequest) -> ApiResult<Pubkey> {
+    let wallet = Pubkey::from_str(&request.wallet_address)
+        .map_err(|_| ApiError::BadRequest("Invalid wallet address".to_string()))?;
+    let public_key = ed25519_dalek::PublicKey::from_bytes(wallet.as_ref())
+        .map_err(|_| ApiError::BadRequest("The wallet address is not an ed25519 public key".to_string()))?;
+    let signature = ed25519_dalek::Signature::try_from(decode_signature(&request.signed_message)?.as_slice())
+        .map_err(|_| ApiError::BadRequest("Invalid signature".to_string()))?;
+    public_key
+        .verify_strict(request.message.as_bytes(), &signature)
+        .map_err(|_| ApiError::Unauthorized("Signature verification failed".to_string()))?;
+    Ok(wallet)
+}
+
+/// Checks the signature, that the message was issued for the action and the wallet recently and that it wasn't
+/// used before.
+pub fn verify_signed_request(state: &ApiState, request: &SignedRequest, action: &str) -> ApiResult<Pubkey> {
+    let wallet = verify_signature(request)?;
+    let message = AuthMessage::parse(&request.message)?;
+    if message.action != action {
+        return Err(ApiError::Unauthorized(format!("The message was not signed for {action}")));
+    }
+    if message.wallet != request.wallet_address {
+        return Err(ApiError::Unauthorized("The message was signed for another wallet".to_string()));
+    }
+    if !message.is_fresh(Utc::now()) {
+        return Err(ApiError::Unauthorized("The message has expired".to_string()));
+    }
+    let nonce_expires_s = 2 * API_AUTH_MESSAGE_MAX_AGE_S as u64;
+    if !cache::claim_api_nonce(&state.redis_pool, &wallet, &message.nonce, nonce_expires_s)? {
+        return Err(ApiError::Unauthorized("The message was already used".to_string()));
+    }
+    Ok(wallet)
+}
+
+/// Checks that the withdrawal is the one signed, the signature itself is checked by `authenticate`.
+pub fn verify_withdrawal(request: &SignedRequest, destination: &str, amount_sol: Option<f64>) -> ApiResult<()> {
+    let signed_amount = match message_field(&request.message, AMOUNT_FIELD) {
+        Some(ALL_AMOUNT) => None,
+        Some(amount) => Some(amount.parse::<f64>().map_err(|_| {
+            ApiError::BadRequest(format!("{AMOUNT_FIELD} must be a number of SOL or {ALL_AMOUNT}"))
+        })?),
+        None => return Err(ApiError::BadRequest(format!("The message has no {AMOUNT_FIELD}"))),
+    };
+    if message_field(&request.message, DESTINATION_FIELD) != Some(destination) || signed_amount != amount_sol {
+        return Err(ApiError::Unauthorized("The withdrawal doesn't match the signed message".to_string()));
+    }
+    Ok(())
+}
+
+/// The registered user who signed the request.
+pub async fn authenticate(state: &ApiState, request: &SignedRequest, action: &str) -> ApiResult<BotUser> {
+    let wallet = verify_signed_request(state, request, action)?;
+    queries::get_user_by_client_wallet(&state.db_pool, &wallet)
+        .await?
+        .ok_or_else(|| ApiError::Unauthorized("User is not registered".to_string()))
+}
+
+/// The user of the wallet who signed the request, registered on the first login.
+pub async fn authenticate_or_register(state: &ApiState, request: &SignedRequest, action: &str) -> ApiResult<BotUser> {
+    let wallet = verify_signed_request(state, request, action)?;
+    match queries::get_user_by_client_wallet(&state.db_pool, &wallet).await? {
+        Some(user) => Ok(user),
+        None => Ok(queries::create_user(&state.db_pool, NewBotUser::new_from_client_wallet(&wallet)).await?),
+    }
+}
+
+/// Issues a token for the calls that don't carry a signed message.
+pub fn create_session(state: &ApiState, user: &BotUser) -> ApiResult<(String, DateTime<Utc>)> {
+    let token = hex::encode(rand::random::<[u8; 32]>());
+    cache::store_api_session(&state.redis_pool, &token, user.id, API_SESSION_EXPIRES_S)?;
+    Ok((token, Utc::now() + Duration::seconds(API_SESSION_EXPIRES_S as i64)))
+}
+
+/// The authenticated user can only act on their own behalf.
+pub fn ensure_client(user: &BotUser, client_id: i32) -> ApiResult<()> {
+    if user.id != client_id {
+        return Err(ApiError::Forbidden("The wallet doesn't belong to the client".to_string()));
+    }
+    Ok(())
+}
+
+/// The client of the `Authorization: Bearer <session token>` header, or of the `session_token`
+/// query parameter for the clients that can't set headers, like the browser event sources.
+#[derive(Debug, Clone, Copy)]
+pub struct ClientSession {
+    pub client_id: i32,
+}
+
+impl ClientSession {
+    pub fn ensure_client(&self, client_id: i32) -> ApiResult<()> {
+        if self.client_id != client_id {
+            return Err(ApiError::Forbidden("The session belongs to another client".to_string()));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Deserialize)]
+struct SessionTokenQuery {
+    session_token: Option<String>,
+}
+
+#[async_trait]
+impl FromRequestParts<ApiState> for ClientSession {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &ApiState) -> Result<Self, Self::Rejection> {
+        let token = parts
+            .headers
+            .get(AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .map(|token| token.trim().to_string())
+            .or_else(|| {
+                Query::<SessionTokenQuery>::try_from_uri(&parts.uri)
+                    .ok()
+                    .and_then(|Query(query)| query.session_token)
+            })
+            .ok_or_else(|| ApiError::Unauthorized("Session token is required".to_string()))?;
+        let client_id = cache::get_api_session_user(&state.redis_pool, &token)?
+            .ok_or_else(|| ApiError::Unauthorized("Session has expired".to_string()))?;
+        Ok(Self { client_id })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::signature::{Keypair, Signer};
+
+    fn signed(keypair: &Keypair, message: &str) -> SignedRequest {
+        SignedRequest {
+            wallet_address: keypair.pubkey().to_string(),
+            signed_message: keypair.sign_message(message.as_bytes()).to_string(),
+            message: message.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_verify_signature() {
+        let keypair = Keypair::new();
+        let request = signed(&keypair, "Login");
+        assert_eq!(verify_signature(&request).unwrap(), keypair.pubkey());
+
+        let base64_request = SignedRequest {
+            signed_message: base64::engine::general_purpose::STANDARD
+                .encode(keypair.sign_message(b"Login").as_ref()),
+            ..request.clone()
+        };
+        assert_eq!(verify_signature(&base64_request).unwrap(), keypair.pubkey());
+
+        let forged = SignedRequest { message: "Logout".to_string(), ..request.clone() };
+        assert!(matches!(verify_signature(&forged), Err(ApiError::Unauthorized(_))));
+        let other_wallet = SignedRequest { wallet_address: Pubkey::new_unique().to_string(), ..request };
+        assert!(verify_signature(&other_wallet).is_err());
+    }
+
+    #[test]
+    fn test_parse_auth_message() {
+        let wallet = Pubkey::new_unique();
+        let issued_at = DateTime::parse_from_rfc3339("2026-10-18T12:00:00Z").unwrap().with_timezone(&Utc);
+        let message = AuthMessage::parse(&auth_message("Login", &wallet, "nonce-0001", issued_at)).unwrap();
+        assert_eq!(message, AuthMessage {
+            action: "Login".to_string(),
+            wallet: wallet.to_string(),
+            nonce: "nonce-0001".to_string(),
+            issued_at,
+        });
+        assert!(message.is_fresh(issued_at + Duration::seconds(API_AUTH_MESSAGE_MAX_AGE_S)));
+        assert!(!message.is_fresh(issued_at + Duration::seconds(API_AUTH_MESSAGE_MAX_AGE_S + 1)));
+        assert!(!message.is_fresh(issued_at - Duration::seconds(API_AUTH_MESSAGE_MAX_AGE_S + 1)));
+
+        assert!(AuthMessage::parse("Login").is_err());
+        assert!(AuthMessage::parse(&auth_message("Login", &wallet, "short", issued_at)).is_err());
+        assert!(AuthMessage::parse(&format!("Login\nWallet: {wallet}\nNonce: nonce-0001\nIssued At: now")).is_err());
+    }
+
+    #[test]
+    fn test_verify_withdrawal() {
+        let keypair = Keypair::new();
+        let destination = Pubkey::new_unique().to_string();
+        let message = withdrawal_message(&keypair.pubkey(), &destination, Some(0.5), "nonce-0001", Utc::now());
+        let request = signed(&keypair, &message);
+        assert!(verify_withdrawal(&request, &destination, Some(0.5)).is_ok());
+        assert!(matches!(verify_withdrawal(&request, &destination, Some(5.0)), Err(ApiError::Unauthorized(_))));
+        assert!(matches!(verify_withdrawal(&request, &destination, None), Err(ApiError::Unauthorized(_))));
+        let other_destination = Pubkey::new_unique().to_string();
+        assert!(matches!(verify_withdrawal(&request, &other_destination, Some(0.5)), Err(ApiError::Unauthorized(_))));
+
+        let message = withdrawal_message(&keypair.pubkey(), &destination, None, "nonce-0001", Utc::now());
+        assert!(verify_withdrawal(&signed(&keypair, &message), &destination, None).is_ok());
+        let login = signed(&keypair, &auth_message("Login", &keypair.pubkey(), "nonce-0001", Utc::now()));
+        assert!(matches!(verify_withdrawal(&login, &destination, None), Err(ApiError::BadRequest(_))));
+    }
+}
@@ -0,0 +1,63 @@
+use crate::types::withdrawal::WithdrawalError;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use serde_json::json;
+use thiserror::Error;
+use tracing::error;
+
+#[derive(Error, Debug)]
+pub enum ApiError {
+    #[error("{0}")]
+    BadRequest(String),
+    #[error("{0}")]
+    Unauthorized(String),
+    #[error("{0}")]
+    Forbidden(String),
+    #[error("{0}")]
+    NotFound(String),
+    #[error("{0}")]
+    Conflict(String),
+    #[error("Internal error")]
+    Internal(#[from] anyhow::Error),
+}
+
+impl ApiError {
+    fn status(&self) -> StatusCode {
+        match self {
+            ApiError::BadRequest(_) => StatusCode::BAD_REQUEST,
+            ApiError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            ApiError::Forbidden(_) => StatusCode::FORBIDDEN,
+            ApiError::NotFound(_) => StatusCode::NOT_FOUND,
+            ApiError::Conflict(_) => StatusCode::CONFLICT,
+            ApiError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+impl From<WithdrawalError> for ApiError {
+    fn from(e: WithdrawalError) -> Self {
+        match e {
+            WithdrawalError::StrategiesRunning(_) | WithdrawalError::InProgress => ApiError::Conflict(e.to_string()),
+            WithdrawalError::NotFound => ApiError::NotFound(e.to_string()),
+            WithdrawalError::Other(e) => ApiError::Internal(e),
+            _ => ApiError::BadRequest(e.to_string()),
+        }
+    }
+}
+
+/// Same envelope as the successful responses, the internal errors are logged and not disclosed.
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        if let ApiError::Internal(e) = &self {
+            error!("API request failed: {:?}", e);
+        }
+        let body = json!({
+            "success": false,
+            "message": self.to_string(),
+        });
+        (self.status(), Json(body)).into_response()
+    }
+}
+
+pub type ApiResult<T> = Result<T, ApiError>;
@@ -0,0 +1,621 @@
+use crate::analytics;
+use crate::api::auth::{self, ClientSession};
+use crate::api::error::{ApiError, ApiResult};
+use crate::api::queries;
+use crate::api::types::*;
+use crate::api::ApiState;
+use crate::config::constants::{API_LOGS_DEFAULT_LIMIT, API_LOGS_MAX_LIMIT, API_WITHDRAWAL_CONFIRMATION_EXPIRES_S};
+use crate::storage::{cache, deposits, subscriptions};
+use crate::strategies::{menu_kinds, new_instance_row, withdrawal, VOLUME_STRATEGY_KIND};
+use crate::tg_bot::volume_strategy_config_args::VolumeStrategyConfigArgs;
+use crate::types::bot_user::NewBotUser;
+use crate::types::engine::{StrategyId, StrategyStatus};
+use crate::types::routing::EventRoute;
+use crate::types::subscription::{NewSubscription, SubscriptionPlan, SubscriptionStatus};
+use crate::types::volume_strategy::{NewVolumeStrategyInstance, VolumeStrategyInstance};
+use crate::types::withdrawal::{WithdrawalError, WithdrawalRequest};
+use axum::extract::{Path, Query, State};
+use axum::Json;
+use chrono::{Duration, Utc};
+use serde_json::json;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::{BTreeSet, HashMap};
+use std::str::FromStr;
+
+pub async fn login(
+    State(state): State<ApiState>,
+    Json(request): Json<SignedRequest>,
+) -> ApiResult<Json<LoginResponse>> {
+    let user = auth::authenticate_or_register(&state, &request, auth::LOGIN_ACTION).await?;
+    queries::touch_last_login(&state.db_pool, user.id).await?;
+    let (session_token, session_expires_at) = auth::create_session(&state, &user)?;
+    let deposit_wallet = deposits::get_or_create_deposit_wallet(&state.db_pool, user.id).await?;
+    // the best plan over the tokens of the client
+    let client_subscription = subscriptions::get_user_subscriptions(&state.db_pool, user.id)
+        .await?
+        .iter()
+        .map(|subscription| subscription.effective_plan(Utc::now()))
+        .max()
+        .unwrap_or(SubscriptionPlan::Free);
+    Ok(Json(LoginResponse {
+        success: true,
+        message: "Login successful".to_string(),
+        client_id: user.id,
+        deposit_wallet: deposit_wallet.wallet.to_string(),
+        client_subscription: client_subscription as i32,
+        session_token,
+        session_expires_at,
+    }))
+}
+
+pub async fn new_user(
+    State(state): State<ApiState>,
+    Json(request): Json<SignedRequest>,
+) -> ApiResult<Json<UserCreatedResponse>> {
+    let wallet = auth::verify_signed_request(&state, &request, auth::REGISTER_ACTION)?;
+    if queries::get_user_by_client_wallet(&state.db_pool, &wallet).await?.is_some() {
+        return Err(ApiError::Conflict("User is already registered".to_string()));
+    }
+    let user = queries::create_user(&state.db_pool, NewBotUser::new_from_client_wallet(&wallet)).await?;
+    let deposit_wallet = deposits::get_or_create_deposit_wallet(&state.db_pool, user.id).await?;
+    Ok(Json(UserCreatedResponse {
+        success: true,
+        message: "User registered successfully".to_string(),
+        client_id: user.id,
+        deposit_address: deposit_wallet.wallet.to_string(),
+    }))
+}
+
+pub async fn set_subscription(
+    State(state): State<ApiState>,
+    Json(request): Json<SubscriptionRequest>,
+) -> ApiResult<Json<SubscriptionResponse>> {
+    let user = auth::authenticate(&state, &request.auth, auth::SUBSCRIBE_ACTION).await?;
+    let plan = SubscriptionPlan::try_from(request.subscription).map_err(|e| ApiError::BadRequest(e.to_string()))?;
+    let token_mint = Pubkey::from_str(&request.token_id)
+        .map_err(|_| ApiError::BadRequest("Invalid token id".to_string()))?
+        .to_string();
+    let (status, paid_until, grace_until) = subscriptions::get_subscription(&state.db_pool, user.id, &token_mint)
+        .await?
+        .map(|current| current.resubscribe(plan))
+        .unwrap_or((SubscriptionStatus::Active, None, None));
+    let subscription = subscriptions::save_subscription(&state.db_pool, NewSubscription {
+        user_id: user.id,
+        token_mint,
+        plan: plan as i32,
+        status: status.to_string(),
+        subscribed_at: Utc::now(),
+    }, paid_until, grace_until)
+    .await?;
+    Ok(Json(SubscriptionResponse {
+        success: true,
+        message: "Subscription updated successfully".to_string(),
+        client_subscription: subscription.plan,
+    }))
+}
+
+pub async fn payment_status(
+    State(state): State<ApiState>,
+    Query(request): Query<SignedRequest>,
+) -> ApiResult<Json<PaymentStatusResponse>> {
+    let user = auth::authenticate(&state, &request, auth::PAYMENTS_ACTION).await?;
+    let payments = subscriptions::get_user_payments(&state.db_pool, user.id)
+        .await?
+        .into_iter()
+        .map(|(payment, token_mint)| PaymentLog {
+            date: payment.paid_at.unwrap_or(payment.created_at),
+            amount: payment.amount_sol,
+            subscription: payment.plan,
+            tx_hash: payment.tx_hash,
+            token_id: token_mint,
+            status: payment.status,
+        })
+        .collect();
+    Ok(Json(PaymentStatusResponse {
+        success: true,
+        message: "Payment status retrieved successfully".to_string(),
+        payments,
+    }))
+}
+
+pub async fn deposit_address(
+    State(state): State<ApiState>,
+    Query(query): Query<DepositAddressQuery>,
+) -> ApiResult<Json<DepositAddressResponse>> {
+    let wallet = Pubkey::from_str(&query.wallet_address)
+        .map_err(|_| ApiError::BadRequest("Invalid wallet address".to_string()))?;
+    let user = queries::get_user_by_client_wallet(&state.db_pool, &wallet)
+        .await?
+        .ok_or_else(|| ApiError::NotFound("User is not registered".to_string()))?;
+    // read-only, the wallet is allocated by the registration and the login
+    let deposit_wallet = deposits::get_deposit_wallet(&state.db_pool, user.id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound("User has no deposit wallet yet, log in first".to_string()))?;
+    Ok(Json(DepositAddressResponse { deposit_wallet: deposit_wallet.wallet.to_string() }))
+}
+
+/// Queued right away, or once confirmed with the returned token when `withdrawal.require_confirmation` is set.
+pub async fn withdraw(
+    State(state): State<ApiState>,
+    Json(request): Json<WithdrawRequest>,
+) -> ApiResult<Json<WithdrawResponse>> {
+    let user = auth::authenticate(&state, &request.auth, auth::WITHDRAW_ACTION).await?;
+    auth::verify_withdrawal(&request.auth, &request.destination, request.amount_sol)?;
+    let withdrawal = WithdrawalRequest::new(&request.destination, request.amount_sol)?;
+    if !state.withdrawal_config.require_confirmation {
+        let withdrawal_id =
+            withdrawal::request_withdrawal(&state.db_pool, state.strategy_manager.as_ref(), &user, &withdrawal).await?;
+        return Ok(Json(WithdrawResponse {
+            success: true,
+            message: "Withdrawal queued".to_string(),
+            withdrawal_id: Some(withdrawal_id),
+            confirmation_token: None,
+            confirmation_expires_at: None,
+        }));
+    }
+    // checked now to fail early, and again when confirmed
+    withdrawal::check_withdrawal(&state.db_pool, state.strategy_manager.as_ref(), &user, &withdrawal).await?;
+    let token = hex::encode(rand::random::<[u8; 32]>());
+    cache::store_pending_withdrawal(
+        &state.redis_pool,
+        &token,
+        user.id,
+        &withdrawal,
+        API_WITHDRAWAL_CONFIRMATION_EXPIRES_S,
+    )?;
+    Ok(Json(WithdrawResponse {
+        success: true,
+        message: "Confirm the withdrawal with /api/withdraw/confirm".to_string(),
+        withdrawal_id: None,
+        confirmation_token: Some(token),
+        confirmation_expires_at: Some(Utc::now() + Duration::seconds(API_WITHDRAWAL_CONFIRMATION_EXPIRES_S as i64)),
+    }))
+}
+
+pub async fn confirm_withdrawal(
+    State(state): State<ApiState>,
+    Json(request): Json<WithdrawConfirmRequest>,
+) -> ApiResult<Json<WithdrawResponse>> {
+    let user = auth::authenticate(&state, &request.auth, auth::CONFIRM_WITHDRAWAL_ACTION).await?;
+    let withdrawal = match cache::take_pending_withdrawal(&state.redis_pool, &request.confirmation_token)? {
+        Some((user_id, withdrawal)) if user_id == user.id => withdrawal,
+        _ => return Err(WithdrawalError::NotFound.into()),
+    };
+    let withdrawal_id =
+        withdrawal::request_withdrawal(&state.db_pool, state.strategy_manager.as_ref(), &user, &withdrawal).await?;
+    Ok(Json(WithdrawResponse {
+        success: true,
+        message: "Withdrawal queued".to_string(),
+        withdrawal_id: Some(withdrawal_id),
+        confirmation_token: None,
+        confirmation_expires_at: None,
+    }))
+}
+
+pub async fn withdrawals(
+    State(state): State<ApiState>,
+    session: ClientSession,
+    Query(query): Query<WithdrawalsQuery>,
+) -> ApiResult<Json<WithdrawalsResponse>> {
+    let limit = query.limit.unwrap_or(API_LOGS_DEFAULT_LIMIT).clamp(1, API_LOGS_MAX_LIMIT);
+    let withdrawals = deposits::get_user_withdrawals(&state.db_pool, session.client_id, limit)
+        .await?
+        .into_iter()
+        .map(|withdrawal| WithdrawalLog {
+            id: withdrawal.id,
+            date: withdrawal.time.and_utc(),
+            status: withdrawal.status().to_string(),
+            destination: withdrawal.destination,
+            amount_sol: withdrawal.amount_sol,
+            tx_hash: withdrawal.signature,
+            error: withdrawal.description,
+        })
+        .collect();
+    Ok(Json(WithdrawalsResponse { withdrawals }))
+}
+
+// strategy 0 is the latest one of the client
+async fn get_client_strategy(
+    state: &ApiState,
+    client_id: i32,
+    strategy_id: StrategyId,
+) -> ApiResult<VolumeStrategyInstance> {
+    let strategy = if strategy_id == 0 {
+        queries::get_latest_strategy(&state.db_pool, client_id).await?
+    } else {
+        queries::get_strategy(&state.db_pool, strategy_id).await?
+    }
+    .ok_or_else(|| ApiError::NotFound("Strategy not found".to_string()))?;
+    if strategy.user_id != client_id {
+        return Err(ApiError::Forbidden("The strategy doesn't belong to the client".to_string()));
+    }
+    Ok(strategy)
+}
+
+pub async fn start_bot(
+    State(state): State<ApiState>,
+    Json(request): Json<BotControlRequest>,
+) -> ApiResult<Json<BotStartedResponse>> {
+    let user = auth::authenticate(&state, &request.auth, auth::START_ACTION).await?;
+    auth::ensure_client(&user, request.client_id)?;
+    let strategy = get_client_strategy(&state, request.client_id, request.strategy_id).await?;
+    if state.strategy_manager.get_strategy_status(strategy.id).await.is_some() {
+        return Err(ApiError::Conflict("Bot is already running".to_string()));
+    }
+    let strategy = queries::mark_strategy_started(&state.db_pool, strategy.id).await?;
+    let row = serde_json::to_value(&strategy).map_err(anyhow::Error::from)?;
+    if let Err(e) = state.strategy_manager.start_strategy_of_kind(VOLUME_STRATEGY_KIND, row).await {
+        queries::mark_strategy_completed(&state.db_pool, strategy.id).await?;
+        return Err(ApiError::Internal(e.context(format!("Failed to start strategy {}", strategy.id))));
+    }
+    Ok(Json(BotStartedResponse {
+        success: true,
+        start_time: strategy.started_at.and_utc(),
+        message: "Bot started successfully".to_string(),
+    }))
+}
+
+pub async fn stop_bot(
+    State(state): State<ApiState>,
+    Json(request): Json<BotControlRequest>,
+) -> ApiResult<Json<BotStoppedResponse>> {
+    let user = auth::authenticate(&state, &request.auth, auth::STOP_ACTION).await?;
+    auth::ensure_client(&user, request.client_id)?;
+    let strategy = get_client_strategy(&state, request.client_id, request.strategy_id).await?;
+    if state.strategy_manager.get_strategy_status(strategy.id).await.is_none() {
+        return Err(ApiError::Conflict("Bot is not running".to_string()));
+    }
+    // the strategy kind marks the instance as completed
+    state.strategy_manager.drop_strategy(strategy.id).await?;
+    Ok(Json(BotStoppedResponse {
+        success: true,
+        stop_time: Utc::now(),
+        message: "Bot stopped successfully".to_string(),
+    }))
+}
+
+/// How the API reports a strategy status, a strategy that isn't running is stopped.
+pub(super) fn status_name(status: Option<&StrategyStatus>) -> &'static str {
+    match status {
+        Some(StrategyStatus::Running(_)) => "running",
+        Some(StrategyStatus::Paused(_)) => "paused",
+        Some(StrategyStatus::Stopping) => "stopping",
+        Some(StrategyStatus::Stopped) | None => "stopped",
+    }
+}
+
+pub async fn bot_status(
+    State(state): State<ApiState>,
+    session: ClientSession,
+    Query(query): Query<BotStatusQuery>,
+) -> ApiResult<Json<BotStatusResponse>> {
+    session.ensure_client(query.client_id)?;
+    let strategy = get_client_strategy(&state, query.client_id, query.strategy_id).await?;
+    let status = status_name(state.strategy_manager.get_strategy_status(strategy.id).await.as_ref());
+    Ok(Json(BotStatusResponse {
+        success: true,
+        message: "Bot status retrieved successfully".to_string(),
+        data: BotStatus {
+            status: status.to_string(),
+            last_start_time: strategy.started_at.and_utc(),
+            last_stop_time: match status {
+                "stopped" => strategy.completed_at.map(|completed_at| completed_at.and_utc()),
+                _ => None,
+            },
+            error_message: None,
+        },
+    }))
+}
+
+// pool, buying and selling agents of the volume strategy
+fn validate_strategy_request(request: &StrategyRequest) -> ApiResult<(Pubkey, i32, i32)> {
+    let target_pool = Pubkey::from_str(&request.target_pool)
+        .map_err(|_| ApiError::BadRequest("Invalid target pool".to_string()))?;
+    match request.daily_volume_sol {
+        Some(daily_volume_sol) if daily_volume_sol <= 0.0 => {
+            return Err(ApiError::BadRequest("daily_volume_sol must be positive".to_string()));
+        }
+        Some(_) => {}
+        None => {
+            if request.tranche_size_sol <= 0.0 {
+                return Err(ApiError::BadRequest("tranche_size_sol must be positive".to_string()));
+            }
+            if request.tranche_frequency <= 0 {
+                return Err(ApiError::BadRequest("tranche_frequency must be positive".to_string()));
+            }
+        }
+    }
+    if request.budget_days.is_some_and(|days| days <= 0) {
+        return Err(ApiError::BadRequest("budget_days must be positive".to_string()));
+    }
+    if request.max_inventory_sol.is_some_and(|max| max < 0.0) || request.max_price_drift_pct.is_some_and(|max| max <= 0.0) {
+        return Err(ApiError::BadRequest("max_inventory_sol can't be negative and max_price_drift_pct must be positive".to_string()));
+    }
+    if request.tranche_length <= 0 {
+        return Err(ApiError::BadRequest("tranche_length must be positive".to_string()));
+    }
+    if request.min_agents <= 0 || request.max_agents < 0 {
+        return Err(ApiError::BadRequest("min_agents must be positive and max_agents not negative".to_string()));
+    }
+    let selling_agents = if request.max_agents == 0 { request.min_agents } else { request.max_agents };
+    Ok((target_pool, request.min_agents, selling_agents))
+}
+
+pub async fn create_strategy(
+    State(state): State<ApiState>,
+    session: ClientSession,
+    Json(request): Json<StrategyRequest>,
+) -> ApiResult<Json<StrategyCreatedResponse>> {
+    session.ensure_client(request.client_id)?;
+    let (target_pool, buying_agents, selling_agents) = validate_strategy_request(&request)?;
+    queries::get_user(&state.db_pool, request.client_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound("Client not found".to_string()))?;
+    let now = Utc::now().naive_utc();
+    // created stopped, `/api/bot/start` runs it
+    let strategy_id = queries::insert_strategy(&state.db_pool, NewVolumeStrategyInstance {
+        user_id: request.client_id,
+        target_pool,
+        started_at: now,
+        completed_at: Some(now),
+        tranche_size_sol: request.tranche_size_sol,
+        tranche_frequency_hbs: request.tranche_frequency,
+        tranche_length_hbs: request.tranche_length,
+        agents_buying_in_tranche: buying_agents,
+        agents_selling_in_tranche: selling_agents,
+        agents_keep_tokens_lamports: 0,
+        daily_volume_sol: request.daily_volume_sol,
+        budget_days: request.budget_days,
+        max_inventory_sol: request.max_inventory_sol,
+        max_price_drift_pct: request.max_price_drift_pct,
+    })
+    .await?;
+    Ok(Json(StrategyCreatedResponse {
+        success: true,
+        message: "Strategy created successfully".to_string(),
+        strategy_id,
+    }))
+}
+
+pub async fn strategy_kinds(State(state): State<ApiState>) -> ApiResult<Json<StrategyKindsResponse>> {
+    let kinds = menu_kinds(state.strategy_manager.as_ref())
+        .into_iter()
+        .map(|(title, kind)| StrategyKindInfo {
+            name: kind.name().to_string(),
+            title: title.to_string(),
+            config_schema: kind.config_schema(),
+        })
+        .collect();
+    Ok(Json(StrategyKindsResponse {
+        success: true,
+        message: "Strategy kinds retrieved successfully".to_string(),
+        kinds,
+    }))
+}
+
+/// Starts a strategy of a kind listed by `/api/strategy-kinds`, the volume strategies have their own endpoints.
+pub async fn start_strategy_kind(
+    State(state): State<ApiState>,
+    Path(kind): Path<String>,
+    Json(request): Json<StrategyKindRequest>,
+) -> ApiResult<Json<StrategyCreatedResponse>> {
+    let user = auth::authenticate(&state, &request.auth, auth::START_STRATEGY_ACTION).await?;
+    auth::ensure_client(&user, request.client_id)?;
+    let strategy_kind = menu_kinds(state.strategy_manager.as_ref())
+        .into_iter()
+        .find(|(_, strategy_kind)| strategy_kind.name() == kind)
+        .map(|(_, strategy_kind)| strategy_kind)
+        .ok_or_else(|| ApiError::NotFound(format!("Unknown strategy kind {}", kind)))?;
+    let row = new_instance_row(&strategy_kind.config_schema(), request.client_id, &request.config)
+        .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+    let strategy_id = state
+        .strategy_manager
+        .start_strategy_of_kind(&kind, row)
+        .await
+        .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+    Ok(Json(StrategyCreatedResponse {
+        success: true,
+        message: "Strategy started successfully".to_string(),
+        strategy_id,
+    }))
+}
+
+pub async fn update_strategy(
+    State(state): State<ApiState>,
+    session: ClientSession,
+    Path(strategy_id): Path<StrategyId>,
+    Json(request): Json<StrategyRequest>,
+) -> ApiResult<Json<MessageResponse>> {
+    session.ensure_client(request.client_id)?;
+    let (target_pool, buying_agents, selling_agents) = validate_strategy_request(&request)?;
+    let mut strategy = get_client_strategy(&state, request.client_id, strategy_id).await?;
+    if state.strategy_manager.get_strategy_status(strategy.id).await.is_some() {
+        if strategy.target_pool != target_pool {
+            return Err(ApiError::Conflict("The target pool can't be changed on a running strategy".to_string()));
+        }
+        // the running strategy stores its new config itself, a budgeted one derives its tranches, the optional
+        // settings left out are cleared like on a stopped strategy
+        let budgeted = request.daily_volume_sol.is_some();
+        let config = VolumeStrategyConfigArgs {
+            tranche_size_sol: (!budgeted).then_some(request.tranche_size_sol),
+            tranche_frequency_hbs: (!budgeted).then_some(request.tranche_frequency),
+            tranche_length_hbs: Some(request.tranche_length),
+            agents_buying_in_tranche: Some(buying_agents),
+            agents_selling_in_tranche: Some(selling_agents),
+            daily_volume_sol: request.daily_volume_sol,
+            budget_days: request.budget_days,
+            max_inventory_sol: request.max_inventory_sol,
+            max_price_drift_pct: request.max_price_drift_pct,
+            clear_unset: true,
+            ..Default::default()
+        };
+        let config = serde_json::to_value(config).map_err(anyhow::Error::from)?;
+        state
+            .strategy_manager
+            .update_strategy_config(strategy.id, config)
+            .await
+            .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+    } else {
+        strategy.target_pool = target_pool;
+        strategy.tranche_size_sol = request.tranche_size_sol;
+        strategy.tranche_frequency_hbs = request.tranche_frequency;
+        strategy.tranche_length_hbs = request.tranche_length;
+        strategy.agents_buying_in_tranche = buying_agents;
+        strategy.agents_selling_in_tranche = selling_agents;
+        strategy.daily_volume_sol = request.daily_volume_sol;
+        strategy.budget_days = request.budget_days;
+        strategy.max_inventory_sol = request.max_inventory_sol;
+        strategy.max_price_drift_pct = request.max_price_drift_pct;
+        queries::update_strategy(&state.db_pool, &strategy).await?;
+    }
+    Ok(Json(MessageResponse::ok("Strategy updated successfully")))
+}
+
+pub async fn get_strategy(
+    State(state): State<ApiState>,
+    session: ClientSession,
+    Path(strategy_id): Path<StrategyId>,
+) -> ApiResult<Json<StrategyResponse>> {
+    let strategy = get_client_strategy(&state, session.client_id, strategy_id).await?;
+    Ok(Json(StrategyResponse::from(&strategy)))
+}
+
+/// The strategies the logs are requested for and the keys their events are stored with.
+pub(super) struct LogsScope {
+    pools: HashMap<String, StrategyId>,
+    wallets: HashMap<String, Option<StrategyId>>,
+}
+
+impl LogsScope {
+    fn strategy_of(&self, pool: Option<&String>, wallets: &[String]) -> Option<StrategyId> {
+        pool.and_then(|pool| self.pools.get(pool).copied())
+            .or_else(|| wallets.iter().find_map(|wallet| self.wallets.get(wallet).copied().flatten()))
+    }
+
+    fn wallets(&self) -> Vec<String> {
+        self.wallets.keys().cloned().collect()
+    }
+
+    /// Whether a live event concerns the pools or the wallets of the scope.
+    pub(super) fn matches(&self, route: &EventRoute) -> bool {
+        route.pool.is_some_and(|pool| self.pools.contains_key(&pool.to_string()))
+            || route.wallets.iter().any(|wallet| self.wallets.contains_key(&wallet.to_string()))
+    }
+
+    pub(super) fn strategy_of_route(&self, route: &EventRoute) -> Option<StrategyId> {
+        let wallets: Vec<String> = route.wallets.iter().map(|wallet| wallet.to_string()).collect();
+        self.strategy_of(route.pool.map(|pool| pool.to_string()).as_ref(), &wallets)
+    }
+
+    pub(super) fn strategy_ids(&self) -> BTreeSet<StrategyId> {
+        self.pools
+            .values()
+            .copied()
+            .chain(self.wallets.values().filter_map(|strategy_id| *strategy_id))
+            .collect()
+    }
+}
+
+pub(super) async fn get_logs_scope(state: &ApiState, session: &ClientSession, query: &LogsQuery) -> ApiResult<LogsScope> {
+    if let Some(client_id) = query.client_id {
+        session.ensure_client(client_id)?;
+    }
+    let strategies = match query.strategy_id {
+        Some(strategy_id) => vec![get_client_strategy(state, session.client_id, strategy_id).await?],
+        None => queries::get_user_strategies(&state.db_pool, session.client_id).await?,
+    };
+    let mut scope = LogsScope { pools: HashMap::new(), wallets: HashMap::new() };
+    // latest strategies first, the pool events go to the latest strategy trading the pool
+    for strategy in &strategies {
+        scope.pools.entry(strategy.target_pool.to_string()).or_insert(strategy.id);
+        for wallet in analytics::queries::get_strategy_wallets(&state.db_pool, strategy.id).await? {
+            scope.wallets.insert(wallet.to_string(), Some(strategy.id));
+        }
+    }
+    // the trading wallet funds all the strategies of the client, the deposits are swept to it
+    if let Some(user) = queries::get_user(&state.db_pool, session.client_id).await? {
+        scope.wallets.entry(user.wallet_address.to_string()).or_insert(None);
+        let deposit_wallet = deposits::get_or_create_deposit_wallet(&state.db_pool, user.id).await?;
+        scope.wallets.entry(deposit_wallet.wallet.to_string()).or_insert(None);
+    }
+    Ok(scope)
+}
+
+fn logs_limit(query: &LogsQuery) -> i64 {
+    query.limit.unwrap_or(API_LOGS_DEFAULT_LIMIT).clamp(1, API_LOGS_MAX_LIMIT)
+}
+
+pub async fn event_logs(
+    State(state): State<ApiState>,
+    session: ClientSession,
+    Query(query): Query<LogsQuery>,
+) -> ApiResult<Json<EventsResponse>> {
+    let scope = get_logs_scope(&state, &session, &query).await?;
+    let pools: Vec<String> = scope.pools.keys().cloned().collect();
+    let records = queries::get_events(&state.db_pool, &pools, &scope.wallets(), logs_limit(&query)).await?;
+    let events = records
+        .into_iter()
+        .map(|record| EventLog {
+            id: record.id,
+            timestamp: record.timestamp,
+            strategy_id: scope.strategy_of(record.pool.as_ref(), &record.wallets),
+            event_type: record.event_type,
+            event_data: record.event_data.0.to_string(),
+        })
+        .collect();
+    Ok(Json(EventsResponse { events }))
+}
+
+pub async fn execution_logs(
+    State(state): State<ApiState>,
+    session: ClientSession,
+    Query(query): Query<LogsQuery>,
+) -> ApiResult<Json<ExecutionsResponse>> {
+    let scope = get_logs_scope(&state, &session, &query).await?;
+    let records = analytics::queries::get_actions(
+        &state.db_pool,
+        &scope.wallets(),
+        query.action_id.as_deref(),
+        None,
+        None,
+        logs_limit(&query),
+    )
+    .await?;
+    let executions = records
+        .into_iter()
+        .map(|record| ExecutionLog {
+            id: record.uuid.clone(),
+            timestamp: record.confirmed_at.or(record.sent_at).unwrap_or(record.created_at),
+            action_id: record.uuid.clone(),
+            success: record.status.as_deref() == Some(analytics::SUCCESS_STATUS),
+            result_data: json!({
+                "status": record.status,
+                "tx_hash": record.tx_hash,
+                "fee": record.fee,
+                "sent_at": record.sent_at,
+                "confirmed_at": record.confirmed_at,
+                "action_payload": record.action_payload.0,
+            })
+            .to_string(),
+        })
+        .collect();
+    Ok(Json(ExecutionsResponse { executions }))
+}
+
+pub async fn strategy_stats(
+    State(state): State<ApiState>,
+    session: ClientSession,
+    Path(strategy_id): Path<StrategyId>,
+    Query(query): Query<StatsQuery>,
+) -> ApiResult<Json<StatsResponse>> {
+    let strategy = get_client_strategy(&state, session.client_id, strategy_id).await?;
+    // the end date is inclusive
+    let from = query.start_date.map(|date| date.and_hms_opt(0, 0, 0).unwrap().and_utc());
+    let to = query.end_date.map(|date| (date + Duration::days(1)).and_hms_opt(0, 0, 0).unwrap().and_utc());
+    let data = analytics::get_strategy_stats(&state.db_pool, strategy.id, from, to).await?;
+    Ok(Json(StatsResponse {
+        success: true,
+        message: "Effectiveness data retrieved successfully".to_string(),
+        data,
+    }))
+}
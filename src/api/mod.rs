@@ -0,0 +1,85 @@
+mod auth;
+mod error;
+mod handlers;
+mod queries;
+mod stream;
+mod types;
+#[cfg(test)]
+mod tests;
+
+use crate::config::app_context::AppContext;
+use crate::config::settings::{ApiConfig, WithdrawalConfig};
+use crate::storage::cache::RedisPool;
+use crate::storage::persistent::DbPool;
+use crate::strategies::StreamedEvent;
+use crate::types::actions::SolanaAction;
+use crate::types::engine::StrategyManager;
+use crate::types::events::BotEvent;
+use anyhow::Result;
+use axum::routing::{get, post};
+use axum::Router;
+use std::sync::Arc;
+use tokio::sync::{broadcast, Mutex};
+use tracing::{error, info};
+
+pub type ApiStrategyManager = Arc<dyn StrategyManager<BotEvent, Arc<Mutex<SolanaAction>>> + Send + Sync>;
+
+/// Shared by the handlers, the same db and strategy manager the telegram bot works with,
+/// redis keeps the used nonces and the sessions.
+#[derive(Clone)]
+pub struct ApiState {
+    pub db_pool: DbPool,
+    pub redis_pool: RedisPool,
+    pub strategy_manager: ApiStrategyManager,
+    /// Published by the [EventStreamStrategy](crate::strategies::EventStreamStrategy).
+    pub event_stream: broadcast::Sender<Arc<StreamedEvent>>,
+    pub withdrawal_config: WithdrawalConfig,
+}
+
+pub fn router(state: ApiState) -> Router {
+    Router::new()
+        .route("/api/auth/login", post(handlers::login))
+        .route("/api/user/new", post(handlers::new_user))
+        .route("/api/subscription", post(handlers::set_subscription))
+        .route("/api/subscription/payment/status", get(handlers::payment_status))
+        .route("/api/deposit/address", get(handlers::deposit_address))
+        .route("/api/withdraw", post(handlers::withdraw))
+        .route("/api/withdraw/confirm", post(handlers::confirm_withdrawal))
+        .route("/api/withdrawals", get(handlers::withdrawals))
+        .route("/api/bot/start", post(handlers::start_bot))
+        .route("/api/bot/stop", post(handlers::stop_bot))
+        .route("/api/bot/status", get(handlers::bot_status))
+        .route("/api/strategy", post(handlers::create_strategy))
+        .route("/api/strategy/:strategy_id", get(handlers::get_strategy).put(handlers::update_strategy))
+        .route("/api/strategy/:strategy_id/stats", get(handlers::strategy_stats))
+        .route("/api/strategy-kinds", get(handlers::strategy_kinds))
+        .route("/api/strategy-kinds/:kind", post(handlers::start_strategy_kind))
+        .route("/api/logs/events", get(handlers::event_logs))
+        .route("/api/logs/executions", get(handlers::execution_logs))
+        .route("/api/events/stream", get(stream::event_stream))
+        .with_state(state)
+}
+
+pub async fn start_api_server(
+    context: &AppContext,
+    api_config: &ApiConfig,
+    strategy_manager: ApiStrategyManager,
+    event_stream: broadcast::Sender<Arc<StreamedEvent>>,
+) -> Result<()> {
+    let listener = tokio::net::TcpListener::bind(&api_config.bind_address).await?;
+    let app = router(ApiState {
+        db_pool: context.db_pool.clone(),
+        redis_pool: context.redis_pool.clone(),
+        strategy_manager,
+        event_stream,
+        withdrawal_config: context.get_settings().await.withdrawal.clone().unwrap_or_default(),
+    });
+    let bind_address = api_config.bind_address.clone();
+    tokio::spawn(async move {
+        info!("Starting API server on {}", bind_address);
+        if let Err(e) = axum::serve(listener, app).await {
+            error!("API server stopped: {:?}", e);
+        }
+    });
+    Ok(())
+}
@@ -0,0 +1,162 @@
+use crate::storage::persistent::DbPool;
+use crate::types::bot_user::{BotUser, NewBotUser};
+use crate::types::engine::StrategyId;
+use crate::types::events::BotEventRecord;
+use crate::types::volume_strategy::{NewVolumeStrategyInstance, VolumeStrategyInstance};
+use anyhow::Result;
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use solana_sdk::pubkey::Pubkey;
+
+pub async fn get_user(db_pool: &DbPool, user_id: i32) -> Result<Option<BotUser>> {
+    use crate::schema::users::dsl::*;
+    let mut conn = db_pool.get().await?;
+    Ok(users.find(user_id).first::<BotUser>(&mut conn).await.optional()?)
+}
+
+pub async fn get_user_by_client_wallet(db_pool: &DbPool, wallet: &Pubkey) -> Result<Option<BotUser>> {
+    use crate::schema::users::dsl::*;
+    let mut conn = db_pool.get().await?;
+    Ok(users
+        .filter(client_wallet.eq(wallet.to_string()))
+        .first::<BotUser>(&mut conn)
+        .await
+        .optional()?)
+}
+
+pub async fn create_user(db_pool: &DbPool, new_user: NewBotUser) -> Result<BotUser> {
+    use crate::schema::users::dsl::*;
+    let mut conn = db_pool.get().await?;
+    Ok(diesel::insert_into(users)
+        .values(new_user)
+        .returning(all_columns)
+        .get_result::<BotUser>(&mut conn)
+        .await?)
+}
+
+pub async fn touch_last_login(db_pool: &DbPool, user_id: i32) -> Result<()> {
+    use crate::schema::users::dsl::*;
+    let mut conn = db_pool.get().await?;
+    diesel::update(users.find(user_id))
+        .set(last_login.eq(Utc::now()))
+        .execute(&mut conn)
+        .await?;
+    Ok(())
+}
+
+pub async fn get_strategy(db_pool: &DbPool, strategy_id: StrategyId) -> Result<Option<VolumeStrategyInstance>> {
+    use crate::schema::volumestrategyinstances::dsl::*;
+    let mut conn = db_pool.get().await?;
+    Ok(volumestrategyinstances
+        .filter(id.eq(strategy_id))
+        .select(VolumeStrategyInstance::as_select())
+        .first(&mut conn)
+        .await
+        .optional()?)
+}
+
+pub async fn get_latest_strategy(db_pool: &DbPool, client_id: i32) -> Result<Option<VolumeStrategyInstance>> {
+    use crate::schema::volumestrategyinstances::dsl::*;
+    let mut conn = db_pool.get().await?;
+    Ok(volumestrategyinstances
+        .filter(user_id.eq(client_id))
+        .order(id.desc())
+        .select(VolumeStrategyInstance::as_select())
+        .first(&mut conn)
+        .await
+        .optional()?)
+}
+
+pub async fn get_user_strategies(db_pool: &DbPool, client_id: i32) -> Result<Vec<VolumeStrategyInstance>> {
+    use crate::schema::volumestrategyinstances::dsl::*;
+    let mut conn = db_pool.get().await?;
+    Ok(volumestrategyinstances
+        .filter(user_id.eq(client_id))
+        .order(id.desc())
+        .select(VolumeStrategyInstance::as_select())
+        .load(&mut conn)
+        .await?)
+}
+
+pub async fn insert_strategy(db_pool: &DbPool, instance: NewVolumeStrategyInstance) -> Result<StrategyId> {
+    use crate::schema::volumestrategyinstances::dsl::*;
+    let mut conn = db_pool.get().await?;
+    Ok(diesel::insert_into(volumestrategyinstances)
+        .values(instance)
+        .returning(id)
+        .get_result(&mut conn)
+        .await?)
+}
+
+/// Stores the config of a strategy that is not running, a running one is updated by the strategy itself.
+pub async fn update_strategy(db_pool: &DbPool, instance: &VolumeStrategyInstance) -> Result<()> {
+    use crate::schema::volumestrategyinstances::dsl::*;
+    let mut conn = db_pool.get().await?;
+    diesel::update(volumestrategyinstances.filter(id.eq(instance.id)))
+        .set((
+            target_pool.eq(instance.target_pool.to_string()),
+            tranche_size_sol.eq(instance.tranche_size_sol),
+            tranche_frequency_hbs.eq(instance.tranche_frequency_hbs),
+            tranche_length_hbs.eq(instance.tranche_length_hbs),
+            agents_buying_in_tranche.eq(instance.agents_buying_in_tranche),
+            agents_selling_in_tranche.eq(instance.agents_selling_in_tranche),
+            daily_volume_sol.eq(instance.daily_volume_sol),
+            budget_days.eq(instance.budget_days),
+            max_inventory_sol.eq(instance.max_inventory_sol),
+            max_price_drift_pct.eq(instance.max_price_drift_pct),
+        ))
+        .execute(&mut conn)
+        .await?;
+    Ok(())
+}
+
+/// Marks the strategy as running from now on, returns it as it's handed over to the strategy manager.
+pub async fn mark_strategy_started(db_pool: &DbPool, strategy_id: StrategyId) -> Result<VolumeStrategyInstance> {
+    use crate::schema::volumestrategyinstances::dsl::*;
+    let mut conn = db_pool.get().await?;
+    Ok(diesel::update(volumestrategyinstances.filter(id.eq(strategy_id)))
+        .set((
+            started_at.eq(Utc::now().naive_utc()),
+            completed_at.eq(None::<chrono::NaiveDateTime>),
+            // the volume budget and the price drift count from the start
+            volume_traded_sol.eq(0.0),
+            volume_counted_until.eq(None::<chrono::NaiveDateTime>),
+            reference_price.eq(None::<f64>),
+        ))
+        .returning(VolumeStrategyInstance::as_returning())
+        .get_result(&mut conn)
+        .await?)
+}
+
+pub async fn mark_strategy_completed(db_pool: &DbPool, strategy_id: StrategyId) -> Result<()> {
+    use crate::schema::volumestrategyinstances::dsl::*;
+    let mut conn = db_pool.get().await?;
+    diesel::update(volumestrategyinstances.filter(id.eq(strategy_id)))
+        .set(completed_at.eq(Some(Utc::now().naive_utc())))
+        .execute(&mut conn)
+        .await?;
+    Ok(())
+}
+
+/// Latest events of the pools or concerning any of the wallets.
+pub async fn get_events(
+    db_pool: &DbPool,
+    pools: &[String],
+    wallets: &[String],
+    limit: i64,
+) -> Result<Vec<BotEventRecord>> {
+    use crate::schema::bot_events;
+    let mut conn = db_pool.get().await?;
+    Ok(bot_events::table
+        .filter(
+            bot_events::pool
+                .eq_any(pools)
+                .or(bot_events::wallets.overlaps_with(wallets)),
+        )
+        .order(bot_events::timestamp.desc())
+        .limit(limit)
+        .select(BotEventRecord::as_select())
+        .load(&mut conn)
+        .await?)
+}
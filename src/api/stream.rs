@@ -0,0 +1,101 @@
+use crate::api::auth::ClientSession;
+use crate::api::error::ApiResult;
+use crate::api::handlers::{get_logs_scope, status_name, LogsScope};
+use crate::api::types::LogsQuery;
+use crate::api::ApiState;
+use crate::config::constants::EVENT_STREAM_REFRESH_S;
+use crate::strategies::StreamedEvent;
+use crate::types::engine::StrategyStatus;
+use axum::extract::{Query, State};
+use axum::response::sse::{Event, KeepAlive, Sse};
+use futures::Stream;
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::convert::Infallible;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::time::{Interval, MissedTickBehavior};
+use tracing::warn;
+
+// a listener of the live events of a client
+struct EventListener {
+    state: ApiState,
+    session: ClientSession,
+    query: LogsQuery,
+    scope: LogsScope,
+    events: broadcast::Receiver<Arc<StreamedEvent>>,
+    refresh: Interval,
+}
+
+impl EventListener {
+    fn to_sse(&self, event: &StreamedEvent) -> Option<Event> {
+        Event::default()
+            .event(event.route.event_type.to_string())
+            .json_data(json!({
+                "strategy_id": self.scope.strategy_of_route(&event.route),
+                "event": event.event,
+            }))
+            .map_err(|e| warn!("Failed to stream {:?}: {:?}", event.route.event_type, e))
+            .ok()
+    }
+
+    async fn status_event(&self) -> Option<Event> {
+        let mut statuses = BTreeMap::new();
+        for strategy_id in self.scope.strategy_ids() {
+            let status = self.state.strategy_manager.get_strategy_status(strategy_id).await;
+            let details = match &status {
+                Some(StrategyStatus::Running(details) | StrategyStatus::Paused(details)) => details.clone(),
+                _ => Default::default(),
+            };
+            statuses.insert(strategy_id, json!({ "status": status_name(status.as_ref()), "details": details }));
+        }
+        Event::default().event("StrategyStatus").json_data(statuses).ok()
+    }
+
+    // the strategies started since the stream was opened are picked up with the statuses
+    async fn next_event(&mut self) -> Option<Event> {
+        loop {
+            tokio::select! {
+                received = self.events.recv() => match received {
+                    Ok(event) if self.scope.matches(&event.route) => {
+                        if let Some(event) = self.to_sse(&event) {
+                            return Some(event);
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(RecvError::Lagged(skipped)) => return Some(Event::default().event("Lagged").data(skipped.to_string())),
+                    Err(RecvError::Closed) => return None,
+                },
+                _ = self.refresh.tick() => {
+                    match get_logs_scope(&self.state, &self.session, &self.query).await {
+                        Ok(scope) => self.scope = scope,
+                        Err(e) => warn!("Failed to refresh the stream of client {}: {:?}", self.session.client_id, e),
+                    }
+                    if let Some(event) = self.status_event().await {
+                        return Some(event);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Server-sent events of the strategies of the client: pool prices and swaps, action results and
+/// receipts, deposits and withdrawals, and their statuses every [EVENT_STREAM_REFRESH_S].
+pub async fn event_stream(
+    State(state): State<ApiState>,
+    session: ClientSession,
+    Query(query): Query<LogsQuery>,
+) -> ApiResult<Sse<impl Stream<Item = Result<Event, Infallible>>>> {
+    let scope = get_logs_scope(&state, &session, &query).await?;
+    let events = state.event_stream.subscribe();
+    let mut refresh = tokio::time::interval(Duration::from_secs(EVENT_STREAM_REFRESH_S));
+    refresh.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    let listener = EventListener { state, session, query, scope, events, refresh };
+    let stream = futures::stream::unfold(listener, |mut listener| async move {
+        listener.next_event().await.map(|event| (Ok(event), listener))
+    });
+    Ok(Sse::new(stream).keep_alive(KeepAlive::default()))
+}
@@ -0,0 +1,328 @@
+use crate::analytics::StrategyStats;
+use crate::types::engine::StrategyId;
+use crate::types::volume_strategy::VolumeStrategyInstance;
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+/// A message signed by the client wallet, e.g. Phantom `signMessage`, the signature is base58 or base64 encoded.
+/// The message is built by `auth::auth_message`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignedRequest {
+    pub wallet_address: String,
+    pub signed_message: String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MessageResponse {
+    pub success: bool,
+    pub message: String,
+}
+
+impl MessageResponse {
+    pub fn ok(message: &str) -> Self {
+        Self { success: true, message: message.to_string() }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoginResponse {
+    pub success: bool,
+    pub message: String,
+    pub client_id: i32,
+    pub deposit_wallet: String,
+    pub client_subscription: i32,
+    /// Sent as `Authorization: Bearer <session_token>` by the calls without a signed message.
+    pub session_token: String,
+    pub session_expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserCreatedResponse {
+    pub success: bool,
+    pub message: String,
+    pub client_id: i32,
+    pub deposit_address: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubscriptionRequest {
+    #[serde(flatten)]
+    pub auth: SignedRequest,
+    pub token_id: String,
+    pub subscription: i32,
+    /// Informational, the subscription starts when it is stored.
+    pub subscribed_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubscriptionResponse {
+    pub success: bool,
+    pub message: String,
+    pub client_subscription: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaymentLog {
+    pub date: DateTime<Utc>,
+    pub amount: f64,
+    pub subscription: i32,
+    pub tx_hash: Option<String>,
+    pub token_id: String,
+    pub status: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaymentStatusResponse {
+    pub success: bool,
+    pub message: String,
+    pub payments: Vec<PaymentLog>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DepositAddressQuery {
+    pub wallet_address: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DepositAddressResponse {
+    pub deposit_wallet: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BotControlRequest {
+    #[serde(flatten)]
+    pub auth: SignedRequest,
+    pub client_id: i32,
+    /// 0 for the latest strategy of the client.
+    #[serde(default)]
+    pub strategy_id: StrategyId,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BotStartedResponse {
+    pub success: bool,
+    pub start_time: DateTime<Utc>,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BotStoppedResponse {
+    pub success: bool,
+    pub stop_time: DateTime<Utc>,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct BotStatusQuery {
+    pub client_id: i32,
+    #[serde(default)]
+    pub strategy_id: StrategyId,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BotStatus {
+    pub status: String,
+    pub last_start_time: DateTime<Utc>,
+    pub last_stop_time: Option<DateTime<Utc>>,
+    pub error_message: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BotStatusResponse {
+    pub success: bool,
+    pub message: String,
+    pub data: BotStatus,
+}
+
+/// Volume strategy parameters, `algorithm` is accepted for the strategy kinds to come and ignored for now.
+/// With `daily_volume_sol` the tranche size and frequency are derived and can be left out.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StrategyRequest {
+    pub client_id: i32,
+    #[serde(default)]
+    pub tranche_size_sol: f64,
+    #[serde(default)]
+    pub tranche_frequency: i64,
+    pub tranche_length: i64,
+    pub min_agents: i32,
+    /// 0 for as many as `min_agents`
+    #[serde(default)]
+    pub max_agents: i32,
+    pub proportion: Option<f64>,
+    pub algorithm: Option<String>,
+    pub target_pool: String,
+    pub daily_volume_sol: Option<f64>,
+    /// until stopped when not set
+    pub budget_days: Option<i32>,
+    pub max_inventory_sol: Option<f64>,
+    pub max_price_drift_pct: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StrategyCreatedResponse {
+    pub success: bool,
+    pub message: String,
+    pub strategy_id: StrategyId,
+}
+
+/// A strategy kind of the registry, `config_schema` lists the fields of [StrategyKindRequest::config].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StrategyKindInfo {
+    pub name: String,
+    pub title: String,
+    pub config_schema: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StrategyKindsResponse {
+    pub success: bool,
+    pub message: String,
+    pub kinds: Vec<StrategyKindInfo>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StrategyKindRequest {
+    #[serde(flatten)]
+    pub auth: SignedRequest,
+    pub client_id: i32,
+    pub config: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StrategyResponse {
+    pub strategy_id: String,
+    pub client_id: i32,
+    pub tranche_size_sol: f64,
+    pub tranche_frequency: i64,
+    pub tranche_length: i64,
+    pub min_agents: i32,
+    pub max_agents: i32,
+    pub proportion: Option<f64>,
+    pub target_pool: String,
+    pub daily_volume_sol: Option<f64>,
+    pub budget_days: Option<i32>,
+    pub volume_traded_sol: f64,
+    pub max_inventory_sol: Option<f64>,
+    pub max_price_drift_pct: Option<f64>,
+    pub reference_price: Option<f64>,
+}
+
+impl From<&VolumeStrategyInstance> for StrategyResponse {
+    fn from(instance: &VolumeStrategyInstance) -> Self {
+        Self {
+            strategy_id: instance.id.to_string(),
+            client_id: instance.user_id,
+            tranche_size_sol: instance.tranche_size_sol,
+            tranche_frequency: instance.tranche_frequency_hbs,
+            tranche_length: instance.tranche_length_hbs,
+            min_agents: instance.agents_buying_in_tranche,
+            max_agents: instance.agents_selling_in_tranche,
+            proportion: None,
+            target_pool: instance.target_pool.to_string(),
+            daily_volume_sol: instance.daily_volume_sol,
+            budget_days: instance.budget_days,
+            volume_traded_sol: instance.volume_traded_sol,
+            max_inventory_sol: instance.max_inventory_sol,
+            max_price_drift_pct: instance.max_price_drift_pct,
+            reference_price: instance.reference_price,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct LogsQuery {
+    pub client_id: Option<i32>,
+    pub strategy_id: Option<StrategyId>,
+    pub action_id: Option<String>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventLog {
+    pub id: i64,
+    pub timestamp: DateTime<Utc>,
+    pub strategy_id: Option<StrategyId>,
+    pub event_type: String,
+    pub event_data: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventsResponse {
+    pub events: Vec<EventLog>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExecutionLog {
+    pub id: String,
+    pub timestamp: DateTime<Utc>,
+    pub action_id: String,
+    pub success: bool,
+    pub result_data: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExecutionsResponse {
+    pub executions: Vec<ExecutionLog>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct StatsQuery {
+    pub start_date: Option<NaiveDate>,
+    pub end_date: Option<NaiveDate>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatsResponse {
+    pub success: bool,
+    pub message: String,
+    pub data: StrategyStats,
+}
+
+/// Everything is withdrawn without `amount_sol`, less the transfer fee.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WithdrawRequest {
+    #[serde(flatten)]
+    pub auth: SignedRequest,
+    pub destination: String,
+    pub amount_sol: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WithdrawConfirmRequest {
+    #[serde(flatten)]
+    pub auth: SignedRequest,
+    pub confirmation_token: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WithdrawResponse {
+    pub success: bool,
+    pub message: String,
+    /// Set once the withdrawal is queued.
+    pub withdrawal_id: Option<i32>,
+    /// Set when the withdrawal waits for `/api/withdraw/confirm`.
+    pub confirmation_token: Option<String>,
+    pub confirmation_expires_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct WithdrawalsQuery {
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WithdrawalLog {
+    pub id: i32,
+    pub date: DateTime<Utc>,
+    pub destination: Option<String>,
+    pub amount_sol: Option<f64>,
+    pub status: String,
+    pub tx_hash: Option<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WithdrawalsResponse {
+    pub withdrawals: Vec<WithdrawalLog>,
+}
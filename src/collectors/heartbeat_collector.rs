@@ -28,6 +28,10 @@ impl HeartbeatCollector {
 
 #[async_trait]
 impl Collector<BotEvent> for HeartbeatCollector {
+    fn name(&self) -> &'static str {
+        "heartbeat"
+    }
+
     async fn get_event_stream(&self) -> anyhow::Result<EventStream<'_, BotEvent>> {
         info!("Initializing HeartbeatCollector event stream");
         let (tx, rx) = mpsc::unbounded_channel();
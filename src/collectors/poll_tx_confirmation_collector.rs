@@ -1,5 +1,6 @@
 use crate::collectors::prices_heartbeat_streamer::start_prices_stream;
 use crate::config::app_context::AppContext;
+use crate::config::constants::TX_CONFIRMATIONS_COLLECTOR;
 use crate::solana::rpc_pool::RpcClientPool;
 use crate::types::engine::{Collector, EventStream};
 use crate::types::events::{BlockchainEvent, BotEvent, ExecutionReceipt};
@@ -36,6 +37,10 @@ impl PollRpcForTxConfirmationsCollector {
 /// This implementation uses the [PubsubClient](PubsubClient) to subscribe to new logs.
 #[async_trait]
 impl Collector<BotEvent> for PollRpcForTxConfirmationsCollector {
+    fn name(&self) -> &'static str {
+        TX_CONFIRMATIONS_COLLECTOR
+    }
+
     async fn get_event_stream(&self) -> Result<EventStream<'_, BotEvent>> {
         let (tx, rx) = mpsc::unbounded_channel();
 
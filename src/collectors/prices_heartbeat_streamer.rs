@@ -1,4 +1,6 @@
 use crate::config::app_context::AppContext;
+use crate::config::constants::REALTIME_FEED_COLLECTOR;
+use crate::supervisor::CollectorHealth;
 use crate::storage::cache::RedisPool;
 use crate::storage::persistent::DbPool;
 use crate::types::events::{BlockchainEvent, BotEvent};
@@ -11,7 +13,7 @@ use solana_client::rpc_client::RpcClient;
 use solana_farm_client::client::FarmClient;
 use solana_sdk::pubkey::Pubkey;
 use solana_sdk::signature::Keypair;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::str::FromStr;
 use std::sync::Arc;
 use std::time::Duration;
@@ -19,6 +21,27 @@ use tokio::sync::mpsc;
 use tokio::task;
 use tracing::{debug, info};
 
+// Pools whose prices come from the geyser account updates, polling is the fallback for the others:
+// no geyser, the realtime feed is down, or the vaults weren't updated since the subscription
+async fn pools_priced_by_geyser(context: &AppContext) -> HashSet<Pubkey> {
+    let feed_is_up = context
+        .collector_health
+        .get(REALTIME_FEED_COLLECTOR)
+        .is_some_and(|state| state.health == CollectorHealth::Healthy);
+    if context.geyser_pool.clients.is_empty() || !feed_is_up {
+        return HashSet::new();
+    }
+    context
+        .cache
+        .pool_accounts
+        .lock()
+        .await
+        .iter()
+        .filter(|(_, state)| state.is_complete())
+        .map(|(pool, _)| *pool)
+        .collect()
+}
+
 pub async fn start_prices_stream(
     context: &AppContext,
     transmitter: mpsc::UnboundedSender<Result<BotEvent>>,
@@ -44,7 +67,8 @@ pub async fn start_prices_stream(
                 .iter()
                 .map(|(_, pool)| pool.id)
                 .collect::<Vec<Pubkey>>();
-            for pool in trading_pairs.iter() {
+            let streamed_pools = pools_priced_by_geyser(&context).await;
+            for pool in trading_pairs.iter().filter(|pool| !streamed_pools.contains(pool)) {
                 match context.rpc_pool.get_pool_price(&pool).await {
                     Ok(pool_price_update) => {
                         let db = db.clone();
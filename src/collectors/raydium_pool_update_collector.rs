@@ -1,5 +1,6 @@
 use crate::collectors::prices_heartbeat_streamer::start_prices_stream;
 use crate::config::app_context::AppContext;
+use crate::config::constants::RAYDIUM_PRICES_COLLECTOR;
 use crate::solana::rpc_pool::RpcClientPool;
 use crate::types::engine::{Collector, EventStream};
 use crate::types::events::{BlockchainEvent, BotEvent};
@@ -32,6 +33,10 @@ impl RaydiumPriceCollector {
 /// This implementation uses the [PubsubClient](PubsubClient) to subscribe to new logs.
 #[async_trait]
 impl Collector<BotEvent> for RaydiumPriceCollector {
+    fn name(&self) -> &'static str {
+        RAYDIUM_PRICES_COLLECTOR
+    }
+
     async fn get_event_stream(&self) -> Result<EventStream<'_, BotEvent>> {
         let (tx, rx) = mpsc::unbounded_channel();
 
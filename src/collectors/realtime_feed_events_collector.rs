@@ -1,9 +1,10 @@
 use crate::collectors::tx_stream::blockchain_stream;
-use crate::collectors::tx_stream::types::GeyserFeedEvent::{Account, Transaction, TxStatusUpdate};
+use crate::collectors::tx_stream::types::GeyserFeedEvent::{Account, RpcTransaction, Slot, Transaction, TxStatusUpdate};
 use crate::collectors::tx_stream::types::{AccountPretty, GeyserFeedEvent, TransactionPretty};
 use crate::config::app_context::AppContext;
 use crate::config::settings::Mode;
 use crate::solana::constants;
+use crate::solana::pool_accounts;
 use crate::solana::rpc_pool::RpcClientPool;
 use crate::solana::tx_parser::{is_tx_a_sol_transfer, is_tx_a_token_transfer, parse_tx_for_set_compute_unit_price, parse_tx_for_swaps};
 use crate::storage::cache::RedisPool;
@@ -27,7 +28,7 @@ use solana_transaction_status::{
     EncodedTransaction, UiInstruction, UiMessage, UiParsedInstruction, UiParsedMessage,
     UiPartiallyDecodedInstruction,
 };
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::future::ready;
 use std::hash::Hash;
 use std::sync::Arc;
@@ -36,9 +37,9 @@ use tokio::sync::{mpsc, Mutex, RwLock};
 use tokio_stream::wrappers::UnboundedReceiverStream;
 use tracing::{debug, error, info, trace, warn};
 use url::quirks::hash;
-use yellowstone_grpc_proto::prelude::{SubscribeUpdateTransaction, SubscribeUpdateTransactionStatus};
-use crate::config::constants::{BALANCE_CHANGE_THRESHOLD_SOL, BASE_TX_FEE_SOL};
-use crate::solana::pool::extract_pool_from_init_tx;
+use yellowstone_grpc_proto::prelude::{CommitmentLevel, SubscribeUpdateSlot, SubscribeUpdateTransaction, SubscribeUpdateTransactionStatus};
+use crate::config::constants::{BALANCE_CHANGE_THRESHOLD_SOL, BASE_TX_FEE_SOL, REALTIME_FEED_COLLECTOR};
+use crate::solana::pool::{extract_pool_from_init_tx, extract_pool_from_tx};
 
 /// A collector that listens to raydium pool events logs based on a [Filter](Filter),
 /// and generates a stream of [events](Log).
@@ -112,7 +113,20 @@ impl RealtimeFeedEventsCollector {
     }
 
     pub async fn parse_transaction(&self, tx_update: SubscribeUpdateTransaction) -> Option<Vec<BotEvent>> {
-        let tx: TransactionPretty = tx_update.clone().into();
+        let new_pool = extract_pool_from_init_tx(&tx_update);
+        self.parse_transaction_pretty(tx_update.into(), new_pool).await
+    }
+
+    pub async fn parse_rpc_transaction(&self, tx: TransactionPretty) -> Option<Vec<BotEvent>> {
+        let new_pool = extract_pool_from_tx(&tx, None);
+        self.parse_transaction_pretty(tx, new_pool).await
+    }
+
+    async fn parse_transaction_pretty(
+        &self,
+        tx: TransactionPretty,
+        new_pool: Option<(RaydiumPool, RaydiumPoolPriceUpdate)>,
+    ) -> Option<Vec<BotEvent>> {
         //failing early
         if tx.is_vote {
             return None;
@@ -171,6 +185,7 @@ impl RealtimeFeedEventsCollector {
                         base_reserve: updated_base_reserve_ui,
                         quote_reserve: updated_quote_reserve_ui,
                         created_at: chrono::Utc::now().naive_utc(),
+                        ..Default::default()
                     };
                     pre_swap_pool_state.base_reserve = updated_base_reserve_ui;
                     pre_swap_pool_state.quote_reserve = updated_quote_reserve_ui;
@@ -190,6 +205,12 @@ impl RealtimeFeedEventsCollector {
     );
                         return None;
                     }
+                    // the reserves are updated anyway, the deltas commute, but a late swap isn't emitted
+                    if !self.context.cache.slot_tracker.lock().await.accept(client_pool.id, tx.slot, tx.tx_index) {
+                        debug!("Late swap {} of pool {} at slot {}, skipping", tx.signature, client_pool.id, tx.slot);
+                        continue;
+                    }
+                    let price_update = price_update.with_position(tx.slot, tx.tx_index);
                     info!("Pool update wih Geyser: {:?}", price_update);
                     events.push(BotEvent::BlockchainEvent(
                         BlockchainEvent::RaydiumSwapEvent(RaydiumSwapEvent {
@@ -201,6 +222,8 @@ impl RealtimeFeedEventsCollector {
                             quote_amount: quote_amount_ui,
                             price: quote_amount_ui / base_amount_ui,
                             volume: quote_amount_ui,
+                            slot: tx.slot,
+                            tx_index: tx.tx_index,
                             created_at: chrono::Utc::now(),
                         }),
                     ));
@@ -209,7 +232,7 @@ impl RealtimeFeedEventsCollector {
         }
 
         // 3. parse new pool creation
-        if let Some((new_pool, price_update)) = extract_pool_from_init_tx(&tx_update) {
+        if let Some((new_pool, price_update)) = new_pool {
             let mut tokens = self.context.cache.target_tokens.lock().await;
             if !tokens.contains(&new_pool.base_mint) {
                 tokens.put(new_pool.base_mint, new_pool.id);
@@ -221,7 +244,80 @@ impl RealtimeFeedEventsCollector {
         Some(events).filter(|events| !events.is_empty())
     }
 
+    // The AMM and vault updates of the traded pools are decoded into prices, no RPC polling needed
+    pub async fn parse_pool_account(&self, acc: &AccountPretty) -> Option<Vec<BotEvent>> {
+        let same_price_threshold = self.context.get_settings().await.collector.same_price_threshold;
+        // held until the state is updated, a pool unmonitored meanwhile doesn't get its entry back
+        let target_pools = self.context.cache.target_pools.read().await;
+        let pool = target_pools
+            .values()
+            .find(|pool| pool_accounts::is_pool_account(pool, &acc.pubkey))
+            .cloned()?;
+        let price_update = {
+            let mut pool_accounts = self.context.cache.pool_accounts.lock().await;
+            let state = pool_accounts.entry(pool.id).or_default();
+            if !state.apply(&pool, acc) {
+                return Some(vec![]);
+            }
+            let Some(price_update) = state.price_update(&pool) else {
+                return Some(vec![]);
+            };
+            if state.is_same_price(price_update.price, same_price_threshold) {
+                return Some(vec![]);
+            }
+            if !self.context.cache.slot_tracker.lock().await.accept(pool.id, acc.slot, None) {
+                return Some(vec![]);
+            }
+            state.set_emitted(price_update.price);
+            price_update
+        };
+        drop(target_pools);
+        let price_update = price_update.with_position(acc.slot, None);
+        self.context
+            .cache
+            .target_pools_prices
+            .lock()
+            .await
+            .insert(pool.id, price_update.clone());
+        trace!("Price update using geyser accounts: {:?}", price_update);
+        Some(vec![BotEvent::BlockchainEvent(
+            BlockchainEvent::RaydiumHeartbeatPriceUpdate(price_update),
+        )])
+    }
+
+    // A confirmed slot settles the processed ones, the pools with events on a dropped fork are
+    // rolled back. Their prices are polled again until the vaults are streamed anew
+    pub async fn parse_slot_update(&self, slot: SubscribeUpdateSlot) -> Option<Vec<BotEvent>> {
+        let confirmed = slot.status == CommitmentLevel::Confirmed as i32;
+        let rolled_back = self.context.cache.slot_tracker.lock().await.on_slot(slot.slot, slot.parent, confirmed);
+        // pool -> dropped slots
+        let mut rolled_back_pools: BTreeMap<Pubkey, Vec<u64>> = BTreeMap::new();
+        for (pool, dropped_slot) in rolled_back {
+            rolled_back_pools.entry(pool).or_default().push(dropped_slot);
+        }
+        let mut events = vec![];
+        for (pool, dropped_slots) in rolled_back_pools {
+            warn!("Events of pool {} in slots {:?} were on a dropped fork, rolling back", pool, dropped_slots);
+            // the streamed vaults may be from the fork as well, the state is rebuilt from the next updates
+            self.context.cache.pool_accounts.lock().await.remove(&pool);
+            let mut prices = self.context.cache.target_pools_prices.lock().await;
+            if prices
+                .get(&pool)
+                .and_then(|price| price.slot)
+                .is_some_and(|price_slot| dropped_slots.contains(&(price_slot as u64)))
+            {
+                prices.remove(&pool);
+            }
+            drop(prices);
+            events.push(BotEvent::BlockchainEvent(BlockchainEvent::PoolEventsRolledBack(pool, dropped_slots)));
+        }
+        Some(events).filter(|events| !events.is_empty())
+    }
+
     pub async fn parse_account(&self, acc: AccountPretty) -> Option<Vec<BotEvent>> {
+        if let Some(events) = self.parse_pool_account(&acc).await {
+            return Some(events);
+        }
         let prev_balance = self.context.cache.get_account(&acc.pubkey).await.flatten().map(|a| a.lamports).unwrap_or(0);
         self.context.cache.update_account(acc.pubkey, Some(acc.clone())).await;
         let mut events = vec![BotEvent::BlockchainEvent(AccountUpdate(acc.clone()))];
@@ -249,6 +345,10 @@ impl RealtimeFeedEventsCollector {
 /// This implementation uses the [PubsubClient](PubsubClient) to subscribe to new logs.
 #[async_trait]
 impl Collector<BotEvent> for RealtimeFeedEventsCollector {
+    fn name(&self) -> &'static str {
+        REALTIME_FEED_COLLECTOR
+    }
+
     async fn get_event_stream(&self) -> Result<EventStream<'_, BotEvent>> {
         info!("Initializing PoolEventsCollector event stream");
         let geyser_stream = blockchain_stream(&self.context).await?;
@@ -260,6 +360,8 @@ impl Collector<BotEvent> for RealtimeFeedEventsCollector {
                         Transaction(tx_update) => self.parse_transaction(tx_update).await,
                         Account(acc) => self.parse_account(acc).await,
                         TxStatusUpdate(tx_status) => self.parse_status_upate_transaction(tx_status).await,
+                        RpcTransaction(tx) => self.parse_rpc_transaction(tx).await,
+                        Slot(slot) => self.parse_slot_update(slot).await,
                     }
                 }
             })
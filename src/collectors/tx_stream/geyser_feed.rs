@@ -1,3 +1,4 @@
+use anyhow::{bail, Result};
 use async_trait::async_trait;
 use diesel::internal::derives::multiconnection::chrono::Utc;
 use futures::Stream;
@@ -36,6 +37,8 @@ use yellowstone_grpc_proto::prelude::{
 use crate::collectors::tx_stream::types::{AccountPretty, GeyserFeedEvent, TransactionPretty};
 use crate::config::app_context::AppContext;
 use crate::config::settings::ProviderName;
+use crate::solana::constants;
+use crate::solana::pool_accounts;
 use crate::solana::geyser_pool::GeyserNamedClient;
 use crate::storage;
 use crate::storage::cache::RedisPool;
@@ -50,35 +53,100 @@ use yellowstone_grpc_proto::prelude::subscribe_update::UpdateOneof;
 use yellowstone_grpc_proto::prost::bytes::Bytes;
 
 
-// Helper function to get updated account filters
-async fn get_account_filters(context: &AppContext) -> HashMap<String, SubscribeRequestFilterAccounts> {
-    let accounts: Vec<String> = context
+/// What the subscription is built from, it's resent when any of it changes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct SubscriptionKeys {
+    accounts: Vec<String>,
+    pools: Vec<String>,
+    pool_accounts: Vec<String>,
+}
+
+async fn get_subscription_keys(context: &AppContext) -> SubscriptionKeys {
+    let mut accounts: Vec<String> = context
         .cache
         .get_accounts()
         .await
         .iter()
         .map(|u| u.to_string())
         .collect();
+    accounts.sort();
+    let target_pools = context.cache.target_pools.read().await;
+    let mut pools: Vec<String> = target_pools.keys().map(|pool| pool.to_string()).collect();
+    pools.sort();
+    // the AMM and the vaults, their updates are decoded into the prices
+    let mut pool_accounts: Vec<String> = target_pools
+        .values()
+        .flat_map(pool_accounts::pool_accounts)
+        .map(|account| account.to_string())
+        .collect();
+    pool_accounts.sort();
+    SubscriptionKeys { accounts, pools, pool_accounts }
+}
 
-    if !accounts.is_empty() {
-        hashmap! {
-            "client".to_owned() => SubscribeRequestFilterAccounts {
-                account: accounts,
-                owner: vec![],
-                filters: vec![],
-            },
+// Builds the subscription from what the strategies need: the programs we parse (new pools, swaps),
+// the traded pools and the monitored wallets, instead of every non-vote transaction
+async fn build_subscribe_request(context: &AppContext, keys: &SubscriptionKeys) -> SubscribeRequest {
+    let settings = context.get_settings().await;
+    let transactions_filter = if settings.collector.geyser_subscribe_all_transactions.unwrap_or(false) {
+        SubscribeRequestFilterTransactions {
+            vote: Some(false),
+            ..Default::default()
         }
     } else {
-        HashMap::default()
+        let mut account_include = settings
+            .collector
+            .geyser_program_ids
+            .clone()
+            .unwrap_or_else(|| vec![constants::RAYDIUM_V4_PROGRAM_ID.to_string(), constants::PUMP_FUN_PROGRAM_ID.to_string()]);
+        account_include.extend(keys.pools.iter().cloned());
+        account_include.extend(keys.accounts.iter().cloned());
+        account_include.sort();
+        account_include.dedup();
+        SubscribeRequestFilterTransactions {
+            vote: Some(false),
+            account_include,
+            ..Default::default()
+        }
+    };
+
+    let mut accounts = HashMap::default();
+    if !keys.accounts.is_empty() {
+        accounts.insert("client".to_owned(), SubscribeRequestFilterAccounts {
+            account: keys.accounts.clone(),
+            owner: vec![],
+            filters: vec![],
+        });
+    }
+    if !keys.pool_accounts.is_empty() {
+        accounts.insert("pools".to_owned(), SubscribeRequestFilterAccounts {
+            account: keys.pool_accounts.clone(),
+            owner: vec![],
+            filters: vec![],
+        });
+    }
+
+    SubscribeRequest {
+        accounts,
+        // the slot statuses are for the fork detection of the processed events
+        slots: hashmap! { "client".to_owned() => SubscribeRequestFilterSlots::default() },
+        transactions: hashmap! { "client".to_owned() => transactions_filter.clone() },
+        transactions_status: hashmap! { "".to_owned() => transactions_filter },
+        blocks: Default::default(),
+        blocks_meta: HashMap::default(),
+        entry: Default::default(),
+        commitment: None,
+        accounts_data_slice: vec![],
+        ping: None,
     }
 }
 
 
 pub async fn geyser_feed(
     context: AppContext,
+    provider: ProviderName,
     geyser_client: Arc<Mutex<GeyserGrpcClient<InterceptorXToken>>>,
-    transmitter: mpsc::UnboundedSender<GeyserFeedEvent>,
-) {
+    transmitter: mpsc::UnboundedSender<(ProviderName, GeyserFeedEvent)>,
+) -> Result<()> {
     let mut average_delay = 0.0;
     let mut number_of_blocks = 0;
     let mut last_block_time = 0;
@@ -86,104 +154,40 @@ pub async fn geyser_feed(
     let account_tx = context.geyser_resubscribe_account_tx_notify.clone();
     let mut account_rx = account_tx.subscribe();
 
-
-    let blocks = hashmap! {
-        "client".to_owned() => SubscribeRequestFilterBlocks::default()
-    };
-
-    let _blocks_meta = hashmap! {
-        "client".to_owned() => SubscribeRequestFilterBlocksMeta {},
-    };
-
-    let transactions = hashmap! {
-        "client".to_owned() => SubscribeRequestFilterTransactions {
-            vote: Some(false),
-            ..Default::default()
-        }
-    };
-
-    let transactions_status = hashmap! {
-            "".to_owned() => SubscribeRequestFilterTransactions {
-                vote: Some(false),
-                ..Default::default()
-            } 
-    };
-
-    let mut users_count = context.cache.get_accounts_count().await;
-    let user_accounts: Vec<String> = context
-        .cache
-        .get_accounts()
-        .await
-        .iter()
-        .map(|u| u.to_string())
-        .collect();
-
-    let accounts = if user_accounts.len() > 0 {
-        hashmap! { "client".to_owned() => SubscribeRequestFilterAccounts {
-            account: user_accounts,
-            owner: vec ! [],
-            filters: vec ! [],
-            },
-        }
-    } else {
-        HashMap::default()
-    };
-
     info!("starting_stream");
-    let blocks_clone = blocks.clone();
+    let mut cached_keys = get_subscription_keys(&context).await;
+    let request = build_subscribe_request(&context, &cached_keys).await;
     let (mut subscribe_tx, mut stream) = geyser_client
         .lock()
         .await
-        .subscribe_with_request(Some(SubscribeRequest {
-            accounts,
-            slots: Default::default(),
-            transactions: transactions.clone(),
-            transactions_status: transactions_status.clone(),
-            blocks: Default::default(),
-            blocks_meta: HashMap::default(),
-            entry: Default::default(),
-            commitment: None,
-            accounts_data_slice: vec![],
-            ping: None,
-        }))
-        .await
-        .unwrap();
+        .subscribe_with_request(Some(request))
+        .await?;
 
 
     let subscribe_tx = Arc::new(Mutex::new(subscribe_tx));
 
     let subscribe_tx_clone = Arc::clone(&subscribe_tx);
-    // Watch for changes in the accounts and update the subscription immediately
-    let mut cached_accounts = context.cache.get_accounts().await;
-    cached_accounts.sort();
-    tokio::spawn(async move {
+    // Watch for changes in the monitored accounts and pools and update the subscription immediately
+    let resubscribe_handle = tokio::spawn(async move {
         while account_rx.changed().await.is_ok() {
-            let mut accs = context.cache.get_accounts().await;
-            accs.sort();
-            if accs == cached_accounts {
-                trace!("Accounts have not changed, skipping subscription update, monitoring: {:?}", accs);
+            let keys = get_subscription_keys(&context).await;
+            if keys == cached_keys {
+                trace!("Subscription has not changed, skipping update, monitoring: {:?}", keys);
                 continue;
             }
-            trace!("Accounts have changed, updating subscription, monitoring: {:?}", accs);
-            cached_accounts = accs;
-            let transactions = transactions.clone();
-            let transactions_status = transactions_status.clone();
+            trace!("Subscription has changed, updating it, monitoring: {:?}", keys);
+            let request = build_subscribe_request(&context, &keys).await;
+            cached_keys = keys;
             // Dynamically send the updated subscription request when accounts change
-            let updated_accounts = get_account_filters(&context).await;
-            subscribe_tx_clone.lock().await
-                .send(SubscribeRequest {
-                    accounts: updated_accounts,
-                    slots: Default::default(),
-                    transactions,
-                    transactions_status,
-                    blocks: Default::default(),
-                    ..Default::default()
-                })
-                .await
-                .map_err(GeyserGrpcClientError::SubscribeSendError)
-                .unwrap();
+            if let Err(e) = subscribe_tx_clone.lock().await.send(request).await {
+                // the stream is broken as well, the feed is going to be restarted
+                error!("Error updating geyser subscription: {:?}", e);
+                break;
+            }
         }
     });
+    // the resubscription task must not outlive the stream
+    let _resubscribe_guard = AbortOnDrop(resubscribe_handle);
 
 
     while let Some(message) = stream.next().await {
@@ -192,7 +196,7 @@ pub async fn geyser_feed(
                 match msg.update_oneof {
                     Some(UpdateOneof::Block(block)) => {
                         let current_time = Utc::now().timestamp_millis();
-                        let block_time = block.block_time.unwrap();
+                        let Some(block_time) = block.block_time else { continue };
                         number_of_blocks += 1;
                         let delay_ms = current_time - block_time.timestamp * 1000;
                         average_delay = (average_delay * (number_of_blocks - 1) as f64
@@ -200,7 +204,7 @@ pub async fn geyser_feed(
                             / number_of_blocks as f64;
                         trace!(
                             "Block: {:?}, Block Time: {}, delay: {}, average: {}",
-                            block.block_height.unwrap(),
+                            block.block_height,
                             block_time.timestamp,
                             delay_ms,
                             average_delay
@@ -215,16 +219,26 @@ pub async fn geyser_feed(
                                 account
                                 );
                         let acc: AccountPretty = account.into();
-                        transmitter.send(GeyserFeedEvent::Account(acc)).unwrap();
+                        if transmitter.send((provider.clone(), GeyserFeedEvent::Account(acc))).is_err() {
+                            return Ok(());
+                        }
 
                         continue;
                     }
+                    Some(UpdateOneof::Slot(slot)) => {
+                        if transmitter.send((provider.clone(), GeyserFeedEvent::Slot(slot))).is_err() {
+                            return Ok(());
+                        }
+                        continue;
+                    }
                     Some(UpdateOneof::Transaction(tx)) => {
                         // info!(
                         //     "new transaction update: filters {:?}, transaction: {:#?}",
                         //     msg.filters, tx
                         // );
-                        transmitter.send(GeyserFeedEvent::Transaction(tx)).unwrap();
+                        if transmitter.send((provider.clone(), GeyserFeedEvent::Transaction(tx))).is_err() {
+                            return Ok(());
+                        }
                         continue;
                     }
                     Some(UpdateOneof::TransactionStatus(status)) => {
@@ -232,7 +246,9 @@ pub async fn geyser_feed(
                         //     "new transaction update: filters {:?}, transaction status: {:?}",
                         //     msg.filters, status
                         // );
-                        transmitter.send(GeyserFeedEvent::TxStatusUpdate(status)).unwrap();
+                        if transmitter.send((provider.clone(), GeyserFeedEvent::TxStatusUpdate(status))).is_err() {
+                            return Ok(());
+                        }
                         continue;
                     }
                     Some(UpdateOneof::Ping(_)) => {
@@ -243,18 +259,22 @@ pub async fn geyser_feed(
                                 ping: Some(SubscribeRequestPing { id: 1 }),
                                 ..Default::default()
                             })
-                            .await
-                            .unwrap();
+                            .await?;
                     }
                     _ => {}
                 }
                 // info!("new message: {msg:?}")
             }
-            Err(error) => {
-                error!("error: {error:?}");
-                break;
-            }
+            Err(error) => bail!("geyser stream error: {:?}", error),
         }
     }
-    info!("stream closed");
+    bail!("geyser stream closed")
+}
+
+struct AbortOnDrop(tokio::task::JoinHandle<()>);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
 }
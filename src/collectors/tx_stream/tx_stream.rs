@@ -1,4 +1,6 @@
 use crate::collectors::tx_stream::geyser_feed::geyser_feed;
+use crate::collectors::tx_stream::ws_feed::ws_feed;
+use crate::config::settings::ProviderName;
 use crate::collectors::tx_stream::types::{GeyserFeedEvent, TransactionPretty};
 use crate::config::app_context::AppContext;
 use crate::solana::geyser_pool::GeyserClientPool;
@@ -11,6 +13,7 @@ use crate::{solana, utils};
 use anyhow::Result;
 use chrono::Utc;
 use futures::stream::Stream;
+use std::future::Future;
 use solana_sdk::signature::Signature;
 use solana_sdk::transaction::Transaction;
 use std::collections::{HashSet, VecDeque};
@@ -20,51 +23,126 @@ use tokio::time::{sleep, Duration};
 use tokio_stream::wrappers::UnboundedReceiverStream;
 use tokio_stream::StreamExt;
 use tracing::field::debug;
-use tracing::{debug, info, instrument, trace};
+use tracing::{debug, info, instrument, trace, warn};
 use tracing_subscriber::layer::Context;
-use crate::config::constants::GEYSER_TX_FEED_BUFFER_CAPACITY;
+use crate::config::constants::{
+    BLOCKCHAIN_FEED_STALE_AFTER_SECS, GEYSER_FEED_STABLE_AFTER_SECS, GEYSER_TX_FEED_BUFFER_CAPACITY,
+};
+use crate::supervisor::Backoff;
 
 // this one is aggregated stream - chooses the fastest from the available  geyser(s) and websocket(s)
 pub async fn blockchain_stream(
     context: &AppContext,
 ) -> Result<EventStream<'static, GeyserFeedEvent>> {
-    let mut tx_buffer = CircularBufferWithLookupByValue::new(GEYSER_TX_FEED_BUFFER_CAPACITY);
+    // shared with the websocket feeds, they don't fetch the transactions a geyser already sent
+    let tx_buffer = Arc::new(Mutex::new(CircularBufferWithLookupByValue::new(GEYSER_TX_FEED_BUFFER_CAPACITY)));
     let (tx, mut rx) = mpsc::unbounded_channel();
     // Initialize Geyser streams
-    for geyser in &context.geyser_pool.clients {
-        let geyser_client = geyser.1.clone();
-        let tx = tx.clone();
-        let context_clone = context.clone();
-        tokio::spawn(async move {
-            geyser_feed(context_clone, geyser_client, tx.clone()).await;
+    for (provider, geyser_client) in &context.geyser_pool.clients {
+        let geyser_client = geyser_client.clone();
+        let context = context.clone();
+        spawn_restarting_feed(provider.clone(), tx.clone(), move |provider, tx| {
+            geyser_feed(context.clone(), provider, geyser_client.clone(), tx)
         });
     }
+    // Websocket feeds race with the geysers, or replace them for plain RPC nodes
+    if let Some(ws_pool) = &context.ws_pool {
+        for (provider, ws_client) in &ws_pool.clients {
+            let ws_client = ws_client.clone();
+            let context = context.clone();
+            let tx_buffer = tx_buffer.clone();
+            spawn_restarting_feed(provider.clone(), tx.clone(), move |provider, tx| {
+                ws_feed(context.clone(), provider, ws_client.clone(), tx_buffer.clone(), tx)
+            });
+        }
+    }
+    let stats = context.geyser_pool.stats.clone();
+    // the feeds retry on their own, the stream ends once they all went quiet so that the supervisor reports it down
+    let feed = UnboundedReceiverStream::new(rx)
+        .timeout(Duration::from_secs(BLOCKCHAIN_FEED_STALE_AFTER_SECS))
+        .map_while(|message| match message {
+            Ok(message) => Some(message),
+            Err(_) => {
+                warn!("No message from any feed for {}s", BLOCKCHAIN_FEED_STALE_AFTER_SECS);
+                None
+            }
+        });
+    // the endpoints race, the first copy of a transaction wins and the provider is credited for it
     let stream_with_dupes_removed =
-        StreamExt::filter_map(UnboundedReceiverStream::new(rx), move |e| match &e {
-            GeyserFeedEvent::Transaction(tx) => {
-                let signature = tx.clone().transaction.map(|tx|
-                    Signature::try_from(tx.signature.as_slice()).ok()).flatten()?;
-                if tx_buffer.contains_key(&signature) {
-                    return None;
-                }
-                tx_buffer.insert(signature, Utc::now().timestamp_millis());
-                trace!("tx: {:?}", tx);
-                Some(e)
+        StreamExt::filter_map(feed, move |(provider, e)| {
+            if !stats.record_message(&provider, e.slot()) {
+                return None;
             }
-            GeyserFeedEvent::TxStatusUpdate(tx) => {
-                let sig = Signature::try_from(tx.signature.as_slice()).expect("valid signature");
-                if tx_buffer.contains_key(&sig) {
-                    return None;
+            let mut tx_buffer = tx_buffer.lock().unwrap();
+            match &e {
+                GeyserFeedEvent::Transaction(tx) => {
+                    let signature = tx.clone().transaction.map(|tx|
+                        Signature::try_from(tx.signature.as_slice()).ok()).flatten()?;
+                    if tx_buffer.contains_key(&signature) {
+                        return None;
+                    }
+                    tx_buffer.insert(signature, Utc::now().timestamp_millis());
+                    stats.record_first_seen(&provider);
+                    trace!("tx from {}: {:?}", provider, tx);
+                    Some(e)
+                }
+                GeyserFeedEvent::TxStatusUpdate(tx) => {
+                    let sig = Signature::try_from(tx.signature.as_slice()).expect("valid signature");
+                    if tx_buffer.contains_key(&sig) {
+                        return None;
+                    }
+                    tx_buffer.insert(sig, Utc::now().timestamp_millis());
+                    stats.record_first_seen(&provider);
+                    trace!("tx status update from {}: {:?}", provider, tx);
+                    Some(e)
+                }
+                GeyserFeedEvent::RpcTransaction(tx) => {
+                    if tx_buffer.contains_key(&tx.signature) {
+                        return None;
+                    }
+                    tx_buffer.insert(tx.signature, Utc::now().timestamp_millis());
+                    stats.record_first_seen(&provider);
+                    trace!("rpc tx from {}: {:?}", provider, tx.signature);
+                    Some(e)
+                }
+                // every endpoint reports the slots, the tracker doesn't mind the duplicates
+                GeyserFeedEvent::Slot(_) => Some(e),
+                GeyserFeedEvent::Account(i) => {
+                    trace!("account from {}: {:?}", provider, i);
+                    Some(e)
                 }
-                tx_buffer.insert(sig, Utc::now().timestamp_millis());
-                trace!("tx status update: {:?}", tx);
-                Some(e)
-            }
-            GeyserFeedEvent::Account(i) => {
-                trace!("account: {:?}", i);
-                Some(e)
             }
         });
 
     Ok(Box::pin(stream_with_dupes_removed))
 }
+
+// One failing endpoint must not take the others down, each feed is restarted on its own
+fn spawn_restarting_feed<F, Fut>(
+    provider: ProviderName,
+    tx: mpsc::UnboundedSender<(ProviderName, GeyserFeedEvent)>,
+    feed: F,
+) where
+    F: Fn(ProviderName, mpsc::UnboundedSender<(ProviderName, GeyserFeedEvent)>) -> Fut + Send + 'static,
+    Fut: Future<Output=Result<()>> + Send,
+{
+    tokio::spawn(async move {
+        let mut backoff = Backoff::default();
+        while !tx.is_closed() {
+            let started = Utc::now();
+            // a stream ended by the supervisor stops its feeds, even the silent ones
+            let result = tokio::select! {
+                _ = tx.closed() => break,
+                result = feed(provider.clone(), tx.clone()) => result,
+            };
+            if let Err(e) = result {
+                if Utc::now() - started > chrono::Duration::seconds(GEYSER_FEED_STABLE_AFTER_SECS) {
+                    backoff.reset();
+                }
+                let delay = backoff.next_delay();
+                warn!("Feed {} failed: {:?}, reconnecting in {:?}", provider, e, delay);
+                sleep(delay).await;
+            }
+        }
+    });
+}
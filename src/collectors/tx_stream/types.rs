@@ -17,19 +17,36 @@ use solana_transaction_status::option_serializer::OptionSerializer;
 use spl_token::solana_program::program_pack::Pack;
 use tokio::sync::{Mutex, RwLock};
 use yellowstone_grpc_proto::geyser::SubscribeUpdateTransaction;
-use yellowstone_grpc_proto::prelude::{SubscribeUpdateAccount, SubscribeUpdateTransactionStatus};
+use yellowstone_grpc_proto::prelude::{SubscribeUpdateAccount, SubscribeUpdateSlot, SubscribeUpdateTransactionStatus};
 #[derive(Clone)]
 pub enum GeyserFeedEvent {
     Transaction(SubscribeUpdateTransaction),
     TxStatusUpdate(SubscribeUpdateTransactionStatus),
     Account(AccountPretty),
+    /// A transaction fetched over RPC after a websocket logs notification, when there's no geyser.
+    RpcTransaction(TransactionPretty),
+    /// Slot status changes, the confirmed ones settle the events of the processed slots.
+    Slot(SubscribeUpdateSlot),
+}
+
+impl GeyserFeedEvent {
+    pub fn slot(&self) -> u64 {
+        match self {
+            GeyserFeedEvent::Transaction(tx) => tx.slot,
+            GeyserFeedEvent::TxStatusUpdate(status) => status.slot,
+            GeyserFeedEvent::Account(account) => account.slot,
+            GeyserFeedEvent::RpcTransaction(tx) => tx.slot,
+            GeyserFeedEvent::Slot(slot) => slot.slot,
+        }
+    }
 }
 
 #[allow(dead_code)]
 #[derive(Clone)]
 pub struct TransactionPretty {
     provider_name: ProviderName,
-    slot: u64,
+    pub(crate) slot: u64,
+    pub(crate) tx_index: Option<u64>,
     pub(crate) signature: Signature,
     pub(crate) is_vote: bool,
     pub(crate) tx: EncodedTransactionWithStatusMeta,
@@ -58,6 +75,7 @@ impl fmt::Debug for TransactionPretty {
 
         f.debug_struct("TransactionPretty")
             .field("slot", &self.slot)
+            .field("tx_index", &self.tx_index)
             .field("signature", &self.signature)
             .field("is_vote", &self.is_vote)
             .field("tx", &TxWrap(&self.tx))
@@ -70,6 +88,7 @@ impl From<SubscribeUpdateTransaction> for TransactionPretty {
         let tx = transaction.expect("should be defined");
         Self {
             slot,
+            tx_index: Some(tx.index),
             provider_name: "unknown geyser".to_string(),
             signature: Signature::try_from(tx.signature.as_slice()).expect("valid signature"),
             is_vote: tx.is_vote,
@@ -81,24 +100,38 @@ impl From<SubscribeUpdateTransaction> for TransactionPretty {
     }
 }
 
-impl From<EncodedConfirmedTransactionWithStatusMeta> for TransactionPretty {
-    fn from(tx: EncodedConfirmedTransactionWithStatusMeta) -> Self {
+// a transaction the RPC node can't decode is skipped, not a reason to stop the feed
+impl TryFrom<EncodedConfirmedTransactionWithStatusMeta> for TransactionPretty {
+    type Error = anyhow::Error;
+
+    fn try_from(tx: EncodedConfirmedTransactionWithStatusMeta) -> anyhow::Result<Self> {
         let encoded_tx = tx.transaction.transaction;
-        let signature_str = match encoded_tx.clone() {
-            EncodedTransaction::Json(json_ui_tx) => json_ui_tx.signatures[0].clone(),
-            _ => panic!("expected json encoding"),
+        let signature = match &encoded_tx {
+            EncodedTransaction::Json(json_ui_tx) => json_ui_tx
+                .signatures
+                .first()
+                .ok_or(anyhow::anyhow!("Transaction without signatures"))?
+                .parse()?,
+            binary_tx => *binary_tx
+                .decode()
+                .ok_or(anyhow::anyhow!("Undecodable transaction"))?
+                .signatures
+                .first()
+                .ok_or(anyhow::anyhow!("Transaction without signatures"))?,
         };
-        Self {
+        Ok(Self {
             slot: tx.slot,
+            tx_index: None,
             provider_name: "unknown ws".to_string(),
-            signature: signature_str.parse().expect("valid signature"),
-            is_vote: tx.transaction.meta.is_some(),
+            signature,
+            // votes are not fetched, they don't mention the programs we subscribe to
+            is_vote: false,
             tx: EncodedTransactionWithStatusMeta {
                 transaction: encoded_tx,
                 meta: tx.transaction.meta,
                 version: None,
             },
-        }
+        })
     }
 }
 
@@ -106,16 +139,16 @@ impl From<EncodedConfirmedTransactionWithStatusMeta> for TransactionPretty {
 #[allow(dead_code)]
 pub struct AccountPretty {
     is_startup: bool,
-    slot: u64,
+    pub(crate) slot: u64,
     pub(crate) pubkey: Pubkey,
     pub(crate) lamports: u64,
     owner: Pubkey,
     executable: bool,
     rent_epoch: u64,
     data_encoded: String,
-    data: Vec<u8>,
+    pub(crate) data: Vec<u8>,
     pub token_unpacked_data: Option<SplTokenAccount>,
-    write_version: u64,
+    pub(crate) write_version: u64,
     pub(crate) txn_signature: String,
 }
 
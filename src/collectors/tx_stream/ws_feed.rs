@@ -1,64 +1,123 @@
+use anyhow::{bail, Result};
 use futures_util::StreamExt;
 use solana_client::nonblocking::pubsub_client::PubsubClient;
-use solana_client::rpc_client::RpcClient;
-use solana_client::rpc_config::{
-    RpcTransactionConfig, RpcTransactionLogsConfig, RpcTransactionLogsFilter,
-};
+use solana_client::rpc_config::{RpcTransactionLogsConfig, RpcTransactionLogsFilter};
 use solana_sdk::commitment_config::CommitmentConfig;
 use solana_sdk::pubkey::Pubkey;
 use solana_sdk::signature::Signature;
 use solana_transaction_status::UiTransactionEncoding;
 use std::str::FromStr;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
-use crate::collectors::tx_stream::types::TransactionPretty;
+use crate::collectors::tx_stream::types::{GeyserFeedEvent, TransactionPretty};
+use crate::config::app_context::AppContext;
 use crate::config::constants::WS_FEED_COMMITMENT_LEVEL;
 use crate::config::settings::ProviderName;
-use crate::solana::rpc_pool::RpcClientPool;
-use crate::solana::ws_pool::WsNamedClient;
-use crate::storage;
-use tokio::sync::{mpsc, Mutex};
-use tracing::field::debug;
-use tracing::warn;
-use tracing::{debug, error};
-use yellowstone_grpc_client::{GeyserGrpcClient, InterceptorXToken};
+use crate::solana::constants::{RAYDIUM_POOL_INIT_INSTRUCTION, RAYDIUM_V4_PROGRAM_ID_PUBKEY};
+use crate::utils::circular_buffer_w_rev::CircularBufferWithLookupByValue;
+use tokio::sync::mpsc;
+use tracing::{debug, trace, warn};
 
+async fn get_target_pools(context: &AppContext) -> Vec<Pubkey> {
+    let mut pools: Vec<Pubkey> = context.cache.target_pools.read().await.keys().cloned().collect();
+    pools.sort();
+    pools
+}
+
+// Fallback feed for plain RPC nodes: Raydium logs are streamed over websocket, the transactions of
+// new pools and of the traded pools' swaps are fetched over RPC and sent as the geyser ones would be.
+// `seen` holds the signatures the stream already got, from a geyser most of the time
 pub async fn ws_feed(
-    ws_client: WsNamedClient,
-    rpc_client_pool: RpcClientPool,
-    transmitter: mpsc::UnboundedSender<TransactionPretty>,
-) {
-    let (mut stream, _) = match ws_client
-        .1
-        .logs_subscribe(
-            RpcTransactionLogsFilter::All,
-            RpcTransactionLogsConfig {
-                commitment: Some(CommitmentConfig {
-                    commitment: WS_FEED_COMMITMENT_LEVEL,
-                }),
-            },
-        )
-        .await
-    {
-        Ok(stream) => stream,
-        Err(e) => {
-            panic!("Failed to subscribe to ws logs: {:?}", e);
-        }
-    };
-    while let Some(logs) = stream.next().await {
-        let transmitter = transmitter.clone();
-        let rpc_client_pool_clone = rpc_client_pool.clone();
-        let signature_str = logs.value.signature.clone();
-        let signature = Signature::from_str(&signature_str).unwrap();
-        debug!(
-            "New pool tx detected, signature: {:?}, querying",
-            signature_str
-        );
-        if let Ok(tx) = rpc_client_pool_clone
-            .get_transaction_with_config(&signature)
-            .await
+    context: AppContext,
+    provider: ProviderName,
+    ws_client: Arc<PubsubClient>,
+    seen: Arc<Mutex<CircularBufferWithLookupByValue<Signature, i64>>>,
+    transmitter: mpsc::UnboundedSender<(ProviderName, GeyserFeedEvent)>,
+) -> Result<()> {
+    let mut resubscribe_rx = context.geyser_resubscribe_account_tx_notify.subscribe();
+    loop {
+        let pools = get_target_pools(&context).await;
+        let config = RpcTransactionLogsConfig {
+            commitment: Some(CommitmentConfig {
+                commitment: WS_FEED_COMMITMENT_LEVEL,
+            }),
+        };
+        let mut streams = vec![];
+        let mut unsubscribes = vec![];
+        // the program logs are for the new pools only, every Raydium tx is there so they aren't fetched otherwise
+        for (mentioned, is_program) in std::iter::once((*RAYDIUM_V4_PROGRAM_ID_PUBKEY, true))
+            .chain(pools.iter().map(|pool| (*pool, false)))
         {
-            transmitter.send(TransactionPretty::from(tx)).unwrap()
+            let (stream, unsubscribe) = ws_client
+                .logs_subscribe(RpcTransactionLogsFilter::Mentions(vec![mentioned.to_string()]), config.clone())
+                .await?;
+            streams.push(stream.map(move |logs| (is_program, logs)).boxed());
+            unsubscribes.push(unsubscribe);
+        }
+        debug!("Ws feed {} subscribed to Raydium logs and {} pools", provider, pools.len());
+        let mut logs_stream = futures::stream::select_all(streams);
+
+        loop {
+            tokio::select! {
+                logs = logs_stream.next() => {
+                    let Some((is_program, logs)) = logs else {
+                        bail!("ws logs stream closed");
+                    };
+                    if logs.value.err.is_some() {
+                        continue;
+                    }
+                    let is_new_pool = logs.value.logs.iter().any(|log| log.contains(RAYDIUM_POOL_INIT_INSTRUCTION));
+                    if is_program && !is_new_pool {
+                        continue;
+                    }
+                    let Ok(signature) = Signature::from_str(&logs.value.signature) else {
+                        continue;
+                    };
+                    if seen.lock().unwrap().contains_key(&signature) {
+                        trace!("Ws feed {} skipping tx {}, already seen", provider, signature);
+                        continue;
+                    }
+                    // new pools are parsed from the json encoding, swaps from the binary one
+                    let encoding = if is_new_pool {
+                        UiTransactionEncoding::JsonParsed
+                    } else {
+                        UiTransactionEncoding::Base64
+                    };
+                    trace!("Ws feed {} fetching tx {}", provider, signature);
+                    let rpc_pool = context.rpc_pool.clone();
+                    let transmitter = transmitter.clone();
+                    let provider = provider.clone();
+                    tokio::spawn(async move {
+                        let tx = match rpc_pool.get_transaction_with_encoding(&signature, encoding).await {
+                            Ok(tx) => tx,
+                            Err(e) => {
+                                warn!("Ws feed {} failed to fetch tx {}: {:?}", provider, signature, e);
+                                return;
+                            }
+                        };
+                        match TransactionPretty::try_from(tx) {
+                            Ok(tx) => {
+                                let tx = tx.with_provider_name(provider.clone());
+                                transmitter.send((provider, GeyserFeedEvent::RpcTransaction(tx))).ok();
+                            }
+                            Err(e) => warn!("Ws feed {} can't read tx {}: {:?}", provider, signature, e),
+                        }
+                    });
+                }
+                changed = resubscribe_rx.changed() => {
+                    if changed.is_err() || transmitter.is_closed() {
+                        return Ok(());
+                    }
+                    if get_target_pools(&context).await != pools {
+                        break;
+                    }
+                }
+            }
+        }
+
+        drop(logs_stream);
+        for unsubscribe in unsubscribes {
+            unsubscribe().await;
         }
     }
 }
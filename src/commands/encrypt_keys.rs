@@ -0,0 +1,102 @@
+use crate::config::constants::ENCRYPTED_KEY_PREFIX;
+use crate::schema::{agent_seeds, deposit_wallets, snipingstrategyinstances, traders, users};
+use crate::storage::persistent::DbPool;
+use crate::utils::crypto::{decrypt_private_key, encrypt_private_key, master_key};
+use anyhow::{bail, Result};
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+
+/// Encrypts the private keys stored in clear with the master key, the encrypted ones are skipped
+/// so it can be run again after an interruption.
+pub async fn run(db_pool: &DbPool) -> Result<()> {
+    if master_key()?.is_none() {
+        bail!("No master key to encrypt with");
+    }
+    let mut conn = db_pool.get().await?;
+    let encrypted_prefix = format!("{}%", ENCRYPTED_KEY_PREFIX);
+
+    let rows = users::table
+        .filter(users::wallet_private_key.not_like(&encrypted_prefix))
+        .select((users::id, users::wallet_private_key))
+        .load::<(i32, String)>(&mut conn)
+        .await?;
+    for (id, private_key) in rows.iter() {
+        diesel::update(users::table.find(id))
+            .set(users::wallet_private_key.eq(encrypt(private_key)?))
+            .execute(&mut conn)
+            .await?;
+    }
+    println!("Encrypted {} user keys", rows.len());
+
+    // the derived agent wallets have no key stored
+    let rows = traders::table
+        .filter(traders::private_key.is_not_null())
+        .filter(traders::private_key.not_like(&encrypted_prefix))
+        .select((traders::id, traders::private_key.assume_not_null()))
+        .load::<(i32, String)>(&mut conn)
+        .await?;
+    for (id, private_key) in rows.iter() {
+        diesel::update(traders::table.find(id))
+            .set(traders::private_key.eq(encrypt(private_key)?))
+            .execute(&mut conn)
+            .await?;
+    }
+    println!("Encrypted {} trader keys", rows.len());
+
+    let rows = snipingstrategyinstances::table
+        .filter(snipingstrategyinstances::sniper_private_key.not_like(&encrypted_prefix))
+        .select((snipingstrategyinstances::id, snipingstrategyinstances::sniper_private_key))
+        .load::<(i32, String)>(&mut conn)
+        .await?;
+    for (id, private_key) in rows.iter() {
+        diesel::update(snipingstrategyinstances::table.find(id))
+            .set(snipingstrategyinstances::sniper_private_key.eq(encrypt(private_key)?))
+            .execute(&mut conn)
+            .await?;
+    }
+    println!("Encrypted {} sniper keys", rows.len());
+
+    let rows = deposit_wallets::table
+        .filter(deposit_wallets::private_key.not_like(&encrypted_prefix))
+        .select((deposit_wallets::id, deposit_wallets::private_key))
+        .load::<(i32, String)>(&mut conn)
+        .await?;
+    for (id, private_key) in rows.iter() {
+        diesel::update(deposit_wallets::table.find(id))
+            .set(deposit_wallets::private_key.eq(encrypt(private_key)?))
+            .execute(&mut conn)
+            .await?;
+    }
+    println!("Encrypted {} deposit wallet keys", rows.len());
+
+    let rows = agent_seeds::table
+        .filter(agent_seeds::seed.not_like(&encrypted_prefix))
+        .select((agent_seeds::strategy_instance_id, agent_seeds::seed))
+        .load::<(i32, String)>(&mut conn)
+        .await?;
+    for (id, seed) in rows.iter() {
+        diesel::update(agent_seeds::table.find(id))
+            .set(agent_seeds::seed.eq(encrypt(seed)?))
+            .execute(&mut conn)
+            .await?;
+    }
+    println!("Encrypted {} agent seeds", rows.len());
+    Ok(())
+}
+
+// checked before it replaces the key in clear
+fn encrypt(private_key: &str) -> Result<String> {
+    let encrypted = encrypt_private_key(private_key)?;
+    if decrypt_private_key(&encrypted)? != private_key {
+        bail!("The encrypted key doesn't decrypt back");
+    }
+    Ok(encrypted)
+}
+
+/// Encrypts a private key read from stdin, for `sniper_privkey` and `executor.private_keys` of the config.
+pub fn encrypt_config_key() -> Result<()> {
+    let mut private_key = String::new();
+    std::io::stdin().read_line(&mut private_key)?;
+    println!("{}", encrypt(private_key.trim())?);
+    Ok(())
+}
@@ -0,0 +1,27 @@
+mod encrypt_keys;
+mod recover_funds;
+mod reclaim_rent;
+
+use crate::config::settings::Settings;
+use crate::storage::persistent;
+use anyhow::{anyhow, bail, Result};
+
+/// One-shot maintenance commands, `solana-bot <command>` runs one instead of the bot.
+pub async fn run(command: &str) -> Result<()> {
+    let settings = Settings::new("config").map_err(|e| anyhow!("Invalid config: {}", e))?;
+    let db_pool = persistent::connect(&settings.storage.database_uri);
+    match command {
+        "encrypt-keys" => encrypt_keys::run(&db_pool).await,
+        "encrypt-key" => encrypt_keys::encrypt_config_key(),
+        "recover-funds" => {
+            let dry_run = std::env::args().any(|arg| arg == "--dry-run");
+            let include_active = std::env::args().any(|arg| arg == "--include-active");
+            recover_funds::run(&settings, &db_pool, dry_run, include_active).await
+        }
+        "reclaim-rent" => {
+            let dry_run = std::env::args().any(|arg| arg == "--dry-run");
+            reclaim_rent::run(&settings, &db_pool, dry_run).await
+        }
+        _ => bail!("Unknown command {}, available: encrypt-keys, encrypt-key, recover-funds [--dry-run] [--include-active], reclaim-rent [--dry-run]", command),
+    }
+}
@@ -0,0 +1,33 @@
+use crate::config::constants::RPC_COMMITMENT_LEVEL;
+use crate::config::settings::Settings;
+use crate::solana::rent_reclaim::reclaim_rent;
+use crate::solana::rpc_pool::RpcClientPool;
+use crate::solana::signer::SignerRegistry;
+use crate::storage::bot_wallets::load_bot_wallets;
+use crate::storage::persistent::DbPool;
+use crate::utils::decimals::lamports_to_sol;
+use anyhow::{bail, Result};
+
+/// Closes the empty token accounts of the bot wallets no running strategy uses now instead of waiting for the
+/// periodic run.
+pub async fn run(settings: &Settings, db_pool: &DbPool, dry_run: bool) -> Result<()> {
+    let rpc_pool = RpcClientPool::new(&settings.rpcs, RPC_COMMITMENT_LEVEL);
+    let signers = SignerRegistry::new(settings.executor.remote_signer.as_ref())?;
+    let wallets = load_bot_wallets(db_pool, false).await?;
+    println!("{} wallets to scan{}", wallets.len(), if dry_run { ", dry run" } else { "" });
+
+    let report = reclaim_rent(&rpc_pool, &signers, &wallets, dry_run).await?;
+    for (strategy, (accounts, lamports)) in report.reclaimed.iter() {
+        println!("{}: {} empty token accounts, {} SOL", strategy, accounts, lamports_to_sol(*lamports));
+    }
+    println!(
+        "{} SOL in {} token accounts {}",
+        lamports_to_sol(report.lamports()),
+        report.accounts(),
+        if dry_run { "to reclaim" } else { "reclaimed" }
+    );
+    if report.failed > 0 {
+        bail!("{} wallets or batches failed, run it again to retry", report.failed);
+    }
+    Ok(())
+}
@@ -0,0 +1,269 @@
+use crate::config::constants::{RECOVER_FUNDS_DUST_SOL, RPC_COMMITMENT_LEVEL};
+use crate::config::settings::Settings;
+use crate::solana::constants::WSOL_MINT_PUBKEY;
+use crate::solana::rpc_pool::{OwnedTokenAccount, RpcClientPool};
+use crate::solana::signer::{sign_transaction, SignerRegistry};
+use crate::storage::bot_wallets::{load_bot_wallets, BotWallet};
+use crate::storage::persistent::DbPool;
+use crate::types::pool::RaydiumPool;
+use crate::utils::decimals::{lamports_to_sol, tokens_to_ui_amount_with_decimals_f64};
+use anyhow::{anyhow, bail, Result};
+use solana_farm_client::raydium_sdk::{make_swap_fixed_in_instruction, LiquiditySwapFixedInInstructionParamsV4, UserKeys};
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::system_instruction;
+use solana_sdk::transaction::Transaction;
+use spl_associated_token_account::get_associated_token_address;
+use spl_associated_token_account::instruction::{create_associated_token_account, create_associated_token_account_idempotent};
+use std::collections::HashMap;
+
+enum TokenStep {
+    /// Dust sold for SOL at any price in one of the pools the wallet traded, then closed, the proceeds wait on the
+    /// wSOL account
+    Sell(OwnedTokenAccount, RaydiumPool),
+    /// No pool to sell in or worth more than dust, the tokens go to the owner's token account
+    Transfer(OwnedTokenAccount),
+    /// Empty, only the rent is reclaimed, or the wSOL account unwrapped to the owner
+    Close(OwnedTokenAccount),
+}
+
+impl TokenStep {
+    fn describe(&self) -> String {
+        match self {
+            TokenStep::Sell(account, pool) => format!("sell {} of {} in pool {} and close", account.amount, account.mint, pool.id),
+            TokenStep::Transfer(account) => format!("transfer {} of {} to the owner and close", account.amount, account.mint),
+            TokenStep::Close(account) if account.mint == *WSOL_MINT_PUBKEY => "unwrap and close wSOL account".to_string(),
+            TokenStep::Close(account) => format!("close empty account of {}", account.mint),
+        }
+    }
+}
+
+/// Scans every trader, derived agent and sniper wallet of the stopped strategies, of the running ones too with
+/// `include_active`, and sweeps what's left on them to the owner's main wallet: dust tokens are sold where the wallet
+/// traded them and the others transferred, empty token accounts are closed for their rent, then SOL is sent.
+pub async fn run(settings: &Settings, db_pool: &DbPool, dry_run: bool, include_active: bool) -> Result<()> {
+    let rpc_pool = RpcClientPool::new(&settings.rpcs, RPC_COMMITMENT_LEVEL);
+    let signers = SignerRegistry::new(settings.executor.remote_signer.as_ref())?;
+    let wallets = load_bot_wallets(db_pool, include_active).await?;
+    println!("{} wallets to scan{}", wallets.len(), if dry_run { ", dry run" } else { "" });
+
+    let mut pools: HashMap<Pubkey, Option<RaydiumPool>> = HashMap::new();
+    let mut reserves: HashMap<Pubkey, Option<(f64, f64)>> = HashMap::new();
+    let (mut total_sol, mut total_accounts, mut failed) = (0, 0, 0);
+    for wallet in wallets.iter() {
+        let pubkey = wallet.key.pubkey();
+        let sol = rpc_pool.get_balance(&pubkey).await.unwrap_or(0);
+        let token_accounts = match rpc_pool.get_token_accounts(&pubkey).await {
+            Ok(token_accounts) => token_accounts,
+            Err(e) => {
+                println!("{} {}: can't list token accounts: {}", wallet.label, pubkey, e);
+                failed += 1;
+                continue;
+            }
+        };
+        if sol == 0 && token_accounts.is_empty() {
+            continue;
+        }
+
+        let mut sellable = HashMap::new();
+        for pool_id in wallet.pools.iter() {
+            if !pools.contains_key(pool_id) {
+                pools.insert(*pool_id, rpc_pool.get_pool_details(pool_id).await.ok());
+            }
+            if let Some(Some(pool)) = pools.get(pool_id) {
+                if pool.base_mint != *WSOL_MINT_PUBKEY && pool.quote_mint == *WSOL_MINT_PUBKEY {
+                    if !reserves.contains_key(pool_id) {
+                        reserves.insert(*pool_id, rpc_pool.get_pool_reserves_f64(pool_id).await.ok());
+                    }
+                    sellable.insert(pool.base_mint, pool.clone());
+                }
+            }
+        }
+        let steps: Vec<TokenStep> = token_accounts
+            .into_iter()
+            .map(|account| match sellable.get(&account.mint) {
+                _ if account.amount == 0 || account.mint == *WSOL_MINT_PUBKEY => TokenStep::Close(account),
+                Some(pool) if is_dust(&account, pool, reserves.get(&pool.id).copied().flatten()) => {
+                    TokenStep::Sell(account, pool.clone())
+                }
+                _ => TokenStep::Transfer(account),
+            })
+            .collect();
+        let steps = with_wsol_closed_last(steps, get_associated_token_address(&pubkey, &WSOL_MINT_PUBKEY));
+
+        println!(
+            "{} of {} {}: {} SOL, {} token accounts, owner {}",
+            wallet.label, wallet.strategy, pubkey, lamports_to_sol(sol), steps.len(), wallet.owner.pubkey()
+        );
+        for step in steps.iter() {
+            println!("  - {}", step.describe());
+        }
+        total_sol += sol;
+        total_accounts += steps.len();
+        if dry_run {
+            continue;
+        }
+
+        for step in steps.iter() {
+            if let Err(e) = recover_tokens(&rpc_pool, &signers, wallet, step).await {
+                println!("  ! {} failed: {}", step.describe(), e);
+                failed += 1;
+            }
+        }
+        if let Err(e) = sweep_sol(&rpc_pool, &signers, wallet).await {
+            println!("  ! SOL sweep failed: {}", e);
+            failed += 1;
+        }
+    }
+
+    println!(
+        "{} SOL and {} token accounts {}",
+        lamports_to_sol(total_sol),
+        total_accounts,
+        if dry_run { "to recover" } else { "processed" }
+    );
+    if failed > 0 {
+        bail!("{} recovery steps failed, run it again to retry", failed);
+    }
+    Ok(())
+}
+
+/// The sells unwrap into the wSOL account, so it's closed once after them, even when the first sell creates it.
+fn with_wsol_closed_last(steps: Vec<TokenStep>, wsol_ata: Pubkey) -> Vec<TokenStep> {
+    let sells = steps.iter().any(|step| matches!(step, TokenStep::Sell(..)));
+    let (mut steps, mut wsol_closes): (Vec<_>, Vec<_>) = steps
+        .into_iter()
+        .partition(|step| !matches!(step, TokenStep::Close(account) if account.mint == *WSOL_MINT_PUBKEY));
+    if sells && !wsol_closes.iter().any(|step| matches!(step, TokenStep::Close(account) if account.address == wsol_ata)) {
+        wsol_closes.push(TokenStep::Close(OwnedTokenAccount {
+            address: wsol_ata,
+            mint: *WSOL_MINT_PUBKEY,
+            amount: 0,
+            lamports: 0,
+        }));
+    }
+    steps.append(&mut wsol_closes);
+    steps
+}
+
+/// Whether selling the tokens would bring less than the dust threshold, unknown reserves aren't.
+fn is_dust(account: &OwnedTokenAccount, pool: &RaydiumPool, reserves: Option<(f64, f64)>) -> bool {
+    let Some((base_reserve, quote_reserve)) = reserves else {
+        return false;
+    };
+    let amount = tokens_to_ui_amount_with_decimals_f64(account.amount, pool.base_decimals);
+    sale_proceeds_sol(amount, base_reserve, quote_reserve) < RECOVER_FUNDS_DUST_SOL
+}
+
+/// SOL out of selling `amount` tokens into the constant product reserves, before the pool fee.
+fn sale_proceeds_sol(amount: f64, base_reserve: f64, quote_reserve: f64) -> f64 {
+    if base_reserve + amount <= 0.0 {
+        return 0.0;
+    }
+    quote_reserve * amount / (base_reserve + amount)
+}
+
+async fn recover_tokens(rpc_pool: &RpcClientPool, signers: &SignerRegistry, wallet: &BotWallet, step: &TokenStep) -> Result<()> {
+    let pubkey = wallet.key.pubkey();
+    let owner = wallet.owner.pubkey();
+    let mut instructions = vec![];
+    match step {
+        TokenStep::Sell(account, pool) => {
+            let wsol_ata = get_associated_token_address(&pubkey, &WSOL_MINT_PUBKEY);
+            if !rpc_pool.account_exists(&wsol_ata).await? {
+                instructions.push(create_associated_token_account(&owner, &pubkey, &WSOL_MINT_PUBKEY, &spl_token::ID));
+            }
+            let keys = pool.to_liquidity_keys();
+            let version = keys.version;
+            instructions.push(make_swap_fixed_in_instruction(
+                LiquiditySwapFixedInInstructionParamsV4::new(
+                    keys,
+                    UserKeys::new(account.address, wsol_ata, pubkey),
+                    account.amount,
+                    // dust only, whatever it's worth
+                    0,
+                ),
+                version,
+            ));
+        }
+        TokenStep::Transfer(account) => {
+            let owner_ata = get_associated_token_address(&owner, &account.mint);
+            instructions.push(create_associated_token_account_idempotent(&owner, &owner, &account.mint, &spl_token::ID));
+            instructions.push(spl_token::instruction::transfer(
+                &spl_token::ID,
+                &account.address,
+                &owner_ata,
+                &pubkey,
+                &[],
+                account.amount,
+            )?);
+        }
+        // nothing to unwrap when every sell failed before creating it
+        TokenStep::Close(account) if account.mint == *WSOL_MINT_PUBKEY && !rpc_pool.account_exists(&account.address).await? => {
+            return Ok(());
+        }
+        TokenStep::Close(_) => {}
+    }
+    let (TokenStep::Sell(account, _) | TokenStep::Transfer(account) | TokenStep::Close(account)) = step;
+    instructions.push(spl_token::instruction::close_account(&spl_token::ID, &account.address, &owner, &pubkey, &[])?);
+    send(rpc_pool, signers, wallet, &instructions).await
+}
+
+/// The owner pays the fee, so the whole balance goes and the account is closed.
+async fn sweep_sol(rpc_pool: &RpcClientPool, signers: &SignerRegistry, wallet: &BotWallet) -> Result<()> {
+    let pubkey = wallet.key.pubkey();
+    let sol = rpc_pool.get_balance(&pubkey).await?;
+    if sol == 0 {
+        return Ok(());
+    }
+    let instructions = vec![system_instruction::transfer(&pubkey, &wallet.owner.pubkey(), sol)];
+    send(rpc_pool, signers, wallet, &instructions).await
+}
+
+async fn send(rpc_pool: &RpcClientPool, signers: &SignerRegistry, wallet: &BotWallet, instructions: &[Instruction]) -> Result<()> {
+    let client = rpc_pool.get_a_client().ok_or_else(|| anyhow!("No rpc configured"))?;
+    let recent_blockhash = client.get_latest_blockhash().await?;
+    let sender = signers.signer_for(&wallet.key.pubkey(), Some(&wallet.key))?;
+    let fee_payer = signers.signer_for(&wallet.owner.pubkey(), Some(&wallet.owner))?;
+    let mut tx = Transaction::new_with_payer(instructions, Some(&wallet.owner.pubkey()));
+    sign_transaction(&mut tx, recent_blockhash, &[sender.as_ref(), fee_payer.as_ref()]).await?;
+    let signature = rpc_pool.send_and_confirm_tx(&tx).await?;
+    println!("  sent {}", signature);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sale_proceeds() {
+        assert_eq!(sale_proceeds_sol(0.0, 1_000.0, 10.0), 0.0);
+        // 1% of the reserve out for 1/99 of the reserve in
+        assert!((sale_proceeds_sol(10.0, 990.0, 100.0) - 1.0).abs() < 1e-9);
+        // a big sale moves the price, it never gets the whole quote reserve
+        assert!(sale_proceeds_sol(1e12, 1_000.0, 10.0) < 10.0);
+        assert!(sale_proceeds_sol(1.0, 1_000_000.0, 10.0) < RECOVER_FUNDS_DUST_SOL);
+    }
+
+    #[test]
+    fn test_wsol_closed_after_the_sells() {
+        let account = |mint: Pubkey, amount: u64| OwnedTokenAccount { address: Pubkey::new_unique(), mint, amount, lamports: 0 };
+        let wsol = account(*WSOL_MINT_PUBKEY, 5);
+        let pool = RaydiumPool::default();
+        let steps = vec![
+            TokenStep::Close(wsol.clone()),
+            TokenStep::Sell(account(Pubkey::new_unique(), 10), pool.clone()),
+            TokenStep::Close(account(Pubkey::new_unique(), 0)),
+        ];
+        let steps = with_wsol_closed_last(steps, wsol.address);
+        assert_eq!(steps.len(), 3);
+        assert!(matches!(&steps[2], TokenStep::Close(closed) if *closed == wsol));
+
+        // created by the first sell
+        let wsol_ata = Pubkey::new_unique();
+        let steps = with_wsol_closed_last(vec![TokenStep::Sell(account(Pubkey::new_unique(), 10), pool)], wsol_ata);
+        assert!(matches!(&steps[1], TokenStep::Close(closed) if closed.address == wsol_ata));
+        assert_eq!(with_wsol_closed_last(vec![], wsol_ata).len(), 0);
+    }
+}
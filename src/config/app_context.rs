@@ -9,14 +9,18 @@ use crate::schema::volumestrategyinstances::dsl::volumestrategyinstances;
 use crate::solana::bloxroute::BloxRoute;
 use crate::solana::geyser_pool::GeyserClientPool;
 use crate::solana::rpc_pool::RpcClientPool;
+use crate::solana::signer::SignerRegistry;
 use crate::solana::ws_pool::PubsubClientPool;
 use crate::storage::cache::RedisPool;
 use crate::storage::persistent::DbPool;
+use crate::supervisor::CollectorHealthRegistry;
 use crate::tg_bot::bot_config::BotConfig;
 use crate::types::actions::SolanaAction;
 use crate::types::engine::StrategyManager;
 use crate::types::events::BotEvent;
+use crate::types::keys::KeypairClonable;
 use crate::types::pool::{RaydiumPool, RaydiumPoolPriceUpdate};
+use crate::utils::bounded_queue::QueueStatsRegistry;
 use crate::utils::fee_metrics::FeeMetrics;
 use crate::{solana, storage, tg_bot};
 use anyhow::{Result};
@@ -58,11 +62,14 @@ pub struct AppContext {
     pub(crate) ws_pool: Option<PubsubClientPool>,
     pub(crate) geyser_pool: GeyserClientPool,
     pub(crate) bloxroute: BloxRoute,
+    pub(crate) signers: SignerRegistry,
     pub(crate) db_pool: DbPool,
     pub(crate) redis_pool: RedisPool,
     pub(crate) cache: OperationalCache,
     pub(crate) tg_bot: Option<Bot>,
     pub geyser_resubscribe_account_tx_notify: watch::Sender<()>,
+    pub(crate) queue_stats: QueueStatsRegistry,
+    pub(crate) collector_health: CollectorHealthRegistry,
 }
 
 impl Debug for AppContext {
@@ -115,6 +122,8 @@ impl AppContext {
             .with_bloxroute_optimal_fee(settings.executor.use_bloxroute_optimal_fee)
             .with_bloxroute_trader_api(settings.executor.use_bloxroute_trader_api)
             .with_fee_percentile(settings.executor.bloxroute_fee_percentile);
+        let signers = SignerRegistry::new(settings.executor.remote_signer.as_ref())
+            .expect("Invalid remote signer config");
         let db_pool = storage::persistent::connect(&settings.storage.database_uri);
         let redis_pool = storage::cache::connect(&settings.storage.redis_uri);
 
@@ -165,11 +174,14 @@ impl AppContext {
             ws_pool,
             geyser_pool,
             bloxroute,
+            signers,
             db_pool,
             redis_pool,
             cache: OperationalCache::new(target_pools, target_pools_prices),
             tg_bot: tgbot.map(|tgbot| Bot::with_client(tgbot.telegram_token, client_from_env())),
             geyser_resubscribe_account_tx_notify: watch::channel(()).0,
+            queue_stats: QueueStatsRegistry::default(),
+            collector_health: CollectorHealthRegistry::default(),
         }
     }
     pub async fn start_telegram_bot(
@@ -205,7 +217,11 @@ impl AppContext {
             .executor
             .private_keys
             .iter()
-            .map(|private_key| Keypair::from_base58_string(private_key))
+            .map(|private_key| {
+                KeypairClonable::new_from_privkey(private_key)
+                    .expect("Invalid executor.private_keys")
+                    .into()
+            })
             .collect::<Vec<Keypair>>()
     }
 }
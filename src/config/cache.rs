@@ -1,6 +1,9 @@
 use crate::config::constants::{BASE_TX_FEE_SOL, CACHED_TX_SIGNATURES_BUFFER_CAPACITY, RT_FEE_PERCENTILE, RT_FEE_PERCENTILE_CAPACITY, RT_FEE_ROLLING_AVERAGE_SIZE};
 use crate::types::pool::{RaydiumPool, RaydiumPoolPriceUpdate};
+use crate::solana::pool_accounts::PoolAccountsState;
+use crate::solana::slot_tracker::SlotTracker;
 use crate::types::bot_user::BotUser;
+use crate::types::engine::StrategyId;
 use crate::utils::fee_metrics::FeeMetrics;
 use anyhow::{anyhow, Result};
 use solana_sdk::pubkey::Pubkey;
@@ -29,6 +32,11 @@ pub struct OperationalCache {
     // token_id, pool_id
     pub target_tokens: Arc<Mutex<LruCache<Pubkey,Pubkey>>>,
     pub target_pools_prices: Arc<Mutex<HashMap<Pubkey, RaydiumPoolPriceUpdate>>>,
+    // pool_id, strategies monitoring the pool, the pools configured at startup have no entry and stay monitored
+    pub pool_monitors: Arc<Mutex<HashMap<Pubkey, HashSet<StrategyId>>>>,
+    // pool_id, AMM and vaults state streamed by geyser
+    pub pool_accounts: Arc<Mutex<HashMap<Pubkey, PoolAccountsState>>>,
+    pub slot_tracker: Arc<Mutex<SlotTracker>>,
     pub accounts: Arc<Mutex<HashMap<Pubkey, Option<AccountPretty>>>>,
 }
 
@@ -53,6 +61,9 @@ impl OperationalCache {
             target_pools: Arc::new(RwLock::new(target_pools)),
             target_tokens: Arc::new(Mutex::new(LruCache::new(NonZeroUsize::try_from(CACHED_TX_SIGNATURES_BUFFER_CAPACITY).unwrap()))),
             target_pools_prices: Arc::new(Mutex::new(target_pools_prices)),
+            pool_monitors: Arc::new(Mutex::new(HashMap::new())),
+            pool_accounts: Arc::new(Mutex::new(HashMap::new())),
+            slot_tracker: Arc::new(Mutex::new(SlotTracker::default())),
             accounts: Arc::new(Mutex::new(HashMap::new())),
         }
     }
@@ -156,6 +167,14 @@ impl OperationalCache {
         balances.insert(acc, account);
     }
 
+    /// A monitored account that was closed is monitored on without data, the next read fetches it again.
+    pub async fn mark_account_closed(&self, acc: &Pubkey) {
+        let mut balances = self.accounts.lock().await;
+        if let Some(account) = balances.get_mut(acc) {
+            *account = None;
+        }
+    }
+
     pub async fn drop_account_monitoring(&self, acc: &Pubkey) {
         let mut balances = self.accounts.lock().await;
         balances.remove(acc);
@@ -1,3 +1,4 @@
+use crate::types::routing::EventType;
 use once_cell::sync::Lazy;
 use solana_sdk::commitment_config::CommitmentLevel as RpcCommitmentLevel;
 use std::str::FromStr;
@@ -7,13 +8,100 @@ use yellowstone_grpc_proto::geyser::CommitmentLevel as GeyserCommitmentLevel;
 pub const ENGINE_MESSAGE_CHANNEL_CAPACITY: usize = 16384;
 pub const GEYSER_TX_FEED_BUFFER_CAPACITY: usize = 65536;
 pub const CACHED_TX_SIGNATURES_BUFFER_CAPACITY: usize = 1024;
+// Per strategy queue of routed events, a strategy that falls behind by that much starts losing droppable events
+pub const STRATEGY_EVENT_QUEUE_CAPACITY: usize = 4096;
+// A queue growing past its capacity with never-dropped items alerts at that many times the capacity, its consumer is stuck
+pub const QUEUE_HIGH_WATER_MARK_FACTOR: usize = 4;
+// Event types superseded by the next update, so the oldest of them are dropped when a queue is full
+pub const DEFAULT_DROP_OLDEST_EVENT_TYPES: [EventType; 5] = [
+    EventType::HeartBeat,
+    EventType::PoolPriceUpdate,
+    EventType::Swap,
+    EventType::Indicator,
+    EventType::Bar,
+];
+// Restart delays of a failed collector, doubling from min to max
+pub const COLLECTOR_RESTART_BACKOFF_MIN_MS: u64 = 500;
+pub const COLLECTOR_RESTART_BACKOFF_MAX_MS: u64 = 30_000;
+pub const REALTIME_FEED_COLLECTOR: &str = "realtime_feed";
+pub const RAYDIUM_PRICES_COLLECTOR: &str = "raydium_prices";
+pub const TX_CONFIRMATIONS_COLLECTOR: &str = "tx_confirmations";
+// the feeds of the prices and of the transaction receipts, the trading strategies hold while one of them is down
+pub const TRADING_FEED_COLLECTORS: [&str; 3] = [REALTIME_FEED_COLLECTOR, RAYDIUM_PRICES_COLLECTOR, TX_CONFIRMATIONS_COLLECTOR];
+// Slots kept for the fork detection, a processed slot is confirmed or dropped well before
+pub const SLOT_TRACKER_MAX_PENDING_SLOTS: u64 = 512;
+// HTTP API
+pub const API_LOGS_DEFAULT_LIMIT: i64 = 100;
+pub const API_LOGS_MAX_LIMIT: i64 = 1000;
+// how old a signed message can be, its nonce is kept for twice as long
+pub const API_AUTH_MESSAGE_MAX_AGE_S: i64 = 300;
+pub const API_SESSION_EXPIRES_S: u64 = 86_400;
+// a withdrawal waits that long for its confirmation
+pub const API_WITHDRAWAL_CONFIRMATION_EXPIRES_S: u64 = 300;
+// Live event streams, a listener further behind skips the oldest events
+pub const EVENT_STREAM_CAPACITY: usize = 1024;
+// the strategies of a stream and their statuses are refreshed every
+pub const EVENT_STREAM_REFRESH_S: u64 = 15;
+
+// Analytics, the stats of a strategy are computed over its latest actions
+pub const STATS_MAX_ACTIONS: i64 = 100_000;
+
+// Deposits are swept to the trading wallets once confirmed, checked every
+pub const DEPOSIT_STRATEGY_ID: i32 = -1;
+pub const DEPOSIT_SWEEP_INTERVAL_S: u64 = 30;
+// the requested withdrawals are sent every
+pub const WITHDRAWAL_POLL_INTERVAL_S: u64 = 2;
+
+// The empty token accounts of the bot wallets are closed for their rent every
+pub const RENT_RECLAIM_STRATEGY_ID: i32 = -3;
+pub const RENT_RECLAIM_INTERVAL_S: u64 = 6 * 3600;
+// closes per transaction, each close may add a signer
+pub const CLOSE_ACCOUNTS_PER_TX: usize = 6;
+// Fund recovery sells the tokens at any price only when they're worth less than this, the rest is transferred
+pub const RECOVER_FUNDS_DUST_SOL: f64 = 0.01;
+
+// Private keys at rest, encrypted with the base64 master key of the env variable or of the file it points to
+pub const MASTER_KEY_ENV: &str = "MASTER_KEY";
+pub const MASTER_KEY_FILE_ENV: &str = "MASTER_KEY_FILE";
+// the keys stored before the encryption have no prefix
+pub const ENCRYPTED_KEY_PREFIX: &str = "enc:v1:";
+
+// Subscriptions
+pub const SUBSCRIPTION_BILLING_STRATEGY_ID: i32 = -2;
+pub const SUBSCRIPTION_BILLING_INTERVAL_S: u64 = 60;
+// a failed payment is charged again after
+pub const SUBSCRIPTION_PAYMENT_RETRY_S: i64 = 3600;
+// A geyser endpoint that many slots behind the fastest one for that long is demoted
+pub const GEYSER_DEMOTION_SLOT_LAG: u64 = 4;
+pub const GEYSER_DEMOTION_AFTER_MS: u64 = 2_000;
+// The window the geyser message rates are measured over
+pub const GEYSER_RATE_WINDOW_MS: u64 = 10_000;
+// A geyser feed that ran that long before failing is reconnected without backoff
+pub const GEYSER_FEED_STABLE_AFTER_SECS: i64 = 60;
+// Every endpoint reports the slots, no message from any of them for that long means the feeds are all down
+pub const BLOCKCHAIN_FEED_STALE_AFTER_SECS: u64 = 30;
 // The polling rate for the strategy manager to check for new strategies
 pub const NEW_STRATEGY_POLLING_FREQUENCY_MS: u64 = 100;
+// The stored strategies take their ids from the `strategy_ids_seq` sequence, the system strategies the negative ids
+// above and the other ones started at runtime count down from
+pub const TRANSIENT_STRATEGY_IDS_START: i32 = -1000;
 
 pub const RT_FEE_ROLLING_AVERAGE_SIZE: usize = 2048;
 pub const RT_FEE_PERCENTILE_CAPACITY: usize = 2048;
 pub const RT_FEE_PERCENTILE: f64 = 80.0;
 
+// Volume budget mode, the tranches are spread about that far apart, smaller tranches are spaced out instead
+pub const BUDGET_TRANCHE_INTERVAL_S: i64 = 900;
+pub const MIN_BUDGET_TRANCHE_SOL: f64 = 0.05;
+// a shortfall is caught up with tranches at most that many times the regular size
+pub const MAX_BUDGET_CATCH_UP_FACTOR: f64 = 4.0;
+// the sells compensating a price drift still sell that share of the tokens bought
+pub const MIN_SELL_SHARE_OF_BOUGHT: f64 = 0.1;
+
+// Market making defaults for the telegram prompt, the EMA and Bollinger length must be one of `indicator_periods_in_ticks`
+pub const MARKET_MAKING_INDICATOR_PERIOD: i32 = 50;
+pub const MARKET_MAKING_LADDER_LEVELS: i32 = 3;
+
 pub const MAX_TRANSFERS_IN_ONE_TX: usize = 12;
 pub const ACTION_EXPIRY_S: u64 = 1000;
 
@@ -41,6 +129,9 @@ pub const REDIS_SWAP_CACHE_PREFIX: &str = "solana_action_swap_keys";
 pub const REDIS_POOLS_DETAILS: &str = "solana_pools_details";
 
 pub const REDIS_USERS: &str = "solana_bot_users";
+pub const REDIS_API_NONCE_PREFIX: &str = "solana_api_nonce:";
+pub const REDIS_API_SESSION_PREFIX: &str = "solana_api_session:";
+pub const REDIS_API_WITHDRAWAL_PREFIX: &str = "solana_api_withdrawal:";
 
 pub const RPC_COMMITMENT_LEVEL: RpcCommitmentLevel = RpcCommitmentLevel::Processed;
 pub const GRPC_FEED_COMMITMENT_LEVEL: GeyserCommitmentLevel = GeyserCommitmentLevel::Processed;
@@ -1,4 +1,5 @@
 use crate::tg_bot::volume_strategy_config_args::VolumeStrategyConfigArgs;
+use crate::types::routing::EventType;
 use crate::types::volume_strategy::VolumeStrategyInstance;
 use config::{Config, ConfigError, File, Map};
 use serde_derive::{Deserialize, Serialize};
@@ -49,6 +50,10 @@ pub struct CollectorConfig {
     pub(crate) same_price_threshold: f64,
     pub(crate) heartbeat_frequency_ms: u64,
     pub(crate) poll_node_for_tx_confirmations_ms: Option<u64>,
+    /// Programs whose transactions are streamed by geyser, Raydium V4 and pump.fun by default.
+    pub(crate) geyser_program_ids: Option<Vec<String>>,
+    /// Streams every non-vote transaction instead of the program-filtered ones.
+    pub(crate) geyser_subscribe_all_transactions: Option<bool>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -70,6 +75,17 @@ pub struct ExecutorConfig {
     pub(crate) bloxroute_tip: u64,
     pub(crate) flat_fee_if_bloxroute_is_not_used: u64,
     pub(crate) simulate_execution: bool,
+    pub(crate) remote_signer: Option<RemoteSignerConfig>,
+}
+
+/// Wallets whose keys are held by a signing service instead of the trading process.
+#[derive(Clone, Deserialize)]
+#[allow(unused)]
+pub struct RemoteSignerConfig {
+    /// `http(s)://host:port/path` or `unix:///path/to/socket`
+    pub(crate) uri: String,
+    pub(crate) wallets: Vec<String>,
+    pub(crate) auth_token: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -97,6 +113,9 @@ pub struct EngineConfig {
     pub mode: Mode,
     pub bot_wallet: Option<String>,
     pub bot_fee: Option<f64>,
+    pub event_queue_capacity: Option<usize>,
+    pub strategy_queue_capacity: Option<usize>,
+    pub drop_oldest_event_types: Option<Vec<EventType>>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -109,6 +128,36 @@ pub struct TgBotConfig {
     pub bot_fee_percentage_taken_from_deposit: f64,
 }
 
+#[derive(Debug, Clone, Deserialize)]
+#[allow(unused)]
+pub struct ApiConfig {
+    pub bind_address: String,
+}
+
+/// Prices of the plans in SOL per period, paid from the trading wallet to `engine.bot_wallet`.
+#[derive(Debug, Clone, Deserialize)]
+#[allow(unused)]
+pub struct SubscriptionConfig {
+    pub period_days: i64,
+    pub grace_period_days: i64,
+    pub volume_plan_price_sol: f64,
+    pub market_making_plan_price_sol: f64,
+    pub premium_plan_price_sol: f64,
+}
+
+/// Withdrawals are confirmed a second time before they're sent, unless disabled.
+#[derive(Debug, Clone, Deserialize)]
+#[allow(unused)]
+pub struct WithdrawalConfig {
+    pub require_confirmation: bool,
+}
+
+impl Default for WithdrawalConfig {
+    fn default() -> Self {
+        Self { require_confirmation: true }
+    }
+}
+
 #[derive(Debug, Clone, Deserialize)]
 #[allow(unused)]
 pub struct Settings {
@@ -123,6 +172,9 @@ pub struct Settings {
     pub storage: StorageConfig,
     pub engine: EngineConfig,
     pub tgbot: Option<TgBotConfig>,
+    pub api: Option<ApiConfig>,
+    pub subscription: Option<SubscriptionConfig>,
+    pub withdrawal: Option<WithdrawalConfig>,
 }
 
 impl std::fmt::Debug for ExecutorConfig {
@@ -137,6 +189,17 @@ impl std::fmt::Debug for ExecutorConfig {
             .field("bloxroute_tip", &self.bloxroute_tip)
             .field("private_keys", &"<hidden>")
             .field("bloxroute_auth_header", &"<hidden>")
+            .field("remote_signer", &self.remote_signer)
+            .finish()
+    }
+}
+
+impl std::fmt::Debug for RemoteSignerConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RemoteSignerConfig")
+            .field("uri", &self.uri)
+            .field("wallets", &self.wallets)
+            .field("auth_token", &"<hidden>")
             .finish()
     }
 }
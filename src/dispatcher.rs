@@ -0,0 +1,203 @@
+use crate::config::constants::STRATEGY_EVENT_QUEUE_CAPACITY;
+use crate::types::engine::StrategyId;
+use crate::types::routing::{EventFilter, EventType, QueuePolicy, Routable};
+use crate::utils::bounded_queue::{BoundedQueue, DeliveryPolicy, QueueStatsRegistry, QueueStatsSnapshot};
+use solana_sdk::pubkey::Pubkey;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::{debug, warn};
+
+struct Subscriber<E> {
+    filters: Vec<EventFilter>,
+    queue: Arc<BoundedQueue<E>>,
+    // events are dispatched under the read lock, the drops warned about are updated concurrently
+    warned_drops: AtomicU64,
+}
+
+/// Subscriptions indexed by event type, and by (event type, pool) for pool-restricted filters.
+#[derive(Default)]
+struct SubscriptionIndex {
+    by_type: HashMap<EventType, Vec<(StrategyId, usize)>>,
+    pool_restricted_by_type: HashMap<EventType, Vec<(StrategyId, usize)>>,
+    by_type_and_pool: HashMap<(EventType, Pubkey), Vec<(StrategyId, usize)>>,
+}
+
+struct DispatcherState<E> {
+    subscribers: HashMap<StrategyId, Subscriber<E>>,
+    index: SubscriptionIndex,
+}
+
+/// Routes events to the strategies whose filters match them, instead of broadcasting everything to everyone.
+pub struct EventDispatcher<E> {
+    state: RwLock<DispatcherState<E>>,
+    queue_capacity: usize,
+    policy: QueuePolicy,
+    queue_stats: QueueStatsRegistry,
+}
+
+impl<E> EventDispatcher<E>
+where
+    E: Routable + Clone + Send + 'static,
+{
+    pub fn new() -> Self {
+        Self {
+            state: RwLock::new(DispatcherState {
+                subscribers: HashMap::new(),
+                index: SubscriptionIndex::default(),
+            }),
+            queue_capacity: STRATEGY_EVENT_QUEUE_CAPACITY,
+            policy: QueuePolicy::default(),
+            queue_stats: QueueStatsRegistry::default(),
+        }
+    }
+
+    pub fn with_queue_capacity(mut self, capacity: usize) -> Self {
+        self.queue_capacity = capacity;
+        self
+    }
+
+    pub fn with_queue_policy(mut self, policy: QueuePolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    pub fn with_queue_stats(mut self, queue_stats: QueueStatsRegistry) -> Self {
+        self.queue_stats = queue_stats;
+        self
+    }
+
+    pub fn queue_name(id: StrategyId) -> String {
+        format!("strategy {}", id)
+    }
+
+    pub async fn subscribe(&self, id: StrategyId, filters: Vec<EventFilter>) -> Arc<BoundedQueue<E>> {
+        let queue = Arc::new(BoundedQueue::new(self.queue_capacity).with_name(&Self::queue_name(id)));
+        self.queue_stats.register(&Self::queue_name(id), queue.stats());
+        let mut state = self.state.write().await;
+        if let Some(previous) = state.subscribers.insert(id, Subscriber {
+            filters,
+            queue: queue.clone(),
+            warned_drops: AtomicU64::new(0),
+        }) {
+            previous.queue.close();
+        }
+        state.reindex();
+        queue
+    }
+
+    pub async fn update_filters(&self, id: StrategyId, filters: Vec<EventFilter>) {
+        // called after every processed event, the write lock is taken only on an actual change
+        match self.state.read().await.subscribers.get(&id) {
+            Some(subscriber) if subscriber.filters != filters => {}
+            _ => return,
+        }
+        let mut state = self.state.write().await;
+        if let Some(subscriber) = state.subscribers.get_mut(&id) {
+            if subscriber.filters != filters {
+                debug!("Strategy {} subscription updated: {:?}", id, filters);
+                subscriber.filters = filters;
+                state.reindex();
+            }
+        }
+    }
+
+    pub async fn unsubscribe(&self, id: StrategyId) {
+        let mut state = self.state.write().await;
+        if let Some(subscriber) = state.subscribers.remove(&id) {
+            subscriber.queue.close();
+            self.queue_stats.unregister(&Self::queue_name(id));
+            state.reindex();
+        }
+    }
+
+    pub async fn dispatch(&self, event: E) {
+        let route = event.route();
+        let policy = self.policy.policy_of(&route);
+        let state = self.state.read().await;
+
+        let mut candidates: Vec<(StrategyId, usize)> = vec![];
+        if let Some(subscriptions) = state.index.by_type.get(&route.event_type) {
+            candidates.extend(subscriptions);
+        }
+        match route.pool {
+            Some(pool) => {
+                if let Some(subscriptions) = state.index.by_type_and_pool.get(&(route.event_type, pool)) {
+                    candidates.extend(subscriptions);
+                }
+            }
+            // not a pool specific event, pool restrictions don't apply
+            None => {
+                if let Some(subscriptions) = state.index.pool_restricted_by_type.get(&route.event_type) {
+                    candidates.extend(subscriptions);
+                }
+            }
+        }
+
+        let mut recipients = HashSet::new();
+        for (id, filter_idx) in candidates {
+            if recipients.contains(&id) {
+                continue;
+            }
+            if let Some(subscriber) = state.subscribers.get(&id) {
+                if subscriber.filters[filter_idx].matches(&route) {
+                    recipients.insert(id);
+                }
+            }
+        }
+
+        for id in recipients {
+            if let Some(subscriber) = state.subscribers.get(&id) {
+                subscriber.queue.push(event.clone(), policy);
+                let stats = subscriber.queue.stats().snapshot();
+                let lost = stats.dropped + stats.overflowed;
+                let warned_drops = subscriber.warned_drops.load(Ordering::Relaxed);
+                if lost > warned_drops
+                    && lost.is_power_of_two()
+                    && subscriber
+                        .warned_drops
+                        .compare_exchange(warned_drops, lost, Ordering::Relaxed, Ordering::Relaxed)
+                        .is_ok()
+                {
+                    warn!(
+                        "Strategy {} is lagging, {} queued, {} events dropped and {} over capacity so far",
+                        id, stats.len, stats.dropped, stats.overflowed
+                    );
+                }
+            }
+        }
+    }
+
+    pub async fn stats(&self) -> HashMap<StrategyId, QueueStatsSnapshot> {
+        self.state
+            .read()
+            .await
+            .subscribers
+            .iter()
+            .map(|(id, subscriber)| (*id, subscriber.queue.stats().snapshot()))
+            .collect()
+    }
+}
+
+impl<E> DispatcherState<E> {
+    fn reindex(&mut self) {
+        let mut index = SubscriptionIndex::default();
+        for (id, subscriber) in self.subscribers.iter() {
+            for (filter_idx, filter) in subscriber.filters.iter().enumerate() {
+                for event_type in EventType::ALL.iter().filter(|t| filter.matches_type(t)) {
+                    match &filter.pools {
+                        None => index.by_type.entry(*event_type).or_default().push((*id, filter_idx)),
+                        Some(pools) => {
+                            index.pool_restricted_by_type.entry(*event_type).or_default().push((*id, filter_idx));
+                            for pool in pools {
+                                index.by_type_and_pool.entry((*event_type, *pool)).or_default().push((*id, filter_idx));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        self.index = index;
+    }
+}
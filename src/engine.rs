@@ -1,16 +1,20 @@
-use crate::config::constants::{ENGINE_MESSAGE_CHANNEL_CAPACITY, NEW_STRATEGY_POLLING_FREQUENCY_MS};
+use crate::config::constants::{ENGINE_MESSAGE_CHANNEL_CAPACITY, NEW_STRATEGY_POLLING_FREQUENCY_MS, STRATEGY_EVENT_QUEUE_CAPACITY};
 use crate::types::engine::{
-    Aggregator, Collector, Executor, Strategy, StrategyId, StrategyManager,
+    ActionQueue, Aggregator, Collector, Executor, Strategy, StrategyId, StrategyManager,
 };
 use futures::FutureExt;
 use std::collections::HashMap;
 use std::sync::Arc;
-use tokio::sync::broadcast::{self, Sender};
 use tokio::sync::Mutex;
 use tokio::task::{JoinHandle, JoinSet};
 use tokio_stream::StreamExt;
 use tracing::{error, info};
 use crate::types::events::{BotEvent, ExecutionError, ExecutionResult};
+use crate::types::routing::{QueuePolicy, Routable};
+use crate::dispatcher::EventDispatcher;
+use crate::utils::bounded_queue::{BoundedQueue, DeliveryPolicy, QueueStatsRegistry};
+use crate::supervisor::{supervise_collector, CollectorHealthRegistry};
+use crate::types::events::SystemEvent;
 
 /// The main engine. This struct is responsible for orchestrating the
 /// data flow between collectors, strategies, and executors.
@@ -32,6 +36,18 @@ pub struct Engine<E, A> {
 
     /// The capacity of the action channel.
     action_channel_capacity: usize,
+
+    /// The capacity of the per-strategy event queues.
+    strategy_queue_capacity: usize,
+
+    /// Which events can be dropped from a full queue.
+    queue_policy: QueuePolicy,
+
+    /// Where the queues' lag counters are published.
+    queue_stats: QueueStatsRegistry,
+
+    /// Where the collectors' health is published.
+    collector_health: CollectorHealthRegistry,
 }
 
 impl<E, A> Engine<E, A> {
@@ -43,6 +59,10 @@ impl<E, A> Engine<E, A> {
             executors: vec![],
             event_channel_capacity: ENGINE_MESSAGE_CHANNEL_CAPACITY,
             action_channel_capacity: ENGINE_MESSAGE_CHANNEL_CAPACITY,
+            strategy_queue_capacity: STRATEGY_EVENT_QUEUE_CAPACITY,
+            queue_policy: QueuePolicy::default(),
+            queue_stats: QueueStatsRegistry::default(),
+            collector_health: CollectorHealthRegistry::default(),
         }
     }
     pub fn with_event_channel_capacity(mut self, capacity: usize) -> Self {
@@ -54,11 +74,46 @@ impl<E, A> Engine<E, A> {
         self.action_channel_capacity = capacity;
         self
     }
+
+    pub fn with_strategy_queue_capacity(mut self, capacity: usize) -> Self {
+        self.strategy_queue_capacity = capacity;
+        self
+    }
+
+    pub fn with_queue_policy(mut self, policy: QueuePolicy) -> Self {
+        self.queue_policy = policy;
+        self
+    }
+
+    pub fn with_queue_stats(mut self, queue_stats: QueueStatsRegistry) -> Self {
+        self.queue_stats = queue_stats;
+        self
+    }
+
+    pub fn with_collector_health(mut self, collector_health: CollectorHealthRegistry) -> Self {
+        self.collector_health = collector_health;
+        self
+    }
+}
+
+/// Fans events out to the queues of the components consuming them, each with its own backlog.
+struct EventBus<E> {
+    queues: Vec<Arc<BoundedQueue<E>>>,
+    policy: QueuePolicy,
+}
+
+impl<E: Routable + Clone> EventBus<E> {
+    fn publish(&self, event: E) {
+        let policy = self.policy.policy_of(&event.route());
+        for queue in self.queues.iter() {
+            queue.push(event.clone(), policy);
+        }
+    }
 }
 
 impl<E, A> Engine<E, A>
 where
-    E: Send + Clone + 'static + std::fmt::Debug,
+    E: Send + Sync + Clone + 'static + std::fmt::Debug + Routable + From<SystemEvent>,
     A: Send + Clone + 'static + std::fmt::Debug,
 {
     /// Adds a collector to be used by the engine.
@@ -80,85 +135,88 @@ where
     /// each collector, strategy, and executor. It will then orchestrate the
     /// data flow between them.
     pub async fn run(self) -> Result<JoinSet<()>, Box<dyn std::error::Error>> {
-        let (event_sender, _): (Sender<E>, _) = broadcast::channel(self.event_channel_capacity);
-        let (action_sender, _): (Sender<A>, _) = broadcast::channel(self.action_channel_capacity);
+        let new_queue = |name: &str, capacity: usize| {
+            let queue = Arc::new(BoundedQueue::new(capacity).with_name(name));
+            self.queue_stats.register(name, queue.stats());
+            queue
+        };
+        let dispatcher_queue = new_queue("dispatcher", self.event_channel_capacity);
+        let aggregator_queues: Vec<_> = (0..self.aggregators.len())
+            .map(|idx| new_queue(&format!("aggregator {}", idx), self.event_channel_capacity))
+            .collect();
+        let action_queue: ActionQueue<A> = new_queue("actions", self.action_channel_capacity);
+
+        let mut queues = vec![dispatcher_queue.clone()];
+        queues.extend(aggregator_queues.iter().cloned());
+        let event_bus = Arc::new(EventBus {
+            queues,
+            policy: self.queue_policy.clone(),
+        });
 
         let mut set = JoinSet::new();
 
-        // Spawn executors in separate threads.
+        // Spawn executors in separate threads, they share the action queue so each action is executed once.
         for executor in self.executors {
-            let mut receiver = action_sender.subscribe();
-            let event_sender = event_sender.clone();
+            let action_queue = action_queue.clone();
+            let event_bus = event_bus.clone();
             set.spawn(async move {
                 info!("starting executor... ");
-                loop {
-                    if let Ok(action) = receiver.recv().await {
-                        let event_sender = event_sender.clone();
-                        let executor = executor.clone();
-                        tokio::spawn(async move {
-                            match executor.execute(action).await {
-                                Ok(event) => {
-                                    match event_sender.send(event) {
-                                        Ok(_) => {}
-                                        Err(e) => error!("error sending event: {}", e),
-                                    }
-                                }
-                                Err(e) => error!("error executing action: {}", e),
-                            }
+                while let Some(action) = action_queue.pop().await {
+                    let event_bus = event_bus.clone();
+                    let executor = executor.clone();
+                    tokio::spawn(async move {
+                        match executor.execute(action).await {
+                            Ok(event) => event_bus.publish(event),
+                            Err(e) => error!("error executing action: {}", e),
                         }
-                        );
-                    }
+                    });
                 }
             });
         }
 
-        // Spawn strategies in separate threads.
+        // Route events to strategies by their subscriptions.
+        let dispatcher = Arc::new(
+            EventDispatcher::new()
+                .with_queue_capacity(self.strategy_queue_capacity)
+                .with_queue_policy(self.queue_policy.clone())
+                .with_queue_stats(self.queue_stats.clone()),
+        );
+        let dispatcher_clone = dispatcher.clone();
+        set.spawn(async move {
+            info!("starting event dispatcher... ");
+            while let Some(event) = dispatcher_queue.pop().await {
+                dispatcher_clone.dispatch(event).await;
+            }
+        });
 
         // Spawn strategy manager handler
         let strategy_manager = Arc::clone(&self.strategy_manager);
-        let event_sender_clone = event_sender.clone();
-        let action_sender = action_sender.clone();
 
         tokio::spawn(async move {
-            if let Err(e) = strategy_manager.run_strategy_manager(event_sender_clone, action_sender).await {
+            if let Err(e) = strategy_manager.run_strategy_manager(dispatcher, action_queue).await {
                 error!("Strategy manager error: {:?}", e);
             }
         });
         self.strategy_manager.sync_state().await?;
 
-        for mut aggregator in self.aggregators {
-            let mut event_receiver = event_sender.subscribe();
-            let event_sender = event_sender.clone();
+        for (mut aggregator, aggregator_queue) in self.aggregators.into_iter().zip(aggregator_queues) {
+            let event_bus = event_bus.clone();
             set.spawn(async move {
                 info!("starting aggregator... ");
-                loop {
-                    match event_receiver.recv().await {
-                        Ok(event) => {
-                            for derived_event in aggregator.aggregate_event(event) {
-                                match event_sender.send(derived_event) {
-                                    Ok(_) => {}
-                                    Err(e) => error!("error sending derived event: {}", e),
-                                }
-                            }
-                        }
-                        Err(e) => error!("error receiving event: {}", e),
+                while let Some(event) = aggregator_queue.pop().await {
+                    for derived_event in aggregator.aggregate_event(event) {
+                        event_bus.publish(derived_event);
                     }
                 }
             });
         }
 
-        // Spawn collectors in separate threads.
+        // Spawn collectors in separate threads, restarted by the supervisor when they fail.
         for collector in self.collectors {
-            let event_sender = event_sender.clone();
+            let event_bus = event_bus.clone();
+            let collector_health = self.collector_health.clone();
             set.spawn(async move {
-                info!("starting collector... ");
-                let mut event_stream = collector.get_event_stream().await.unwrap();
-                while let Some(event) = event_stream.next().await {
-                    match event_sender.send(event) {
-                        Ok(_) => {}
-                        Err(e) => error!("error sending event: {}", e),
-                    }
-                }
+                supervise_collector(collector, collector_health, |event| event_bus.publish(event)).await;
             });
         }
 
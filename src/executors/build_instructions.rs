@@ -280,7 +280,7 @@ pub async fn build_instructions(context: &AppContext, action: &Arc<Mutex<SolanaA
                                         user_keys,
                                         swap_sol_amount_in,
                                         // todo having min_amount_out 0 is generally a bad idea
-                                        0,
+                                        swap.min_amount_out,
                                     ), swap.keys.version),
                                 spl_token::instruction::close_account(
                                     &spl_token::ID,
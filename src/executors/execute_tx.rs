@@ -3,6 +3,7 @@ use crate::config::settings::Geyser;
 use crate::solana::bloxroute::BloxRoute;
 use crate::solana::geyser_pool::GeyserClientPool;
 use crate::solana::rpc_pool::RpcClientPool;
+use crate::solana::signer::{sign_transaction, TxSigner};
 use crate::utils::keys::clone_keypair;
 use anyhow::{anyhow, bail, Result};
 use futures_util::future::select_all;
@@ -27,8 +28,8 @@ use tracing::{debug, error, info, trace, warn};
 pub async fn execute_tx(
     context: &AppContext,
     recent_blockhash: Hash,
-    sender: &Keypair,
-    fee_payer: &Keypair,
+    sender: &dyn TxSigner,
+    fee_payer: &dyn TxSigner,
     instructions: &[Instruction],
 ) -> Result<Signature> {
     debug!(
@@ -36,13 +37,10 @@ pub async fn execute_tx(
         instructions.len()
     );
     let mut tx = Transaction::new_with_payer(instructions, Some(&fee_payer.pubkey()));
-    let senders = if sender.pubkey() != fee_payer.pubkey() {
+    if sender.pubkey() != fee_payer.pubkey() {
         debug!("Adding fee payer to the transaction");
-        vec![sender, fee_payer]
-    } else {
-        vec![sender]
-    };
-    tx.sign(&senders, recent_blockhash);
+    }
+    sign_transaction(&mut tx, recent_blockhash, &[sender, fee_payer]).await?;
     // simulating transaction before signing
 
     let signature = *tx.get_signature();
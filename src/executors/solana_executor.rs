@@ -131,7 +131,7 @@ impl Executor<Arc<Mutex<SolanaAction>>, BotEvent> for SolanaExecutor {
 
                 // add bloxroute tip if needed
                 //build a tx
-                let action_guard = action.lock().await;
+                let mut action_guard = action.lock().await;
                 let fee_payer = action_guard.fee_payer.clone();
                 let mut tx = Transaction::new_with_payer(
                     &prefinal_itxs_with_cu, Some(&fee_payer.pubkey()),
@@ -152,7 +152,7 @@ impl Executor<Arc<Mutex<SolanaAction>>, BotEvent> for SolanaExecutor {
                                 retries -= 1;
                                 if retries == 0 {
                                     return Ok(BotEvent::ExecutionResult(
-                                        action.lock().await.uuid,
+                                        action_guard.uuid,
                                         Arc::clone(&action),
                                         ExecutionResult::ExecutionError(ExecutionError::SimulationFailed(err.to_string())),
                                     ));
@@ -165,11 +165,13 @@ impl Executor<Arc<Mutex<SolanaAction>>, BotEvent> for SolanaExecutor {
                 }
 
                 // execute
+                let sender = self.context.signers.signer_for_wallet(&action_guard.sniper)?;
+                let fee_payer = self.context.signers.signer_for_wallet(&action_guard.fee_payer)?;
                 let signature = execute_tx(
                     &self.context,
                     recent_blockhash,
-                    &action_guard.sniper.get_keypair(),
-                    &action_guard.fee_payer.get_keypair(),
+                    sender.as_ref(),
+                    fee_payer.as_ref(),
                     &prefinal_itxs_with_cu).await?;
 
                 // update the action and the cache
@@ -178,13 +180,16 @@ impl Executor<Arc<Mutex<SolanaAction>>, BotEvent> for SolanaExecutor {
                     .add_agent_tx(action_guard.uuid, signature.to_string())
                     .await;
 
-                action.lock().await.sent(
+                // the guard is still held, locking the action again would never return
+                *action_guard = action_guard.sent(
                     balance_before,
                     signature,
                     BASE_TX_FEE_SOL as i64 + price_per_cu_priority as i64 * compute_units_per_tx_estimate as i64,
                 );
-                debug!("Action executed: {:?}", action);
-                Ok(BotEvent::ExecutionResult(action.lock().await.uuid, action.clone(), ExecutionResult::Sent))
+                debug!("Action executed: {:?}", *action_guard);
+                let action_uuid = action_guard.uuid;
+                drop(action_guard);
+                Ok(BotEvent::ExecutionResult(action_uuid, action.clone(), ExecutionResult::Sent))
             }
             Err(e) => {
                 //downcasting to crate::types::events::ExecutionError
@@ -14,19 +14,24 @@
 extern crate core;
 
 mod aggregators;
+mod analytics;
+mod api;
 mod collectors;
+mod commands;
 mod config;
+mod dispatcher;
 mod engine;
 mod executors;
 mod schema;
 mod solana;
 mod storage;
 mod strategies;
+mod supervisor;
 mod tg_bot;
 mod types;
 mod utils;
 
-use crate::config::constants::ENGINE_MESSAGE_CHANNEL_CAPACITY;
+use crate::config::constants::{ENGINE_MESSAGE_CHANNEL_CAPACITY, STRATEGY_EVENT_QUEUE_CAPACITY};
 use crate::config::settings;
 use crate::config::settings::Mode;
 use crate::engine::Engine;
@@ -52,15 +57,22 @@ use tracing::{debug, error, info, warn, Level};
 use tracing_subscriber::{filter, prelude::*};
 use types::events::BlockchainEvent;
 use crate::types::engine::StrategyManager;
+use crate::types::routing::QueuePolicy;
 use crate::types::sniping_strategy::{NewSnipingStrategyInstance, SnipingStrategyInstance};
 
 
 #[tokio::main]
 async fn main() -> Result<()> {
+    if let Some(command) = std::env::args().nth(1) {
+        return commands::run(&command).await;
+    }
     // Load settings from config.toml and check if it's valid, panics in case of error
     let context = config::app_context::AppContext::new("config").await;
     let settings = context.get_settings().await;
     info!("Starting the Solana bot with settings: {settings:?}");
+    if utils::crypto::master_key()?.is_none() {
+        warn!("No master key, the private keys are stored in clear");
+    }
 
     let solana_strat_manager =
         Arc::new(crate::strategies::SolanaStrategyManager::new(&context).await?);
@@ -68,8 +80,14 @@ async fn main() -> Result<()> {
 
     info!("Initializing engine..");
     let mut engine: Engine<BotEvent, Arc<Mutex<SolanaAction>>> = Engine::new(solana_strat_manager.clone())
-        .with_event_channel_capacity(ENGINE_MESSAGE_CHANNEL_CAPACITY)
-        .with_action_channel_capacity(ENGINE_MESSAGE_CHANNEL_CAPACITY);
+        .with_event_channel_capacity(settings.engine.event_queue_capacity.unwrap_or(ENGINE_MESSAGE_CHANNEL_CAPACITY))
+        .with_action_channel_capacity(ENGINE_MESSAGE_CHANNEL_CAPACITY)
+        .with_strategy_queue_capacity(settings.engine.strategy_queue_capacity.unwrap_or(STRATEGY_EVENT_QUEUE_CAPACITY))
+        .with_queue_stats(context.queue_stats.clone())
+        .with_collector_health(context.collector_health.clone());
+    if let Some(drop_oldest_event_types) = settings.engine.drop_oldest_event_types.clone() {
+        engine = engine.with_queue_policy(QueuePolicy::new(drop_oldest_event_types));
+    }
 
     /// adding raydium pool collector getting new pools and prices
     let raydium_pool_collector =
@@ -116,6 +134,12 @@ async fn main() -> Result<()> {
         context.start_telegram_bot(solana_strat_manager.clone()).await;
     }
 
+    if let Some(api_config) = settings.api.clone() {
+        let event_stream = strategies::EventStreamStrategy::new(&context);
+        api::start_api_server(&context, &api_config, solana_strat_manager.clone(), event_stream.publisher()).await?;
+        solana_strat_manager.start_strategy(Box::new(event_stream)).await;
+    }
+
     /// Add startup strategies here
     if let Some(sniping_strategy_config) = settings.get_sniping_strategy_config() {
         let sniping_strategy_instance = storage::persistent::save_new_sniping_strategy_to_db(
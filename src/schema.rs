@@ -1,10 +1,32 @@
 // @generated automatically by Diesel CLI.
 
 diesel::table! {
-    bot_events (timestamp) {
+    agent_seeds (strategy_instance_id) {
+        strategy_instance_id -> Int4,
+        seed -> Text,
+        next_index -> Int4,
+        created -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    bot_events (id, timestamp) {
         timestamp -> Timestamptz,
         event_type -> Text,
         event_data -> Jsonb,
+        id -> Int8,
+        pool -> Nullable<Text>,
+        wallets -> Array<Text>,
+    }
+}
+
+diesel::table! {
+    deposit_wallets (id) {
+        id -> Int4,
+        user_id -> Int4,
+        wallet -> Text,
+        private_key -> Text,
+        created_at -> Timestamptz,
     }
 }
 
@@ -16,10 +38,29 @@ diesel::table! {
         is_deposit -> Bool,
         amount_sol -> Nullable<Float8>,
         is_success -> Bool,
-        signature -> Text,
+        signature -> Nullable<Text>,
         signature_fee -> Nullable<Text>,
         fee_taken_sol -> Nullable<Float8>,
         description -> Nullable<Text>,
+        action_uuid -> Nullable<Text>,
+        destination -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    marketmakingstrategyinstances (id) {
+        id -> Int4,
+        user_id -> Int4,
+        target_pool -> Text,
+        started_at -> Timestamptz,
+        completed_at -> Nullable<Timestamptz>,
+        indicator_period -> Int4,
+        spread_pct -> Float8,
+        ladder_levels -> Int4,
+        order_size_sol -> Float8,
+        max_inventory_sol -> Float8,
+        bought_sol -> Float8,
+        sold_sol -> Float8,
     }
 }
 
@@ -31,6 +72,8 @@ diesel::table! {
         created_at -> Timestamp,
         base_reserve -> Nullable<Float8>,
         quote_reserve -> Nullable<Float8>,
+        slot -> Nullable<Int8>,
+        tx_index -> Nullable<Int8>,
     }
 }
 
@@ -71,22 +114,67 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    subscription_payments (id) {
+        id -> Int4,
+        subscription_id -> Int4,
+        action_uuid -> Text,
+        plan -> Int4,
+        amount_sol -> Float8,
+        status -> Text,
+        tx_hash -> Nullable<Text>,
+        created_at -> Timestamptz,
+        paid_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    subscriptions (id) {
+        id -> Int4,
+        user_id -> Int4,
+        token_mint -> Text,
+        plan -> Int4,
+        status -> Text,
+        subscribed_at -> Timestamptz,
+        paid_until -> Nullable<Timestamptz>,
+        grace_until -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    swaps (id, created_at) {
+        id -> Int8,
+        created_at -> Timestamptz,
+        pool -> Varchar,
+        signature -> Varchar,
+        is_buy -> Bool,
+        base_amount -> Float8,
+        quote_amount -> Float8,
+        price -> Float8,
+        base_reserve -> Float8,
+        quote_reserve -> Float8,
+        slot -> Int8,
+        tx_index -> Nullable<Int8>,
+    }
+}
+
 diesel::table! {
     traders (id) {
         id -> Int4,
         strategy_instance_id -> Nullable<Int4>,
         wallet -> Text,
-        private_key -> Text,
+        private_key -> Nullable<Text>,
         created -> Timestamptz,
         is_active -> Bool,
+        derivation_index -> Nullable<Int4>,
     }
 }
 
 diesel::table! {
     users (id) {
         id -> Int4,
-        chat_id -> Int8,
-        tg_name -> Text,
+        chat_id -> Nullable<Int8>,
+        tg_name -> Nullable<Text>,
         wallet_address -> Text,
         wallet_private_key -> Text,
         created -> Timestamptz,
@@ -95,6 +183,7 @@ diesel::table! {
         last_name -> Nullable<Text>,
         is_active -> Bool,
         is_superuser -> Bool,
+        client_wallet -> Nullable<Text>,
     }
 }
 
@@ -111,20 +200,38 @@ diesel::table! {
         agents_buying_in_tranche -> Int4,
         agents_selling_in_tranche -> Int4,
         agents_keep_tokens_lamports -> Int8,
+        daily_volume_sol -> Nullable<Float8>,
+        budget_days -> Nullable<Int4>,
+        volume_traded_sol -> Float8,
+        max_inventory_sol -> Nullable<Float8>,
+        max_price_drift_pct -> Nullable<Float8>,
+        reference_price -> Nullable<Float8>,
+        volume_counted_until -> Nullable<Timestamptz>,
     }
 }
 
+diesel::joinable!(agent_seeds -> volumestrategyinstances (strategy_instance_id));
+diesel::joinable!(deposit_wallets -> users (user_id));
 diesel::joinable!(depositswithdrawals -> users (user_id));
+diesel::joinable!(marketmakingstrategyinstances -> users (user_id));
 diesel::joinable!(snipingstrategyinstances -> users (user_id));
+diesel::joinable!(subscription_payments -> subscriptions (subscription_id));
+diesel::joinable!(subscriptions -> users (user_id));
 diesel::joinable!(traders -> volumestrategyinstances (strategy_instance_id));
 diesel::joinable!(volumestrategyinstances -> users (user_id));
 
 diesel::allow_tables_to_appear_in_same_query!(
+    agent_seeds,
     bot_events,
+    deposit_wallets,
     depositswithdrawals,
+    marketmakingstrategyinstances,
     prices,
     snipingstrategyinstances,
     solana_actions,
+    subscription_payments,
+    subscriptions,
+    swaps,
     traders,
     users,
     volumestrategyinstances,
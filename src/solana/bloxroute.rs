@@ -8,6 +8,7 @@ use generic_array::typenum::U64;
 use generic_array::GenericArray;
 use reqwest::Client;
 use serde_derive::Deserialize;
+use crate::solana::signer::{sign_transaction, TxSigner};
 use serde_json::json;
 use solana_client::rpc_client::RpcClient;
 use solana_sdk::instruction::{AccountMeta, Instruction};
@@ -176,8 +177,8 @@ impl BloxRoute {
     pub async fn add_bx_tip_and_send_tx(
         &self,
         recent_blockhash: &Hash,
-        sender: &Keypair,
-        fee_payer: &Keypair,
+        sender: &dyn TxSigner,
+        fee_payer: &dyn TxSigner,
         instructions: &[Instruction],
     ) -> Result<()> {
         // Convert the tip wallet address to a Pubkey
@@ -200,13 +201,8 @@ impl BloxRoute {
 
         // Get a recent blockhash
         // Create and sign the transaction
-        let senders = if sender.pubkey() != fee_payer.pubkey() {
-            vec![sender, fee_payer]
-        } else {
-            vec![sender]
-        };
         let mut transaction = Transaction::new_with_payer(&all_instructions, Some(&fee_payer.pubkey()));
-        transaction.sign(&senders, *recent_blockhash);
+        sign_transaction(&mut transaction, *recent_blockhash, &[sender, fee_payer]).await?;
 
         // Serialize the transaction to raw bytes using bincode
         let serialized_transaction = bincode::serialize(&transaction)?;
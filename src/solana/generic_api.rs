@@ -8,6 +8,9 @@ use tracing::field::debug;
 use thiserror::Error;
 use crate::collectors::tx_stream::types::AccountPretty;
 use crate::config::app_context::AppContext;
+use crate::types::engine::StrategyId;
+use crate::types::pool::RaydiumPool;
+use std::collections::HashSet;
 
 #[derive(Error, Debug)]
 pub enum AccountError {
@@ -65,6 +68,42 @@ pub async fn start_monitoring_account(context: &AppContext, pubkey: &Pubkey) {
     context.geyser_resubscribe_account_tx_notify.send(());
 }
 
+/// Adds the pool to the traded pools for the strategy, its swaps are parsed into price updates and it's included in the
+/// geyser subscription. A pool is monitored until the last strategy monitoring it stops.
+pub async fn start_monitoring_pool(context: &AppContext, pool: &RaydiumPool, strategy_id: StrategyId) -> Result<()> {
+    let mut pool_monitors = context.cache.pool_monitors.lock().await;
+    if let Some(strategies) = pool_monitors.get_mut(&pool.id) {
+        strategies.insert(strategy_id);
+        return Ok(());
+    }
+    // configured at startup
+    if context.cache.target_pools.read().await.contains_key(&pool.id) {
+        return Ok(());
+    }
+    let pool_price = context.rpc_pool.get_pool_price(&pool.id).await?;
+    pool_monitors.insert(pool.id, HashSet::from([strategy_id]));
+    context.cache.target_pools.write().await.insert(pool.id, pool.clone());
+    context.cache.target_pools_prices.lock().await.insert(pool.id, pool_price);
+    context.geyser_resubscribe_account_tx_notify.send(());
+    Ok(())
+}
+
+pub async fn stop_monitoring_pool(context: &AppContext, pool_id: &Pubkey, strategy_id: StrategyId) {
+    let mut pool_monitors = context.cache.pool_monitors.lock().await;
+    let Some(strategies) = pool_monitors.get_mut(pool_id) else {
+        return;
+    };
+    strategies.remove(&strategy_id);
+    if !strategies.is_empty() {
+        return;
+    }
+    pool_monitors.remove(pool_id);
+    context.cache.target_pools.write().await.remove(pool_id);
+    context.cache.pool_accounts.lock().await.remove(pool_id);
+    context.cache.slot_tracker.lock().await.remove_pool(pool_id);
+    context.geyser_resubscribe_account_tx_notify.send(());
+}
+
 pub async fn start_monitoring_token_account(context: &AppContext, sniper: &Pubkey, token_mint: &Pubkey) {
     let ata = spl_associated_token_account::get_associated_token_address(sniper, token_mint);
     start_monitoring_account(context, &ata).await;
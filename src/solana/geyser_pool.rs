@@ -1,5 +1,6 @@
 use crate::config::constants::GRPC_FEED_COMMITMENT_LEVEL;
 use crate::config::settings::{Geyser, ProviderName};
+use crate::solana::geyser_stats::GeyserFeedStats;
 use anyhow::{bail, Result};
 use config::Map;
 use futures::channel::mpsc;
@@ -26,12 +27,14 @@ pub type GeyserNamedClient = (
 #[derive(Default, Clone)]
 pub struct GeyserClientPool {
     pub(crate) clients: Map<ProviderName, Arc<Mutex<GeyserGrpcClient<InterceptorXToken>>>>,
+    pub(crate) stats: GeyserFeedStats,
 }
 
 impl Debug for GeyserClientPool {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("GeyserClientPool")
             .field("clients", &self.clients.keys())
+            .field("stats", &self.stats.snapshot())
             .finish()
     }
 }
@@ -65,6 +68,7 @@ impl GeyserClientPool {
         let results = futures::future::join_all(connection_futures).await;
         Self {
             clients: results.into_iter().collect::<Map<ProviderName, Arc<_>>>(),
+            stats: GeyserFeedStats::default(),
         }
     }
     
@@ -0,0 +1,126 @@
+use crate::config::constants::{GEYSER_DEMOTION_AFTER_MS, GEYSER_DEMOTION_SLOT_LAG, GEYSER_RATE_WINDOW_MS};
+use crate::config::settings::ProviderName;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+/// Racing statistics of a geyser endpoint, `slot_lag` is behind the most advanced endpoint.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ProviderFeedStats {
+    pub messages: u64,
+    pub first_seen: u64,
+    pub last_slot: u64,
+    pub slot_lag: u64,
+    pub messages_per_sec: f64,
+    pub demoted: bool,
+    #[serde(skip)]
+    window_start: Option<Instant>,
+    #[serde(skip)]
+    window_messages: u64,
+    #[serde(skip)]
+    lagging_since: Option<Instant>,
+}
+
+impl ProviderFeedStats {
+    /// Share of the deduplicated transactions this endpoint delivered first.
+    pub fn first_seen_ratio(&self, total_first_seen: u64) -> f64 {
+        if total_first_seen == 0 {
+            0.0
+        } else {
+            self.first_seen as f64 / total_first_seen as f64
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct FeedStatsState {
+    providers: BTreeMap<ProviderName, ProviderFeedStats>,
+    max_slot: u64,
+}
+
+/// Per endpoint statistics of the merged geyser feed, an endpoint lagging for too long is demoted
+/// and its events are ignored until it catches up, unless every endpoint is demoted.
+#[derive(Debug, Clone, Default)]
+pub struct GeyserFeedStats(Arc<RwLock<FeedStatsState>>);
+
+impl GeyserFeedStats {
+    /// Records a message and returns whether the events of the provider should be used.
+    pub fn record_message(&self, provider: &ProviderName, slot: u64) -> bool {
+        self.record_message_at(provider, slot, Instant::now())
+    }
+
+    // the clock is passed in for the demotion window to be tested
+    fn record_message_at(&self, provider: &ProviderName, slot: u64, now: Instant) -> bool {
+        let mut state = self.0.write().unwrap();
+        state.max_slot = state.max_slot.max(slot);
+        let max_slot = state.max_slot;
+
+        let stats = state.providers.entry(provider.clone()).or_default();
+        stats.messages += 1;
+        stats.last_slot = stats.last_slot.max(slot);
+        stats.slot_lag = max_slot - stats.last_slot;
+
+        stats.window_messages += 1;
+        let window_start = *stats.window_start.get_or_insert(now);
+        let elapsed = now.duration_since(window_start);
+        if elapsed >= Duration::from_millis(GEYSER_RATE_WINDOW_MS) {
+            stats.messages_per_sec = stats.window_messages as f64 / elapsed.as_secs_f64();
+            stats.window_messages = 0;
+            stats.window_start = Some(now);
+        }
+
+        if stats.slot_lag > GEYSER_DEMOTION_SLOT_LAG {
+            let lagging_since = *stats.lagging_since.get_or_insert(now);
+            if !stats.demoted && now.duration_since(lagging_since) >= Duration::from_millis(GEYSER_DEMOTION_AFTER_MS) {
+                warn!("Geyser {} is {} slots behind, demoting it", provider, stats.slot_lag);
+                stats.demoted = true;
+            }
+        } else {
+            stats.lagging_since = None;
+            if stats.demoted {
+                info!("Geyser {} caught up, promoting it back", provider);
+                stats.demoted = false;
+            }
+        }
+
+        !stats.demoted || state.providers.values().all(|stats| stats.demoted)
+    }
+
+    pub fn record_first_seen(&self, provider: &ProviderName) {
+        if let Some(stats) = self.0.write().unwrap().providers.get_mut(provider) {
+            stats.first_seen += 1;
+        }
+    }
+
+    pub fn snapshot(&self) -> BTreeMap<ProviderName, ProviderFeedStats> {
+        self.0.read().unwrap().providers.clone()
+    }
+
+    pub fn total_first_seen(&self) -> u64 {
+        self.0.read().unwrap().providers.values().map(|stats| stats.first_seen).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lagging_provider_is_demoted() {
+        let stats = GeyserFeedStats::default();
+        let (fast, slow) = ("fast".to_string(), "slow".to_string());
+        let start = Instant::now();
+        assert!(stats.record_message_at(&fast, 100, start));
+        assert!(stats.record_message_at(&slow, 100, start));
+        assert!(stats.record_message_at(&fast, 100 + GEYSER_DEMOTION_SLOT_LAG + 1, start));
+        // the lag has to last for a while before the demotion
+        assert!(stats.record_message_at(&slow, 100, start));
+        assert!(stats.record_message_at(&slow, 100, start + Duration::from_millis(GEYSER_DEMOTION_AFTER_MS - 1)));
+        let later = start + Duration::from_millis(GEYSER_DEMOTION_AFTER_MS);
+        assert!(!stats.record_message_at(&slow, 100, later));
+        assert!(stats.record_message_at(&slow, 100 + GEYSER_DEMOTION_SLOT_LAG + 1, later));
+        assert!(!stats.snapshot()[&slow].demoted);
+    }
+}
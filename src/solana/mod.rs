@@ -2,9 +2,14 @@ pub mod bloxroute;
 pub mod constants;
 pub(crate) mod getters;
 pub mod geyser_pool;
+pub mod geyser_stats;
 pub mod instructions;
 pub mod pool;
+pub mod pool_accounts;
+pub mod rent_reclaim;
 pub mod rpc_pool;
+pub mod signer;
+pub mod slot_tracker;
 pub mod tx_parser;
 pub mod ws_pool;
 mod generic_api;
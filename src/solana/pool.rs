@@ -12,16 +12,23 @@ use solana_sdk::transaction::TransactionVersion;
 use spl_token::solana_program::message::v0::LoadedAddresses;
 use tracing::{debug, error, info, trace};
 use yellowstone_grpc_proto::convert_from::{create_loaded_addresses, create_meta_inner_instructions, create_token_balances, create_tx_meta};
-use yellowstone_grpc_proto::prelude::SubscribeUpdateTransaction;
+use yellowstone_grpc_proto::prelude::{SubscribeUpdateTransaction, TransactionStatusMeta as GeyserTransactionStatusMeta};
 use crate::collectors::tx_stream::types::TransactionPretty;
 use crate::utils::decimals::{lamports_to_sol, tokens_to_ui_amount_with_decimals_f64};
-// this only works with  EncodedTransaction::Json variant
-
 pub(crate) fn extract_pool_from_init_tx(
     tx_update: &SubscribeUpdateTransaction,
 ) -> Option<(RaydiumPool, RaydiumPoolPriceUpdate)> {
     let tx_pretty: TransactionPretty = tx_update.clone().into();
-    let transaction = tx_pretty.tx;
+    let geyser_meta = tx_update.transaction.as_ref()?.meta.clone();
+    extract_pool_from_tx(&tx_pretty, geyser_meta)
+}
+
+// this only works with  EncodedTransaction::Json variant, binary ones are re-encoded with the geyser meta
+pub(crate) fn extract_pool_from_tx(
+    tx_pretty: &TransactionPretty,
+    geyser_meta: Option<GeyserTransactionStatusMeta>,
+) -> Option<(RaydiumPool, RaydiumPoolPriceUpdate)> {
+    let transaction = tx_pretty.tx.clone();
     let initialize_log = match &transaction.clone().meta?.log_messages {
         OptionSerializer::Some(log_messages) => {
             let log_message = log_messages.iter().find(|m| m.contains("initialize2"))?;
@@ -52,7 +59,7 @@ pub(crate) fn extract_pool_from_init_tx(
             trace!("transaction: {:#?}",transaction_clone);
             let t = transaction_clone.transaction.decode();
             let ui_meta = transaction_clone.meta?;
-            let meta = tx_update.clone().transaction?.meta?;
+            let meta = geyser_meta?;
             let tx_meta = create_tx_meta(meta).ok()?;
             let encoded_tx = t
                 .map(|t| t.encode_with_meta(UiTransactionEncoding::JsonParsed, &tx_meta));
@@ -68,7 +75,7 @@ pub(crate) fn extract_pool_from_init_tx(
     let message = match &json.message {
         UiMessage::Parsed(m) => Some(m),
         _ => {
-            error!("UiMessage::Parsed not found tx: {:#?}",tx_update);
+            error!("UiMessage::Parsed not found tx: {:?}", tx_pretty.signature);
             None
         }
     }?;
@@ -153,7 +160,9 @@ pub(crate) fn extract_pool_from_init_tx(
             base_reserve,
             quote_reserve,
             created_at: chrono::Utc::now().naive_utc(),
-        },
+            ..Default::default()
+        }
+        .with_position(tx_pretty.slot, tx_pretty.tx_index),
     ))
 }
 
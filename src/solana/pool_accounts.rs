@@ -0,0 +1,122 @@
+use crate::collectors::tx_stream::types::AccountPretty;
+use crate::solana::constants::WSOL_MINT_PUBKEY;
+use crate::types::pool::{RaydiumPool, RaydiumPoolPriceUpdate};
+use crate::utils::decimals;
+use borsh::BorshDeserialize;
+use chrono::Utc;
+use solana_farm_client::raydium_sdk::LiquidityStateV4;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::{HashMap, HashSet};
+use tracing::warn;
+
+/// The accounts of a pool streamed by geyser: the AMM and both vaults.
+pub fn pool_accounts(pool: &RaydiumPool) -> [Pubkey; 3] {
+    [pool.id, pool.base_vault, pool.quote_vault]
+}
+
+pub fn is_pool_account(pool: &RaydiumPool, pubkey: &Pubkey) -> bool {
+    pool_accounts(pool).contains(pubkey)
+}
+
+/// Last known raw state of a pool accounts, the price is derived from it the same way the
+/// program does: the vault balances minus the pnl not taken yet.
+#[derive(Debug, Clone, Default)]
+pub struct PoolAccountsState {
+    pub base_vault_amount: Option<u64>,
+    pub quote_vault_amount: Option<u64>,
+    pub base_need_take_pnl: u64,
+    pub quote_need_take_pnl: u64,
+    // (slot, write_version) per account, the racing endpoints deliver the same update several times
+    versions: HashMap<Pubkey, (u64, u64)>,
+    // a swap updates both vaults in separate account updates, the vaults updated by the last (slot, txn_signature)
+    vaults_updated: Option<((u64, String), HashSet<Pubkey>)>,
+    last_price: Option<f64>,
+}
+
+impl PoolAccountsState {
+    /// Both vaults were seen, the price can be derived without RPC.
+    pub fn is_complete(&self) -> bool {
+        self.base_vault_amount.is_some() && self.quote_vault_amount.is_some()
+    }
+
+    /// Applies an account update of the pool, returns false if it's stale or not decodable.
+    pub fn apply(&mut self, pool: &RaydiumPool, acc: &AccountPretty) -> bool {
+        let version = (acc.slot, acc.write_version);
+        if self.versions.get(&acc.pubkey).is_some_and(|last| *last >= version) {
+            return false;
+        }
+        if acc.pubkey == pool.id {
+            let Ok(amm) = LiquidityStateV4::try_from_slice(&acc.data) else {
+                warn!("Failed to decode AMM account of pool {}", pool.id);
+                return false;
+            };
+            self.base_need_take_pnl = amm.base_need_take_pnl;
+            self.quote_need_take_pnl = amm.quote_need_take_pnl;
+        } else {
+            let Some(token_account) = &acc.token_unpacked_data else {
+                warn!("Failed to decode vault {} of pool {}", acc.pubkey, pool.id);
+                return false;
+            };
+            if acc.pubkey == pool.base_vault {
+                self.base_vault_amount = Some(token_account.amount);
+            } else if acc.pubkey == pool.quote_vault {
+                self.quote_vault_amount = Some(token_account.amount);
+            } else {
+                return false;
+            }
+            let txn = (acc.slot, acc.txn_signature.clone());
+            match &mut self.vaults_updated {
+                Some((last_txn, vaults)) if *last_txn == txn => {
+                    vaults.insert(acc.pubkey);
+                }
+                _ => self.vaults_updated = Some((txn, HashSet::from([acc.pubkey]))),
+            }
+        }
+        self.versions.insert(acc.pubkey, version);
+        true
+    }
+
+    /// Both vaults were updated by the last transaction, one of them alone is half of a swap.
+    pub fn is_settled(&self, pool: &RaydiumPool) -> bool {
+        self.vaults_updated
+            .as_ref()
+            .is_some_and(|(_, vaults)| vaults.contains(&pool.base_vault) && vaults.contains(&pool.quote_vault))
+    }
+
+    /// The price moved by less than the threshold, relative to the last one emitted.
+    pub fn is_same_price(&self, price: f64, same_price_threshold: f64) -> bool {
+        self.last_price.is_some_and(|last_price| (price / last_price - 1.0).abs() < same_price_threshold)
+    }
+
+    pub fn set_emitted(&mut self, price: f64) {
+        self.last_price = Some(price);
+    }
+
+    /// Price in SOL per token, SOL is the quote whichever side of the AMM it's on, as for the RPC prices.
+    /// None until the vaults are settled.
+    pub fn price_update(&self, pool: &RaydiumPool) -> Option<RaydiumPoolPriceUpdate> {
+        if !self.is_settled(pool) {
+            return None;
+        }
+        let base_amount = self.base_vault_amount?.saturating_sub(self.base_need_take_pnl);
+        let quote_amount = self.quote_vault_amount?.saturating_sub(self.quote_need_take_pnl);
+        let base_reserve = decimals::tokens_to_ui_amount_with_decimals_f64(base_amount, pool.base_decimals);
+        let quote_reserve = decimals::tokens_to_ui_amount_with_decimals_f64(quote_amount, pool.quote_decimals);
+        let (base_reserve, quote_reserve) = if pool.base_mint == *WSOL_MINT_PUBKEY {
+            (quote_reserve, base_reserve)
+        } else {
+            (base_reserve, quote_reserve)
+        };
+        if base_reserve <= 0.0 {
+            return None;
+        }
+        Some(RaydiumPoolPriceUpdate {
+            pool: pool.id,
+            price: quote_reserve / base_reserve,
+            base_reserve,
+            quote_reserve,
+            created_at: Utc::now().naive_utc(),
+            ..Default::default()
+        })
+    }
+}
@@ -0,0 +1,106 @@
+use crate::config::constants::CLOSE_ACCOUNTS_PER_TX;
+use crate::solana::rpc_pool::{OwnedTokenAccount, RpcClientPool};
+use crate::solana::signer::{sign_transaction, SignerRegistry, TxSigner};
+use crate::storage::bot_wallets::BotWallet;
+use anyhow::{anyhow, Result};
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::transaction::Transaction;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use tracing::{debug, warn};
+
+/// Accounts closed and lamports reclaimed, by strategy.
+#[derive(Debug, Clone, Default)]
+pub struct RentReclaimReport {
+    pub reclaimed: BTreeMap<String, (usize, u64)>,
+    pub failed: usize,
+    /// the token accounts closed, none on a dry run
+    pub closed: Vec<Pubkey>,
+}
+
+impl RentReclaimReport {
+    pub fn accounts(&self) -> usize {
+        self.reclaimed.values().map(|(accounts, _)| accounts).sum()
+    }
+
+    pub fn lamports(&self) -> u64 {
+        self.reclaimed.values().map(|(_, lamports)| lamports).sum()
+    }
+}
+
+struct EmptyAccount<'a> {
+    wallet: &'a BotWallet,
+    account: OwnedTokenAccount,
+}
+
+/// Closes the empty token accounts of the wallets, the rent goes to the owner's main wallet which pays the fees.
+/// The closes of the wallets of one owner are batched, `dry_run` only reports what would be reclaimed.
+pub async fn reclaim_rent(
+    rpc_pool: &RpcClientPool,
+    signers: &SignerRegistry,
+    wallets: &[BotWallet],
+    dry_run: bool,
+) -> Result<RentReclaimReport> {
+    let mut by_owner: HashMap<Pubkey, Vec<EmptyAccount>> = HashMap::new();
+    let mut report = RentReclaimReport::default();
+    for wallet in wallets {
+        match rpc_pool.get_token_accounts(&wallet.key.pubkey()).await {
+            Ok(accounts) => by_owner.entry(wallet.owner.pubkey()).or_default().extend(
+                accounts
+                    .into_iter()
+                    .filter(|account| account.amount == 0)
+                    .map(|account| EmptyAccount { wallet, account }),
+            ),
+            Err(e) => {
+                warn!("Can't list the token accounts of {}: {:?}", wallet.key.pubkey(), e);
+                report.failed += 1;
+            }
+        }
+    }
+
+    for batch in by_owner.values().flat_map(|accounts| accounts.chunks(CLOSE_ACCOUNTS_PER_TX)) {
+        if !dry_run {
+            if let Err(e) = close_accounts(rpc_pool, signers, batch).await {
+                warn!("Failed to close {} token accounts: {:?}", batch.len(), e);
+                report.failed += 1;
+                continue;
+            }
+            report.closed.extend(batch.iter().map(|empty| empty.account.address));
+        }
+        for empty in batch {
+            let (accounts, lamports) = report.reclaimed.entry(empty.wallet.strategy.clone()).or_default();
+            *accounts += 1;
+            *lamports += empty.account.lamports;
+        }
+    }
+    Ok(report)
+}
+
+async fn close_accounts(rpc_pool: &RpcClientPool, signers: &SignerRegistry, batch: &[EmptyAccount<'_>]) -> Result<()> {
+    let Some(first) = batch.first() else {
+        return Ok(());
+    };
+    let owner = first.wallet.owner.pubkey();
+    let mut instructions: Vec<Instruction> = vec![];
+    let mut tx_signers: Vec<Arc<dyn TxSigner>> = vec![signers.signer_for(&owner, Some(&first.wallet.owner))?];
+    for empty in batch {
+        let wallet = empty.wallet.key.pubkey();
+        instructions.push(spl_token::instruction::close_account(
+            &spl_token::ID,
+            &empty.account.address,
+            &owner,
+            &wallet,
+            &[],
+        )?);
+        tx_signers.push(signers.signer_for(&wallet, Some(&empty.wallet.key))?);
+    }
+    let client = rpc_pool.get_a_client().ok_or_else(|| anyhow!("No rpc configured"))?;
+    let recent_blockhash = client.get_latest_blockhash().await?;
+    let mut tx = Transaction::new_with_payer(&instructions, Some(&owner));
+    let tx_signers: Vec<&dyn TxSigner> = tx_signers.iter().map(|signer| signer.as_ref()).collect();
+    sign_transaction(&mut tx, recent_blockhash, &tx_signers).await?;
+    let signature = rpc_pool.send_and_confirm_tx(&tx).await?;
+    debug!("Closed {} token accounts of {}: {}", batch.len(), owner, signature);
+    Ok(())
+}
@@ -14,11 +14,13 @@ use futures_util::stream::FuturesUnordered;
 use futures_util::{SinkExt, TryFutureExt};
 use log::{info, trace, warn};
 use solana_account_decoder::parse_token::UiTokenAmount;
+use solana_account_decoder::UiAccountData;
 use solana_client::client_error::ClientErrorKind::TransactionError;
 use solana_client::nonblocking::rpc_client::RpcClient;
 use solana_client::rpc_config::{
     RpcSendTransactionConfig, RpcSimulateTransactionConfig, RpcTransactionConfig,
 };
+use solana_client::rpc_request::TokenAccountsFilter;
 use solana_client::rpc_response::Response;
 use spl_token::solana_program::program_option::COption;
 use spl_token::solana_program::program_pack::Pack;
@@ -45,6 +47,16 @@ use tokio_stream::StreamExt;
 use tracing::{debug, error, instrument};
 use crate::solana::AccountError;
 
+/// A token account of a wallet, `amount` in the token's base units.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OwnedTokenAccount {
+    pub address: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    /// the rent held by the account
+    pub lamports: u64,
+}
+
 #[derive(Default, Clone)]
 pub struct RpcClientPool {
     //todo add throttling configuration here
@@ -196,6 +208,14 @@ impl RpcClientPool {
     pub async fn get_transaction_with_config(
         &self,
         signature: &solana_sdk::signature::Signature,
+    ) -> Result<EncodedConfirmedTransactionWithStatusMeta> {
+        self.get_transaction_with_encoding(signature, UiTransactionEncoding::JsonParsed).await
+    }
+
+    pub async fn get_transaction_with_encoding(
+        &self,
+        signature: &solana_sdk::signature::Signature,
+        encoding: UiTransactionEncoding,
     ) -> Result<EncodedConfirmedTransactionWithStatusMeta> {
         let signature = signature.clone();
         self.execute_rpc_method_consequently_till_first_success(move |client| {
@@ -205,7 +225,7 @@ impl RpcClientPool {
                     .get_transaction_with_config(
                         &signature,
                         RpcTransactionConfig {
-                            encoding: Some(UiTransactionEncoding::JsonParsed),
+                            encoding: Some(encoding),
                             // this method doesn't support the commitment below confirmed
                             commitment: Some(CommitmentConfig::confirmed()),
                             max_supported_transaction_version: Some(3),
@@ -377,20 +397,25 @@ impl RpcClientPool {
             .map_err(|e| anyhow!("Failed to parse liquidity state data: {:?}", e))
             .unwrap();
 
-        let (quote_vault, quote_decimal, base_vault, base_decimal) =
+        // the pnl not taken yet is in the vaults but isn't part of the reserves
+        let (quote_vault, quote_decimal, quote_pnl, base_vault, base_decimal, base_pnl) =
             if market.base_mint == *WSOL_MINT_PUBKEY {
                 (
                     market.base_vault,
                     market.base_decimal as u8,
+                    market.base_need_take_pnl,
                     market.quote_vault,
                     market.quote_decimal as u8,
+                    market.quote_need_take_pnl,
                 )
             } else {
                 (
                     market.quote_vault,
                     market.quote_decimal as u8,
+                    market.quote_need_take_pnl,
                     market.base_vault,
                     market.base_decimal as u8,
+                    market.base_need_take_pnl,
                 )
             };
 
@@ -399,14 +424,16 @@ impl RpcClientPool {
             .await?
             .amount
             .parse::<u64>()
-            .unwrap();
+            .unwrap()
+            .saturating_sub(base_pnl);
 
         let token_b_balance = self
             .get_token_account_balance_ui(&quote_vault)
             .await?
             .amount
             .parse::<u64>()
-            .unwrap();
+            .unwrap()
+            .saturating_sub(quote_pnl);
 
         Ok((
             decimals::tokens_to_ui_amount_with_decimals_f64(token_a_balance, base_decimal),
@@ -422,6 +449,7 @@ impl RpcClientPool {
             base_reserve: reserve_base,
             quote_reserve: reserve_quote,
             created_at: Utc::now().naive_utc(),
+            ..Default::default()
         })
     }
 
@@ -487,6 +515,52 @@ impl RpcClientPool {
         }
     }
 
+    /// Every spl token account of the owner, the empty ones included.
+    pub async fn get_token_accounts(&self, owner: &Pubkey) -> Result<Vec<OwnedTokenAccount>> {
+        let owner = Arc::new(*owner);
+        let keyed_accounts = self
+            .execute_rpc_method_consequently_till_first_success(move |client| {
+                let owner = Arc::clone(&owner);
+                async move {
+                    client
+                        .get_token_accounts_by_owner(&owner, TokenAccountsFilter::ProgramId(spl_token::id()))
+                        .await
+                }
+            })
+            .await?;
+        keyed_accounts
+            .into_iter()
+            .map(|keyed_account| {
+                let UiAccountData::Json(parsed) = keyed_account.account.data else {
+                    bail!("Token account {} isn't json parsed", keyed_account.pubkey);
+                };
+                let info = &parsed.parsed["info"];
+                let mint = info["mint"]
+                    .as_str()
+                    .ok_or_else(|| anyhow!("Token account {} has no mint", keyed_account.pubkey))?;
+                let amount = info["tokenAmount"]["amount"]
+                    .as_str()
+                    .ok_or_else(|| anyhow!("Token account {} has no amount", keyed_account.pubkey))?;
+                Ok(OwnedTokenAccount {
+                    address: Pubkey::from_str(&keyed_account.pubkey)?,
+                    mint: Pubkey::from_str(mint)?,
+                    amount: amount.parse()?,
+                    lamports: keyed_account.account.lamports,
+                })
+            })
+            .collect()
+    }
+
+    /// Sends the tx and waits for its confirmation, unlike `send_tx_to_all_providers`.
+    pub async fn send_and_confirm_tx(&self, tx: &Transaction) -> Result<solana_sdk::signature::Signature> {
+        let tx = tx.clone();
+        self.execute_rpc_method_consequently_till_first_success(move |client| {
+            let tx = tx.clone();
+            async move { client.send_and_confirm_transaction(&tx).await }
+        })
+            .await
+    }
+
     pub async fn get_signature_status(
         &self,
         signature: &solana_sdk::signature::Signature,
@@ -0,0 +1,356 @@
+use crate::config::settings::RemoteSignerConfig;
+use crate::types::keys::{ActionWallet, KeypairClonable};
+use anyhow::{anyhow, bail, Result};
+use async_trait::async_trait;
+use base64::{engine::general_purpose, Engine};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use solana_sdk::hash::Hash;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signature, Signer};
+use solana_sdk::transaction::Transaction;
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::UnixStream;
+use tracing::debug;
+
+const REMOTE_SIGNER_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Signs transaction messages for a single wallet, wherever its key lives.
+#[async_trait]
+pub trait TxSigner: Send + Sync {
+    fn pubkey(&self) -> Pubkey;
+
+    async fn sign_message(&self, message: &[u8]) -> Result<Signature>;
+}
+
+#[async_trait]
+impl TxSigner for Keypair {
+    fn pubkey(&self) -> Pubkey {
+        Signer::pubkey(self)
+    }
+
+    async fn sign_message(&self, message: &[u8]) -> Result<Signature> {
+        Ok(self.try_sign_message(message)?)
+    }
+}
+
+#[async_trait]
+impl TxSigner for KeypairClonable {
+    fn pubkey(&self) -> Pubkey {
+        KeypairClonable::pubkey(self)
+    }
+
+    async fn sign_message(&self, message: &[u8]) -> Result<Signature> {
+        Ok(self.get_keypair().try_sign_message(message)?)
+    }
+}
+
+/// Sets the blockhash and fills in the signature of every signer, signers sharing a pubkey sign once.
+pub async fn sign_transaction(
+    tx: &mut Transaction,
+    recent_blockhash: Hash,
+    signers: &[&dyn TxSigner],
+) -> Result<()> {
+    tx.message.recent_blockhash = recent_blockhash;
+    let message = tx.message_data();
+    let mut signed = HashSet::new();
+    for signer in signers {
+        let pubkey = signer.pubkey();
+        if !signed.insert(pubkey) {
+            continue;
+        }
+        let index = tx
+            .get_signing_keypair_positions(&[pubkey])?
+            .first()
+            .copied()
+            .flatten()
+            .ok_or_else(|| anyhow!("{} is not a signer of the transaction", pubkey))?;
+        tx.signatures[index] = signer.sign_message(&message).await?;
+    }
+    if tx.signatures.iter().any(|signature| *signature == Signature::default()) {
+        bail!("Transaction is missing signatures");
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SignerEndpoint {
+    Http(String),
+    Unix(PathBuf),
+}
+
+impl FromStr for SignerEndpoint {
+    type Err = anyhow::Error;
+
+    fn from_str(uri: &str) -> Result<Self> {
+        if let Some(path) = uri.strip_prefix("unix://") {
+            Ok(SignerEndpoint::Unix(PathBuf::from(path)))
+        } else if uri.starts_with("http://") || uri.starts_with("https://") {
+            Ok(SignerEndpoint::Http(uri.to_string()))
+        } else {
+            bail!("Unsupported signer uri `{}`", uri)
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SignRequest {
+    pub pubkey: String,
+    /// base64 of the serialized transaction message
+    pub message: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SignResponse {
+    pub signature: Option<String>,
+    pub error: Option<String>,
+}
+
+/// Asks a signing service for signatures. Over http the request is POSTed as json,
+/// over a unix socket it's written as a single json line and answered the same way.
+#[derive(Clone)]
+pub struct RemoteSigner {
+    pubkey: Pubkey,
+    endpoint: SignerEndpoint,
+    auth_token: Option<String>,
+    client: Client,
+}
+
+impl RemoteSigner {
+    pub fn new(pubkey: Pubkey, endpoint: SignerEndpoint) -> Self {
+        Self {
+            pubkey,
+            endpoint,
+            auth_token: None,
+            client: Client::new(),
+        }
+    }
+
+    pub fn with_auth_token(mut self, auth_token: Option<String>) -> Self {
+        self.auth_token = auth_token;
+        self
+    }
+
+    pub fn with_client(mut self, client: Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    async fn request(&self, request: &SignRequest) -> Result<SignResponse> {
+        match &self.endpoint {
+            SignerEndpoint::Http(url) => {
+                let mut builder = self
+                    .client
+                    .post(url)
+                    .timeout(REMOTE_SIGNER_TIMEOUT)
+                    .json(request);
+                if let Some(token) = &self.auth_token {
+                    builder = builder.bearer_auth(token);
+                }
+                let response = builder.send().await?;
+                if !response.status().is_success() {
+                    bail!(
+                        "Signer returned {}: {}",
+                        response.status(),
+                        response.text().await?
+                    );
+                }
+                Ok(response.json().await?)
+            }
+            SignerEndpoint::Unix(path) => {
+                tokio::time::timeout(REMOTE_SIGNER_TIMEOUT, async {
+                    let mut stream = UnixStream::connect(path).await?;
+                    let mut line = serde_json::to_vec(request)?;
+                    line.push(b'\n');
+                    stream.write_all(&line).await?;
+                    let mut response = String::new();
+                    BufReader::new(stream).read_line(&mut response).await?;
+                    Ok::<SignResponse, anyhow::Error>(serde_json::from_str(&response)?)
+                })
+                .await
+                .map_err(|_| anyhow!("Signer timed out"))?
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl TxSigner for RemoteSigner {
+    fn pubkey(&self) -> Pubkey {
+        self.pubkey
+    }
+
+    async fn sign_message(&self, message: &[u8]) -> Result<Signature> {
+        let request = SignRequest {
+            pubkey: self.pubkey.to_string(),
+            message: general_purpose::STANDARD.encode(message),
+        };
+        let response = self.request(&request).await?;
+        let signature = match (response.signature, response.error) {
+            (Some(signature), _) => Signature::from_str(&signature)?,
+            (None, Some(error)) => bail!("Signer refused to sign: {}", error),
+            (None, None) => bail!("Signer returned no signature"),
+        };
+        // a signature for the wrong key or message would only fail later at the rpc
+        if !signature.verify(self.pubkey.as_ref(), message) {
+            bail!("Signer returned an invalid signature for {}", self.pubkey);
+        }
+        Ok(signature)
+    }
+}
+
+/// Picks the signer for a wallet: the signing service for the configured wallets,
+/// the in-memory key otherwise.
+#[derive(Clone, Default)]
+pub struct SignerRegistry {
+    remote: Option<(SignerEndpoint, Option<String>, HashSet<Pubkey>)>,
+    client: Client,
+}
+
+impl SignerRegistry {
+    pub fn new(config: Option<&RemoteSignerConfig>) -> Result<Self> {
+        let remote = match config {
+            Some(config) => {
+                let endpoint = SignerEndpoint::from_str(&config.uri)?;
+                let wallets = config
+                    .wallets
+                    .iter()
+                    .map(|wallet| Pubkey::from_str(wallet))
+                    .collect::<Result<HashSet<_>, _>>()?;
+                Some((endpoint, config.auth_token.clone(), wallets))
+            }
+            None => None,
+        };
+        Ok(Self {
+            remote,
+            client: Client::new(),
+        })
+    }
+
+    /// The signing service for the configured wallets, the in-memory key otherwise. A wallet known by its pubkey
+    /// alone can only be signed for by the service.
+    pub fn signer_for(&self, pubkey: &Pubkey, keypair: Option<&KeypairClonable>) -> Result<Arc<dyn TxSigner>> {
+        match (&self.remote, keypair) {
+            (Some((endpoint, auth_token, wallets)), _) if wallets.contains(pubkey) => {
+                debug!("Signing with the remote signer for {}", pubkey);
+                Ok(Arc::new(
+                    RemoteSigner::new(*pubkey, endpoint.clone())
+                        .with_auth_token(auth_token.clone())
+                        .with_client(self.client.clone()),
+                ))
+            }
+            (_, Some(keypair)) => Ok(Arc::new(keypair.clone())),
+            (_, None) => bail!("No signer for {}, it's neither a remote signer wallet nor a known key", pubkey),
+        }
+    }
+
+    pub fn signer_for_wallet(&self, wallet: &ActionWallet) -> Result<Arc<dyn TxSigner>> {
+        self.signer_for(&wallet.pubkey(), wallet.keypair())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::routing::post;
+    use axum::{Json, Router};
+    use solana_sdk::system_instruction::transfer;
+    use tokio::net::{TcpListener, UnixListener};
+
+    fn stub_sign(keypair: &Keypair, request: SignRequest) -> SignResponse {
+        if request.pubkey != Signer::pubkey(keypair).to_string() {
+            return SignResponse { signature: None, error: Some("unknown wallet".to_string()) };
+        }
+        let message = general_purpose::STANDARD.decode(request.message).unwrap();
+        SignResponse {
+            signature: Some(Signer::sign_message(keypair, &message).to_string()),
+            error: None,
+        }
+    }
+
+    fn unsigned_transfer(sender: &Pubkey, fee_payer: &Pubkey) -> Transaction {
+        let instruction = transfer(sender, &Pubkey::new_unique(), 1);
+        Transaction::new_with_payer(&[instruction], Some(fee_payer))
+    }
+
+    #[tokio::test]
+    async fn test_http_remote_signer() {
+        let keypair = Arc::new(Keypair::new());
+        let stub_keypair = keypair.clone();
+        let app = Router::new().route(
+            "/sign",
+            post(move |Json(request): Json<SignRequest>| {
+                let keypair = stub_keypair.clone();
+                async move { Json(stub_sign(&keypair, request)) }
+            }),
+        );
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let endpoint = SignerEndpoint::from_str(&format!("http://{}/sign", address)).unwrap();
+        let fee_payer = Keypair::new();
+        let remote = RemoteSigner::new(Signer::pubkey(keypair.as_ref()), endpoint.clone());
+        let mut tx = unsigned_transfer(&TxSigner::pubkey(&remote), &Signer::pubkey(&fee_payer));
+        sign_transaction(&mut tx, Hash::new_unique(), &[&remote, &fee_payer])
+            .await
+            .unwrap();
+        assert!(tx.verify().is_ok());
+
+        // the stub doesn't hold this key
+        let unknown = RemoteSigner::new(Pubkey::new_unique(), endpoint);
+        assert!(unknown.sign_message(b"message").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_unix_remote_signer() {
+        let path = std::env::temp_dir().join(format!("signer-{}.sock", Pubkey::new_unique()));
+        let listener = UnixListener::bind(&path).unwrap();
+        let keypair = Keypair::new();
+        let pubkey = Signer::pubkey(&keypair);
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let mut reader = BufReader::new(stream);
+                let mut line = String::new();
+                reader.read_line(&mut line).await.unwrap();
+                let response = stub_sign(&keypair, serde_json::from_str(&line).unwrap());
+                let mut response = serde_json::to_vec(&response).unwrap();
+                response.push(b'\n');
+                reader.into_inner().write_all(&response).await.unwrap();
+            }
+        });
+
+        let endpoint = SignerEndpoint::from_str(&format!("unix://{}", path.display())).unwrap();
+        let remote = RemoteSigner::new(pubkey, endpoint);
+        let mut tx = unsigned_transfer(&pubkey, &pubkey);
+        sign_transaction(&mut tx, Hash::new_unique(), &[&remote, &remote])
+            .await
+            .unwrap();
+        assert!(tx.verify().is_ok());
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_signer_registry() {
+        let local = KeypairClonable::new();
+        let remote = KeypairClonable::new();
+        let config = RemoteSignerConfig {
+            uri: "unix:///tmp/signer.sock".to_string(),
+            wallets: vec![remote.pubkey().to_string()],
+            auth_token: None,
+        };
+        let registry = SignerRegistry::new(Some(&config)).unwrap();
+        assert_eq!(registry.signer_for(&local.pubkey(), Some(&local)).unwrap().pubkey(), local.pubkey());
+        assert_eq!(registry.signer_for(&remote.pubkey(), Some(&remote)).unwrap().pubkey(), remote.pubkey());
+        // the remote wallets are signed for without their key
+        let remote_wallet = ActionWallet::from(remote.pubkey());
+        assert_eq!(registry.signer_for_wallet(&remote_wallet).unwrap().pubkey(), remote.pubkey());
+        assert!(registry.signer_for_wallet(&ActionWallet::from(Pubkey::new_unique())).is_err());
+        assert!(SignerEndpoint::from_str("ftp://signer").is_err());
+    }
+}
@@ -0,0 +1,159 @@
+use crate::config::constants::SLOT_TRACKER_MAX_PENDING_SLOTS;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Orders the events of each pool by their chain position and remembers the pools having events
+/// in processed slots, so the ones on a fork dropped by the cluster can be rolled back.
+#[derive(Debug, Default)]
+pub struct SlotTracker {
+    // pool -> last (slot, tx_index) emitted
+    positions: HashMap<Pubkey, (u64, Option<u64>)>,
+    // slot -> pools with events in it, until the slot is confirmed or dropped
+    pending: BTreeMap<u64, HashSet<Pubkey>>,
+    // slot -> parent, from the slot updates
+    parents: BTreeMap<u64, u64>,
+}
+
+impl SlotTracker {
+    /// Whether an event of the pool at this position can be emitted, the late ones are not.
+    /// Events at the same slot without a tx index (account updates) are accepted, as are the ones
+    /// of an earlier slot on another fork: the fork emitted first may be the one dropped.
+    pub fn accept(&mut self, pool: Pubkey, slot: u64, tx_index: Option<u64>) -> bool {
+        if let Some((last_slot, last_index)) = self.positions.get(&pool).copied() {
+            if slot < last_slot && self.is_ancestor(slot, last_slot) {
+                return false;
+            }
+            if slot == last_slot {
+                if let (Some(index), Some(last_index)) = (tx_index, last_index) && index <= last_index {
+                    return false;
+                }
+                self.positions.insert(pool, (slot, tx_index.or(last_index)));
+                self.pending.entry(slot).or_default().insert(pool);
+                return true;
+            }
+        }
+        self.positions.insert(pool, (slot, tx_index));
+        self.pending.entry(slot).or_default().insert(pool);
+        self.prune(slot);
+        true
+    }
+
+    /// Records a slot update, once a slot is confirmed returns the (pool, slot) events that were
+    /// emitted on another fork. The ordering of these pools starts over.
+    pub fn on_slot(&mut self, slot: u64, parent: Option<u64>, confirmed: bool) -> Vec<(Pubkey, u64)> {
+        if let Some(parent) = parent {
+            self.parents.insert(slot, parent);
+        }
+        if !confirmed {
+            return vec![];
+        }
+        let Some(oldest_pending) = self.pending.keys().next().copied() else {
+            self.prune(slot);
+            return vec![];
+        };
+        // the confirmed chain, as far down as the ancestry is known
+        let mut chain = HashSet::new();
+        let mut lowest_known = slot;
+        loop {
+            chain.insert(lowest_known);
+            if lowest_known <= oldest_pending {
+                break;
+            }
+            match self.parents.get(&lowest_known) {
+                Some(parent) => lowest_known = *parent,
+                None => break,
+            }
+        }
+        let settled: Vec<u64> = self.pending.range(..=slot).map(|(pending_slot, _)| *pending_slot).collect();
+        let mut rolled_back = vec![];
+        for pending_slot in settled {
+            let pools = self.pending.remove(&pending_slot).unwrap_or_default();
+            // below the known ancestry the slot is assumed to be on the confirmed chain
+            if pending_slot >= lowest_known && !chain.contains(&pending_slot) {
+                rolled_back.extend(pools.into_iter().map(|pool| (pool, pending_slot)));
+            }
+        }
+        for (pool, _) in &rolled_back {
+            self.positions.remove(pool);
+        }
+        self.prune(slot);
+        rolled_back
+    }
+
+    // unknown ancestry (no slot updates) counts as the same chain
+    fn is_ancestor(&self, ancestor: u64, mut slot: u64) -> bool {
+        while slot > ancestor {
+            match self.parents.get(&slot) {
+                Some(parent) => slot = *parent,
+                None => return true,
+            }
+        }
+        slot == ancestor
+    }
+
+    pub fn remove_pool(&mut self, pool: &Pubkey) {
+        self.positions.remove(pool);
+    }
+
+    // without slot updates (websocket feeds) nothing is ever confirmed, the old slots are forgotten
+    fn prune(&mut self, slot: u64) {
+        let oldest = slot.saturating_sub(SLOT_TRACKER_MAX_PENDING_SLOTS);
+        self.pending = self.pending.split_off(&oldest);
+        self.parents = self.parents.split_off(&oldest);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_late_events_are_rejected() {
+        let mut tracker = SlotTracker::default();
+        let pool = Pubkey::new_unique();
+        assert!(tracker.accept(pool, 10, Some(5)));
+        assert!(!tracker.accept(pool, 10, Some(3)));
+        assert!(!tracker.accept(pool, 9, None));
+        assert!(tracker.accept(pool, 10, None));
+        assert!(!tracker.accept(pool, 10, Some(5)));
+        assert!(tracker.accept(pool, 11, Some(0)));
+    }
+
+    #[test]
+    fn test_events_on_dropped_fork_are_rolled_back() {
+        let mut tracker = SlotTracker::default();
+        let (pool, other_pool) = (Pubkey::new_unique(), Pubkey::new_unique());
+        tracker.on_slot(10, Some(9), false);
+        tracker.on_slot(11, Some(10), false);
+        // 12 is on a fork, 13 builds on 11
+        tracker.on_slot(12, Some(10), false);
+        tracker.on_slot(13, Some(11), false);
+        assert!(tracker.accept(pool, 11, Some(0)));
+        assert!(tracker.accept(other_pool, 12, Some(0)));
+        assert!(tracker.accept(pool, 12, Some(1)));
+        assert_eq!(tracker.on_slot(11, Some(10), true), vec![]);
+        let mut rolled_back = tracker.on_slot(13, Some(11), true);
+        rolled_back.sort();
+        let mut expected = vec![(pool, 12), (other_pool, 12)];
+        expected.sort();
+        assert_eq!(rolled_back, expected);
+        // the canonical events of the pool are accepted again
+        assert!(tracker.accept(pool, 13, Some(0)));
+    }
+
+    #[test]
+    fn test_earlier_events_on_another_fork_are_accepted() {
+        let mut tracker = SlotTracker::default();
+        let pool = Pubkey::new_unique();
+        tracker.on_slot(10, Some(9), false);
+        tracker.on_slot(11, Some(10), false);
+        tracker.on_slot(12, Some(10), false);
+        assert!(tracker.accept(pool, 12, Some(0)));
+        // 11 isn't an ancestor of 12, it's not late
+        assert!(tracker.accept(pool, 11, Some(0)));
+        // 10 is
+        assert!(!tracker.accept(pool, 10, Some(0)));
+        tracker.on_slot(13, Some(11), false);
+        assert_eq!(tracker.on_slot(13, Some(11), true), vec![(pool, 12)]);
+    }
+}
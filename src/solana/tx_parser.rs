@@ -90,6 +90,19 @@ pub fn parse_notional(
     Err("could not parse notional".into())
 }
 
+/// The most lamports any account of the transaction received.
+pub fn parse_max_received(
+    tx: &EncodedConfirmedTransactionWithStatusMeta,
+) -> Result<u64, Box<dyn std::error::Error>> {
+    if let Some(meta) = &tx.transaction.meta {
+        return std::iter::zip(&meta.pre_balances, &meta.post_balances)
+            .map(|(pre, post)| post.saturating_sub(*pre))
+            .max()
+            .ok_or_else(|| "no balances".into());
+    }
+    Err("could not parse received amount".into())
+}
+
 pub fn deserialize<T: Clone>(item: &OptionSerializer<T>) -> Option<T> {
     match item {
         OptionSerializer::Some(val) => Some(val.clone()),
@@ -163,7 +176,8 @@ pub fn parse_tx_for_swaps(tx: &EncodedTransactionWithStatusMeta) -> Option<Vec<S
     // From EncodedTransactionWithStatusMeta we need to extract the following:
     // instruction that has Raydium program as a program_id - this is a swap instruction
     // from that instruction we need to extract Input Accounts and Inner Instructions (which are two transfers)
-    let versioned_tx = &tx.transaction.decode().unwrap();
+    // only binary encoded transactions can be decoded, json ones (fetched for new pools) have no swaps of interest
+    let versioned_tx = &tx.transaction.decode()?;
     let signature = versioned_tx.signatures[0];
     let signature_str = signature.to_string();
     let ui_message = match &versioned_tx.message {
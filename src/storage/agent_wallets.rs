@@ -0,0 +1,53 @@
+use crate::schema::agent_seeds;
+use crate::storage::persistent::DbPool;
+use crate::types::engine::StrategyId;
+use crate::types::volume_strategy::{AgentSeed, NewAgentSeed};
+use anyhow::Result;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+
+/// The agent seed of the strategy, generated on the first request.
+pub async fn get_or_create_agent_seed(db_pool: &DbPool, strategy_id: StrategyId) -> Result<AgentSeed> {
+    let mut conn = db_pool.get().await?;
+    diesel::insert_into(agent_seeds::table)
+        .values(NewAgentSeed::generate(strategy_id))
+        .on_conflict(agent_seeds::strategy_instance_id)
+        .do_nothing()
+        .execute(&mut conn)
+        .await?;
+    Ok(agent_seeds::table
+        .find(strategy_id)
+        .select(AgentSeed::as_select())
+        .first(&mut conn)
+        .await?)
+}
+
+pub async fn get_agent_seed(db_pool: &DbPool, strategy_id: StrategyId) -> Result<Option<AgentSeed>> {
+    let mut conn = db_pool.get().await?;
+    Ok(agent_seeds::table
+        .find(strategy_id)
+        .select(AgentSeed::as_select())
+        .first(&mut conn)
+        .await
+        .optional()?)
+}
+
+pub async fn get_agent_seeds(db_pool: &DbPool) -> Result<Vec<AgentSeed>> {
+    let mut conn = db_pool.get().await?;
+    Ok(agent_seeds::table
+        .order(agent_seeds::strategy_instance_id.asc())
+        .select(AgentSeed::as_select())
+        .load(&mut conn)
+        .await?)
+}
+
+/// Reserves the next derivation index of the strategy, indexes are never handed out twice.
+pub async fn next_agent_index(db_pool: &DbPool, strategy_id: StrategyId) -> Result<i32> {
+    let mut conn = db_pool.get().await?;
+    let next_index: i32 = diesel::update(agent_seeds::table.find(strategy_id))
+        .set(agent_seeds::next_index.eq(agent_seeds::next_index + 1))
+        .returning(agent_seeds::next_index)
+        .get_result(&mut conn)
+        .await?;
+    Ok(next_index - 1)
+}
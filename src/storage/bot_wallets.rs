@@ -0,0 +1,161 @@
+use crate::schema::{bot_events, snipingstrategyinstances, traders, users, volumestrategyinstances};
+use crate::storage::agent_wallets::get_agent_seeds;
+use crate::storage::persistent::DbPool;
+use crate::types::bot_user::{BotUser, Trader};
+use crate::types::keys::KeypairClonable;
+use crate::types::volume_strategy::VolumeStrategyInstance;
+use anyhow::{anyhow, Result};
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+use tracing::warn;
+
+/// A wallet the bot holds the key of, its funds belong to the owner of `owner`, the user's main wallet.
+#[derive(Clone)]
+pub struct BotWallet {
+    pub label: String,
+    /// the strategy the wallet trades for, to report by strategy
+    pub strategy: String,
+    pub key: KeypairClonable,
+    pub owner: KeypairClonable,
+    /// the pools the wallet traded
+    pub pools: Vec<Pubkey>,
+}
+
+/// Every wallet of the bot holding funds of a user besides the user's main wallet: the traders and derived agents of
+/// the volume strategies and the snipers, with the main wallet of their owner. The wallets still in use by a running
+/// strategy are left out unless `include_active`: its snipers, its agents until deactivated and the derived wallets it
+/// hasn't written a trader for yet.
+pub async fn load_bot_wallets(db_pool: &DbPool, include_active: bool) -> Result<Vec<BotWallet>> {
+    let mut conn = db_pool.get().await?;
+    let owners: HashMap<i32, KeypairClonable> = users::table
+        .select(BotUser::as_select())
+        .load(&mut conn)
+        .await?
+        .into_iter()
+        .filter_map(|user| Some((user.id, KeypairClonable::new_from_privkey(&user.wallet_private_key).ok()?)))
+        .collect();
+    let strategies: HashMap<i32, VolumeStrategyInstance> = volumestrategyinstances::table
+        .select(VolumeStrategyInstance::as_select())
+        .load(&mut conn)
+        .await?
+        .into_iter()
+        .map(|instance| (instance.id, instance))
+        .collect();
+    let seeds: HashMap<i32, _> = get_agent_seeds(db_pool)
+        .await?
+        .into_iter()
+        .map(|seed| (seed.strategy_instance_id, seed))
+        .collect();
+
+    let mut wallets = vec![];
+    // the main wallets are the destinations, never swept
+    let mut seen: HashSet<Pubkey> = owners.values().map(|owner| owner.pubkey()).collect();
+    let rows = traders::table.select(Trader::as_select()).load(&mut conn).await?;
+    for trader in rows {
+        let Some(instance) = trader.strategy_instance_id.and_then(|strategy_id| strategies.get(&strategy_id)) else {
+            warn!("Skipping trader {} {}: no strategy to find its owner", trader.id, trader.wallet);
+            continue;
+        };
+        if !include_active && instance.completed_at.is_none() && trader.is_active {
+            continue;
+        }
+        let key = match (&trader.private_key, trader.derivation_index) {
+            (Some(private_key), _) => KeypairClonable::new_from_privkey(private_key),
+            (None, Some(index)) => seeds
+                .get(&instance.id)
+                .ok_or_else(|| anyhow!("no agent seed"))
+                .and_then(|seed| seed.derive(index)),
+            (None, None) => Err(anyhow!("no key")),
+        };
+        match (key, owners.get(&instance.user_id)) {
+            (Ok(key), Some(owner)) if key.pubkey() == trader.wallet => {
+                if seen.insert(key.pubkey()) {
+                    wallets.push(BotWallet {
+                        label: format!("trader {}", trader.id),
+                        strategy: format!("volume strategy {}", instance.id),
+                        key,
+                        owner: owner.clone(),
+                        pools: vec![instance.target_pool],
+                    });
+                }
+            }
+            _ => warn!("Skipping trader {} {}: no key or owner", trader.id, trader.wallet),
+        }
+    }
+
+    // derived wallets whose trader row was never written
+    for (strategy_id, seed) in seeds.iter() {
+        let (Some(instance), Some(owner)) = (
+            strategies.get(strategy_id),
+            strategies.get(strategy_id).and_then(|instance| owners.get(&instance.user_id)),
+        ) else {
+            continue;
+        };
+        if !include_active && instance.completed_at.is_none() {
+            continue;
+        }
+        for (index, key) in seed.derived_wallets()? {
+            if seen.insert(key.pubkey()) {
+                wallets.push(BotWallet {
+                    label: format!("agent {}", index),
+                    strategy: format!("volume strategy {}", strategy_id),
+                    key,
+                    owner: owner.clone(),
+                    pools: vec![instance.target_pool],
+                });
+            }
+        }
+    }
+
+    let snipers = snipingstrategyinstances::table
+        .order(snipingstrategyinstances::id.asc())
+        .select((
+            snipingstrategyinstances::id,
+            snipingstrategyinstances::user_id,
+            snipingstrategyinstances::sniper_private_key,
+            snipingstrategyinstances::completed_at,
+        ))
+        .load::<(i32, i32, String, Option<chrono::NaiveDateTime>)>(&mut conn)
+        .await?;
+    // a sniper shared with a running strategy is still in use
+    let active_snipers: HashSet<&String> = snipers
+        .iter()
+        .filter(|(_, _, _, completed_at)| !include_active && completed_at.is_none())
+        .map(|(_, _, private_key, _)| private_key)
+        .collect();
+    // several sniping strategies can share a sniper, it's reported under the first one
+    for (strategy_id, user_id, private_key, _) in snipers.iter() {
+        if active_snipers.contains(private_key) {
+            continue;
+        }
+        let (Ok(key), Some(owner)) = (KeypairClonable::new_from_privkey(private_key), owners.get(user_id)) else {
+            warn!("Skipping a sniper of user {}: no key or owner", user_id);
+            continue;
+        };
+        if !seen.insert(key.pubkey()) {
+            continue;
+        }
+        // snipers trade many pools, the ones they traded are in their events
+        let pools = bot_events::table
+            .filter(bot_events::wallets.contains(vec![key.pubkey().to_string()]))
+            .filter(bot_events::pool.is_not_null())
+            .select(bot_events::pool.assume_not_null())
+            .distinct()
+            .load::<String>(&mut conn)
+            .await?
+            .iter()
+            .filter_map(|pool| Pubkey::from_str(pool).ok())
+            .collect();
+        wallets.push(BotWallet {
+            label: format!("sniper of user {}", user_id),
+            strategy: format!("sniping strategy {}", strategy_id),
+            key,
+            owner: owner.clone(),
+            pools,
+        });
+    }
+    Ok(wallets)
+}
@@ -1,9 +1,10 @@
 use crate::config::constants::{
-    REDIS_LP_MINT_KEYS, REDIS_POOLS_DETAILS, REDIS_POOLS_KEYS, REDIS_SWAP_CACHE_EXPIRES_S,
-    REDIS_SWAP_CACHE_PREFIX, REDIS_USERS,
+    REDIS_API_NONCE_PREFIX, REDIS_API_SESSION_PREFIX, REDIS_API_WITHDRAWAL_PREFIX, REDIS_LP_MINT_KEYS,
+    REDIS_POOLS_DETAILS, REDIS_POOLS_KEYS, REDIS_SWAP_CACHE_EXPIRES_S, REDIS_SWAP_CACHE_PREFIX, REDIS_USERS,
 };
 use crate::types::events::ExecutionReceipt;
 use crate::types::pool::RaydiumPool;
+use crate::types::withdrawal::WithdrawalRequest;
 use anyhow::Result;
 use r2d2_redis::redis::{Commands, RedisResult};
 use serde::Serialize;
@@ -178,3 +179,52 @@ pub fn monitor_users(redis: &RedisPool, previous_count: usize) -> Result<(usize,
         Ok((current_count, None))
     }
 }
+
+// A signed API message is accepted once, false if its nonce was already used
+pub fn claim_api_nonce(redis: &RedisPool, wallet: &Pubkey, nonce: &str, expires_s: u64) -> Result<bool> {
+    let mut conn = redis.get()?;
+    let key = format!("{}{}:{}", REDIS_API_NONCE_PREFIX, wallet, nonce);
+    let claimed: Option<String> = r2d2_redis::redis::cmd("SET")
+        .arg(key)
+        .arg(1)
+        .arg("NX")
+        .arg("EX")
+        .arg(expires_s)
+        .query(&mut *conn)?;
+    Ok(claimed.is_some())
+}
+
+pub fn store_api_session(redis: &RedisPool, token: &str, user_id: i32, expires_s: u64) -> Result<()> {
+    let mut conn = redis.get()?;
+    let key = format!("{}{}", REDIS_API_SESSION_PREFIX, token);
+    conn.set_ex(key, user_id, expires_s as usize)?;
+    Ok(())
+}
+
+pub fn get_api_session_user(redis: &RedisPool, token: &str) -> Result<Option<i32>> {
+    let mut conn = redis.get()?;
+    let key = format!("{}{}", REDIS_API_SESSION_PREFIX, token);
+    Ok(conn.get(key)?)
+}
+
+// A withdrawal waiting for the second confirmation of the API client
+pub fn store_pending_withdrawal(
+    redis: &RedisPool,
+    token: &str,
+    user_id: i32,
+    request: &WithdrawalRequest,
+    expires_s: u64,
+) -> Result<()> {
+    let mut conn = redis.get()?;
+    let key = format!("{}{}", REDIS_API_WITHDRAWAL_PREFIX, token);
+    conn.set_ex(key, serde_json::to_string(&(user_id, request))?, expires_s as usize)?;
+    Ok(())
+}
+
+// The withdrawal is confirmed once, the token is deleted as it's read
+pub fn take_pending_withdrawal(redis: &RedisPool, token: &str) -> Result<Option<(i32, WithdrawalRequest)>> {
+    let mut conn = redis.get()?;
+    let key = format!("{}{}", REDIS_API_WITHDRAWAL_PREFIX, token);
+    let pending: Option<String> = r2d2_redis::redis::cmd("GETDEL").arg(key).query(&mut *conn)?;
+    Ok(pending.map(|pending| serde_json::from_str(&pending)).transpose()?)
+}
@@ -0,0 +1,251 @@
+use crate::schema::{deposit_wallets, depositswithdrawals};
+use crate::storage::persistent::DbPool;
+use crate::types::bot_user::{DepositWallet, DepositsWithdrawals, NewDepositWallet, NewDepositsWithdrawals};
+use crate::types::withdrawal::WithdrawalRequest;
+use anyhow::Result;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+
+/// The deposit wallet of the user, allocated on the first request.
+pub async fn get_or_create_deposit_wallet(db_pool: &DbPool, user_id: i32) -> Result<DepositWallet> {
+    let mut conn = db_pool.get().await?;
+    diesel::insert_into(deposit_wallets::table)
+        .values(NewDepositWallet::generate(user_id))
+        .on_conflict(deposit_wallets::user_id)
+        .do_nothing()
+        .execute(&mut conn)
+        .await?;
+    Ok(deposit_wallets::table
+        .filter(deposit_wallets::user_id.eq(user_id))
+        .select(DepositWallet::as_select())
+        .first(&mut conn)
+        .await?)
+}
+
+/// The deposit wallet of the user, if one was allocated already.
+pub async fn get_deposit_wallet(db_pool: &DbPool, user_id: i32) -> Result<Option<DepositWallet>> {
+    let mut conn = db_pool.get().await?;
+    Ok(deposit_wallets::table
+        .filter(deposit_wallets::user_id.eq(user_id))
+        .select(DepositWallet::as_select())
+        .first(&mut conn)
+        .await
+        .optional()?)
+}
+
+pub async fn get_deposit_wallets(db_pool: &DbPool) -> Result<Vec<DepositWallet>> {
+    let mut conn = db_pool.get().await?;
+    Ok(deposit_wallets::table
+        .select(DepositWallet::as_select())
+        .load(&mut conn)
+        .await?)
+}
+
+/// Records the deposit unless its transaction is recorded already, returns whether it was new.
+pub async fn insert_deposit(db_pool: &DbPool, deposit: NewDepositsWithdrawals) -> Result<bool> {
+    let mut conn = db_pool.get().await?;
+    let inserted = diesel::insert_into(depositswithdrawals::table)
+        .values(deposit)
+        .on_conflict(depositswithdrawals::signature)
+        .do_nothing()
+        .execute(&mut conn)
+        .await?;
+    Ok(inserted > 0)
+}
+
+/// Deposits that aren't swept nor being swept, oldest first.
+pub async fn get_unswept_deposits(db_pool: &DbPool) -> Result<Vec<DepositsWithdrawals>> {
+    let mut conn = db_pool.get().await?;
+    Ok(depositswithdrawals::table
+        .filter(depositswithdrawals::is_deposit.eq(true))
+        .filter(depositswithdrawals::is_success.eq(false))
+        .filter(depositswithdrawals::action_uuid.is_null())
+        .filter(depositswithdrawals::description.is_null())
+        .order(depositswithdrawals::time.asc())
+        .select(DepositsWithdrawals::as_select())
+        .load(&mut conn)
+        .await?)
+}
+
+pub async fn start_sweep(db_pool: &DbPool, deposit_ids: &[i32], action_uuid: &str) -> Result<()> {
+    let mut conn = db_pool.get().await?;
+    diesel::update(depositswithdrawals::table.filter(depositswithdrawals::id.eq_any(deposit_ids)))
+        .set(depositswithdrawals::action_uuid.eq(action_uuid))
+        .execute(&mut conn)
+        .await?;
+    Ok(())
+}
+
+/// Credits the deposits of the sweep and the fee taken from each, returns them.
+pub async fn settle_sweep(
+    db_pool: &DbPool,
+    action_uuid: &str,
+    tx_hash: &str,
+    bot_fee: f64,
+) -> Result<Vec<DepositsWithdrawals>> {
+    let mut conn = db_pool.get().await?;
+    let deposits = depositswithdrawals::table
+        .filter(depositswithdrawals::action_uuid.eq(action_uuid))
+        .filter(depositswithdrawals::is_success.eq(false))
+        .select(DepositsWithdrawals::as_select())
+        .load(&mut conn)
+        .await?;
+    for deposit in deposits.iter() {
+        diesel::update(depositswithdrawals::table.find(deposit.id))
+            .set((
+                depositswithdrawals::is_success.eq(true),
+                depositswithdrawals::signature_fee.eq(tx_hash),
+                depositswithdrawals::fee_taken_sol.eq(deposit.amount_sol.unwrap_or(0.0) * bot_fee),
+            ))
+            .execute(&mut conn)
+            .await?;
+    }
+    Ok(deposits)
+}
+
+/// The deposits of a failed sweep are swept again.
+pub async fn fail_sweep(db_pool: &DbPool, action_uuid: &str) -> Result<usize> {
+    let mut conn = db_pool.get().await?;
+    Ok(diesel::update(
+        depositswithdrawals::table
+            .filter(depositswithdrawals::action_uuid.eq(action_uuid))
+            .filter(depositswithdrawals::is_success.eq(false)),
+    )
+    .set(depositswithdrawals::action_uuid.eq(None::<String>))
+    .execute(&mut conn)
+    .await?)
+}
+
+/// Sweeps interrupted by a restart never get a receipt, they're swept again.
+pub async fn release_sweeps(db_pool: &DbPool) -> Result<usize> {
+    let mut conn = db_pool.get().await?;
+    Ok(diesel::update(
+        depositswithdrawals::table
+            .filter(depositswithdrawals::is_deposit.eq(true))
+            .filter(depositswithdrawals::action_uuid.is_not_null())
+            .filter(depositswithdrawals::is_success.eq(false)),
+    )
+    .set(depositswithdrawals::action_uuid.eq(None::<String>))
+    .execute(&mut conn)
+    .await?)
+}
+
+/// A deposit whose transaction failed is never swept.
+pub async fn reject_deposit(db_pool: &DbPool, deposit_id: i32, reason: &str) -> Result<()> {
+    let mut conn = db_pool.get().await?;
+    diesel::update(depositswithdrawals::table.find(deposit_id))
+        .set(depositswithdrawals::description.eq(reason))
+        .execute(&mut conn)
+        .await?;
+    Ok(())
+}
+
+pub async fn insert_withdrawal(db_pool: &DbPool, user_id: i32, request: &WithdrawalRequest) -> Result<i32> {
+    let mut conn = db_pool.get().await?;
+    Ok(diesel::insert_into(depositswithdrawals::table)
+        .values(NewDepositsWithdrawals {
+            user_id: Some(user_id),
+            time: chrono::Utc::now().naive_utc(),
+            is_deposit: false,
+            amount_sol: request.amount_sol,
+            is_success: false,
+            signature: None,
+            description: None,
+            destination: Some(request.destination.clone()),
+        })
+        .returning(depositswithdrawals::id)
+        .get_result(&mut conn)
+        .await?)
+}
+
+/// The withdrawal of the user that is neither completed nor failed.
+pub async fn get_pending_withdrawal(db_pool: &DbPool, user_id: i32) -> Result<Option<DepositsWithdrawals>> {
+    let mut conn = db_pool.get().await?;
+    Ok(depositswithdrawals::table
+        .filter(depositswithdrawals::user_id.eq(user_id))
+        .filter(depositswithdrawals::is_deposit.eq(false))
+        .filter(depositswithdrawals::is_success.eq(false))
+        .filter(depositswithdrawals::description.is_null())
+        .select(DepositsWithdrawals::as_select())
+        .first(&mut conn)
+        .await
+        .optional()?)
+}
+
+/// Withdrawals waiting to be sent, oldest first.
+pub async fn get_queued_withdrawals(db_pool: &DbPool) -> Result<Vec<DepositsWithdrawals>> {
+    let mut conn = db_pool.get().await?;
+    Ok(depositswithdrawals::table
+        .filter(depositswithdrawals::is_deposit.eq(false))
+        .filter(depositswithdrawals::is_success.eq(false))
+        .filter(depositswithdrawals::action_uuid.is_null())
+        .filter(depositswithdrawals::description.is_null())
+        .order(depositswithdrawals::time.asc())
+        .select(DepositsWithdrawals::as_select())
+        .load(&mut conn)
+        .await?)
+}
+
+pub async fn start_withdrawal(db_pool: &DbPool, withdrawal_id: i32, action_uuid: &str) -> Result<()> {
+    let mut conn = db_pool.get().await?;
+    diesel::update(depositswithdrawals::table.find(withdrawal_id))
+        .set(depositswithdrawals::action_uuid.eq(action_uuid))
+        .execute(&mut conn)
+        .await?;
+    Ok(())
+}
+
+/// The withdrawn amount is known once confirmed when everything was withdrawn.
+pub async fn settle_withdrawal(db_pool: &DbPool, withdrawal_id: i32, tx_hash: &str, amount_sol: Option<f64>) -> Result<()> {
+    let mut conn = db_pool.get().await?;
+    diesel::update(depositswithdrawals::table.find(withdrawal_id))
+        .set((
+            depositswithdrawals::is_success.eq(true),
+            depositswithdrawals::signature.eq(tx_hash),
+            depositswithdrawals::amount_sol.eq(amount_sol),
+        ))
+        .execute(&mut conn)
+        .await?;
+    Ok(())
+}
+
+/// A failed withdrawal is never retried, the user requests it again.
+pub async fn fail_withdrawal(db_pool: &DbPool, withdrawal_id: i32, tx_hash: Option<String>, reason: &str) -> Result<()> {
+    let mut conn = db_pool.get().await?;
+    diesel::update(depositswithdrawals::table.find(withdrawal_id))
+        .set((
+            depositswithdrawals::signature.eq(tx_hash),
+            depositswithdrawals::description.eq(reason),
+        ))
+        .execute(&mut conn)
+        .await?;
+    Ok(())
+}
+
+/// Withdrawals sent before a restart may have landed, they're failed rather than sent twice.
+pub async fn fail_interrupted_withdrawals(db_pool: &DbPool) -> Result<usize> {
+    let mut conn = db_pool.get().await?;
+    Ok(diesel::update(
+        depositswithdrawals::table
+            .filter(depositswithdrawals::is_deposit.eq(false))
+            .filter(depositswithdrawals::is_success.eq(false))
+            .filter(depositswithdrawals::action_uuid.is_not_null())
+            .filter(depositswithdrawals::description.is_null()),
+    )
+    .set(depositswithdrawals::description.eq("Interrupted by a restart, check the destination before retrying"))
+    .execute(&mut conn)
+    .await?)
+}
+
+/// Latest withdrawals of the user first.
+pub async fn get_user_withdrawals(db_pool: &DbPool, user_id: i32, limit: i64) -> Result<Vec<DepositsWithdrawals>> {
+    let mut conn = db_pool.get().await?;
+    Ok(depositswithdrawals::table
+        .filter(depositswithdrawals::user_id.eq(user_id))
+        .filter(depositswithdrawals::is_deposit.eq(false))
+        .order(depositswithdrawals::time.desc())
+        .limit(limit)
+        .select(DepositsWithdrawals::as_select())
+        .load(&mut conn)
+        .await?)
+}
@@ -0,0 +1,33 @@
+use crate::schema::marketmakingstrategyinstances;
+use crate::storage::persistent::DbPool;
+use crate::types::market_making_strategy::MarketMakingStrategyInstance;
+use anyhow::Result;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+
+pub async fn store_ladder_config(db_pool: &DbPool, instance: &MarketMakingStrategyInstance) -> Result<()> {
+    let mut conn = db_pool.get().await?;
+    diesel::update(marketmakingstrategyinstances::table.find(instance.id))
+        .set((
+            marketmakingstrategyinstances::spread_pct.eq(instance.spread_pct),
+            marketmakingstrategyinstances::ladder_levels.eq(instance.ladder_levels),
+            marketmakingstrategyinstances::order_size_sol.eq(instance.order_size_sol),
+            marketmakingstrategyinstances::max_inventory_sol.eq(instance.max_inventory_sol),
+        ))
+        .execute(&mut conn)
+        .await?;
+    Ok(())
+}
+
+/// SOL spent on the buys and received for the sells of the ladders.
+pub async fn store_volume_traded(db_pool: &DbPool, instance: &MarketMakingStrategyInstance) -> Result<()> {
+    let mut conn = db_pool.get().await?;
+    diesel::update(marketmakingstrategyinstances::table.find(instance.id))
+        .set((
+            marketmakingstrategyinstances::bought_sol.eq(instance.bought_sol),
+            marketmakingstrategyinstances::sold_sol.eq(instance.sold_sol),
+        ))
+        .execute(&mut conn)
+        .await?;
+    Ok(())
+}
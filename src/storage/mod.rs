@@ -1,3 +1,9 @@
+pub mod agent_wallets;
+pub mod bot_wallets;
 pub mod cache;
+pub mod deposits;
+pub mod market_making_strategies;
 pub mod persistent;
+pub mod subscriptions;
+pub mod volume_strategies;
 mod bot_event_db;
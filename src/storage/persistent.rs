@@ -3,7 +3,7 @@ use crate::schema::users::dsl::users;
 use crate::schema::bot_events::dsl::bot_events;
 use crate::schema::users::{all_columns, chat_id, id, last_login};
 use crate::storage::cache::RedisPool;
-use crate::types::pool::RaydiumPoolPriceUpdate;
+use crate::types::pool::{NewSwap, RaydiumPoolPriceUpdate};
 use crate::types::bot_user::{BotUser, NewBotUser};
 use crate::types::sniping_strategy::SnipingStrategyInstance;
 use crate::utils::keys::{private_key_string_base58, public_key_string};
@@ -13,6 +13,7 @@ use diesel_async::RunQueryDsl;
 use diesel_async::pooled_connection::deadpool::{Object, Pool};
 use diesel_async::pooled_connection::AsyncDieselConnectionManager;
 use diesel_async::{pooled_connection, AsyncConnection, AsyncPgConnection};
+use solana_sdk::pubkey::Pubkey;
 use solana_sdk::signature::{Keypair, Signer};
 use std::default::Default;
 use std::fmt::Debug;
@@ -20,6 +21,7 @@ use std::sync::Arc;
 use teloxide::types::User as TelegramUser;
 use crate::schema::snipingstrategyinstances;
 use crate::solana;
+use crate::types::actions::SolanaAction;
 use crate::types::events::{BotEvent, BotEventModel};
 
 pub type DbPool = Arc<Pool<AsyncPgConnection>>;
@@ -42,6 +44,29 @@ pub async fn save_price_to_db(diesel_pool: DbPool, price: RaydiumPoolPriceUpdate
     Ok(())
 }
 
+pub async fn save_swap_to_db(diesel_pool: &DbPool, swap: NewSwap) -> Result<()> {
+    let mut conn = diesel_pool.get().await?;
+    diesel::insert_into(crate::schema::swaps::table)
+        .values(swap)
+        .execute(&mut conn)
+        .await?;
+    Ok(())
+}
+
+/// Removes the swaps of the pool that were on a dropped fork, the slots after them may be canonical.
+pub async fn delete_rolled_back_swaps(diesel_pool: &DbPool, pool: &Pubkey, dropped_slots: &[u64]) -> Result<usize> {
+    use crate::schema::swaps;
+    let mut conn = diesel_pool.get().await?;
+    let dropped_slots: Vec<i64> = dropped_slots.iter().map(|slot| *slot as i64).collect();
+    Ok(diesel::delete(
+        swaps::table
+            .filter(swaps::pool.eq(pool.to_string()))
+            .filter(swaps::slot.eq_any(dropped_slots)),
+    )
+    .execute(&mut conn)
+    .await?)
+}
+
 pub async fn save_new_sniping_strategy_to_db(
     diesel_pool: DbPool,
     strategy: crate::types::sniping_strategy::NewSnipingStrategyInstance,
@@ -115,4 +140,27 @@ pub async fn save_bot_event_to_db(
         .execute(&mut conn)
         .await?;
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Inserts the action or updates its execution state if it was stored already.
+pub async fn save_action_to_db(diesel_pool: &DbPool, action: SolanaAction) -> Result<()> {
+    use crate::schema::solana_actions;
+    use diesel::upsert::excluded;
+    let mut conn = diesel_pool.get().await?;
+    let _ = diesel::insert_into(solana_actions::table)
+        .values(action)
+        .on_conflict(solana_actions::uuid)
+        .do_update()
+        .set((
+            solana_actions::status.eq(excluded(solana_actions::status)),
+            solana_actions::tx_hash.eq(excluded(solana_actions::tx_hash)),
+            solana_actions::balance_before.eq(excluded(solana_actions::balance_before)),
+            solana_actions::balance_after.eq(excluded(solana_actions::balance_after)),
+            solana_actions::fee.eq(excluded(solana_actions::fee)),
+            solana_actions::sent_at.eq(excluded(solana_actions::sent_at)),
+            solana_actions::confirmed_at.eq(excluded(solana_actions::confirmed_at)),
+        ))
+        .execute(&mut conn)
+        .await?;
+    Ok(())
+}
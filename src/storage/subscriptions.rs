@@ -0,0 +1,193 @@
+use crate::schema::{subscription_payments, subscriptions};
+use crate::storage::persistent::DbPool;
+use crate::types::subscription::{
+    NewSubscription, NewSubscriptionPayment, PaymentStatus, Subscription, SubscriptionPayment, SubscriptionStatus,
+};
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+
+pub async fn get_subscription(db_pool: &DbPool, user_id: i32, token_mint: &str) -> Result<Option<Subscription>> {
+    let mut conn = db_pool.get().await?;
+    Ok(subscriptions::table
+        .filter(subscriptions::user_id.eq(user_id))
+        .filter(subscriptions::token_mint.eq(token_mint))
+        .select(Subscription::as_select())
+        .first(&mut conn)
+        .await
+        .optional()?)
+}
+
+pub async fn get_user_subscriptions(db_pool: &DbPool, user_id: i32) -> Result<Vec<Subscription>> {
+    let mut conn = db_pool.get().await?;
+    Ok(subscriptions::table
+        .filter(subscriptions::user_id.eq(user_id))
+        .select(Subscription::as_select())
+        .load(&mut conn)
+        .await?)
+}
+
+/// Sets the plan of the user for the token, the paid period and the grace period are the given ones.
+pub async fn save_subscription(
+    db_pool: &DbPool,
+    new_subscription: NewSubscription,
+    paid_until: Option<DateTime<Utc>>,
+    grace_until: Option<DateTime<Utc>>,
+) -> Result<Subscription> {
+    let mut conn = db_pool.get().await?;
+    Ok(diesel::insert_into(subscriptions::table)
+        .values(&new_subscription)
+        .on_conflict((subscriptions::user_id, subscriptions::token_mint))
+        .do_update()
+        .set((
+            subscriptions::plan.eq(new_subscription.plan),
+            subscriptions::status.eq(&new_subscription.status),
+            subscriptions::subscribed_at.eq(new_subscription.subscribed_at),
+            subscriptions::paid_until.eq(paid_until),
+            subscriptions::grace_until.eq(grace_until),
+        ))
+        .returning(Subscription::as_returning())
+        .get_result(&mut conn)
+        .await?)
+}
+
+/// Paid subscriptions whose period is over, that aren't being charged already and whose last
+/// payment didn't fail after `retry_failed_before`.
+pub async fn get_due_subscriptions(
+    db_pool: &DbPool,
+    now: DateTime<Utc>,
+    retry_failed_before: DateTime<Utc>,
+) -> Result<Vec<Subscription>> {
+    let mut conn = db_pool.get().await?;
+    let pending_payments = subscription_payments::table
+        .filter(subscription_payments::subscription_id.eq(subscriptions::id))
+        .filter(
+            subscription_payments::status.eq(PaymentStatus::Pending.to_string()).or(subscription_payments::status
+                .eq(PaymentStatus::Failed.to_string())
+                .and(subscription_payments::created_at.ge(retry_failed_before))),
+        );
+    Ok(subscriptions::table
+        .filter(subscriptions::plan.gt(0))
+        .filter(subscriptions::status.ne(SubscriptionStatus::Suspended.to_string()))
+        .filter(subscriptions::paid_until.is_null().or(subscriptions::paid_until.le(now)))
+        .filter(diesel::dsl::not(diesel::dsl::exists(pending_payments)))
+        .select(Subscription::as_select())
+        .load(&mut conn)
+        .await?)
+}
+
+/// Starts the grace period unless it was started already.
+pub async fn enter_grace_period(db_pool: &DbPool, subscription_id: i32, grace_until: DateTime<Utc>) -> Result<()> {
+    let mut conn = db_pool.get().await?;
+    diesel::update(subscriptions::table.find(subscription_id).filter(subscriptions::grace_until.is_null()))
+        .set((
+            subscriptions::status.eq(SubscriptionStatus::Grace.to_string()),
+            subscriptions::grace_until.eq(grace_until),
+        ))
+        .execute(&mut conn)
+        .await?;
+    Ok(())
+}
+
+/// The plan is back to free, `plan` keeps what the user had for the notification.
+pub async fn suspend_subscription(db_pool: &DbPool, subscription_id: i32) -> Result<()> {
+    let mut conn = db_pool.get().await?;
+    diesel::update(subscriptions::table.find(subscription_id))
+        .set(subscriptions::status.eq(SubscriptionStatus::Suspended.to_string()))
+        .execute(&mut conn)
+        .await?;
+    Ok(())
+}
+
+pub async fn insert_payment(db_pool: &DbPool, payment: NewSubscriptionPayment) -> Result<()> {
+    let mut conn = db_pool.get().await?;
+    diesel::insert_into(subscription_payments::table)
+        .values(payment)
+        .execute(&mut conn)
+        .await?;
+    Ok(())
+}
+
+pub async fn get_pending_payment(db_pool: &DbPool, action_uuid: &str) -> Result<Option<SubscriptionPayment>> {
+    let mut conn = db_pool.get().await?;
+    Ok(subscription_payments::table
+        .filter(subscription_payments::action_uuid.eq(action_uuid))
+        .filter(subscription_payments::status.eq(PaymentStatus::Pending.to_string()))
+        .select(SubscriptionPayment::as_select())
+        .first(&mut conn)
+        .await
+        .optional()?)
+}
+
+/// Records a confirmed payment and extends the subscription by a period, returns the subscription.
+pub async fn settle_payment(
+    db_pool: &DbPool,
+    payment: &SubscriptionPayment,
+    tx_hash: &str,
+    period: Duration,
+) -> Result<Subscription> {
+    let mut conn = db_pool.get().await?;
+    let now = Utc::now();
+    diesel::update(subscription_payments::table.find(payment.id))
+        .set((
+            subscription_payments::status.eq(PaymentStatus::Paid.to_string()),
+            subscription_payments::tx_hash.eq(tx_hash),
+            subscription_payments::paid_at.eq(now),
+        ))
+        .execute(&mut conn)
+        .await?;
+    let subscription = subscriptions::table
+        .find(payment.subscription_id)
+        .select(Subscription::as_select())
+        .first(&mut conn)
+        .await?;
+    // the period starts when the previous one ended, unless it ended long ago
+    let period_start = subscription.paid_until.map_or(now, |paid_until| paid_until.max(now - period));
+    Ok(diesel::update(subscriptions::table.find(payment.subscription_id))
+        .set((
+            subscriptions::status.eq(SubscriptionStatus::Active.to_string()),
+            subscriptions::paid_until.eq(period_start + period),
+            subscriptions::grace_until.eq(None::<DateTime<Utc>>),
+        ))
+        .returning(Subscription::as_returning())
+        .get_result(&mut conn)
+        .await?)
+}
+
+pub async fn fail_payment(db_pool: &DbPool, payment_id: i32, tx_hash: Option<String>) -> Result<()> {
+    let mut conn = db_pool.get().await?;
+    diesel::update(subscription_payments::table.find(payment_id))
+        .set((
+            subscription_payments::status.eq(PaymentStatus::Failed.to_string()),
+            subscription_payments::tx_hash.eq(tx_hash),
+        ))
+        .execute(&mut conn)
+        .await?;
+    Ok(())
+}
+
+/// Payments that never got a receipt, e.g. the transfer was dropped, are retried.
+pub async fn fail_stale_payments(db_pool: &DbPool, created_before: DateTime<Utc>) -> Result<usize> {
+    let mut conn = db_pool.get().await?;
+    Ok(diesel::update(
+        subscription_payments::table
+            .filter(subscription_payments::status.eq(PaymentStatus::Pending.to_string()))
+            .filter(subscription_payments::created_at.lt(created_before)),
+    )
+    .set(subscription_payments::status.eq(PaymentStatus::Failed.to_string()))
+    .execute(&mut conn)
+    .await?)
+}
+
+/// Payments of all the subscriptions of the user, latest first.
+pub async fn get_user_payments(db_pool: &DbPool, user_id: i32) -> Result<Vec<(SubscriptionPayment, String)>> {
+    let mut conn = db_pool.get().await?;
+    Ok(subscription_payments::table
+        .inner_join(subscriptions::table)
+        .filter(subscriptions::user_id.eq(user_id))
+        .order(subscription_payments::created_at.desc())
+        .select((SubscriptionPayment::as_select(), subscriptions::token_mint))
+        .load(&mut conn)
+        .await?)
+}
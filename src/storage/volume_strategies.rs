@@ -0,0 +1,41 @@
+use crate::schema::volumestrategyinstances;
+use crate::storage::persistent::DbPool;
+use crate::types::volume_strategy::VolumeStrategyInstance;
+use anyhow::Result;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+
+/// The tranche size and frequency the budget mode derived for the next tranche.
+pub async fn store_tranche_plan(db_pool: &DbPool, instance: &VolumeStrategyInstance) -> Result<()> {
+    let mut conn = db_pool.get().await?;
+    diesel::update(volumestrategyinstances::table.find(instance.id))
+        .set((
+            volumestrategyinstances::tranche_size_sol.eq(instance.tranche_size_sol),
+            volumestrategyinstances::tranche_frequency_hbs.eq(instance.tranche_frequency_hbs),
+        ))
+        .execute(&mut conn)
+        .await?;
+    Ok(())
+}
+
+/// The volume traded along with how far the swaps were counted, so a restart doesn't count them twice.
+pub async fn store_volume_traded(db_pool: &DbPool, instance: &VolumeStrategyInstance) -> Result<()> {
+    let mut conn = db_pool.get().await?;
+    diesel::update(volumestrategyinstances::table.find(instance.id))
+        .set((
+            volumestrategyinstances::volume_traded_sol.eq(instance.volume_traded_sol),
+            volumestrategyinstances::volume_counted_until.eq(instance.volume_counted_until),
+        ))
+        .execute(&mut conn)
+        .await?;
+    Ok(())
+}
+
+pub async fn store_reference_price(db_pool: &DbPool, instance: &VolumeStrategyInstance) -> Result<()> {
+    let mut conn = db_pool.get().await?;
+    diesel::update(volumestrategyinstances::table.find(instance.id))
+        .set(volumestrategyinstances::reference_price.eq(instance.reference_price))
+        .execute(&mut conn)
+        .await?;
+    Ok(())
+}
@@ -1,2 +1,3 @@
 mod strategy;
+pub mod withdrawal;
 pub use strategy::DepositWithdrawStrategy;
@@ -1,39 +1,394 @@
 use crate::config::app_context::AppContext;
+use crate::config::constants::{
+    ACTION_EXPIRY_S, BASE_TX_FEE_SOL, DEPOSIT_SWEEP_INTERVAL_S, NEW_ACCOUNT_THRESHOLD_SOL, TRANSFER_PRIORITY_FEE_SOL,
+    WITHDRAWAL_POLL_INTERVAL_S,
+};
 use crate::schema::users::dsl::users;
-use crate::schema::users::wallet_address;
+use crate::solana::start_monitoring_account;
+use crate::solana::tx_parser::parse_max_received;
+use crate::storage::deposits;
 use crate::tg_bot::notify_user;
 use crate::types::actions::{Amount, Asset, SolanaAction, SolanaActionPayload, SolanaTransferActionPayload};
 use crate::types::engine::{Strategy, StrategyStatus};
-use crate::types::events::{BlockchainEvent, BotEvent};
+use crate::types::events::{BlockchainEvent, BotEvent, ExecutionResult};
 use crate::types::keys::KeypairClonable;
-use crate::types::bot_user::BotUser;
+use crate::types::bot_user::{BotUser, DepositWallet, DepositsWithdrawals, NewDepositsWithdrawals};
+use crate::types::routing::{EventFilter, EventType};
+use crate::types::withdrawal::{check_withdrawal_amount, WithdrawalRequest};
 use crate::utils::decimals::lamports_to_sol;
+use crate::tg_bot::helpers::formatters::format_feed_stats;
 use crate::utils::formatters::format_sol;
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use async_trait::async_trait;
 use diesel::prelude::*;
 use diesel_async::RunQueryDsl;
+use solana_sdk::native_token::sol_to_lamports;
 use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
 use std::any::Any;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt::{Debug, Formatter};
 use std::str::FromStr;
 use std::sync::Arc;
+use std::time::Instant;
 use maplit::hashmap;
-use teloxide::payloads::AnswerPreCheckoutQuerySetters;
 use tokio::sync::Mutex;
-use uuid::Uuid;
+use tracing::{error, info, warn};
 
+/// Records the deposits to the deposit wallets of the users and sweeps them, once confirmed, to
+/// the trading wallets of the users, less `engine.bot_fee` sent to `engine.bot_wallet`.
+/// Sends the withdrawals requested from the trading wallets and records their result.
 #[derive(Clone)]
 pub struct DepositWithdrawStrategy {
     context: AppContext,
+    deposit_wallets: HashMap<Pubkey, DepositWallet>,
+    // the sweeps waiting for a receipt
+    sweeps: HashMap<String, Instant>,
+    // the withdrawals waiting for a receipt, by action
+    withdrawals: HashMap<String, (DepositsWithdrawals, Instant)>,
+    last_sweep: Option<Instant>,
+    last_withdrawal_check: Option<Instant>,
+    deposits_credited: u64,
+    withdrawals_sent: u64,
 }
 
 impl DepositWithdrawStrategy {
     /// Create a new instance of the strategy.
     pub async fn new(context: AppContext) -> Self {
-        Self { context }
+        Self {
+            context,
+            deposit_wallets: HashMap::new(),
+            sweeps: HashMap::new(),
+            withdrawals: HashMap::new(),
+            last_sweep: None,
+            last_withdrawal_check: None,
+            deposits_credited: 0,
+            withdrawals_sent: 0,
+        }
+    }
+
+    async fn notify(&self, user: &BotUser, text: &str) {
+        if let Some(user_chat_id) = user.chat_id && let Some(bot) = self.context.tg_bot.as_ref() {
+            notify_user(bot, user_chat_id, text).await;
+        }
+    }
+
+    async fn get_user(&self, user_id: i32) -> Result<BotUser> {
+        let mut conn = self.context.db_pool.get().await?;
+        Ok(users.find(user_id).first::<BotUser>(&mut conn).await?)
+    }
+
+    // the fee and where it goes, no fee without a bot wallet
+    async fn get_bot_fee(&self) -> Result<Option<(Pubkey, f64)>> {
+        let engine_config = self.context.get_settings().await.engine.clone();
+        let Some(bot_wallet) = engine_config.bot_wallet else {
+            return Ok(None);
+        };
+        let bot_wallet = Pubkey::from_str(&bot_wallet).map_err(|e| anyhow!("Invalid engine.bot_wallet: {}", e))?;
+        Ok(Some((bot_wallet, engine_config.bot_fee.unwrap_or(0.0))))
+    }
+
+    // the wallets allocated since the last refresh are monitored for deposits
+    async fn refresh_deposit_wallets(&mut self) -> Result<()> {
+        for deposit_wallet in deposits::get_deposit_wallets(&self.context.db_pool).await? {
+            if !self.deposit_wallets.contains_key(&deposit_wallet.wallet) {
+                start_monitoring_account(&self.context, &deposit_wallet.wallet).await;
+                self.deposit_wallets.insert(deposit_wallet.wallet, deposit_wallet);
+            }
+        }
+        Ok(())
     }
+
+    async fn on_deposit(&mut self, signature: String, wallet: Pubkey, amount: u64) -> Result<()> {
+        let Some(deposit_wallet) = self.deposit_wallets.get(&wallet) else {
+            return Ok(());
+        };
+        let user_id = deposit_wallet.user_id;
+        let is_new = deposits::insert_deposit(&self.context.db_pool, NewDepositsWithdrawals {
+            user_id: Some(user_id),
+            time: chrono::Utc::now().naive_utc(),
+            is_deposit: true,
+            amount_sol: Some(lamports_to_sol(amount)),
+            is_success: false,
+            signature: Some(signature),
+            description: None,
+            destination: None,
+        })
+        .await?;
+        if is_new {
+            info!("Deposit of {} SOL to {} of user {}", lamports_to_sol(amount), wallet, user_id);
+            let user = self.get_user(user_id).await?;
+            self.notify(&user, &format!(
+                "💸 Deposit received `{}` SOL, it's credited once confirmed",
+                format_sol(lamports_to_sol(amount))
+            ))
+            .await;
+        }
+        Ok(())
+    }
+
+    // only the deposits whose transaction is confirmed are swept
+    async fn get_confirmed_deposits(&self, deposits: Vec<DepositsWithdrawals>) -> Vec<DepositsWithdrawals> {
+        let mut confirmed = vec![];
+        for deposit in deposits {
+            let Some(Ok(signature)) = deposit.signature.as_deref().map(Signature::from_str) else {
+                continue;
+            };
+            match self.context.rpc_pool.get_signature_status(&signature).await {
+                Ok(Some(Ok(()))) => confirmed.push(deposit),
+                Ok(Some(Err(e))) => {
+                    warn!("Deposit {} failed: {:?}", deposit.id, e);
+                    if let Err(e) = deposits::reject_deposit(&self.context.db_pool, deposit.id, "Transaction failed").await {
+                        error!("Failed to reject deposit {}: {:?}", deposit.id, e);
+                    }
+                }
+                Ok(None) => {}
+                Err(e) => warn!("Failed to get the status of deposit {}: {:?}", deposit.id, e),
+            }
+        }
+        confirmed
+    }
+
+    async fn sweep(&mut self, user_id: i32, deposits: Vec<DepositsWithdrawals>) -> Result<Option<Arc<Mutex<SolanaAction>>>> {
+        let deposits = self.get_confirmed_deposits(deposits).await;
+        if deposits.is_empty() {
+            return Ok(None);
+        }
+        let deposit_wallet = deposits::get_or_create_deposit_wallet(&self.context.db_pool, user_id).await?;
+        let user = self.get_user(user_id).await?;
+        let balance = self.context.rpc_pool.get_balance(&deposit_wallet.wallet).await?;
+        let deposited = deposits.iter().map(|deposit| sol_to_lamports(deposit.amount_sol.unwrap_or(0.0))).sum();
+        let bot_fee = self.get_bot_fee().await?;
+        let Some((fee, to_trading)) = sweep_amounts(balance, deposited, bot_fee.map_or(0.0, |(_, fee)| fee)) else {
+            warn!("Deposit wallet {} of user {} is empty, the deposits are not swept", deposit_wallet.wallet, user_id);
+            return Ok(None);
+        };
+        let mut transfers = vec![SolanaActionPayload::SolanaTransferActionPayload(SolanaTransferActionPayload {
+            asset: Asset::Sol,
+            receiver: user.wallet_address,
+            amount: Amount::Exact(to_trading),
+        })];
+        if let Some((bot_wallet, _)) = bot_fee && fee > 0 {
+            transfers.push(SolanaActionPayload::SolanaTransferActionPayload(SolanaTransferActionPayload {
+                asset: Asset::Sol,
+                receiver: bot_wallet,
+                amount: Amount::Exact(fee),
+            }));
+        }
+        // the deposit wallet pays for the sweep, the fee is held back from the amounts
+        let action = SolanaAction::new(KeypairClonable::new_from_privkey(&deposit_wallet.private_key)?, transfers);
+        let action_uuid = action.uuid.to_string();
+        let deposit_ids: Vec<i32> = deposits.iter().map(|deposit| deposit.id).collect();
+        deposits::start_sweep(&self.context.db_pool, &deposit_ids, &action_uuid).await?;
+        info!("Sweeping {} SOL of user {}, fee {} SOL", lamports_to_sol(to_trading), user_id, lamports_to_sol(fee));
+        self.sweeps.insert(action_uuid, Instant::now());
+        Ok(Some(Arc::new(Mutex::new(action))))
+    }
+
+    async fn sweep_all(&mut self) -> Result<Vec<Arc<Mutex<SolanaAction>>>> {
+        self.refresh_deposit_wallets().await?;
+        let expired: Vec<String> = self
+            .sweeps
+            .iter()
+            .filter(|(_, sent)| sent.elapsed().as_secs() > ACTION_EXPIRY_S)
+            .map(|(action_uuid, _)| action_uuid.clone())
+            .collect();
+        for action_uuid in expired {
+            self.on_sweep_failed(&action_uuid).await?;
+        }
+        let mut by_user: BTreeMap<i32, Vec<DepositsWithdrawals>> = BTreeMap::new();
+        for deposit in deposits::get_unswept_deposits(&self.context.db_pool).await? {
+            if let Some(user_id) = deposit.user_id {
+                by_user.entry(user_id).or_default().push(deposit);
+            }
+        }
+        let mut actions = vec![];
+        for (user_id, deposits) in by_user {
+            match self.sweep(user_id, deposits).await {
+                Ok(action) => actions.extend(action),
+                Err(e) => error!("Failed to sweep the deposits of user {}: {:?}", user_id, e),
+            }
+        }
+        Ok(actions)
+    }
+
+    async fn on_sweep_failed(&mut self, action_uuid: &str) -> Result<()> {
+        if self.sweeps.remove(action_uuid).is_some() {
+            let released = deposits::fail_sweep(&self.context.db_pool, action_uuid).await?;
+            warn!("Sweep {} of {} deposits failed, retrying", action_uuid, released);
+        }
+        Ok(())
+    }
+
+    async fn on_sweep_confirmed(&mut self, action_uuid: &str, tx_hash: &str) -> Result<()> {
+        if self.sweeps.remove(action_uuid).is_none() {
+            return Ok(());
+        }
+        let bot_fee = self.get_bot_fee().await?.map_or(0.0, |(_, fee)| fee);
+        let credited = deposits::settle_sweep(&self.context.db_pool, action_uuid, tx_hash, bot_fee).await?;
+        self.deposits_credited += credited.len() as u64;
+        let Some(user_id) = credited.first().and_then(|deposit| deposit.user_id) else {
+            return Ok(());
+        };
+        let amount_sol: f64 = credited.iter().map(|deposit| deposit.amount_sol.unwrap_or(0.0) * (1.0 - bot_fee)).sum();
+        info!("Deposits of user {} credited, {} SOL", user_id, amount_sol);
+        let user = self.get_user(user_id).await?;
+        self.notify(&user, &format!("✅ `{}` SOL credited to your trading wallet", format_sol(amount_sol))).await;
+        Ok(())
+    }
+
+    async fn withdraw(&mut self, withdrawal: DepositsWithdrawals) -> Result<Option<Arc<Mutex<SolanaAction>>>> {
+        let user_id = withdrawal.user_id.ok_or_else(|| anyhow!("Withdrawal {} has no user", withdrawal.id))?;
+        let destination = withdrawal.destination.as_deref().unwrap_or_default();
+        let request = match WithdrawalRequest::new(destination, withdrawal.amount_sol) {
+            Ok(request) => request,
+            Err(e) => {
+                deposits::fail_withdrawal(&self.context.db_pool, withdrawal.id, None, &e.to_string()).await?;
+                return Ok(None);
+            }
+        };
+        let user = self.get_user(user_id).await?;
+        let balance = self.context.rpc_pool.get_balance(&user.wallet_address).await?;
+        let destination_exists = self.context.rpc_pool.account_exists(&request.destination()).await?;
+        if let Err(reason) = check_withdrawal_amount(balance, &request.amount(), destination_exists) {
+            deposits::fail_withdrawal(&self.context.db_pool, withdrawal.id, None, &reason).await?;
+            self.notify(&user, &format!("❌ Withdrawal failed: `{}`", reason)).await;
+            return Ok(None);
+        }
+        let action = SolanaAction::new(
+            KeypairClonable::new_from_privkey(&user.wallet_private_key)?,
+            vec![SolanaActionPayload::SolanaTransferActionPayload(SolanaTransferActionPayload {
+                asset: Asset::Sol,
+                receiver: request.destination(),
+                amount: request.amount(),
+            })],
+        );
+        let action_uuid = action.uuid.to_string();
+        deposits::start_withdrawal(&self.context.db_pool, withdrawal.id, &action_uuid).await?;
+        info!("Sending withdrawal {} of user {} to {}", withdrawal.id, user_id, request.destination);
+        self.withdrawals.insert(action_uuid, (withdrawal, Instant::now()));
+        self.withdrawals_sent += 1;
+        Ok(Some(Arc::new(Mutex::new(action))))
+    }
+
+    async fn withdraw_all(&mut self) -> Result<Vec<Arc<Mutex<SolanaAction>>>> {
+        let expired: Vec<String> = self
+            .withdrawals
+            .iter()
+            .filter(|(_, (_, sent))| sent.elapsed().as_secs() > ACTION_EXPIRY_S)
+            .map(|(action_uuid, _)| action_uuid.clone())
+            .collect();
+        for action_uuid in expired {
+            self.on_withdrawal_failed(&action_uuid, None, "No receipt").await?;
+        }
+        let mut actions = vec![];
+        for withdrawal in deposits::get_queued_withdrawals(&self.context.db_pool).await? {
+            let withdrawal_id = withdrawal.id;
+            match self.withdraw(withdrawal).await {
+                Ok(action) => actions.extend(action),
+                Err(e) => error!("Failed to send withdrawal {}: {:?}", withdrawal_id, e),
+            }
+        }
+        Ok(actions)
+    }
+
+    async fn on_withdrawal_failed(&mut self, action_uuid: &str, tx_hash: Option<String>, reason: &str) -> Result<()> {
+        let Some((withdrawal, _)) = self.withdrawals.remove(action_uuid) else {
+            return Ok(());
+        };
+        deposits::fail_withdrawal(&self.context.db_pool, withdrawal.id, tx_hash, reason).await?;
+        warn!("Withdrawal {} failed: {}", withdrawal.id, reason);
+        if let Some(user_id) = withdrawal.user_id {
+            let user = self.get_user(user_id).await?;
+            self.notify(&user, &format!("❌ Withdrawal failed: `{}`", reason)).await;
+        }
+        Ok(())
+    }
+
+    async fn on_withdrawal_confirmed(&mut self, action_uuid: &str, tx_hash: &Signature) -> Result<()> {
+        let Some((withdrawal, _)) = self.withdrawals.remove(action_uuid) else {
+            return Ok(());
+        };
+        // what was received when everything was withdrawn
+        let amount_sol = match withdrawal.amount_sol {
+            Some(amount_sol) => Some(amount_sol),
+            None => match self.context.rpc_pool.get_transaction_with_config(tx_hash).await {
+                Ok(tx) => parse_max_received(&tx).ok().map(lamports_to_sol),
+                Err(e) => {
+                    warn!("Failed to get the amount of withdrawal {}: {:?}", withdrawal.id, e);
+                    None
+                }
+            },
+        };
+        deposits::settle_withdrawal(&self.context.db_pool, withdrawal.id, &tx_hash.to_string(), amount_sol).await?;
+        info!("Withdrawal {} confirmed", withdrawal.id);
+        if let Some(user_id) = withdrawal.user_id {
+            let user = self.get_user(user_id).await?;
+            let amount = amount_sol.map_or("everything".to_string(), |amount_sol| format!("`{}` SOL", format_sol(amount_sol)));
+            self.notify(&user, &format!(
+                "✅ Withdrawal of {} to `{}` confirmed",
+                amount,
+                withdrawal.destination.unwrap_or_default()
+            ))
+            .await;
+        }
+        Ok(())
+    }
+
+    async fn handle_event(&mut self, event: BotEvent) -> Result<Vec<Arc<Mutex<SolanaAction>>>> {
+        match event {
+            BotEvent::HeartBeat(..) => {
+                let mut actions = vec![];
+                if !self.last_withdrawal_check.is_some_and(|last| last.elapsed().as_secs() < WITHDRAWAL_POLL_INTERVAL_S) {
+                    self.last_withdrawal_check = Some(Instant::now());
+                    actions.extend(self.withdraw_all().await?);
+                }
+                if !self.last_sweep.is_some_and(|last| last.elapsed().as_secs() < DEPOSIT_SWEEP_INTERVAL_S) {
+                    self.last_sweep = Some(Instant::now());
+                    actions.extend(self.sweep_all().await?);
+                }
+                Ok(actions)
+            }
+            BotEvent::BlockchainEvent(BlockchainEvent::Deposit(signature, wallet, amount)) => {
+                self.on_deposit(signature, wallet, amount).await?;
+                Ok(vec![])
+            }
+            BotEvent::ExecutionResult(uuid, _, ExecutionResult::ExecutionError(e)) => {
+                let action_uuid = uuid.to_string();
+                self.on_sweep_failed(&action_uuid).await?;
+                self.on_withdrawal_failed(&action_uuid, None, &format!("{:?}", e)).await?;
+                Ok(vec![])
+            }
+            BotEvent::BlockchainEvent(BlockchainEvent::ExecutionReceipt(receipt)) => {
+                let action_uuid = receipt.action_uuid.to_string();
+                let tx_hash = receipt.transaction_signature;
+                match receipt.err {
+                    None => {
+                        self.on_sweep_confirmed(&action_uuid, &tx_hash.to_string()).await?;
+                        self.on_withdrawal_confirmed(&action_uuid, &tx_hash).await?;
+                    }
+                    Some(e) => {
+                        self.on_sweep_failed(&action_uuid).await?;
+                        self.on_withdrawal_failed(&action_uuid, Some(tx_hash.to_string()), &e.to_string()).await?;
+                    }
+                }
+                Ok(vec![])
+            }
+            _ => Ok(vec![]),
+        }
+    }
+}
+
+/// The fee and the amount sent to the trading wallet out of the deposits, less the transaction fee paid
+/// by the deposit wallet. The dust left after the deposits would make the wallet non rent-exempt so it's swept along.
+fn sweep_amounts(balance: u64, deposited: u64, bot_fee: f64) -> Option<(u64, u64)> {
+    let swept = if balance.saturating_sub(deposited) < NEW_ACCOUNT_THRESHOLD_SOL { balance } else { deposited };
+    let swept = swept.saturating_sub(BASE_TX_FEE_SOL + TRANSFER_PRIORITY_FEE_SOL);
+    if swept == 0 {
+        return None;
+    }
+    let fee = ((deposited.min(swept) as f64 * bot_fee) as u64).min(swept);
+    Some((fee, swept - fee))
 }
 
 impl Debug for DepositWithdrawStrategy {
@@ -46,59 +401,23 @@ impl Debug for DepositWithdrawStrategy {
 impl Strategy<BotEvent, Arc<Mutex<SolanaAction>>> for DepositWithdrawStrategy {
     /// Initialize the strategy. This is called once at startup
     async fn sync_state(&mut self) -> Result<()> {
-        Ok(())
+        let released = deposits::release_sweeps(&self.context.db_pool).await?;
+        if released > 0 {
+            info!("{} deposits of interrupted sweeps are swept again", released);
+        }
+        let interrupted = deposits::fail_interrupted_withdrawals(&self.context.db_pool).await?;
+        if interrupted > 0 {
+            warn!("{} withdrawals were interrupted", interrupted);
+        }
+        self.refresh_deposit_wallets().await
     }
 
     // Process incoming signals2
     async fn process_event(&mut self, event: BotEvent) -> Vec<Arc<Mutex<SolanaAction>>> {
-        match event {
-            BotEvent::BlockchainEvent(BlockchainEvent::Deposit(
-                                          _signature,
-                                          user_wallet,
-                                          amount,
-                                      )) => {
-                let engine_config = self.context.settings.read().await.engine.clone();
-                let executor_config = self.context.settings.read().await.executor.clone();
-                let bot_wallet_opt = engine_config.bot_wallet.clone();
-                if let Some(bot_wallet_str) = bot_wallet_opt {
-                    let bot_fee = engine_config.bot_fee.unwrap_or(0.0);
-                    let mut conn = self.context.db_pool.get().await.unwrap();
-                    if let Ok(user) = users
-                        .filter(wallet_address.eq(&user_wallet.to_string()))
-                        .first::<BotUser>(&mut conn)
-                        .await
-                    {
-                        notify_user(
-                            &self.context.tg_bot.as_ref().unwrap(),
-                            user.chat_id,
-                            &format!(
-                                "💸 Deposit received `{}` SOL",
-                                format_sol(lamports_to_sol(amount))
-                            ),
-                        )
-                            .await;
-                        let main_wallet =
-                            KeypairClonable::new_from_privkey(&user.wallet_private_key).unwrap();
-                        vec![Arc::new(Mutex::new(SolanaAction::new(
-                            main_wallet.clone(),
-                            vec![
-                                SolanaActionPayload::SolanaTransferActionPayload(
-                                    SolanaTransferActionPayload {
-                                        asset: Asset::Sol,
-                                        receiver: Pubkey::from_str(&bot_wallet_str).unwrap(),
-                                        amount: Amount::Exact((amount as f64 * bot_fee) as u64),
-                                    },
-                                )],
-                        )))]
-                    } else {
-                        vec![]
-                    }
-                } else {
-                    vec![]
-                }
-            }
-            _ => vec![],
-        }
+        self.handle_event(event).await.unwrap_or_else(|e| {
+            error!("Deposit processing failed: {:?}", e);
+            vec![]
+        })
     }
 
     fn as_any(&self) -> &dyn Any {
@@ -106,8 +425,55 @@ impl Strategy<BotEvent, Arc<Mutex<SolanaAction>>> for DepositWithdrawStrategy {
     }
 
     async fn get_status(&self) -> StrategyStatus {
-        StrategyStatus::Running(hashmap! {
+        let mut status = hashmap! {
             "Deposit loop health".to_owned() => "Running".to_owned(),
-        })
+            "Deposit wallets".to_owned() => self.deposit_wallets.len().to_string(),
+            "Deposits credited".to_owned() => self.deposits_credited.to_string(),
+            "Sweeps pending".to_owned() => self.sweeps.len().to_string(),
+            "Withdrawals sent".to_owned() => self.withdrawals_sent.to_string(),
+            "Withdrawals pending".to_owned() => self.withdrawals.len().to_string(),
+        };
+        let total_first_seen = self.context.geyser_pool.stats.total_first_seen();
+        for (provider, stats) in self.context.geyser_pool.stats.snapshot() {
+            status.insert(format!("Geyser {}", provider), format_feed_stats(&provider, &stats, total_first_seen));
+        }
+        StrategyStatus::Running(status)
+    }
+
+    async fn subscriptions(&self) -> Vec<EventFilter> {
+        vec![EventFilter::of_types(&[
+            EventType::HeartBeat,
+            EventType::Deposit,
+            EventType::ExecutionReceipt,
+            EventType::ExecutionResult,
+        ])]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TX_FEE: u64 = BASE_TX_FEE_SOL + TRANSFER_PRIORITY_FEE_SOL;
+
+    #[test]
+    fn test_sweep_amounts() {
+        let deposit = 1_000_000_000;
+        let swept = deposit - TX_FEE;
+        assert_eq!(sweep_amounts(deposit, deposit, 0.5), Some((swept / 2, swept - swept / 2)));
+        // dust is swept along
+        assert_eq!(sweep_amounts(deposit + 1000, deposit, 0.0), Some((0, deposit + 1000 - TX_FEE)));
+        // an unconfirmed deposit stays on the wallet
+        assert_eq!(sweep_amounts(3 * deposit, deposit, 0.0), Some((0, swept)));
+        assert_eq!(sweep_amounts(0, deposit, 0.01), None);
+        assert_eq!(sweep_amounts(TX_FEE, TX_FEE, 0.0), None);
+    }
+
+    #[test]
+    fn test_sweep_with_an_empty_trading_wallet() {
+        // the deposit wallet pays the fee out of the deposit and is emptied, nothing is needed on the trading wallet
+        let deposit = 10_000_000;
+        let (fee, to_trading) = sweep_amounts(deposit, deposit, 0.01).unwrap();
+        assert_eq!(fee + to_trading + TX_FEE, deposit);
     }
 }
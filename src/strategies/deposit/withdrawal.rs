@@ -0,0 +1,49 @@
+use crate::storage::deposits;
+use crate::storage::persistent::DbPool;
+use crate::types::actions::SolanaAction;
+use crate::types::bot_user::BotUser;
+use crate::types::engine::{StrategyManager, StrategyStatus};
+use crate::types::events::BotEvent;
+use crate::types::withdrawal::{WithdrawalError, WithdrawalRequest};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+/// Checks the withdrawal can be requested: it goes to a foreign wallet, the strategies of the user
+/// don't use the funds and no other withdrawal is in progress. The balance is checked when it's sent.
+pub async fn check_withdrawal(
+    db_pool: &DbPool,
+    strategy_manager: &dyn StrategyManager<BotEvent, Arc<Mutex<SolanaAction>>>,
+    user: &BotUser,
+    request: &WithdrawalRequest,
+) -> Result<(), WithdrawalError> {
+    let destination = request.destination();
+    let deposit_wallet = deposits::get_or_create_deposit_wallet(db_pool, user.id).await?;
+    if destination == user.wallet_address || destination == deposit_wallet.wallet {
+        return Err(WithdrawalError::OwnWallet);
+    }
+    // the strategies being stopped are still collecting the funds
+    let mut strategies = 0;
+    for strategy in strategy_manager.get_user_strategies(user.id).await.into_values() {
+        if !matches!(strategy.lock().await.get_status().await, StrategyStatus::Stopped) {
+            strategies += 1;
+        }
+    }
+    if strategies > 0 {
+        return Err(WithdrawalError::StrategiesRunning(strategies));
+    }
+    if deposits::get_pending_withdrawal(db_pool, user.id).await?.is_some() {
+        return Err(WithdrawalError::InProgress);
+    }
+    Ok(())
+}
+
+/// Queues the withdrawal for the [DepositWithdrawStrategy](super::DepositWithdrawStrategy), returns its id.
+pub async fn request_withdrawal(
+    db_pool: &DbPool,
+    strategy_manager: &dyn StrategyManager<BotEvent, Arc<Mutex<SolanaAction>>>,
+    user: &BotUser,
+    request: &WithdrawalRequest,
+) -> Result<i32, WithdrawalError> {
+    check_withdrawal(db_pool, strategy_manager, user, request).await?;
+    Ok(deposits::insert_withdrawal(db_pool, user.id, request).await?)
+}
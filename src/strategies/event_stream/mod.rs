@@ -0,0 +1,2 @@
+mod strategy;
+pub use strategy::{EventStreamStrategy, StreamedEvent};
@@ -0,0 +1,108 @@
+use crate::config::app_context::AppContext;
+use crate::config::constants::EVENT_STREAM_CAPACITY;
+use crate::types::actions::SolanaAction;
+use crate::types::engine::{Strategy, StrategyStatus};
+use crate::types::events::{BlockchainEvent, BotEvent};
+use crate::types::routing::{EventFilter, EventRoute, EventType, Routable};
+use anyhow::Result;
+use async_trait::async_trait;
+use maplit::hashmap;
+use std::any::Any;
+use std::fmt::{Debug, Formatter};
+use std::sync::Arc;
+use tokio::sync::{broadcast, Mutex};
+use tracing::error;
+
+/// An event for the live streams, serialized once for all the listeners. The route has the
+/// wallets of the action for the execution results and receipts.
+#[derive(Debug, Clone)]
+pub struct StreamedEvent {
+    pub route: EventRoute,
+    pub event: serde_json::Value,
+}
+
+/// Publishes the pool, action and funding events to the API streams, nothing is serialized
+/// while no one listens.
+pub struct EventStreamStrategy {
+    context: AppContext,
+    publisher: broadcast::Sender<Arc<StreamedEvent>>,
+}
+
+impl EventStreamStrategy {
+    pub fn new(context: &AppContext) -> Self {
+        let (publisher, _) = broadcast::channel(EVENT_STREAM_CAPACITY);
+        Self { context: context.clone(), publisher }
+    }
+
+    pub fn publisher(&self) -> broadcast::Sender<Arc<StreamedEvent>> {
+        self.publisher.clone()
+    }
+
+    async fn route(&self, event: &BotEvent) -> EventRoute {
+        let action = match event {
+            BotEvent::ExecutionResult(_, action, _) => Some(action.clone()),
+            BotEvent::BlockchainEvent(BlockchainEvent::ExecutionReceipt(receipt)) => {
+                self.context.cache.get_action_by_uuid(receipt.action_uuid).await
+            }
+            _ => None,
+        };
+        let route = event.route();
+        match action {
+            Some(action) => {
+                let action = action.lock().await;
+                route.with_wallet(action.sniper.pubkey()).with_wallet(action.fee_payer.pubkey())
+            }
+            None => route,
+        }
+    }
+}
+
+impl Debug for EventStreamStrategy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "EventStreamStrategy")
+    }
+}
+
+#[async_trait]
+impl Strategy<BotEvent, Arc<Mutex<SolanaAction>>> for EventStreamStrategy {
+    async fn sync_state(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    async fn process_event(&mut self, event: BotEvent) -> Vec<Arc<Mutex<SolanaAction>>> {
+        if self.publisher.receiver_count() == 0 {
+            return vec![];
+        }
+        let route = self.route(&event).await;
+        match serde_json::to_value(&event) {
+            // no listener left is fine
+            Ok(event) => {
+                let _ = self.publisher.send(Arc::new(StreamedEvent { route, event }));
+            }
+            Err(e) => error!("Failed to serialize {:?} for the stream: {:?}", route.event_type, e),
+        }
+        vec![]
+    }
+
+    async fn get_status(&self) -> StrategyStatus {
+        StrategyStatus::Running(hashmap! {
+            "Listeners".to_owned() => self.publisher.receiver_count().to_string(),
+        })
+    }
+
+    async fn subscriptions(&self) -> Vec<EventFilter> {
+        vec![EventFilter::of_types(&[
+            EventType::PoolPriceUpdate,
+            EventType::Swap,
+            EventType::Rollback,
+            EventType::ExecutionResult,
+            EventType::ExecutionReceipt,
+            EventType::Deposit,
+            EventType::Withdrawal,
+        ])]
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
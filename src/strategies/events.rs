@@ -1,10 +1,12 @@
 use crate::types::events::BotEvent;
 use crate::types::actions::{Amount, SolanaTransferActionPayload};
+use crate::tg_bot::volume_strategy_config_args::VolumeStrategyConfigArgs;
 
 #[derive(Debug, Clone)]
 pub enum SolanaStrategyEvent {
     Original(BotEvent),
     ForAgent(AgentEvent),
+    ForStrategy(StrategyControlEvent),
 }
 impl From<BotEvent> for SolanaStrategyEvent {
     fn from(event: BotEvent) -> Self {
@@ -19,4 +21,9 @@ pub enum AgentEvent {
     Transfer(Vec<SolanaTransferActionPayload>),
     Collect,
     Deactivate,
-}
\ No newline at end of file
+}
+/// Sent by the strategy wrapper to its own state machine, agents ignore these.
+#[derive(Debug, Clone)]
+pub enum StrategyControlEvent {
+    UpdateVolumeConfig(VolumeStrategyConfigArgs),
+}
@@ -3,9 +3,9 @@ use crate::config::settings::StrategyConfig;
 use crate::schema::volumestrategyinstances::completed_at;
 use crate::schema::volumestrategyinstances::dsl::volumestrategyinstances;
 use crate::schema::*;
-use crate::types::actions::{SolanaAction, SwapMethod};
+use crate::types::actions::{ActionExecutionStatus, SolanaAction, SwapMethod};
 use crate::types::engine::{Strategy, StrategyStatus};
-use crate::types::events::{BlockchainEvent, BotEvent, BotEventModel};
+use crate::types::events::{BlockchainEvent, BotEvent, BotEventModel, ExecutionResult};
 use crate::types::keys::KeypairClonable;
 use crate::types::pool::{RaydiumPool, RaydiumPoolPriceUpdate, RaydiumSwapEvent};
 use crate::types::bot_user::Trader;
@@ -61,6 +61,53 @@ impl LoggerInterceptorStrategy {
     pub fn new(context: &AppContext) -> Self {
         Self { context: context.clone() }
     }
+
+    // actions are stored once sent (or failed to) and updated with their receipt
+    async fn save_action(&self, event: &BotEvent) -> Result<()> {
+        let action = match event {
+            BotEvent::ExecutionResult(_, action, result) => {
+                let mut action = action.lock().await.clone();
+                if let ExecutionResult::ExecutionError(e) = result {
+                    action.status = ActionExecutionStatus::TxError { error: e.to_string() };
+                }
+                action
+            }
+            BotEvent::BlockchainEvent(BlockchainEvent::ExecutionReceipt(receipt)) => {
+                let Some(action) = self.context.cache.get_action_by_uuid(receipt.action_uuid).await else {
+                    return Ok(());
+                };
+                let mut action = action.lock().await.clone();
+                action.status = match &receipt.err {
+                    None => ActionExecutionStatus::Success,
+                    Some(err) => ActionExecutionStatus::TxError { error: err.to_string() },
+                };
+                action.tx_hash = receipt.transaction_signature;
+                action.confirmed_at = Some(receipt.status_changed_at);
+                action
+            }
+            _ => return Ok(()),
+        };
+        storage::persistent::save_action_to_db(&self.context.db_pool, action).await
+    }
+
+    // prices and swaps go to their own tables, the swaps of a dropped fork are removed
+    async fn save_event(&self, event: BotEvent) -> Result<()> {
+        match &event {
+            BotEvent::HeartBeat(..) => Ok(()),
+            BotEvent::BlockchainEvent(BlockchainEvent::RaydiumHeartbeatPriceUpdate(price_update)) => {
+                storage::persistent::save_price_to_db(self.context.db_pool.clone(), price_update.clone()).await
+            }
+            BotEvent::BlockchainEvent(BlockchainEvent::RaydiumSwapEvent(swap)) => {
+                storage::persistent::save_price_to_db(self.context.db_pool.clone(), swap.price_update.clone()).await?;
+                storage::persistent::save_swap_to_db(&self.context.db_pool, swap.into()).await
+            }
+            BotEvent::BlockchainEvent(BlockchainEvent::PoolEventsRolledBack(pool, dropped_slots)) => {
+                storage::persistent::delete_rolled_back_swaps(&self.context.db_pool, pool, dropped_slots).await?;
+                storage::persistent::save_bot_event_to_db(&self.context.db_pool, event.clone().into()).await
+            }
+            _ => storage::persistent::save_bot_event_to_db(&self.context.db_pool, event.into()).await,
+        }
+    }
 }
 
 #[async_trait]
@@ -74,15 +121,10 @@ impl Strategy<BotEvent, Arc<Mutex<SolanaAction>>> for LoggerInterceptorStrategy
     // Process incoming signals2
     // #[instrument(skip(self))]
     async fn process_event(&mut self, event: BotEvent) -> Vec<Arc<Mutex<SolanaAction>>> {
-        if let Err(e) = match &event {
-            BotEvent::HeartBeat(..) => { Ok(()) }
-            BotEvent::BlockchainEvent(BlockchainEvent::RaydiumHeartbeatPriceUpdate(price_update)) |
-            BotEvent::BlockchainEvent(BlockchainEvent::RaydiumSwapEvent(RaydiumSwapEvent { price_update, .. })) => {
-                storage::persistent::save_price_to_db(self.context.db_pool.clone(), price_update.clone()).await
-            }
-            _ => storage::persistent::save_bot_event_to_db(&self.context.db_pool, event.into()).await
+        if let Err(e) = self.save_action(&event).await {
+            error!("Failed to save action to db: {:?}", e);
         }
-        {
+        if let Err(e) = self.save_event(event).await {
             error!("Failed to save event to db: {:?}", e);
         }
         vec![]
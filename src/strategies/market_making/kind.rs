@@ -0,0 +1,111 @@
+use crate::config::app_context::AppContext;
+use crate::config::constants::{MARKET_MAKING_INDICATOR_PERIOD, MARKET_MAKING_LADDER_LEVELS};
+use crate::schema::users;
+use crate::strategies::market_making::MarketMakingStrategy;
+use crate::types::actions::SolanaAction;
+use crate::types::engine::{Strategy, StrategyId, StrategyKind};
+use crate::types::events::BotEvent;
+use crate::types::market_making_strategy::{MarketMakingStrategyInstance, NewMarketMakingStrategyInstance};
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use serde_json::json;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+pub const MARKET_MAKING_STRATEGY_KIND: &str = "market_making";
+
+pub struct MarketMakingStrategyKind {
+    context: AppContext,
+}
+
+impl MarketMakingStrategyKind {
+    pub fn new(context: &AppContext) -> Self {
+        Self {
+            context: context.clone(),
+        }
+    }
+}
+
+#[async_trait]
+impl StrategyKind<BotEvent, Arc<Mutex<SolanaAction>>> for MarketMakingStrategyKind {
+    fn name(&self) -> &'static str {
+        MARKET_MAKING_STRATEGY_KIND
+    }
+
+    fn config_schema(&self) -> serde_json::Value {
+        json!({
+            "target_pool": {"type": "pubkey", "description": "Raydium pool to quote"},
+            "indicator_period": {"type": "i32", "description": "Ticks of the EMA giving the fair price and of the Bollinger band, one of the aggregator's indicator periods", "default": MARKET_MAKING_INDICATOR_PERIOD},
+            "spread_pct": {"type": "f64", "description": "Distance from the fair price to the first level of each ladder, in percent"},
+            "ladder_levels": {"type": "i32", "description": "Levels of the bid and of the ask ladders, spaced by the spread or wider to span the Bollinger band", "default": MARKET_MAKING_LADDER_LEVELS},
+            "order_size_sol": {"type": "f64", "description": "SOL traded per level"},
+            "max_inventory_sol": {"type": "f64", "description": "Most the tokens held are worth, the buys stop there"},
+        })
+    }
+
+    fn title(&self) -> Option<&'static str> {
+        Some("🪜 Market Making")
+    }
+
+    fn is_persistent(&self) -> bool {
+        true
+    }
+
+    async fn persist(
+        &self,
+        row: serde_json::Value,
+    ) -> Result<(StrategyId, Box<dyn Strategy<BotEvent, Arc<Mutex<SolanaAction>>> + Send + Sync>)> {
+        use crate::schema::marketmakingstrategyinstances::dsl::*;
+        let mut instance: MarketMakingStrategyInstance = serde_json::from_value(row)?;
+        if instance.id == 0 {
+            instance.validate()?;
+            let periods = self.context.get_settings().await.aggregator.indicator_periods_in_ticks.clone();
+            if !periods.iter().any(|period| *period as i32 == instance.indicator_period) {
+                bail!("The indicator period must be one of {:?}", periods);
+            }
+            let mut conn = self.context.db_pool.get().await?;
+            instance.id = diesel::insert_into(marketmakingstrategyinstances)
+                .values(NewMarketMakingStrategyInstance::from(&instance))
+                .returning(id)
+                .get_result(&mut conn)
+                .await?;
+        }
+        Ok((instance.id, Box::new(MarketMakingStrategy::new(&self.context, &instance).await?)))
+    }
+
+    async fn complete(&self, strat_id: StrategyId) -> Result<()> {
+        use crate::schema::marketmakingstrategyinstances::dsl::*;
+        let mut conn = self.context.db_pool.get().await?;
+        diesel::update(marketmakingstrategyinstances.filter(id.eq(strat_id)))
+            .set(completed_at.eq(Some(Utc::now().naive_utc())))
+            .execute(&mut conn)
+            .await?;
+        Ok(())
+    }
+
+    async fn load_active(
+        &self,
+    ) -> Result<Vec<(StrategyId, Box<dyn Strategy<BotEvent, Arc<Mutex<SolanaAction>>> + Send + Sync>)>> {
+        use crate::schema::marketmakingstrategyinstances::dsl::*;
+        let mut conn = self.context.db_pool.get().await?;
+        let active_strategies: Vec<MarketMakingStrategyInstance> = marketmakingstrategyinstances
+            .inner_join(users::table)
+            .filter(completed_at.is_null())
+            .filter(users::is_active.eq(true))
+            .select(MarketMakingStrategyInstance::as_select())
+            .load(&mut conn)
+            .await?;
+
+        let mut strategies: Vec<(StrategyId, Box<dyn Strategy<BotEvent, Arc<Mutex<SolanaAction>>> + Send + Sync>)> = vec![];
+        for strategy_instance in active_strategies {
+            strategies.push((
+                strategy_instance.id,
+                Box::new(MarketMakingStrategy::new(&self.context, &strategy_instance).await?),
+            ));
+        }
+        Ok(strategies)
+    }
+}
@@ -0,0 +1,224 @@
+use crate::aggregators::every_tick_indicators::EveryTickIndicatorsValue;
+use crate::config::constants::RAYDIUM_SWAP_FEE;
+use crate::types::market_making_strategy::MarketMakingStrategyInstance;
+use crate::utils::decimals::{tokens_to_ui_amount_with_decimals_f64, ui_amount_with_decimals_to_tokens};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Side {
+    Buy,
+    Sell,
+}
+
+/// The EMA and the half width of the Bollinger band around it.
+#[derive(Debug, Clone, Copy)]
+pub struct FairPrice {
+    pub price: f64,
+    pub band: f64,
+}
+
+impl FairPrice {
+    pub fn from_indicators(value: &EveryTickIndicatorsValue) -> Option<Self> {
+        let band = (value.t_bollinger.value(0) - value.t_bollinger.value(2)).abs() / 2.0;
+        if !(value.t_ema > 0.0) || !band.is_finite() {
+            return None;
+        }
+        Some(Self { price: value.t_ema, band })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LadderOrder {
+    pub side: Side,
+    pub levels: u32,
+    pub size_sol: f64,
+}
+
+/// A bid ladder below the fair price and an ask ladder above it, each level crossed sends an order of
+/// `order_size_sol` once. The levels filled are cleared when the price comes back within the spread.
+#[derive(Debug, Clone, Default)]
+pub struct Ladder {
+    pub spread_pct: f64,
+    pub levels: u32,
+    pub order_size_sol: f64,
+    pub max_inventory_sol: f64,
+    pub bids_filled: u32,
+    pub asks_filled: u32,
+}
+
+impl Ladder {
+    pub fn new(instance: &MarketMakingStrategyInstance) -> Self {
+        Self {
+            spread_pct: instance.spread_pct,
+            levels: instance.ladder_levels.max(1) as u32,
+            order_size_sol: instance.order_size_sol,
+            max_inventory_sol: instance.max_inventory_sol,
+            ..Default::default()
+        }
+    }
+
+    /// Keeps the levels filled, they still apply to the inventory held.
+    pub fn update(&mut self, instance: &MarketMakingStrategyInstance) {
+        *self = Self {
+            bids_filled: self.bids_filled,
+            asks_filled: self.asks_filled,
+            ..Self::new(instance)
+        };
+    }
+
+    // the levels are spaced by the spread, wider to span the Bollinger band when the market is volatile
+    fn step(&self, fair: &FairPrice) -> f64 {
+        (self.spread_pct / 100.0).max(fair.band / fair.price / self.levels as f64)
+    }
+
+    fn level(&self, fair: &FairPrice, price: f64) -> (Side, u32) {
+        let deviation = price / fair.price - 1.0;
+        let side = if deviation < 0.0 { Side::Buy } else { Side::Sell };
+        let spread = self.spread_pct / 100.0;
+        if deviation.abs() < spread {
+            return (side, 0);
+        }
+        let step = self.step(fair);
+        let level = 1 + ((deviation.abs() - spread) / step).floor() as u32;
+        (side, level.min(self.levels))
+    }
+
+    /// The order for the levels newly crossed at `price`, marked filled. Buys are whole orders within the
+    /// inventory band, sells are limited by the inventory held, both in SOL.
+    pub fn next_order(&mut self, fair: &FairPrice, price: f64, inventory_sol: f64) -> Option<LadderOrder> {
+        let (side, level) = self.level(fair, price);
+        match side {
+            Side::Buy => self.asks_filled = 0,
+            Side::Sell => self.bids_filled = 0,
+        }
+        if level == 0 {
+            self.bids_filled = 0;
+            self.asks_filled = 0;
+            return None;
+        }
+        match side {
+            Side::Buy => {
+                let room = ((self.max_inventory_sol - inventory_sol) / self.order_size_sol).floor().max(0.0) as u32;
+                let levels = level.saturating_sub(self.bids_filled).min(room);
+                if levels == 0 {
+                    return None;
+                }
+                self.bids_filled += levels;
+                Some(LadderOrder { side, levels, size_sol: levels as f64 * self.order_size_sol })
+            }
+            Side::Sell => {
+                let levels = level.saturating_sub(self.asks_filled);
+                if levels == 0 || !(inventory_sol > 0.0) {
+                    return None;
+                }
+                self.asks_filled += levels;
+                Some(LadderOrder { side, levels, size_sol: (levels as f64 * self.order_size_sol).min(inventory_sol) })
+            }
+        }
+    }
+
+    /// The least an order of `amount_in` quoted at `price` gets, in the base units of the other side of the pool.
+    /// The price can move by one level against it, further than that the order fails instead of filling past
+    /// the ladder.
+    pub fn min_amount_out(
+        &self,
+        fair: &FairPrice,
+        side: Side,
+        price: f64,
+        amount_in: u64,
+        base_decimals: u8,
+        quote_decimals: u8,
+    ) -> u64 {
+        let slippage = self.step(fair);
+        match side {
+            Side::Buy => {
+                let sol = tokens_to_ui_amount_with_decimals_f64(amount_in, quote_decimals) * (1.0 - RAYDIUM_SWAP_FEE);
+                ui_amount_with_decimals_to_tokens(sol / (price * (1.0 + slippage)), base_decimals)
+            }
+            Side::Sell => {
+                let tokens = tokens_to_ui_amount_with_decimals_f64(amount_in, base_decimals) * (1.0 - RAYDIUM_SWAP_FEE);
+                ui_amount_with_decimals_to_tokens(tokens * price * (1.0 - slippage), quote_decimals)
+            }
+        }
+    }
+
+    /// The order failed, its levels can be filled again.
+    pub fn cancel(&mut self, order: &LadderOrder) {
+        match order.side {
+            Side::Buy => self.bids_filled = self.bids_filled.saturating_sub(order.levels),
+            Side::Sell => self.asks_filled = self.asks_filled.saturating_sub(order.levels),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ladder() -> Ladder {
+        Ladder { spread_pct: 1.0, levels: 3, order_size_sol: 0.1, max_inventory_sol: 1.0, ..Default::default() }
+    }
+
+    const FAIR: FairPrice = FairPrice { price: 1.0, band: 0.0 };
+
+    #[test]
+    fn test_levels_are_filled_once() {
+        let mut ladder = ladder();
+        assert_eq!(ladder.next_order(&FAIR, 0.995, 0.0), None);
+        let order = ladder.next_order(&FAIR, 0.985, 0.0).unwrap();
+        assert_eq!((order.side, order.levels), (Side::Buy, 1));
+        assert_eq!(ladder.next_order(&FAIR, 0.985, 0.1), None);
+        // two levels further down at once, the third is the last
+        let order = ladder.next_order(&FAIR, 0.95, 0.1).unwrap();
+        assert_eq!((order.side, order.levels), (Side::Buy, 2));
+        assert!((order.size_sol - 0.2).abs() < 1e-9);
+        assert_eq!(ladder.next_order(&FAIR, 0.9, 0.3), None);
+
+        // back within the spread, the levels are cleared
+        assert_eq!(ladder.next_order(&FAIR, 1.0, 0.3), None);
+        assert_eq!(ladder.bids_filled, 0);
+        let order = ladder.next_order(&FAIR, 1.025, 0.3).unwrap();
+        assert_eq!((order.side, order.levels), (Side::Sell, 2));
+        assert_eq!(ladder.next_order(&FAIR, 1.005, 0.3), None);
+        assert_eq!(ladder.asks_filled, 0);
+    }
+
+    #[test]
+    fn test_inventory_limits() {
+        let mut ladder = ladder();
+        // room for a single order below the band
+        let order = ladder.next_order(&FAIR, 0.9, 0.85).unwrap();
+        assert_eq!(order.levels, 1);
+        assert_eq!(ladder.next_order(&FAIR, 0.9, 0.95), None);
+
+        let mut ladder = self::ladder();
+        assert_eq!(ladder.next_order(&FAIR, 1.1, 0.0), None);
+        let order = ladder.next_order(&FAIR, 1.1, 0.05).unwrap();
+        assert!((order.size_sol - 0.05).abs() < 1e-9);
+        ladder.cancel(&order);
+        assert_eq!(ladder.asks_filled, 0);
+    }
+
+    #[test]
+    fn test_min_amount_out_allows_a_level_of_slippage() {
+        let ladder = ladder();
+        // 1 SOL for tokens of 6 decimals at 0.985, the price can rise by the 1% spread
+        let tokens = ladder.min_amount_out(&FAIR, Side::Buy, 0.985, 1_000_000_000, 6, 9);
+        let expected = (1.0 - RAYDIUM_SWAP_FEE) / (0.985 * 1.01);
+        assert!((tokens as f64 / 1e6 - expected).abs() < 1e-5);
+        let lamports = ladder.min_amount_out(&FAIR, Side::Sell, 1.02, 1_000_000, 6, 9);
+        let expected = (1.0 - RAYDIUM_SWAP_FEE) * 1.02 * 0.99;
+        assert!((lamports as f64 / 1e9 - expected).abs() < 1e-8);
+        // wider levels on a volatile market
+        let fair = FairPrice { price: 1.0, band: 0.09 };
+        assert!(ladder.min_amount_out(&fair, Side::Sell, 1.02, 1_000_000, 6, 9) < lamports);
+    }
+
+    #[test]
+    fn test_volatile_band_spaces_levels_out() {
+        let mut ladder = ladder();
+        // a 9% band over 3 levels, 3% apart instead of 1%
+        let fair = FairPrice { price: 1.0, band: 0.09 };
+        assert_eq!(ladder.next_order(&fair, 0.975, 0.0).unwrap().levels, 1);
+        assert_eq!(ladder.next_order(&fair, 0.955, 0.1).unwrap().levels, 1);
+    }
+}
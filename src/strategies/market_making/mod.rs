@@ -0,0 +1,6 @@
+mod kind;
+mod ladder;
+mod strategy;
+
+pub use kind::{MarketMakingStrategyKind, MARKET_MAKING_STRATEGY_KIND};
+pub use strategy::MarketMakingStrategy;
@@ -0,0 +1,290 @@
+use crate::config::app_context::AppContext;
+use crate::config::constants::{ACTION_EXPIRY_S, BASE_TX_FEE_SOL, TRADING_FEED_COLLECTORS, TRANSFER_PRIORITY_FEE_SOL};
+use crate::schema::users;
+use crate::strategies::market_making::ladder::{FairPrice, Ladder, LadderOrder, Side};
+use crate::strategies::market_making::MARKET_MAKING_STRATEGY_KIND;
+use crate::types::actions::{Amount, SolanaAction, SolanaActionPayload, SolanaSwapActionPayload, SwapMethod};
+use crate::types::bot_user::BotUser;
+use crate::types::engine::{Strategy, StrategyStatus};
+use crate::types::events::{BlockchainEvent, BotEvent, DerivedEvent, ExecutionResult, SystemEvent};
+use crate::types::keys::KeypairClonable;
+use crate::types::market_making_strategy::{MarketMakingConfigArgs, MarketMakingStrategyInstance};
+use crate::types::pool::RaydiumPool;
+use crate::types::routing::{EventFilter, EventType};
+use crate::utils::decimals::{sol_to_lamports, tokens_to_ui_amount_with_decimals_f64, ui_amount_with_decimals_to_tokens};
+use crate::{solana, storage};
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use maplit::hashmap;
+use std::any::Any;
+use std::collections::HashSet;
+use std::fmt::{Debug, Formatter};
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+/// Quotes the pool from the user's trading wallet: buys when the price falls below the fair price by the spread,
+/// sells when it rises above it, a level of the ladders at a time. One order is in flight at most.
+pub struct MarketMakingStrategy {
+    context: AppContext,
+    instance: MarketMakingStrategyInstance,
+    pool: Arc<RaydiumPool>,
+    wallet: KeypairClonable,
+    ladder: Ladder,
+    fair_price: Option<FairPrice>,
+    pending: Option<(Uuid, LadderOrder, Instant)>,
+    paused: bool,
+    /// `on_stop` was called, no more orders are sent
+    stopped: bool,
+    /// Collectors reported down, quoting on stale prices is held until they're back.
+    feeds_down: HashSet<String>,
+}
+
+impl MarketMakingStrategy {
+    pub async fn new(context: &AppContext, instance: &MarketMakingStrategyInstance) -> Result<Self> {
+        let pool = Arc::new(context.rpc_pool.get_pool_details(&instance.target_pool).await?);
+        // the indicators and the prices are only derived for the monitored pools
+        if let Err(e) = solana::start_monitoring_pool(context, &pool, instance.id).await {
+            warn!("Error monitoring pool {}: {:?}", pool.id, e);
+        }
+        let mut conn = context.db_pool.get().await?;
+        let user: BotUser = users::table
+            .find(instance.user_id)
+            .first::<BotUser>(&mut conn)
+            .await
+            .map_err(|e| anyhow!("User not found {:?}", e))?;
+        let wallet = KeypairClonable::new_from_privkey(&user.wallet_private_key)?;
+        info!("Market making strategy {} created with instance {:?}", instance.id, instance);
+        Ok(Self {
+            context: context.clone(),
+            instance: instance.clone(),
+            pool,
+            wallet,
+            ladder: Ladder::new(instance),
+            fair_price: None,
+            pending: None,
+            paused: false,
+            stopped: false,
+            feeds_down: HashSet::new(),
+        })
+    }
+
+    async fn quote(&mut self) -> Option<SolanaAction> {
+        // an order without a receipt is given up on once it expired, its levels stay filled
+        if self.pending.is_some_and(|(_, _, sent)| sent.elapsed().as_secs() > ACTION_EXPIRY_S) {
+            warn!("Market making strategy {} got no receipt for its order", self.instance.id);
+            self.pending = None;
+        }
+        if self.stopped || self.paused || self.pending.is_some() || !self.feeds_down.is_empty() {
+            return None;
+        }
+        let fair_price = self.fair_price?;
+        let price = self.context.cache.target_pools_prices.lock().await.get(&self.pool.id)?.price;
+        if !(price > 0.0) {
+            return None;
+        }
+        let tokens = match solana::get_token_balance(&self.context, &self.wallet.pubkey(), &self.pool.base_mint).await {
+            Ok(tokens) => tokens,
+            Err(e) => {
+                warn!("Market making strategy {} can't get its inventory: {:?}", self.instance.id, e);
+                return None;
+            }
+        };
+        let inventory_sol = tokens_to_ui_amount_with_decimals_f64(tokens, self.pool.base_decimals) * price;
+        let order = self.ladder.next_order(&fair_price, price, inventory_sol)?;
+        let (swap_method, amount_in) = match order.side {
+            Side::Buy => {
+                let lamports = sol_to_lamports(order.size_sol);
+                let balance = solana::get_balance(&self.context, &self.wallet.pubkey()).await.unwrap_or(0);
+                if balance < lamports + BASE_TX_FEE_SOL + TRANSFER_PRIORITY_FEE_SOL {
+                    warn!("Market making strategy {} can't buy {} SOL, the wallet has {} lamports", self.instance.id, order.size_sol, balance);
+                    self.ladder.cancel(&order);
+                    return None;
+                }
+                (SwapMethod::BuyTokensForExactSol, lamports)
+            }
+            Side::Sell => (
+                SwapMethod::SellExactTokensForSol,
+                ui_amount_with_decimals_to_tokens(order.size_sol / price, self.pool.base_decimals).min(tokens),
+            ),
+        };
+        let min_amount_out = self.ladder.min_amount_out(
+            &fair_price,
+            order.side,
+            price,
+            amount_in,
+            self.pool.base_decimals,
+            self.pool.quote_decimals,
+        );
+        info!(
+            "Market making strategy {}: {:?} {} SOL at {}, fair price {}, inventory {} SOL",
+            self.instance.id, order.side, order.size_sol, price, fair_price.price, inventory_sol
+        );
+        let action = SolanaAction::new(
+            self.wallet.clone(),
+            vec![SolanaActionPayload::SolanaSwapActionPayload(SolanaSwapActionPayload {
+                keys: self.pool.to_liquidity_keys(),
+                swap_method,
+                amount_in: Amount::Exact(amount_in),
+                min_amount_out,
+            })],
+        );
+        self.pending = Some((action.uuid, order, Instant::now()));
+        Some(action)
+    }
+
+    // the sells are counted at the price they were quoted at
+    async fn on_filled(&mut self, order: LadderOrder) {
+        match order.side {
+            Side::Buy => self.instance.bought_sol += order.size_sol,
+            Side::Sell => self.instance.sold_sol += order.size_sol,
+        }
+        if let Err(e) = storage::market_making_strategies::store_volume_traded(&self.context.db_pool, &self.instance).await {
+            warn!("Failed to store the volume of market making strategy {}: {:?}", self.instance.id, e);
+        }
+    }
+
+    fn take_pending(&mut self, uuid: &Uuid) -> Option<LadderOrder> {
+        match self.pending {
+            Some((pending_uuid, order, _)) if pending_uuid == *uuid => {
+                self.pending = None;
+                Some(order)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Debug for MarketMakingStrategy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "MarketMakingStrategy, config: {:?}, ladder: {:?}", self.instance, self.ladder)
+    }
+}
+
+#[async_trait]
+impl Strategy<BotEvent, Arc<Mutex<SolanaAction>>> for MarketMakingStrategy {
+    async fn sync_state(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    async fn process_event(&mut self, event: BotEvent) -> Vec<Arc<Mutex<SolanaAction>>> {
+        match event {
+            BotEvent::DerivedEvent(DerivedEvent::TickIndicatorEvent(pool, indicators))
+                if pool == self.pool.id && indicators.length as i32 == self.instance.indicator_period =>
+            {
+                self.fair_price = FairPrice::from_indicators(&indicators);
+                if let Some(action) = self.quote().await {
+                    return vec![Arc::new(Mutex::new(action))];
+                }
+            }
+            // the indicators were fed the prices of the dropped fork, quoting waits for the next ones
+            BotEvent::BlockchainEvent(BlockchainEvent::PoolEventsRolledBack(pool, _)) if pool == self.pool.id => {
+                self.fair_price = None;
+            }
+            BotEvent::ExecutionResult(uuid, _, ExecutionResult::ExecutionError(e)) => {
+                if let Some(order) = self.take_pending(&uuid) {
+                    warn!("Market making strategy {} order failed: {:?}", self.instance.id, e);
+                    self.ladder.cancel(&order);
+                }
+            }
+            BotEvent::BlockchainEvent(BlockchainEvent::ExecutionReceipt(receipt)) => {
+                if let Some(order) = self.take_pending(&receipt.action_uuid) {
+                    match receipt.err {
+                        None => self.on_filled(order).await,
+                        Some(e) => {
+                            warn!("Market making strategy {} order failed: {}", self.instance.id, e);
+                            self.ladder.cancel(&order);
+                        }
+                    }
+                }
+            }
+            BotEvent::SystemEvent(SystemEvent::FeedDown(feed)) if TRADING_FEED_COLLECTORS.contains(&feed.as_str()) => {
+                warn!("Market making strategy {} holds its orders, feed {} is down", self.instance.id, feed);
+                self.feeds_down.insert(feed);
+            }
+            BotEvent::SystemEvent(SystemEvent::FeedUp(feed)) => {
+                self.feeds_down.remove(&feed);
+            }
+            _ => {}
+        }
+        vec![]
+    }
+
+    async fn get_status(&self) -> StrategyStatus {
+        if self.stopped {
+            // the order in flight is tracked until its receipt or its expiry
+            return match self.pending {
+                Some((_, _, sent)) if sent.elapsed().as_secs() <= ACTION_EXPIRY_S => StrategyStatus::Stopping,
+                _ => StrategyStatus::Stopped,
+            };
+        }
+        let mut state = hashmap! {
+            "Fair price".to_owned() => self.fair_price.map_or("-".to_owned(), |fair_price| fair_price.price.to_string()),
+            "Levels filled".to_owned() => format!("{} bids, {} asks", self.ladder.bids_filled, self.ladder.asks_filled),
+            "Bought".to_owned() => format!("{} SOL", self.instance.bought_sol),
+            "Sold".to_owned() => format!("{} SOL", self.instance.sold_sol),
+        };
+        if !self.feeds_down.is_empty() {
+            state.insert("Feeds down".to_owned(), self.feeds_down.iter().cloned().collect::<Vec<_>>().join(", "));
+        }
+        if self.paused {
+            StrategyStatus::Paused(state)
+        } else {
+            StrategyStatus::Running(state)
+        }
+    }
+
+    async fn pause(&mut self) -> Result<()> {
+        self.paused = true;
+        info!("Market making strategy {} paused", self.instance.id);
+        Ok(())
+    }
+
+    async fn resume(&mut self) -> Result<()> {
+        self.paused = false;
+        info!("Market making strategy {} resumed", self.instance.id);
+        Ok(())
+    }
+
+    async fn on_stop(&mut self) -> Vec<Arc<Mutex<SolanaAction>>> {
+        self.stopped = true;
+        info!("Market making strategy {} stopped", self.instance.id);
+        solana::stop_monitoring_pool(&self.context, &self.pool.id, self.instance.id).await;
+        vec![]
+    }
+
+    async fn update_config(&mut self, config: serde_json::Value) -> Result<()> {
+        let config: MarketMakingConfigArgs = serde_json::from_value(config)?;
+        self.instance.update(&config)?;
+        self.ladder.update(&self.instance);
+        storage::market_making_strategies::store_ladder_config(&self.context.db_pool, &self.instance).await
+    }
+
+    // receipts and execution results aren't routed by pool
+    async fn subscriptions(&self) -> Vec<EventFilter> {
+        vec![
+            EventFilter::of_types(&[EventType::Indicator, EventType::Rollback]).with_pools([self.pool.id]),
+            EventFilter::of_types(&[EventType::ExecutionReceipt, EventType::ExecutionResult, EventType::System]),
+        ]
+    }
+
+    fn kind(&self) -> Option<&'static str> {
+        Some(MARKET_MAKING_STRATEGY_KIND)
+    }
+
+    fn owner_user_id(&self) -> Option<i32> {
+        Some(self.instance.user_id)
+    }
+
+    fn instance(&self) -> serde_json::Value {
+        serde_json::to_value(&self.instance).unwrap_or_default()
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
@@ -1,14 +1,24 @@
 mod deposit;
+mod event_stream;
 mod solana_strategy_manager;
 mod volume_strategy;
 mod logger_interceptor;
+mod market_making;
+mod registry;
+mod rent_reclaim;
+mod subscription;
 pub mod sweeper_strategy;
 pub mod events;
 pub mod sniper_strategy;
 
-pub use deposit::DepositWithdrawStrategy;
+pub use deposit::{withdrawal, DepositWithdrawStrategy};
+pub use event_stream::{EventStreamStrategy, StreamedEvent};
 pub use solana_strategy_manager::SolanaStrategyManager;
-pub use volume_strategy::VolumeStrategy;
+pub use volume_strategy::{VolumeStrategy, VOLUME_STRATEGY_KIND};
 pub use sweeper_strategy::SweeperStrategyStateMachine;
 pub use sniper_strategy::SniperStrategyStateMachine;
 pub use logger_interceptor::LoggerInterceptorStrategy;
+pub use market_making::{MarketMakingStrategy, MARKET_MAKING_STRATEGY_KIND};
+pub use registry::{menu_kinds, new_instance_row, parse_config_text, SolanaStrategyKind, StrategyRegistry};
+pub use rent_reclaim::RentReclaimStrategy;
+pub use subscription::{new_billing_strategy, SubscriptionBillingStrategy, SubscriptionGuard};
@@ -0,0 +1,142 @@
+use crate::config::app_context::AppContext;
+use crate::strategies::market_making::MarketMakingStrategyKind;
+use crate::strategies::sniper_strategy::SniperStrategyKind;
+use crate::strategies::sweeper_strategy::SweeperStrategyKind;
+use crate::strategies::volume_strategy::VolumeStrategyKind;
+use crate::types::actions::SolanaAction;
+use crate::types::engine::{StrategyKind, StrategyManager};
+use crate::types::events::BotEvent;
+use anyhow::{anyhow, bail, Result};
+use chrono::Utc;
+use serde_json::{json, Map, Value};
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+pub type SolanaStrategyKind = dyn StrategyKind<BotEvent, Arc<Mutex<SolanaAction>>>;
+
+/// Strategy kinds known to the manager, a new strategy plugs in by registering its kind here.
+#[derive(Clone, Default)]
+pub struct StrategyRegistry {
+    kinds: HashMap<&'static str, Arc<SolanaStrategyKind>>,
+}
+
+impl StrategyRegistry {
+    pub fn new(context: &AppContext) -> Self {
+        Self::default()
+            .with_kind(Arc::new(VolumeStrategyKind::new(context)))
+            .with_kind(Arc::new(SweeperStrategyKind::new(context)))
+            .with_kind(Arc::new(SniperStrategyKind::new(context)))
+            .with_kind(Arc::new(MarketMakingStrategyKind::new(context)))
+    }
+
+    pub fn with_kind(mut self, kind: Arc<SolanaStrategyKind>) -> Self {
+        self.kinds.insert(kind.name(), kind);
+        self
+    }
+
+    pub fn get(&self, name: &str) -> Option<Arc<SolanaStrategyKind>> {
+        self.kinds.get(name).cloned()
+    }
+
+    pub fn kinds(&self) -> Vec<Arc<SolanaStrategyKind>> {
+        self.kinds.values().cloned().collect()
+    }
+}
+
+/// Kinds listed in the start menus, by title.
+pub fn menu_kinds(strategy_manager: &dyn StrategyManager<BotEvent, Arc<Mutex<SolanaAction>>>) -> Vec<(&'static str, Arc<SolanaStrategyKind>)> {
+    let mut kinds = strategy_manager
+        .get_strategy_kinds()
+        .into_iter()
+        .filter_map(|kind| Some((kind.title()?, kind)))
+        .collect::<Vec<_>>();
+    kinds.sort_by_key(|(title, _)| *title);
+    kinds
+}
+
+/// `field=value` pairs separated by spaces, as typed in telegram.
+pub fn parse_config_text(text: &str) -> Result<Map<String, Value>> {
+    text.split_whitespace()
+        .map(|pair| match pair.split_once('=') {
+            Some((field, value)) => Ok((field.to_owned(), Value::String(value.to_owned()))),
+            None => Err(anyhow!("Expected field=value, got {}", pair)),
+        })
+        .collect()
+}
+
+/// Builds the row of a new instance owned by `user_id` from the config fields of a kind,
+/// typed after its [config_schema](StrategyKind::config_schema). The fields left out take their default.
+pub fn new_instance_row(schema: &Value, user_id: i32, config: &Map<String, Value>) -> Result<Value> {
+    let fields = schema.as_object().ok_or(anyhow!("The strategy kind has no config"))?;
+    if let Some(field) = config.keys().find(|field| !fields.contains_key(*field)) {
+        bail!("Unknown field {}", field);
+    }
+    let mut row = json!({
+        "id": 0,
+        "user_id": user_id,
+        "started_at": Utc::now().naive_utc(),
+        "completed_at": null,
+    });
+    for (field, spec) in fields {
+        let value = match (config.get(field), spec.get("default")) {
+            (Some(value), _) => typed_value(spec["type"].as_str().unwrap_or_default(), value)
+                .map_err(|e| anyhow!("{}: {}", field, e))?,
+            (None, Some(default)) => default.clone(),
+            (None, None) => bail!("{} is missing", field),
+        };
+        row[field] = value;
+    }
+    Ok(row)
+}
+
+// the values typed in telegram are strings, the API sends them as json
+fn typed_value(kind: &str, value: &Value) -> Result<Value> {
+    let text = match value {
+        Value::String(text) => text.clone(),
+        value => value.to_string(),
+    };
+    Ok(match kind {
+        "pubkey" => json!(Pubkey::from_str(&text).map_err(|_| anyhow!("not a valid address"))?),
+        "f64" => json!(text.parse::<f64>().ok().filter(|number| number.is_finite()).ok_or(anyhow!("must be a number"))?),
+        "i32" => json!(text.parse::<i32>().map_err(|_| anyhow!("must be a whole number"))?),
+        "i64" => json!(text.parse::<i64>().map_err(|_| anyhow!("must be a whole number"))?),
+        "bool" => json!(text.parse::<bool>().map_err(|_| anyhow!("must be true or false"))?),
+        _ => Value::String(text),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new_instance_row() {
+        let schema = json!({
+            "target_pool": {"type": "pubkey", "description": ""},
+            "size_sol": {"type": "f64", "description": ""},
+            "levels": {"type": "i32", "description": "", "default": 3},
+        });
+        let pool = Pubkey::new_unique();
+        let config = parse_config_text(&format!("target_pool={} size_sol=0.5", pool)).unwrap();
+        let row = new_instance_row(&schema, 7, &config).unwrap();
+        assert_eq!(row["target_pool"], json!(pool));
+        assert_eq!(row["size_sol"], json!(0.5));
+        assert_eq!(row["levels"], json!(3));
+        assert_eq!((row["id"].as_i64(), row["user_id"].as_i64()), (Some(0), Some(7)));
+
+        // the API sends json values
+        let config = json!({"target_pool": pool.to_string(), "size_sol": 1, "levels": 5});
+        let row = new_instance_row(&schema, 7, config.as_object().unwrap()).unwrap();
+        assert_eq!((row["size_sol"].as_f64(), row["levels"].as_i64()), (Some(1.0), Some(5)));
+
+        assert!(parse_config_text("size_sol 0.5").is_err());
+        assert!(new_instance_row(&schema, 7, &parse_config_text("size_sol=0.5").unwrap()).is_err());
+        let config = parse_config_text(&format!("target_pool={} size_sol=abc", pool)).unwrap();
+        assert!(new_instance_row(&schema, 7, &config).is_err());
+        let config = parse_config_text(&format!("target_pool={} size_sol=1 user_id=1", pool)).unwrap();
+        assert!(new_instance_row(&schema, 7, &config).is_err());
+    }
+}
@@ -0,0 +1,3 @@
+mod strategy;
+
+pub use strategy::RentReclaimStrategy;
@@ -0,0 +1,118 @@
+use crate::config::app_context::AppContext;
+use crate::config::constants::RENT_RECLAIM_INTERVAL_S;
+use crate::solana::rent_reclaim::{reclaim_rent, RentReclaimReport};
+use crate::storage::bot_wallets::load_bot_wallets;
+use crate::types::actions::SolanaAction;
+use crate::types::engine::{Strategy, StrategyStatus};
+use crate::types::events::BotEvent;
+use crate::types::routing::{EventFilter, EventType};
+use crate::utils::decimals::lamports_to_sol;
+use anyhow::Result;
+use async_trait::async_trait;
+use maplit::hashmap;
+use std::any::Any;
+use std::fmt::{Debug, Formatter};
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
+use tracing::{error, info};
+
+/// Closes the empty token accounts of the agent and sniper wallets every `RENT_RECLAIM_INTERVAL_S`,
+/// the rent goes back to the owners. Only the wallets no running strategy uses are touched: those of the completed
+/// strategies and the deactivated agents. A run sends its own transactions in the background.
+pub struct RentReclaimStrategy {
+    context: AppContext,
+    last_run: Option<Instant>,
+    running: Option<JoinHandle<()>>,
+    totals: Arc<Mutex<RentReclaimReport>>,
+}
+
+impl RentReclaimStrategy {
+    pub fn new(context: AppContext) -> Self {
+        Self {
+            context,
+            last_run: None,
+            running: None,
+            totals: Arc::new(Mutex::new(RentReclaimReport::default())),
+        }
+    }
+
+    fn start_run(&mut self) {
+        if self.running.as_ref().is_some_and(|handle| !handle.is_finished())
+            || self.last_run.is_some_and(|last| last.elapsed().as_secs() < RENT_RECLAIM_INTERVAL_S)
+        {
+            return;
+        }
+        self.last_run = Some(Instant::now());
+        let context = self.context.clone();
+        let totals = self.totals.clone();
+        self.running = Some(tokio::spawn(async move {
+            match run(&context).await {
+                Ok(report) => {
+                    let mut totals = totals.lock().await;
+                    for (strategy, (accounts, lamports)) in report.reclaimed {
+                        let total = totals.reclaimed.entry(strategy).or_default();
+                        total.0 += accounts;
+                        total.1 += lamports;
+                    }
+                    totals.failed += report.failed;
+                }
+                Err(e) => error!("Rent reclaim failed: {:?}", e),
+            }
+        }));
+    }
+}
+
+async fn run(context: &AppContext) -> Result<RentReclaimReport> {
+    let wallets = load_bot_wallets(&context.db_pool, false).await?;
+    let report = reclaim_rent(&context.rpc_pool, &context.signers, &wallets, false).await?;
+    // a cached account would still be seen as existing by whoever trades the wallet next
+    for account in report.closed.iter() {
+        context.cache.mark_account_closed(account).await;
+    }
+    for (strategy, (accounts, lamports)) in report.reclaimed.iter() {
+        info!("Reclaimed {} SOL closing {} token accounts of {}", lamports_to_sol(*lamports), accounts, strategy);
+    }
+    if report.failed > 0 {
+        error!("{} rent reclaim batches failed", report.failed);
+    }
+    Ok(report)
+}
+
+impl Debug for RentReclaimStrategy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "RentReclaimStrategy")
+    }
+}
+
+#[async_trait]
+impl Strategy<BotEvent, Arc<Mutex<SolanaAction>>> for RentReclaimStrategy {
+    async fn sync_state(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    async fn process_event(&mut self, event: BotEvent) -> Vec<Arc<Mutex<SolanaAction>>> {
+        if let BotEvent::HeartBeat(..) = event {
+            self.start_run();
+        }
+        vec![]
+    }
+
+    async fn get_status(&self) -> StrategyStatus {
+        let totals = self.totals.lock().await;
+        StrategyStatus::Running(hashmap! {
+            "Accounts closed".to_owned() => totals.accounts().to_string(),
+            "SOL reclaimed".to_owned() => lamports_to_sol(totals.lamports()).to_string(),
+            "Failed batches".to_owned() => totals.failed.to_string(),
+        })
+    }
+
+    async fn subscriptions(&self) -> Vec<EventFilter> {
+        vec![EventFilter::of_types(&[EventType::HeartBeat])]
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
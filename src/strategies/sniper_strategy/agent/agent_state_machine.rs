@@ -295,34 +295,21 @@ impl SniperAgentState {
     async fn set_when_bought_timer(&mut self) {
         match self.context.rpc_pool.get_pool_details(&self.pool.id).await {
             Ok(pool_info) => {
-                self.context
-                    .cache
-                    .target_pools
-                    .write()
-                    .await
-                    .insert(self.pool.id, pool_info);
+                if let Err(e) = solana::start_monitoring_pool(&self.context, &pool_info, self.sniping_strategy_instance.id).await {
+                    error!("Error getting pool price: {:?}", e);
+                }
             }
             Err(e) => {
                 error!("Error getting pool details: {:?}", e);
             }
         }
 
-        match self.context.rpc_pool.get_pool_price(&self.pool.id).await {
-            Ok(pool_price) => {
-                self.context
-                    .cache
-                    .target_pools_prices
-                    .lock()
-                    .await
-                    .insert(self.pool.id, pool_price);
-            }
-            Err(e) => {
-                error!("Error getting pool price: {:?}", e);
-            }
-        }
-
         self.when_bought_timer = Instant::now();
-        self.buy_price = self.context.cache.target_pools_prices.lock().await.get(&self.pool.id).unwrap().clone();
+        // without a price the first update after the buy is taken as the buy price
+        match self.context.cache.target_pools_prices.lock().await.get(&self.pool.id) {
+            Some(price) => self.buy_price = price.clone(),
+            None => warn!("No price for pool {} after the buy, waiting for the first update", self.pool.id),
+        }
     }
 
     #[state(entry_action = "set_when_bought_timer")]
@@ -335,7 +322,9 @@ impl SniperAgentState {
         match event {
             SolanaStrategyEvent::Original(BotEvent::BlockchainEvent(BlockchainEvent::RaydiumHeartbeatPriceUpdate(price_update))) |
             SolanaStrategyEvent::Original(BotEvent::BlockchainEvent(BlockchainEvent::RaydiumSwapEvent(RaydiumSwapEvent { price_update, .. }))) => {
-                if price_update.pool == self.pool.id {
+                if price_update.pool == self.pool.id && !(self.buy_price.price > 0.0) {
+                    self.buy_price = price_update.clone();
+                } else if price_update.pool == self.pool.id {
                     let relative_price_change_per_cent = (100.0 * (price_update.price - self.buy_price.price)) / self.buy_price.price;
                     debug!("Price change: {:.5}% from buy price", relative_price_change_per_cent);
                     if price_update.price < self.buy_price.price * self.sniping_strategy_instance.stop_loss_percent_move_down {
@@ -347,6 +336,14 @@ impl SniperAgentState {
                     }
                 }
             }
+            // a buy price from a dropped fork is taken again from the next update
+            SolanaStrategyEvent::Original(BotEvent::BlockchainEvent(BlockchainEvent::PoolEventsRolledBack(pool, dropped_slots)))
+                if *pool == self.pool.id
+                    && self.buy_price.slot.is_some_and(|slot| dropped_slots.contains(&(slot as u64))) =>
+            {
+                warn!("Token `{:?}` buy price was rolled back, waiting for the next update", self.pool.base_mint);
+                self.buy_price = RaydiumPoolPriceUpdate::default();
+            }
             _ => {}
         }
         if elapsed - self.last_time > 1 {
@@ -386,13 +383,7 @@ impl SniperAgentState {
 
     #[action]
     pub async fn stop_monitoring(&mut self) {
-        self
-            .context
-            .cache
-            .target_pools
-            .write()
-            .await
-            .remove(&self.pool.id);
+        solana::stop_monitoring_pool(&self.context, &self.pool.id, self.sniping_strategy_instance.id).await;
         solana::stop_monitoring_account(&self.context, &self.agent_key.pubkey()).await;
         solana::stop_monitoring_token_account(&self.context, &self.agent_key.pubkey(), &self.pool.base_mint).await;
     }
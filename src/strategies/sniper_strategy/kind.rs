@@ -0,0 +1,87 @@
+use crate::config::app_context::AppContext;
+use crate::storage;
+use crate::strategies::sniper_strategy::SniperStrategy;
+use crate::types::actions::SolanaAction;
+use crate::types::engine::{Strategy, StrategyId, StrategyKind};
+use crate::types::events::BotEvent;
+use crate::types::sniping_strategy::{NewSnipingStrategyInstance, SnipingStrategyInstance};
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use serde_json::json;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+pub const SNIPER_STRATEGY_KIND: &str = "sniper";
+
+pub struct SniperStrategyKind {
+    context: AppContext,
+}
+
+impl SniperStrategyKind {
+    pub fn new(context: &AppContext) -> Self {
+        Self {
+            context: context.clone(),
+        }
+    }
+}
+
+#[async_trait]
+impl StrategyKind<BotEvent, Arc<Mutex<SolanaAction>>> for SniperStrategyKind {
+    fn name(&self) -> &'static str {
+        SNIPER_STRATEGY_KIND
+    }
+
+    fn config_schema(&self) -> serde_json::Value {
+        json!({
+            "sniper_private_key": {"type": "string", "description": "Base58 private key of the sniping wallet, in clear or encrypted with the master key"},
+            "size_sol": {"type": "f64", "description": "SOL spent per snipe"},
+            "stop_loss_percent_move_down": {"type": "f64", "description": "Exit if the price drops by this percent"},
+            "take_profit_percent_move_up": {"type": "f64", "description": "Exit if the price rises by this percent"},
+            "force_exit_horizon_s": {"type": "i64", "description": "Exit after this many seconds in any case"},
+            "max_simultaneous_snipes": {"type": "i64", "description": "Pools held at the same time"},
+            "min_pool_liquidity_sol": {"type": "f64", "description": "Skip pools with less SOL liquidity"},
+            "skip_pump_fun": {"type": "bool", "description": "Skip pump.fun pools"},
+            "skip_mintable": {"type": "bool", "description": "Skip tokens with a mint authority"},
+            "buy_delay_ms": {"type": "i64", "description": "Delay before buying a new pool"},
+            "skip_if_price_drops_percent": {"type": "f64", "description": "Skip the pool if the price drops by this percent during the delay"},
+        })
+    }
+
+    fn is_persistent(&self) -> bool {
+        true
+    }
+
+    async fn persist(
+        &self,
+        row: serde_json::Value,
+    ) -> Result<(StrategyId, Box<dyn Strategy<BotEvent, Arc<Mutex<SolanaAction>>> + Send + Sync>)> {
+        let mut instance: SnipingStrategyInstance = serde_json::from_value(row)?;
+        if instance.id == 0 {
+            instance = storage::persistent::save_new_sniping_strategy_to_db(
+                self.context.db_pool.clone(),
+                NewSnipingStrategyInstance::from(&instance),
+            ).await?;
+        }
+        Ok((instance.id, Box::new(SniperStrategy::new(&self.context, &instance).await?)))
+    }
+
+    async fn complete(&self, strat_id: StrategyId) -> Result<()> {
+        use crate::schema::snipingstrategyinstances::dsl::*;
+        let mut conn = self.context.db_pool.get().await?;
+        diesel::update(snipingstrategyinstances.filter(id.eq(strat_id)))
+            .set(completed_at.eq(Some(Utc::now().naive_utc())))
+            .execute(&mut conn)
+            .await?;
+        Ok(())
+    }
+
+    // the sniper is started from the config on every launch, resuming the stored ones would duplicate it
+    async fn load_active(
+        &self,
+    ) -> Result<Vec<(StrategyId, Box<dyn Strategy<BotEvent, Arc<Mutex<SolanaAction>>> + Send + Sync>)>> {
+        Ok(vec![])
+    }
+}
@@ -1,5 +1,7 @@
 pub mod strategy;
 pub mod agent;
 pub mod strategy_state_machine;
+mod kind;
 pub use strategy_state_machine::SniperStrategyStateMachine;
-pub use strategy::SniperStrategy;
\ No newline at end of file
+pub use strategy::SniperStrategy;
+pub use kind::{SniperStrategyKind, SNIPER_STRATEGY_KIND};
\ No newline at end of file
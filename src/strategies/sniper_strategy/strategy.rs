@@ -4,7 +4,9 @@ use crate::schema::volumestrategyinstances::completed_at;
 use crate::schema::volumestrategyinstances::dsl::volumestrategyinstances;
 use crate::schema::*;
 use crate::types::actions::{SolanaAction, SwapMethod};
+use crate::strategies::sniper_strategy::agent;
 use crate::types::engine::{Strategy, StrategyStatus};
+use crate::types::routing::{EventFilter, EventType};
 use crate::types::events::{BlockchainEvent, BotEvent};
 use crate::types::keys::KeypairClonable;
 use crate::types::pool::{RaydiumPool, RaydiumPoolPriceUpdate};
@@ -41,6 +43,7 @@ use tokio::sync::{Mutex, RwLock};
 use tokio::task::JoinHandle;
 use tracing::field::debug;
 use tracing::{debug, error, info, instrument, Event};
+use crate::strategies::sniper_strategy::SNIPER_STRATEGY_KIND;
 use crate::strategies::{SniperStrategyStateMachine, SweeperStrategyStateMachine};
 use crate::tg_bot::sniping_strategy_config_args::SnipingStrategyConfigArgs;
 
@@ -50,6 +53,8 @@ use crate::tg_bot::sniping_strategy_config_args::SnipingStrategyConfigArgs;
 #[derive(Clone)]
 pub struct SniperStrategy {
     pub state_machine: StateMachine<SniperStrategyStateMachine>,
+    pub paused: bool,
+    pub stopping: bool,
 }
 
 impl Debug for SniperStrategy {
@@ -72,6 +77,8 @@ impl SniperStrategy {
             .state_machine();
         Ok(Self {
             state_machine,
+            paused: false,
+            stopping: false,
         })
     }
 
@@ -98,6 +105,8 @@ impl Strategy<BotEvent, Arc<Mutex<SolanaAction>>> for SniperStrategy {
             BotEvent::HeartBeat(tick_size_ms, _) => {
                 self.state_machine.handle(&event.clone().into()).await;
             }
+            // no new snipes while paused or stopping, the ones in progress are still managed
+            BotEvent::BlockchainEvent(BlockchainEvent::RaydiumNewPoolEvent(..)) if self.paused || self.stopping => {}
             BotEvent::BlockchainEvent(_) => {
                 self.state_machine.handle(&event.clone().into()).await;
             }
@@ -115,13 +124,69 @@ impl Strategy<BotEvent, Arc<Mutex<SolanaAction>>> for SniperStrategy {
     }
 
 
+    // new pools from everywhere, everything else only for the pools being sniped
+    async fn subscriptions(&self) -> Vec<EventFilter> {
+        let sniped_pools = self.state_machine.pool_snipes.lock().await.keys().cloned().collect::<Vec<_>>();
+        vec![
+            EventFilter::of_types(&[EventType::NewPool]),
+            EventFilter::all().with_pools(sniped_pools),
+        ]
+    }
+
+    fn kind(&self) -> Option<&'static str> {
+        Some(SNIPER_STRATEGY_KIND)
+    }
+
+    fn owner_user_id(&self) -> Option<i32> {
+        Some(self.get_user_id())
+    }
+
+    fn instance(&self) -> serde_json::Value {
+        serde_json::to_value(&*self.state_machine.instance).unwrap_or_default()
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
 
     async fn get_status(&self) -> StrategyStatus {
-        StrategyStatus::Running(hashmap! {
+        if self.stopping {
+            return if self.state_machine.pool_snipes.lock().await.is_empty() {
+                StrategyStatus::Stopped
+            } else {
+                StrategyStatus::Stopping
+            };
+        }
+        let state = hashmap! {
             "Running".to_owned() => format!("{:?}", self.state_machine.state()),
-        })
+        };
+        if self.paused {
+            StrategyStatus::Paused(state)
+        } else {
+            StrategyStatus::Running(state)
+        }
+    }
+
+    async fn pause(&mut self) -> Result<()> {
+        self.paused = true;
+        Ok(())
+    }
+
+    async fn resume(&mut self) -> Result<()> {
+        self.paused = false;
+        Ok(())
+    }
+
+    // the snipes in progress keep their pools monitored to exit at the stop loss or the take profit,
+    // the pools are released as the snipes finish
+    async fn on_stop(&mut self) -> Vec<Arc<Mutex<SolanaAction>>> {
+        self.stopping = true;
+        for (pool, snipe) in self.state_machine.pool_snipes.lock().await.iter() {
+            if matches!(snipe.lock().await.state(), agent::State::Done { .. } | agent::State::Error { .. }) {
+                solana::stop_monitoring_pool(&self.state_machine.context, pool, self.state_machine.instance.id).await;
+            }
+        }
+        let mut actions = self.state_machine.actions.lock().await;
+        std::mem::take(&mut *actions)
     }
 }
@@ -176,6 +176,8 @@ impl SniperStrategyStateMachine {
             StateOrSuperstate::State(_) => {
                 let pool_snipes_arc = Arc::clone(&self.pool_snipes);
                 let event = event.clone();
+                let context = self.context.clone();
+                let strategy_id = self.instance.id;
 
                 tokio::spawn(async move {
                     let futures: Vec<_> = pool_snipes_arc.lock().await
@@ -214,8 +216,11 @@ impl SniperStrategyStateMachine {
                     stream::iter(pubkeys_to_remove)
                         .for_each_concurrent(None, |pubkey| {
                             let pool_snipes = Arc::clone(&pool_snipes_arc);
+                            let context = context.clone();
                             async move {
                                 pool_snipes.lock().await.remove(&pubkey);
+                                // the failed snipes don't get to release their pool
+                                solana::stop_monitoring_pool(&context, &pubkey, strategy_id).await;
                             }
                         })
                         .await;
@@ -4,29 +4,30 @@ use chrono::{NaiveDateTime, Utc};
 use diesel::prelude::*;
 use diesel_async::RunQueryDsl;
 use futures_util::future::join_all;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicI32, Ordering};
 use std::sync::Arc;
-use rand::random;
 use tokio::sync::broadcast::{Receiver, Sender};
 use tokio::sync::{Mutex, RwLock};
 use tokio::task::JoinHandle;
 use tracing::{debug, error, info};
 use tokio::sync::watch;
 use crate::config::app_context::AppContext;
-use crate::config::constants::NEW_STRATEGY_POLLING_FREQUENCY_MS;
-use crate::schema::users::dsl::users;
-use crate::schema::users::is_active;
-use crate::schema::volumestrategyinstances;
+use crate::dispatcher::EventDispatcher;
+use crate::config::constants::{
+    DEPOSIT_STRATEGY_ID, NEW_STRATEGY_POLLING_FREQUENCY_MS, RENT_RECLAIM_STRATEGY_ID, SUBSCRIPTION_BILLING_INTERVAL_S,
+    SUBSCRIPTION_BILLING_STRATEGY_ID, TRANSIENT_STRATEGY_IDS_START,
+};
+use crate::config::settings::Mode;
 use crate::{solana, utils};
-use crate::strategies::{DepositWithdrawStrategy, VolumeStrategy};
-use crate::strategies::sniper_strategy::SniperStrategy;
-use crate::strategies::sweeper_strategy::SweeperStrategy;
+use crate::strategies::{
+    new_billing_strategy, DepositWithdrawStrategy, RentReclaimStrategy, SolanaStrategyKind, StrategyRegistry,
+    SubscriptionGuard,
+};
 use crate::types::actions::SolanaAction;
-use crate::types::engine::{Strategy, StrategyId, StrategyManager, StrategyStatus};
+use crate::types::engine::{ActionQueue, Strategy, StrategyId, StrategyManager, StrategyStatus};
 use crate::types::events::{BotEvent, SystemEvent};
-use crate::types::bot_user::{BotUser};
-use crate::types::volume_strategy::{NewVolumeStrategyInstance, VolumeStrategyInstance};
-use crate::utils::crypto::hash_i32_to_i32;
+use crate::utils::bounded_queue::{DeliveryPolicy, QueueStatsSnapshot};
 
 pub struct SolanaStrategyManager {
     strategies: Arc<
@@ -37,54 +38,33 @@ pub struct SolanaStrategyManager {
             >,
         >,
     >,
+    /// the strategies `on_stop` was called for, until they're removed
+    stopping: Mutex<HashSet<StrategyId>>,
+    /// the next id of a strategy that isn't stored, they count down and can't collide with the stored ones
+    next_transient_id: AtomicI32,
     context: AppContext,
     strategy_notify: watch::Sender<()>,
+    registry: StrategyRegistry,
+    subscription_guard: SubscriptionGuard,
+    action_queue: RwLock<Option<ActionQueue<Arc<Mutex<SolanaAction>>>>>,
+    dispatcher: RwLock<Option<Arc<EventDispatcher<BotEvent>>>>,
 }
 
 #[async_trait]
 impl StrategyManager<BotEvent, Arc<Mutex<SolanaAction>>> for SolanaStrategyManager {
     async fn sync_state(&self) -> Result<()> {
-        use crate::schema::volumestrategyinstances::dsl::*;
-
-        let mut conn = self.context.db_pool.get().await?;
-
-        let active_users: Vec<BotUser> = users
-            .filter(is_active.eq(true))
-            .load::<BotUser>(&mut conn)
-            .await?;
-
-        let active_strategies: Vec<VolumeStrategyInstance> = volumestrategyinstances
-            .filter(completed_at.is_null())
-            .load::<VolumeStrategyInstance>(&mut conn)
-            .await?;
-
-        let update_balances = |user: &BotUser| {
-            let rpc_pool = self.context.rpc_pool.clone();
-            let cache = self.context.cache.clone();
-            let wallet_address = user.wallet_address.clone();
-            let active_strategies = active_strategies.clone();
-            let uid = user.id;
-            async move {
-                let _ = solana::get_balance(&self.context, &wallet_address).await;
-                if let Some(strategy_pool) = active_strategies.iter().find(|s| s.user_id == uid) {
-                    let pool_details = self.context.rpc_pool.get_pool_details(&strategy_pool.target_pool).await?;
-                    let _ = solana::get_token_balance(&self.context, &wallet_address, &pool_details.base_mint).await;
+        let mut strategies = self.strategies.write().await;
+        for kind in self.registry.kinds().into_iter().filter(|kind| kind.is_persistent()) {
+            for (strat_id, strategy) in kind.load_active().await? {
+                // a strategy of another kind already runs under that id, it isn't replaced
+                if strategies.contains_key(&strat_id) {
+                    error!("Strategy {} of kind {} isn't loaded, its id is taken", strat_id, kind.name());
+                    continue;
                 }
-                Ok::<(), anyhow::Error>(())
+                strategies.insert(strat_id, Arc::new(Mutex::new(strategy)));
             }
-        };
-
-        join_all(active_users.iter().map(update_balances)).await;
-
-        let mut strategies = self.strategies.write().await;
-        for strategy_instance in active_strategies {
-            let strat_id = strategy_instance.id.clone();
-            let mut strategy =
-                Box::new(VolumeStrategy::new(&self.context, &strategy_instance).await?);
-            strategy.sync_state().await;
-            strategies.insert(strat_id, Arc::new(Mutex::new(strategy)));
-            self.strategy_notify.send(()).ok();
         }
+        self.strategy_notify.send(()).ok();
 
         Ok(())
     }
@@ -93,111 +73,181 @@ impl StrategyManager<BotEvent, Arc<Mutex<SolanaAction>>> for SolanaStrategyManag
         &self,
         strategy: Box<dyn Strategy<BotEvent, Arc<Mutex<SolanaAction>>> + Send + Sync>,
     ) -> Result<StrategyId> {
-        let mut strategies = self.strategies.write().await;
-        let id = if let Some(volume_strategy) = strategy.as_any().downcast_ref::<VolumeStrategy>() {
-            let mut strategy = volume_strategy.state_machine.instance.clone();
-            let mut strategy_instance = NewVolumeStrategyInstance::from(&strategy);
-            use crate::schema::volumestrategyinstances::dsl::*;
-
-            let mut conn = self.context.db_pool.get().await.unwrap();
-            let strat_id = diesel::insert_into(volumestrategyinstances)
-                .values(strategy_instance)
-                .returning(id)
-                .get_result(&mut conn)
-                .await?;
-            strategy.id = strat_id;
-            let volume_strategy =
-                Box::new(VolumeStrategy::new(&self.context, &strategy).await?);
-            strategies.insert(strat_id, Arc::new(Mutex::new(volume_strategy)));
-            strat_id
-        } else if let Some(sweeper_strategy) = strategy.as_any().downcast_ref::<SweeperStrategy>() {
-            let mut strategy = sweeper_strategy.state_machine.instance.clone();
-            let mut strategy_instance = Box::new(SweeperStrategy::new(&self.context, &strategy).await?);
-            let strat_id = utils::crypto::hash_i32_to_i32(strategy_instance.state_machine.instance.id);
-            strategies.insert(strat_id, Arc::new(Mutex::new(strategy_instance)));
-            strat_id
-        } else if let Some(sniper_strategy) = strategy.as_any().downcast_ref::<SniperStrategy>() {
-            let mut strategy = sniper_strategy.state_machine.instance.clone();
-            let mut strategy_instance = Box::new(SniperStrategy::new(&self.context, &strategy).await?);
-            let strat_id = strategy_instance.state_machine.instance.id;
-            strategies.insert(strat_id, Arc::new(Mutex::new(strategy_instance)));
-            strat_id
-        } else {
-            let id = hash_i32_to_i32(random());
-            strategies.insert(id, Arc::new(Mutex::new(strategy)));
-            id
+        if let Some(kind) = strategy.kind() {
+            self.subscription_guard.ensure_allowed(kind, &strategy.instance()).await?;
+        }
+        let (id, strategy) = match strategy.kind().and_then(|kind| self.registry.get(kind)) {
+            Some(kind) if kind.is_persistent() => kind.persist(strategy.instance()).await?,
+            Some(kind) => (self.next_transient_id(), kind.persist(strategy.instance()).await?.1),
+            // system strategies aren't registered
+            None => (self.next_transient_id(), strategy),
         };
+        self.strategies.write().await.insert(id, Arc::new(Mutex::new(strategy)));
         self.strategy_notify.send(()).ok();
         Ok(id)
     }
 
+    async fn start_strategy_of_kind(&self, kind: &str, row: serde_json::Value) -> Result<StrategyId> {
+        let kind = self.registry.get(kind).ok_or(anyhow::anyhow!("Unknown strategy kind {}", kind))?;
+        self.subscription_guard.ensure_allowed(kind.name(), &row).await?;
+        let (id, mut strategy) = kind.persist(row).await?;
+        let id = if kind.is_persistent() { id } else { self.next_transient_id() };
+        {
+            let mut strategies = self.strategies.write().await;
+            if !strategies.contains_key(&id) {
+                strategies.insert(id, Arc::new(Mutex::new(strategy)));
+                self.strategy_notify.send(()).ok();
+                return Ok(id);
+            }
+        }
+        // a strategy of another kind runs under that id, the new one is completed rather than replacing it
+        strategy.on_stop().await;
+        kind.complete(id).await?;
+        bail!("Strategy id {} is already taken, {} strategy not started", id, kind.name())
+    }
+
     async fn drop_strategy(&self, strat_id: StrategyId) -> Result<()> {
-        let mut strategies = self.strategies.write().await;
-        match strategies.get(&strat_id) {
+        let strategy = match self.strategies.read().await.get(&strat_id) {
             None => { return Err(anyhow::anyhow!("Strategy with id {} not found", strat_id)); }
-            Some(strategy) => {
-                strategy.lock().await.process_event(
-                    BotEvent::SystemEvent(SystemEvent::DestroyStrategy(strat_id))
-                ).await;
-            }
+            Some(strategy) => strategy.clone(),
+        };
+        // stopped once, a second stop would deactivate the agents and complete the instance again
+        if !self.stopping.lock().await.insert(strat_id) {
+            debug!("Strategy {} is already stopping", strat_id);
+            return Ok(());
         }
 
-        strategies.remove(&strat_id);
+        // the strategy stays registered until it reports `Stopped`, so that the final actions are tracked
+        let (kind, final_actions) = {
+            let mut strategy = strategy.lock().await;
+            (strategy.kind(), strategy.on_stop().await)
+        };
+        info!("Stopping strategy {}, {} final actions", strat_id, final_actions.len());
+        self.send_actions(strat_id, final_actions).await;
+        self.strategy_notify.send(()).ok();
 
-        use crate::schema::volumestrategyinstances::dsl::*;
-        let mut conn = self.context.db_pool.get().await?;
-        diesel::update(volumestrategyinstances.filter(id.eq(strat_id)))
-            .set(completed_at.eq(Some(Utc::now().naive_utc())))
-            .execute(&mut conn)
-            .await?;
+        if let Some(kind) = kind.and_then(|kind| self.registry.get(kind)).filter(|kind| kind.is_persistent()) {
+            kind.complete(strat_id).await?;
+        }
         Ok(())
     }
 
+    async fn pause_strategy(&self, strat_id: StrategyId) -> Result<()> {
+        match self.strategies.read().await.get(&strat_id) {
+            None => Err(anyhow::anyhow!("Strategy with id {} not found", strat_id)),
+            Some(strategy) => strategy.lock().await.pause().await,
+        }
+    }
+
+    async fn resume_strategy(&self, strat_id: StrategyId) -> Result<()> {
+        match self.strategies.read().await.get(&strat_id) {
+            None => Err(anyhow::anyhow!("Strategy with id {} not found", strat_id)),
+            Some(strategy) => strategy.lock().await.resume().await,
+        }
+    }
+
+    async fn update_strategy_config(&self, strat_id: StrategyId, config: serde_json::Value) -> Result<()> {
+        match self.strategies.read().await.get(&strat_id) {
+            None => Err(anyhow::anyhow!("Strategy with id {} not found", strat_id)),
+            Some(strategy) => strategy.lock().await.update_config(config).await,
+        }
+    }
+
     async fn get_active_strategies(
         &self,
     ) -> HashMap<StrategyId, Arc<Mutex<Box<dyn Strategy<BotEvent, Arc<Mutex<SolanaAction>>> + Send + Sync>>>> {
-        let strategies = self.strategies.read().await;
         let mut active_strategies = HashMap::new();
-        for (k, v) in strategies.iter() {
-            let strategy = v.lock().await;
+        for (id, strategy) in self.strategies.read().await.iter() {
+            if !strategy.lock().await.is_completed() {
+                active_strategies.insert(*id, strategy.clone());
+            }
+        }
+        active_strategies
+    }
 
-            let is_strategy_active = if let Some(volume_strategy) = strategy.as_any().downcast_ref::<VolumeStrategy>() {
-                volume_strategy.state_machine.instance.completed_at.is_none()
-            } else {
-                true
-            };
-            if is_strategy_active {
-                active_strategies.insert(*k, v.clone());
+    async fn get_strategy_status(&self, id: StrategyId) -> Option<StrategyStatus> {
+        let strategy = self.get_active_strategies().await.get(&id)?.clone();
+        let mut status = strategy.lock().await.get_status().await;
+        if let StrategyStatus::Running(fields) | StrategyStatus::Paused(fields) = &mut status {
+            if let Some(stats) = self.context.queue_stats.get(&EventDispatcher::<BotEvent>::queue_name(id)) {
+                fields.insert("Queued events".to_string(), stats.len.to_string());
+                fields.insert("Dropped events".to_string(), stats.dropped.to_string());
             }
         }
+        Some(status)
+    }
 
-        active_strategies
+    fn get_strategy_kind(&self, name: &str) -> Option<Arc<SolanaStrategyKind>> {
+        self.registry.get(name)
+    }
+
+    fn get_strategy_kinds(&self) -> Vec<Arc<SolanaStrategyKind>> {
+        self.registry.kinds()
     }
 
     async fn run_strategy_manager(
         &self,
-        event_sender: tokio::sync::broadcast::Sender<BotEvent>,
-        action_sender: tokio::sync::broadcast::Sender<Arc<Mutex<SolanaAction>>>,
+        dispatcher: Arc<EventDispatcher<BotEvent>>,
+        action_queue: ActionQueue<Arc<Mutex<SolanaAction>>>,
     ) -> Result<()> {
         let mut running_strategies: HashMap<StrategyId, JoinHandle<()>> = HashMap::new();
         let mut rx = self.strategy_notify.subscribe();
+        *self.action_queue.write().await = Some(action_queue.clone());
+        *self.dispatcher.write().await = Some(dispatcher.clone());
 
-        if self.context.tg_bot.is_some() {
-            let deposit = DepositWithdrawStrategy::new(self.context.clone()).await;
+        // the deposits of the API users are swept as well, with or without telegram
+        let deposit = DepositWithdrawStrategy::new(self.context.clone()).await;
+        self.spawn_strategy(
+            DEPOSIT_STRATEGY_ID,
+            Arc::new(Mutex::new(Box::new(deposit))),
+            dispatcher.clone(),
+            action_queue.clone(),
+        )
+            .await?;
+        if let Some(billing) = new_billing_strategy(&self.context).await? {
+            self.spawn_strategy(
+                SUBSCRIPTION_BILLING_STRATEGY_ID,
+                Arc::new(Mutex::new(Box::new(billing))),
+                dispatcher.clone(),
+                action_queue.clone(),
+            )
+                .await?;
+        }
+        // closing accounts sends real transactions
+        let live = matches!(self.context.get_settings().await.engine.mode, Mode::Live);
+        if live {
             self.spawn_strategy(
-                i32::MAX,
-                Arc::new(Mutex::new(Box::new(deposit))),
-                event_sender.subscribe(),
-                action_sender.clone(),
+                RENT_RECLAIM_STRATEGY_ID,
+                Arc::new(Mutex::new(Box::new(RentReclaimStrategy::new(self.context.clone())))),
+                dispatcher.clone(),
+                action_queue.clone(),
             )
                 .await?;
         }
+        let mut subscription_check = tokio::time::interval(tokio::time::Duration::from_secs(SUBSCRIPTION_BILLING_INTERVAL_S));
         let mut cached_strategies_ids = vec![];
         //todo add self destruct if a strategy is in done or error state
 
         loop {
             tokio::select! {
                 _ = rx.changed() => {
+                    // Remove strategies that have finished winding down
+                    let mut strategies_ids_to_stop = vec![];
+                    for (id, strategy) in self.get_active_strategies().await.iter() {
+                        if let StrategyStatus::Stopped = strategy.lock().await.get_status().await {
+                            strategies_ids_to_stop.push(*id);
+                        }
+                    }
+                    if !strategies_ids_to_stop.is_empty() {
+                        let mut strategies = self.strategies.write().await;
+                        let mut stopping = self.stopping.lock().await;
+                        for id in strategies_ids_to_stop.iter() {
+                            strategies.remove(id);
+                            stopping.remove(id);
+                            info!("Strategy {} stopped and removed", id);
+                        }
+                    }
+
                     let current_strategies = self.get_active_strategies().await;
                     let mut ids = current_strategies.keys().cloned().collect::<Vec<_>>();
                     ids.sort();
@@ -208,38 +258,43 @@ impl StrategyManager<BotEvent, Arc<Mutex<SolanaAction>>> for SolanaStrategyManag
                     }
                     debug!("Strategy manager notified about strategies list change: {:?}", cached_strategies_ids);
                     // Start new strategies
-                    let mut strategies_ids_to_stop = vec![];
                     for (id, strategy) in current_strategies.iter() {
                         if !running_strategies.contains_key(id) {
                             let handle = self
                                 .spawn_strategy(
                                     *id,
                                     strategy.clone(),
-                                    event_sender.subscribe(),
-                                    action_sender.clone(),
+                                    dispatcher.clone(),
+                                    action_queue.clone(),
                                 )
                                 .await?;
                             running_strategies.insert(*id, handle);
                         }
-                        if let StrategyStatus::Stopped = strategy.lock().await.get_status().await {
-                            strategies_ids_to_stop.push(*id);
-                        }
                     }
-                    // Remove stopped strategies
+                    // Abort tasks of the removed strategies
+                    let mut removed_strategies_ids = vec![];
                     running_strategies.retain(|id, handle| {
-                        // terminated by user
-                        if !current_strategies.contains_key(id) || strategies_ids_to_stop.contains(id) {
+                        if !current_strategies.contains_key(id) {
                             handle.abort();
+                            removed_strategies_ids.push(*id);
                             false
                         } else {
                             true
                         }
                     });
+                    for id in removed_strategies_ids {
+                        dispatcher.unsubscribe(id).await;
+                    }
                 }
                     // Periodic check, just in case
                 _ = tokio::time::sleep(tokio::time::Duration::from_secs(3)) => {
                     self.strategy_notify.send(()).ok();
                 }
+                _ = subscription_check.tick() => {
+                    if let Err(e) = self.stop_unsubscribed_strategies().await {
+                        error!("Failed to check the subscriptions of the running strategies: {:?}", e);
+                    }
+                }
             }
         }
     }
@@ -250,18 +305,76 @@ impl SolanaStrategyManager {
         let (tx, _) = watch::channel(());
         let manager = SolanaStrategyManager {
             strategies: Arc::new(Default::default()),
+            stopping: Mutex::new(HashSet::new()),
+            next_transient_id: AtomicI32::new(TRANSIENT_STRATEGY_IDS_START),
             context: context.clone(),
             strategy_notify: tx,
+            registry: StrategyRegistry::new(context),
+            subscription_guard: SubscriptionGuard::new(context),
+            action_queue: RwLock::new(None),
+            dispatcher: RwLock::new(None),
         };
         Ok(manager)
     }
+
+    fn next_transient_id(&self) -> StrategyId {
+        self.next_transient_id.fetch_sub(1, Ordering::Relaxed)
+    }
+
+    pub fn with_strategy_kind(mut self, kind: Arc<SolanaStrategyKind>) -> Self {
+        self.registry = self.registry.with_kind(kind);
+        self
+    }
+
+    /// Per strategy delivery and lag counters of the event routing.
+    pub async fn get_routing_stats(&self) -> HashMap<StrategyId, QueueStatsSnapshot> {
+        match self.dispatcher.read().await.as_ref() {
+            Some(dispatcher) => dispatcher.stats().await,
+            None => HashMap::new(),
+        }
+    }
+
+    // strategies whose subscription was suspended are stopped
+    async fn stop_unsubscribed_strategies(&self) -> Result<()> {
+        let mut strategies = vec![];
+        for (id, strategy) in self.get_active_strategies().await {
+            let strategy = strategy.lock().await;
+            if let Some(kind) = strategy.kind() {
+                strategies.push((id, kind, strategy.instance()));
+            }
+        }
+        for id in self.subscription_guard.get_unallowed(strategies).await? {
+            info!("Stopping strategy {}, its subscription plan doesn't allow it", id);
+            if let Err(e) = self.drop_strategy(id).await {
+                error!("Failed to stop strategy {}: {:?}", id, e);
+            }
+        }
+        Ok(())
+    }
+
+    async fn send_actions(&self, id: StrategyId, actions: Vec<Arc<Mutex<SolanaAction>>>) {
+        match self.action_queue.read().await.as_ref() {
+            Some(action_queue) => {
+                for action in actions {
+                    action_queue.push(action, DeliveryPolicy::NeverDrop);
+                }
+            }
+            None => {
+                if !actions.is_empty() {
+                    error!("Strategy manager is not running, {} actions of strategy {} are dropped", actions.len(), id);
+                }
+            }
+        }
+    }
+
     async fn spawn_strategy(
         &self,
         id: StrategyId,
         strategy: Arc<Mutex<Box<dyn Strategy<BotEvent, Arc<Mutex<SolanaAction>>> + Send + Sync>>>,
-        mut event_receiver: Receiver<BotEvent>,
-        action_sender: Sender<Arc<Mutex<SolanaAction>>>,
+        dispatcher: Arc<EventDispatcher<BotEvent>>,
+        action_queue: ActionQueue<Arc<Mutex<SolanaAction>>>,
     ) -> Result<JoinHandle<()>> {
+        let event_queue = dispatcher.subscribe(id, strategy.lock().await.subscriptions().await).await;
         Ok(tokio::spawn(async move {
             {
                 let mut strategy = strategy.lock().await;
@@ -273,15 +386,17 @@ impl SolanaStrategyManager {
             info!("Starting strategy {}, {:?} ...", id, strategy.lock().await);
 
             let process_event = |event: BotEvent| async {
-                let actions = strategy.lock().await.process_event(event).await;
+                let (actions, subscriptions) = {
+                    let mut strategy = strategy.lock().await;
+                    (strategy.process_event(event).await, strategy.subscriptions().await)
+                };
                 for action in actions {
-                    if let Err(e) = action_sender.send(action) {
-                        error!("Error sending action for strategy {}: {:?}", id, e);
-                    }
+                    action_queue.push(action, DeliveryPolicy::NeverDrop);
                 }
+                dispatcher.update_filters(id, subscriptions).await;
             };
 
-            while let Ok(event) = event_receiver.recv().await {
+            while let Some(event) = event_queue.pop().await {
                 process_event(event).await;
             }
 
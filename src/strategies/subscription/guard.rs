@@ -0,0 +1,87 @@
+use crate::config::app_context::AppContext;
+use crate::solana::constants::WSOL_MINT_PUBKEY;
+use crate::storage::subscriptions;
+use crate::types::subscription::SubscriptionPlan;
+use anyhow::{anyhow, bail, Result};
+use chrono::Utc;
+use solana_sdk::pubkey::Pubkey;
+use tracing::warn;
+
+/// Checks the plan of the owner before a paid kind of strategy runs, every strategy is allowed
+/// without the `[subscription]` config.
+#[derive(Clone)]
+pub struct SubscriptionGuard {
+    context: AppContext,
+}
+
+impl SubscriptionGuard {
+    pub fn new(context: &AppContext) -> Self {
+        Self { context: context.clone() }
+    }
+
+    async fn is_enabled(&self) -> bool {
+        self.context.get_settings().await.subscription.is_some()
+    }
+
+    /// The token traded by the strategy, the other side of SOL in its target pool.
+    async fn get_traded_token(&self, instance: &serde_json::Value) -> Result<Pubkey> {
+        let target_pool: Pubkey = serde_json::from_value(instance["target_pool"].clone())
+            .map_err(|_| anyhow!("The strategy has no target pool"))?;
+        let pool = self.context.rpc_pool.get_pool_details(&target_pool).await?;
+        Ok(if pool.base_mint == *WSOL_MINT_PUBKEY { pool.quote_mint } else { pool.base_mint })
+    }
+
+    /// Whether the user can run a strategy of the kind on the token.
+    pub async fn is_allowed(&self, kind: &str, user_id: i32, token: &Pubkey) -> Result<bool> {
+        if !SubscriptionPlan::is_paid_kind(kind) || !self.is_enabled().await {
+            return Ok(true);
+        }
+        let plan = subscriptions::get_subscription(&self.context.db_pool, user_id, &token.to_string())
+            .await?
+            .map_or(SubscriptionPlan::Free, |subscription| subscription.effective_plan(Utc::now()));
+        Ok(plan.allows_kind(kind))
+    }
+
+    /// Fails unless the owner of the instance row has a plan for the kind and the traded token.
+    pub async fn ensure_allowed(&self, kind: &str, instance: &serde_json::Value) -> Result<()> {
+        if !SubscriptionPlan::is_paid_kind(kind) || !self.is_enabled().await {
+            return Ok(());
+        }
+        let user_id = instance["user_id"]
+            .as_i64()
+            .ok_or_else(|| anyhow!("The strategy has no owner"))? as i32;
+        let token = self.get_traded_token(instance).await?;
+        if !self.is_allowed(kind, user_id, &token).await? {
+            bail!("The subscription plan for {} doesn't include {} strategies", token, kind);
+        }
+        Ok(())
+    }
+
+    /// Running strategies whose owner's plan doesn't allow them anymore: suspended, downgraded
+    /// or with an expired payment. A strategy whose traded token can't be fetched is kept.
+    pub async fn get_unallowed(&self, strategies: Vec<(i32, &'static str, serde_json::Value)>) -> Result<Vec<i32>> {
+        if !self.is_enabled().await {
+            return Ok(vec![]);
+        }
+        let mut strategy_ids = vec![];
+        for (strategy_id, kind, instance) in strategies {
+            if !SubscriptionPlan::is_paid_kind(kind) {
+                continue;
+            }
+            let Some(user_id) = instance["user_id"].as_i64().map(|user_id| user_id as i32) else {
+                continue;
+            };
+            let token = match self.get_traded_token(&instance).await {
+                Ok(token) => token,
+                Err(e) => {
+                    warn!("Skipping the subscription check of strategy {}: {:?}", strategy_id, e);
+                    continue;
+                }
+            };
+            if !self.is_allowed(kind, user_id, &token).await? {
+                strategy_ids.push(strategy_id);
+            }
+        }
+        Ok(strategy_ids)
+    }
+}
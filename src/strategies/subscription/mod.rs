@@ -0,0 +1,5 @@
+mod guard;
+mod strategy;
+
+pub use guard::SubscriptionGuard;
+pub use strategy::{new_billing_strategy, SubscriptionBillingStrategy};
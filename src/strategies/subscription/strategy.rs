@@ -0,0 +1,236 @@
+use crate::config::app_context::AppContext;
+use crate::config::constants::{
+    ACTION_EXPIRY_S, BASE_TX_FEE_SOL, SUBSCRIPTION_BILLING_INTERVAL_S, SUBSCRIPTION_PAYMENT_RETRY_S,
+};
+use crate::config::settings::SubscriptionConfig;
+use crate::schema::users::dsl::users;
+use crate::storage::subscriptions;
+use crate::tg_bot::notify_user;
+use crate::types::actions::{Amount, Asset, SolanaAction, SolanaActionPayload, SolanaTransferActionPayload};
+use crate::types::bot_user::BotUser;
+use crate::types::engine::{Strategy, StrategyStatus};
+use crate::types::events::{BlockchainEvent, BotEvent, ExecutionResult};
+use crate::types::keys::KeypairClonable;
+use crate::types::routing::{EventFilter, EventType};
+use crate::types::subscription::{
+    BillingDecision, NewSubscriptionPayment, PaymentStatus, Subscription, SubscriptionPlan,
+};
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use chrono::{Duration, Utc};
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use maplit::hashmap;
+use solana_sdk::native_token::sol_to_lamports;
+use solana_sdk::pubkey::Pubkey;
+use std::any::Any;
+use std::fmt::{Debug, Formatter};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::sync::Mutex;
+use tracing::{error, info, warn};
+
+/// Charges the subscriptions every period from the trading wallets to `engine.bot_wallet`,
+/// a subscription that can't be paid gets a grace period and is suspended after it.
+pub struct SubscriptionBillingStrategy {
+    context: AppContext,
+    config: SubscriptionConfig,
+    bot_wallet: Pubkey,
+    last_billing: Option<Instant>,
+    payments_sent: u64,
+    payments_failed: u64,
+}
+
+impl SubscriptionBillingStrategy {
+    pub fn new(context: AppContext, config: SubscriptionConfig, bot_wallet: Pubkey) -> Self {
+        Self {
+            context,
+            config,
+            bot_wallet,
+            last_billing: None,
+            payments_sent: 0,
+            payments_failed: 0,
+        }
+    }
+
+    async fn notify(&self, user: &BotUser, text: &str) {
+        if let Some(user_chat_id) = user.chat_id && let Some(bot) = self.context.tg_bot.as_ref() {
+            notify_user(bot, user_chat_id, text).await;
+        }
+    }
+
+    async fn get_user(&self, user_id: i32) -> Result<BotUser> {
+        let mut conn = self.context.db_pool.get().await?;
+        Ok(users.find(user_id).first::<BotUser>(&mut conn).await?)
+    }
+
+    async fn charge(&mut self, subscription: &Subscription) -> Result<Option<Arc<Mutex<SolanaAction>>>> {
+        let user = self.get_user(subscription.user_id).await?;
+        let plan = subscription.get_plan();
+        let amount_sol = plan.price_sol(&self.config);
+        let amount_lamports = sol_to_lamports(amount_sol);
+        let balance = self.context.rpc_pool.get_balance(&user.wallet_address).await?;
+        let grace_period = Duration::days(self.config.grace_period_days);
+        match subscription.billing_decision(Utc::now(), balance >= amount_lamports + BASE_TX_FEE_SOL, grace_period) {
+            BillingDecision::NotDue => Ok(None),
+            BillingDecision::EnterGrace(grace_until) => {
+                subscriptions::enter_grace_period(&self.context.db_pool, subscription.id, grace_until).await?;
+                info!("Subscription {} can't be paid, grace period until {}", subscription.id, grace_until);
+                self.notify(&user, &format!(
+                    "⚠️ Not enough SOL on the trading wallet to pay the subscription for `{}`, top it up before {} UTC or the subscription is suspended",
+                    subscription.token_mint,
+                    grace_until.format("%Y\\-%m\\-%d %H:%M"),
+                ))
+                .await;
+                Ok(None)
+            }
+            BillingDecision::Suspend => {
+                subscriptions::suspend_subscription(&self.context.db_pool, subscription.id).await?;
+                warn!("Subscription {} suspended", subscription.id);
+                self.notify(&user, &format!(
+                    "⛔ The subscription for `{}` is suspended, its strategies are stopped",
+                    subscription.token_mint,
+                ))
+                .await;
+                Ok(None)
+            }
+            BillingDecision::Charge => {
+                let action = SolanaAction::new(
+                    KeypairClonable::new_from_privkey(&user.wallet_private_key)?,
+                    vec![SolanaActionPayload::SolanaTransferActionPayload(SolanaTransferActionPayload {
+                        asset: Asset::Sol,
+                        receiver: self.bot_wallet,
+                        amount: Amount::Exact(amount_lamports),
+                    })],
+                );
+                subscriptions::insert_payment(&self.context.db_pool, NewSubscriptionPayment {
+                    subscription_id: subscription.id,
+                    action_uuid: action.uuid.to_string(),
+                    plan: plan as i32,
+                    amount_sol,
+                    status: PaymentStatus::Pending.to_string(),
+                })
+                .await?;
+                info!("Charging {} SOL for subscription {}", amount_sol, subscription.id);
+                self.payments_sent += 1;
+                Ok(Some(Arc::new(Mutex::new(action))))
+            }
+        }
+    }
+
+    async fn bill(&mut self) -> Result<Vec<Arc<Mutex<SolanaAction>>>> {
+        let now = Utc::now();
+        subscriptions::fail_stale_payments(&self.context.db_pool, now - Duration::seconds(ACTION_EXPIRY_S as i64))
+            .await?;
+        let retry_failed_before = now - Duration::seconds(SUBSCRIPTION_PAYMENT_RETRY_S);
+        let mut actions = vec![];
+        for subscription in subscriptions::get_due_subscriptions(&self.context.db_pool, now, retry_failed_before).await? {
+            match self.charge(&subscription).await {
+                Ok(action) => actions.extend(action),
+                Err(e) => error!("Failed to bill subscription {}: {:?}", subscription.id, e),
+            }
+        }
+        Ok(actions)
+    }
+
+    // the payment failed, the subscription is retried during the grace period
+    async fn on_payment_failed(&mut self, action_uuid: &str, tx_hash: Option<String>) -> Result<()> {
+        let Some(payment) = subscriptions::get_pending_payment(&self.context.db_pool, action_uuid).await? else {
+            return Ok(());
+        };
+        subscriptions::fail_payment(&self.context.db_pool, payment.id, tx_hash).await?;
+        let grace_until = Utc::now() + Duration::days(self.config.grace_period_days);
+        subscriptions::enter_grace_period(&self.context.db_pool, payment.subscription_id, grace_until).await?;
+        self.payments_failed += 1;
+        warn!("Payment {} of subscription {} failed", payment.id, payment.subscription_id);
+        Ok(())
+    }
+
+    async fn on_payment_confirmed(&mut self, action_uuid: &str, tx_hash: &str) -> Result<()> {
+        let Some(payment) = subscriptions::get_pending_payment(&self.context.db_pool, action_uuid).await? else {
+            return Ok(());
+        };
+        let period = Duration::days(self.config.period_days);
+        let subscription = subscriptions::settle_payment(&self.context.db_pool, &payment, tx_hash, period).await?;
+        info!("Payment {} of subscription {} confirmed", payment.id, payment.subscription_id);
+        let plan = SubscriptionPlan::try_from(payment.plan)?;
+        let user = self.get_user(subscription.user_id).await?;
+        self.notify(&user, &format!("✅ Subscription {:?} for `{}` paid", plan, subscription.token_mint)).await;
+        Ok(())
+    }
+
+    async fn handle_event(&mut self, event: BotEvent) -> Result<Vec<Arc<Mutex<SolanaAction>>>> {
+        match event {
+            BotEvent::HeartBeat(..) => {
+                if self
+                    .last_billing
+                    .is_some_and(|last| last.elapsed().as_secs() < SUBSCRIPTION_BILLING_INTERVAL_S)
+                {
+                    return Ok(vec![]);
+                }
+                self.last_billing = Some(Instant::now());
+                self.bill().await
+            }
+            BotEvent::ExecutionResult(uuid, _, ExecutionResult::ExecutionError(_)) => {
+                self.on_payment_failed(&uuid.to_string(), None).await?;
+                Ok(vec![])
+            }
+            BotEvent::BlockchainEvent(BlockchainEvent::ExecutionReceipt(receipt)) => {
+                let action_uuid = receipt.action_uuid.to_string();
+                let tx_hash = receipt.transaction_signature.to_string();
+                match receipt.err {
+                    None => self.on_payment_confirmed(&action_uuid, &tx_hash).await?,
+                    Some(_) => self.on_payment_failed(&action_uuid, Some(tx_hash)).await?,
+                }
+                Ok(vec![])
+            }
+            _ => Ok(vec![]),
+        }
+    }
+}
+
+impl Debug for SubscriptionBillingStrategy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "SubscriptionBillingStrategy")
+    }
+}
+
+#[async_trait]
+impl Strategy<BotEvent, Arc<Mutex<SolanaAction>>> for SubscriptionBillingStrategy {
+    async fn sync_state(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    async fn process_event(&mut self, event: BotEvent) -> Vec<Arc<Mutex<SolanaAction>>> {
+        self.handle_event(event).await.unwrap_or_else(|e| {
+            error!("Subscription billing failed: {:?}", e);
+            vec![]
+        })
+    }
+
+    async fn get_status(&self) -> StrategyStatus {
+        StrategyStatus::Running(hashmap! {
+            "Payments sent".to_owned() => self.payments_sent.to_string(),
+            "Payments failed".to_owned() => self.payments_failed.to_string(),
+        })
+    }
+
+    async fn subscriptions(&self) -> Vec<EventFilter> {
+        vec![EventFilter::of_types(&[EventType::HeartBeat, EventType::ExecutionReceipt, EventType::ExecutionResult])]
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// The billing runs when the plans and the wallet receiving the payments are configured.
+pub async fn new_billing_strategy(context: &AppContext) -> Result<Option<SubscriptionBillingStrategy>> {
+    let settings = context.get_settings().await;
+    let (Some(config), Some(bot_wallet)) = (settings.subscription.clone(), settings.engine.bot_wallet.as_ref()) else {
+        return Ok(None);
+    };
+    let bot_wallet = Pubkey::from_str(bot_wallet).map_err(|e| anyhow!("Invalid engine.bot_wallet: {}", e))?;
+    Ok(Some(SubscriptionBillingStrategy::new(context.clone(), config, bot_wallet)))
+}
@@ -0,0 +1,63 @@
+use crate::config::app_context::AppContext;
+use crate::strategies::sweeper_strategy::SweeperStrategy;
+use crate::types::actions::SolanaAction;
+use crate::types::engine::{Strategy, StrategyId, StrategyKind};
+use crate::types::events::BotEvent;
+use crate::types::volume_strategy::VolumeStrategyInstance;
+use anyhow::Result;
+use async_trait::async_trait;
+use serde_json::json;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+pub const SWEEPER_STRATEGY_KIND: &str = "sweeper";
+
+/// Sweeper runs on top of a volume strategy instance and is not stored on its own,
+/// the manager gives it a transient id that can't collide with the stored strategies.
+pub struct SweeperStrategyKind {
+    context: AppContext,
+}
+
+impl SweeperStrategyKind {
+    pub fn new(context: &AppContext) -> Self {
+        Self {
+            context: context.clone(),
+        }
+    }
+}
+
+#[async_trait]
+impl StrategyKind<BotEvent, Arc<Mutex<SolanaAction>>> for SweeperStrategyKind {
+    fn name(&self) -> &'static str {
+        SWEEPER_STRATEGY_KIND
+    }
+
+    fn config_schema(&self) -> serde_json::Value {
+        json!({
+            "id": {"type": "i32", "description": "Volume strategy to collect the funds from"},
+        })
+    }
+
+    fn is_persistent(&self) -> bool {
+        false
+    }
+
+    async fn persist(
+        &self,
+        row: serde_json::Value,
+    ) -> Result<(StrategyId, Box<dyn Strategy<BotEvent, Arc<Mutex<SolanaAction>>> + Send + Sync>)> {
+        let instance: VolumeStrategyInstance = serde_json::from_value(row)?;
+        // not stored, the id is the manager's
+        Ok((0, Box::new(SweeperStrategy::new(&self.context, &instance).await?)))
+    }
+
+    async fn complete(&self, _id: StrategyId) -> Result<()> {
+        Ok(())
+    }
+
+    async fn load_active(
+        &self,
+    ) -> Result<Vec<(StrategyId, Box<dyn Strategy<BotEvent, Arc<Mutex<SolanaAction>>> + Send + Sync>)>> {
+        Ok(vec![])
+    }
+}
@@ -1,4 +1,6 @@
 pub mod strategy;
 pub mod strategy_state_machine;
+mod kind;
 pub use strategy_state_machine::SweeperStrategyStateMachine;
-pub use strategy::SweeperStrategy;
\ No newline at end of file
+pub use strategy::SweeperStrategy;
+pub use kind::{SweeperStrategyKind, SWEEPER_STRATEGY_KIND};
\ No newline at end of file
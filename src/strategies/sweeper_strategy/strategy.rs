@@ -5,6 +5,7 @@ use crate::schema::volumestrategyinstances::dsl::volumestrategyinstances;
 use crate::schema::*;
 use crate::types::actions::{SolanaAction, SwapMethod};
 use crate::types::engine::{Strategy, StrategyStatus};
+use crate::types::routing::{EventFilter, EventType};
 use crate::types::events::{BlockchainEvent, BotEvent};
 use crate::types::keys::KeypairClonable;
 use crate::types::pool::{RaydiumPool, RaydiumPoolPriceUpdate};
@@ -40,6 +41,7 @@ use tokio::sync::{Mutex, RwLock};
 use tokio::task::JoinHandle;
 use tracing::field::debug;
 use tracing::{debug, error, info, instrument, Event};
+use crate::strategies::sweeper_strategy::SWEEPER_STRATEGY_KIND;
 use crate::strategies::SweeperStrategyStateMachine;
 
 // strategy basically manages a collection of position state machines,
@@ -48,6 +50,7 @@ use crate::strategies::SweeperStrategyStateMachine;
 #[derive(Clone)]
 pub struct SweeperStrategy {
     pub state_machine: StateMachine<SweeperStrategyStateMachine>,
+    pub stopping: bool,
 }
 
 impl Debug for SweeperStrategy {
@@ -70,6 +73,7 @@ impl SweeperStrategy {
             .state_machine();
         Ok(Self {
             state_machine,
+            stopping: false,
         })
     }
 
@@ -103,13 +107,40 @@ impl Strategy<BotEvent, Arc<Mutex<SolanaAction>>> for SweeperStrategy {
         actions
     }
 
+    // events of other pools are of no interest, the ones that aren't pool specific all pass
+    async fn subscriptions(&self) -> Vec<EventFilter> {
+        vec![EventFilter::all().with_pools([self.state_machine.pool.id])]
+    }
+
+    fn kind(&self) -> Option<&'static str> {
+        Some(SWEEPER_STRATEGY_KIND)
+    }
+
+    fn owner_user_id(&self) -> Option<i32> {
+        Some(self.get_user_id())
+    }
+
+    fn instance(&self) -> serde_json::Value {
+        serde_json::to_value(&self.state_machine.instance).unwrap_or_default()
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
     
     async fn get_status(&self) -> StrategyStatus {
+        if self.stopping {
+            return StrategyStatus::Stopped;
+        }
         StrategyStatus::Running(hashmap! {
             "Running".to_owned() => format!("{:?}", self.state_machine.state()),
         })
     }
+
+    // the sweeper is a one-off cleanup, there's nothing to wind down
+    async fn on_stop(&mut self) -> Vec<Arc<Mutex<SolanaAction>>> {
+        self.stopping = true;
+        let mut strat_actions_generated_from_event = self.state_machine.strat_actions_generated_from_event.lock().await;
+        std::mem::take(&mut *strat_actions_generated_from_event)
+    }
 }
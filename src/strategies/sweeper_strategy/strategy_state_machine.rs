@@ -97,6 +97,7 @@ impl SweeperStrategyStateMachine {
                 pool.clone(),
                 KeypairClonable::new_from_privkey(&user.wallet_private_key).unwrap(),
                 None,
+                None,
                 strat_actions_generated_from_event.clone(),
                 None,
             )
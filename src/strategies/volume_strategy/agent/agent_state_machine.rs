@@ -9,7 +9,7 @@ use crate::types::events::{BlockchainEvent, BotEvent, ExecutionReceipt, Executio
 use crate::types::keys::KeypairClonable;
 use crate::types::pool::RaydiumPool;
 use crate::types::bot_user::{NewTrader, Trader};
-use crate::{solana, utils};
+use crate::{solana, storage, utils};
 use crate::utils::decimals::sol_to_lamports;
 use anyhow::{anyhow, bail, Error, Result};
 use chrono::Utc;
@@ -61,10 +61,12 @@ pub struct AgentState {
 
 impl AgentState {
     // Create a new agent - can be either a fresh trader or a main wallet with some balance on it.
+    // Agents derived from the strategy's seed pass their derivation index, their key isn't stored.
     pub async fn new(
         context: &AppContext,
         pool: Arc<RaydiumPool>,
         agent_key: KeypairClonable,
+        derivation_index: Option<i32>,
         strategy_id_opt: Option<StrategyId>,
         strat_actions_generated_from_event: Arc<Mutex<Vec<Arc<Mutex<SolanaAction>>>>>,
         main_wallet: Option<KeypairClonable>,
@@ -88,7 +90,7 @@ impl AgentState {
         };
 
         if let Some(strategy_id) = strategy_id_opt {
-            agent.trader = agent.db_read_or_create(&context.db_pool, &strategy_id).await?;
+            agent.trader = agent.db_read_or_create(&context.db_pool, &strategy_id, derivation_index).await?;
             if agent.trader.is_active & &agent.trader.strategy_instance_id.is_some() {
                 if agent.trader.strategy_instance_id.unwrap() != strategy_id {
                     warn!("Agent is already active in another strategy instance, probably main wallet");
@@ -113,7 +115,7 @@ impl AgentState {
         main_wallet: Option<KeypairClonable>,
     ) -> Result<Self> {
         let strategy_id_opt = trader.strategy_instance_id;
-        let agent_key = KeypairClonable::new_from_privkey(&trader.private_key)?;
+        let agent_key = Self::trader_key(context, &trader).await?;
         let mut agent = Self {
             context: context.clone(),
             pool: pool.clone(),
@@ -137,7 +139,24 @@ impl AgentState {
         self.agent_key.pubkey()
     }
 
-    async fn db_read_or_create(&self, db_pool: &DbPool, strategy_id: &StrategyId) -> Result<Trader> {
+    async fn trader_key(context: &AppContext, trader: &Trader) -> Result<KeypairClonable> {
+        let agent_key = match (&trader.private_key, trader.derivation_index, trader.strategy_instance_id) {
+            (Some(private_key), _, _) => KeypairClonable::new_from_privkey(private_key)?,
+            (None, Some(index), Some(strategy_id)) => {
+                storage::agent_wallets::get_agent_seed(&context.db_pool, strategy_id)
+                    .await?
+                    .ok_or_else(|| anyhow!("No agent seed for strategy {}", strategy_id))?
+                    .derive(index)?
+            }
+            _ => bail!("Trader {} has neither a key nor a derivation path", trader.id),
+        };
+        if agent_key.pubkey() != trader.wallet {
+            bail!("Trader {} key doesn't match its wallet {}", trader.id, trader.wallet);
+        }
+        Ok(agent_key)
+    }
+
+    async fn db_read_or_create(&self, db_pool: &DbPool, strategy_id: &StrategyId, derivation_index: Option<i32>) -> Result<Trader> {
         let mut conn = db_pool.get().await?;
         let trader = traders
             .filter(wallet.eq(self.agent_key.pubkey().to_string()))
@@ -150,9 +169,13 @@ impl AgentState {
                 let new_trader = NewTrader {
                     strategy_instance_id: Some(*strategy_id),
                     wallet: self.agent_key.pubkey(),
-                    private_key: utils::keys::private_key_string_base58(&self.agent_key.get_keypair()),
+                    private_key: match derivation_index {
+                        Some(_) => None,
+                        None => Some(utils::keys::private_key_string_base58(&self.agent_key.get_keypair())),
+                    },
                     created: Utc::now().naive_utc(),
                     is_active: true,
+                    derivation_index,
                 };
                 let trader = diesel::insert_into(traders)
                     .values(new_trader)
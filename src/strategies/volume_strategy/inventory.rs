@@ -0,0 +1,121 @@
+use crate::config::constants::MIN_SELL_SHARE_OF_BOUGHT;
+use crate::types::pool::RaydiumPoolPriceUpdate;
+
+/// Limits on the token inventory the strategy holds and on the price drift from its reference price,
+/// either can be left out.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct InventoryBands {
+    pub max_inventory_sol: Option<f64>,
+    pub max_price_drift_pct: Option<f64>,
+}
+
+impl InventoryBands {
+    pub fn is_set(&self) -> bool {
+        self.max_inventory_sol.is_some() || self.max_price_drift_pct.is_some()
+    }
+}
+
+/// The inventory held and the pool price before the buys of a tranche, in ui amounts.
+#[derive(Debug, Clone, Copy)]
+pub struct TrancheStart {
+    pub inventory: f64,
+    pub price: f64,
+    /// Slot of the price, none for the polled ones
+    pub slot: Option<u64>,
+}
+
+/// Tokens to sell once the tranche bought, in ui amount, out of `balance`.
+/// A neutral tranche sells what brings the price back to where the tranche started. When that price drifted
+/// out of the band, it targets the reference price instead, compensating by at most the tokens bought but
+/// still selling a share of them for the volume. The inventory left stays between 0 and the band, which takes precedence.
+/// A tranche that bought nothing only sells the inventory above the band.
+pub fn tokens_to_sell(
+    bands: &InventoryBands,
+    reference_price: f64,
+    start: &TrancheStart,
+    balance: f64,
+    pool: &RaydiumPoolPriceUpdate,
+) -> f64 {
+    let drift_pct = 100.0 * (start.price / reference_price - 1.0);
+    let target_price = match bands.max_price_drift_pct {
+        Some(max_drift_pct) if drift_pct.abs() > max_drift_pct => reference_price,
+        _ => start.price,
+    };
+    let max_inventory = bands
+        .max_inventory_sol
+        .map(|max_inventory_sol| max_inventory_sol / pool.price)
+        .unwrap_or(f64::INFINITY);
+    let bought = (balance - start.inventory).max(0.0);
+    if bought == 0.0 {
+        return (balance - max_inventory).clamp(0.0, balance.max(0.0));
+    }
+    if pool.base_reserve <= 0.0 || pool.quote_reserve <= 0.0 || !(target_price > 0.0) {
+        return balance;
+    }
+    // constant product, the base reserve at the target price
+    let to_target = (pool.base_reserve * pool.quote_reserve / target_price).sqrt() - pool.base_reserve;
+    let sell = to_target.clamp(MIN_SELL_SHARE_OF_BOUGHT * bought, 2.0 * bought);
+    sell.clamp((balance - max_inventory).max(0.0), balance)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 1000 tokens and 1000 SOL, then 100 SOL bought about 90.9 tokens
+    fn pool_after_buy() -> RaydiumPoolPriceUpdate {
+        let quote_reserve = 1100.0;
+        let base_reserve = 1_000_000.0 / quote_reserve;
+        RaydiumPoolPriceUpdate {
+            price: quote_reserve / base_reserve,
+            base_reserve,
+            quote_reserve,
+            ..Default::default()
+        }
+    }
+
+    fn assert_close(left: f64, right: f64) {
+        assert!((left - right).abs() < 0.01, "{} != {}", left, right);
+    }
+
+    #[test]
+    fn test_neutral_tranche_sells_what_it_bought() {
+        let pool = pool_after_buy();
+        let bought = 1000.0 - pool.base_reserve;
+        let bands = InventoryBands { max_price_drift_pct: Some(10.0), ..Default::default() };
+        let start = TrancheStart { inventory: 50.0, price: 1.0, slot: None };
+        assert_close(tokens_to_sell(&bands, 1.05, &start, 50.0 + bought, &pool), bought);
+    }
+
+    #[test]
+    fn test_drift_is_compensated() {
+        let pool = pool_after_buy();
+        let bought = 1000.0 - pool.base_reserve;
+        let start = TrancheStart { inventory: 200.0, price: 1.0, slot: None };
+        let bands = InventoryBands { max_price_drift_pct: Some(10.0), ..Default::default() };
+        // 25% above the reference, the inventory is sold down by at most the tranche
+        assert_close(tokens_to_sell(&bands, 0.8, &start, 200.0 + bought, &pool), 2.0 * bought);
+        // 20% below, most of the tokens bought are kept
+        assert_close(tokens_to_sell(&bands, 1.25, &start, 200.0 + bought, &pool), MIN_SELL_SHARE_OF_BOUGHT * bought);
+
+        // unless the inventory band is reached, worth 50 SOL
+        let bands = InventoryBands { max_inventory_sol: Some(50.0), max_price_drift_pct: Some(10.0) };
+        let start = TrancheStart { inventory: 0.0, price: 1.0, slot: None };
+        assert_close(tokens_to_sell(&bands, 1.25, &start, bought, &pool), bought - 50.0 / pool.price);
+    }
+
+    #[test]
+    fn test_nothing_bought_sells_the_excess_only() {
+        let pool = pool_after_buy();
+        let start = TrancheStart { inventory: 200.0, price: 1.0, slot: None };
+        // drifted from the reference, but the drift is only compensated with the tokens bought
+        let bands = InventoryBands { max_price_drift_pct: Some(10.0), ..Default::default() };
+        assert_eq!(tokens_to_sell(&bands, 0.8, &start, 200.0, &pool), 0.0);
+        assert_eq!(tokens_to_sell(&bands, 0.8, &start, 150.0, &pool), 0.0);
+
+        // the band is 50 SOL of tokens at the pool price
+        let bands = InventoryBands { max_inventory_sol: Some(50.0), max_price_drift_pct: Some(10.0) };
+        assert_close(tokens_to_sell(&bands, 0.8, &start, 200.0, &pool), 200.0 - 50.0 / pool.price);
+        assert_close(tokens_to_sell(&bands, 0.8, &start, 30.0, &pool), 0.0);
+    }
+}
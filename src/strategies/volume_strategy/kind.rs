@@ -0,0 +1,131 @@
+use crate::config::app_context::AppContext;
+use crate::schema::users::dsl::users;
+use crate::schema::users::is_active;
+use crate::solana;
+use crate::strategies::volume_strategy::VolumeStrategy;
+use crate::types::actions::SolanaAction;
+use crate::types::bot_user::BotUser;
+use crate::types::engine::{Strategy, StrategyId, StrategyKind};
+use crate::types::events::BotEvent;
+use crate::types::volume_strategy::{NewVolumeStrategyInstance, VolumeStrategyInstance};
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use futures_util::future::join_all;
+use serde_json::json;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+pub const VOLUME_STRATEGY_KIND: &str = "volume";
+
+pub struct VolumeStrategyKind {
+    context: AppContext,
+}
+
+impl VolumeStrategyKind {
+    pub fn new(context: &AppContext) -> Self {
+        Self {
+            context: context.clone(),
+        }
+    }
+}
+
+#[async_trait]
+impl StrategyKind<BotEvent, Arc<Mutex<SolanaAction>>> for VolumeStrategyKind {
+    fn name(&self) -> &'static str {
+        VOLUME_STRATEGY_KIND
+    }
+
+    fn config_schema(&self) -> serde_json::Value {
+        json!({
+            "target_pool": {"type": "pubkey", "description": "Raydium pool to trade"},
+            "tranche_size_sol": {"type": "f64", "description": "SOL traded per tranche, split among the agents"},
+            "tranche_frequency_hbs": {"type": "i64", "description": "Heartbeats between tranches"},
+            "tranche_length_hbs": {"type": "i64", "description": "Heartbeats for all agents to complete a tranche"},
+            "agents_buying_in_tranche": {"type": "i32", "description": "Buying agents per tranche"},
+            "agents_selling_in_tranche": {"type": "i32", "description": "Selling agents per tranche"},
+            "agents_keep_tokens_lamports": {"type": "i64", "description": "Tokens left on the agent wallets after each tranche"},
+            "daily_volume_sol": {"type": "f64", "description": "Volume to trade a day, the tranche size and frequency are derived from it when set", "default": null},
+            "budget_days": {"type": "i32", "description": "Days the volume budget runs for, until stopped when not set", "default": null},
+            "max_inventory_sol": {"type": "f64", "description": "Most the tokens kept by the tranches are worth", "default": null},
+            "max_price_drift_pct": {"type": "f64", "description": "Price drift from the first tranche the sells compensate beyond, in percent", "default": null},
+        })
+    }
+
+    fn is_persistent(&self) -> bool {
+        true
+    }
+
+    async fn persist(
+        &self,
+        row: serde_json::Value,
+    ) -> Result<(StrategyId, Box<dyn Strategy<BotEvent, Arc<Mutex<SolanaAction>>> + Send + Sync>)> {
+        use crate::schema::volumestrategyinstances::dsl::*;
+        let mut instance: VolumeStrategyInstance = serde_json::from_value(row)?;
+        if instance.id == 0 {
+            let mut conn = self.context.db_pool.get().await?;
+            instance.id = diesel::insert_into(volumestrategyinstances)
+                .values(NewVolumeStrategyInstance::from(&instance))
+                .returning(id)
+                .get_result(&mut conn)
+                .await?;
+        }
+        Ok((instance.id, Box::new(VolumeStrategy::new(&self.context, &instance).await?)))
+    }
+
+    async fn complete(&self, strat_id: StrategyId) -> Result<()> {
+        use crate::schema::volumestrategyinstances::dsl::*;
+        let mut conn = self.context.db_pool.get().await?;
+        diesel::update(volumestrategyinstances.filter(id.eq(strat_id)))
+            .set(completed_at.eq(Some(Utc::now().naive_utc())))
+            .execute(&mut conn)
+            .await?;
+        Ok(())
+    }
+
+    async fn load_active(
+        &self,
+    ) -> Result<Vec<(StrategyId, Box<dyn Strategy<BotEvent, Arc<Mutex<SolanaAction>>> + Send + Sync>)>> {
+        use crate::schema::volumestrategyinstances::dsl::*;
+
+        let mut conn = self.context.db_pool.get().await?;
+
+        let active_users: Vec<BotUser> = users
+            .filter(is_active.eq(true))
+            .load::<BotUser>(&mut conn)
+            .await?;
+
+        let active_strategies: Vec<VolumeStrategyInstance> = volumestrategyinstances
+            .filter(completed_at.is_null())
+            .load::<VolumeStrategyInstance>(&mut conn)
+            .await?;
+
+        // warming up the balances cache for the wallets the strategies are going to trade from
+        let update_balances = |user: &BotUser| {
+            let wallet_address = user.wallet_address.clone();
+            let active_strategies = active_strategies.clone();
+            let uid = user.id;
+            async move {
+                let _ = solana::get_balance(&self.context, &wallet_address).await;
+                if let Some(strategy_pool) = active_strategies.iter().find(|s| s.user_id == uid) {
+                    let pool_details = self.context.rpc_pool.get_pool_details(&strategy_pool.target_pool).await?;
+                    let _ = solana::get_token_balance(&self.context, &wallet_address, &pool_details.base_mint).await;
+                }
+                Ok::<(), anyhow::Error>(())
+            }
+        };
+
+        join_all(active_users.iter().map(update_balances)).await;
+
+        let mut strategies: Vec<(StrategyId, Box<dyn Strategy<BotEvent, Arc<Mutex<SolanaAction>>> + Send + Sync>)> = vec![];
+        for strategy_instance in active_strategies {
+            strategies.push((
+                strategy_instance.id,
+                Box::new(VolumeStrategy::new(&self.context, &strategy_instance).await?),
+            ));
+        }
+        Ok(strategies)
+    }
+}
@@ -1,6 +1,9 @@
 mod strategy;
 mod strategy_state_machine;
+mod kind;
+mod inventory;
 pub mod agent;
 
 pub use strategy::VolumeStrategy;
-pub use strategy_state_machine::VolumeStrategyStateMachine;
\ No newline at end of file
+pub use strategy_state_machine::VolumeStrategyStateMachine;
+pub use kind::{VolumeStrategyKind, VOLUME_STRATEGY_KIND};
\ No newline at end of file
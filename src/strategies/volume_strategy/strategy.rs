@@ -1,22 +1,25 @@
 use crate::config::app_context::AppContext;
+use crate::config::constants::TRADING_FEED_COLLECTORS;
 use crate::config::settings::StrategyConfig;
 use crate::schema::volumestrategyinstances::completed_at;
 use crate::schema::volumestrategyinstances::dsl::volumestrategyinstances;
 use crate::schema::*;
 use crate::types::actions::{SolanaAction, SwapMethod};
 use crate::types::engine::{Strategy, StrategyStatus};
-use crate::types::events::{BlockchainEvent, BotEvent};
+use crate::types::routing::{EventFilter, EventType};
+use crate::types::events::{BlockchainEvent, BotEvent, SystemEvent};
 use crate::types::keys::KeypairClonable;
 use crate::types::pool::{RaydiumPool, RaydiumPoolPriceUpdate};
 use crate::types::bot_user::Trader;
 use crate::types::volume_strategy::VolumeStrategyInstance;
 use crate::utils::helpers::{max_time, zero_time};
 use crate::{solana, storage, utils};
-use anyhow::Result;
+use anyhow::{bail, Result};
 use async_trait::async_trait;
 use chrono::{Duration, Utc};
 use diesel::pg::Pg;
 use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
 use diesel::sql_types::*;
 use diesel_derives::{Associations, Identifiable, Insertable, Queryable, Selectable};
 use futures::stream::{self, StreamExt};
@@ -28,7 +31,7 @@ use solana_sdk::pubkey::Pubkey;
 use solana_sdk::signature::{Keypair, Signer};
 use statig::awaitable::{InitializedStateMachine, IntoStateMachineExt, StateMachine};
 use std::any::Any;
-use std::collections::{BTreeMap, VecDeque};
+use std::collections::{BTreeMap, HashSet, VecDeque};
 use std::ops::Deref;
 use std::str::FromStr;
 use std::sync::Arc;
@@ -39,8 +42,13 @@ use maplit::hashmap;
 use tokio::sync::{Mutex, RwLock};
 use tokio::task::JoinHandle;
 use tracing::field::debug;
-use tracing::{debug, error, info, instrument, Event};
+use tracing::{debug, error, info, instrument, warn, Event};
+use crate::strategies::volume_strategy::VOLUME_STRATEGY_KIND;
 use crate::strategies::volume_strategy::VolumeStrategyStateMachine;
+use crate::strategies::volume_strategy::agent;
+use crate::strategies::volume_strategy::strategy_state_machine::State;
+use crate::strategies::events::{AgentEvent, SolanaStrategyEvent, StrategyControlEvent};
+use crate::tg_bot::volume_strategy_config_args::VolumeStrategyConfigArgs;
 
 // strategy basically manages a collection of position state machines,
 // this struct is just a message filter
@@ -48,6 +56,10 @@ use crate::strategies::volume_strategy::VolumeStrategyStateMachine;
 #[derive(Clone)]
 pub struct VolumeStrategy {
     pub state_machine: StateMachine<VolumeStrategyStateMachine>,
+    pub paused: bool,
+    pub stopping: bool,
+    /// Collectors reported down, trading on stale prices is held until they're back.
+    pub feeds_down: HashSet<String>,
 }
 
 impl Debug for VolumeStrategy {
@@ -70,50 +82,192 @@ impl VolumeStrategy {
             .state_machine();
         Ok(Self {
             state_machine,
+            paused: false,
+            stopping: false,
+            feeds_down: HashSet::new(),
         })
     }
 
     pub fn get_user_id(&self) -> i32 {
         self.state_machine.instance.user_id
     }
-}
 
-#[async_trait]
-impl Strategy<BotEvent, Arc<Mutex<SolanaAction>>> for VolumeStrategy {
-    /// Initialize the strategy. This is called once at startup
-    async fn sync_state(&mut self) -> Result<()> {
-        // collect amounts from all traders to their main wallets
-        Ok(())
+    // pause takes effect at the tranche boundary: the running tranche is finished, the next one isn't started
+    fn is_holding_tranche(&self) -> bool {
+        (self.paused || !self.feeds_down.is_empty()) && matches!(self.state_machine.state(), State::Sleeping {} | State::Idle {})
     }
 
-    // Process incoming signals2
-    // #[instrument(skip(self))]
-    async fn process_event(&mut self, event: BotEvent) -> Vec<Arc<Mutex<SolanaAction>>> {
+    async fn dispatch_event(&mut self, event: BotEvent) {
         match &event {
             BotEvent::ExecutionResult(..) => {
                 self.state_machine.handle(&event.clone().into()).await;
             }
+            // heartbeats drive the tranche timer, holding them keeps the strategy asleep
+            BotEvent::HeartBeat(..) if self.is_holding_tranche() => {}
             BotEvent::HeartBeat(..) => {
                 self.state_machine.handle(&event.clone().into()).await;
             }
             BotEvent::BlockchainEvent(_) => {
                 self.state_machine.handle(&event.clone().into()).await;
             }
+            // the other feeds, telegram or the deposits, don't affect the trading
+            BotEvent::SystemEvent(SystemEvent::FeedDown(feed)) if TRADING_FEED_COLLECTORS.contains(&feed.as_str()) => {
+                warn!("Strategy {} holds tranches, feed {} is down", self.state_machine.instance.id, feed);
+                self.feeds_down.insert(feed.clone());
+            }
+            BotEvent::SystemEvent(SystemEvent::FeedUp(feed)) => {
+                self.feeds_down.remove(feed);
+            }
             _ => {}
         }
+    }
+
+    async fn are_agents_deactivated(&self) -> bool {
+        for agent in self.state_machine.agents.iter() {
+            match agent.lock().await.state() {
+                agent::State::Deactivated {} | agent::State::Error { .. } => {}
+                _ => return false,
+            }
+        }
+        true
+    }
+}
+
+#[async_trait]
+impl Strategy<BotEvent, Arc<Mutex<SolanaAction>>> for VolumeStrategy {
+    /// Initialize the strategy. This is called once at startup
+    async fn sync_state(&mut self) -> Result<()> {
+        // collect amounts from all traders to their main wallets
+        Ok(())
+    }
+
+    // Process incoming signals2
+    // #[instrument(skip(self))]
+    async fn process_event(&mut self, event: BotEvent) -> Vec<Arc<Mutex<SolanaAction>>> {
+        if self.stopping {
+            // the state machine is frozen, only the agents being deactivated need the events
+            let event: SolanaStrategyEvent = event.into();
+            for agent in self.state_machine.agents.iter() {
+                agent.lock().await.handle(&event).await;
+            }
+        } else {
+            self.dispatch_event(event).await;
+        }
         // Lock the mutex to get mutable access
         let mut strat_actions_generated_from_event = self.state_machine.strat_actions_generated_from_event.lock().await;
         let actions = std::mem::replace(&mut *strat_actions_generated_from_event, Vec::new());
         actions
     }
 
+    // the tranches run on heartbeats and the pool price cache, the agents on their receipts and results.
+    // Those don't carry a pool or a wallet, the account updates and swaps of the pool aren't needed
+    async fn subscriptions(&self) -> Vec<EventFilter> {
+        vec![
+            EventFilter::of_types(&[
+                EventType::HeartBeat,
+                EventType::ExecutionReceipt,
+                EventType::ExecutionResult,
+                EventType::System,
+            ]),
+            EventFilter::of_types(&[EventType::Rollback]).with_pools([self.state_machine.pool.id]),
+        ]
+    }
+
+    fn kind(&self) -> Option<&'static str> {
+        Some(VOLUME_STRATEGY_KIND)
+    }
+
+    fn owner_user_id(&self) -> Option<i32> {
+        Some(self.get_user_id())
+    }
+
+    fn instance(&self) -> serde_json::Value {
+        serde_json::to_value(&self.state_machine.instance).unwrap_or_default()
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
 
     async fn get_status(&self) -> StrategyStatus {
-        StrategyStatus::Running(hashmap! {
+        if self.stopping {
+            return if self.are_agents_deactivated().await {
+                StrategyStatus::Stopped
+            } else {
+                StrategyStatus::Stopping
+            };
+        }
+        let mut state = hashmap! {
             "Running".to_owned() => format!("{:?}", self.state_machine.state()),
-        })
+        };
+        if let Some(progress) = self.state_machine.instance.budget_progress(Utc::now().naive_utc()) {
+            state.insert("Volume budget".to_owned(), progress);
+        }
+        if !self.feeds_down.is_empty() {
+            state.insert("Feeds down".to_owned(), self.feeds_down.iter().cloned().collect::<Vec<_>>().join(", "));
+        }
+        if self.paused {
+            StrategyStatus::Paused(state)
+        } else {
+            StrategyStatus::Running(state)
+        }
+    }
+
+    async fn pause(&mut self) -> Result<()> {
+        self.paused = true;
+        info!("Strategy {} paused", self.state_machine.instance.id);
+        Ok(())
+    }
+
+    async fn resume(&mut self) -> Result<()> {
+        self.paused = false;
+        info!("Strategy {} resumed", self.state_machine.instance.id);
+        Ok(())
+    }
+
+    async fn on_stop(&mut self) -> Vec<Arc<Mutex<SolanaAction>>> {
+        self.stopping = true;
+        // the agents' sells and transfers don't need the prices
+        solana::stop_monitoring_pool(&self.state_machine.context, &self.state_machine.pool.id, self.state_machine.instance.id).await;
+        // agents collect everything back to the main wallet on deactivation
+        for agent in self.state_machine.agents.iter() {
+            agent.lock().await.handle(&SolanaStrategyEvent::ForAgent(AgentEvent::Deactivate)).await;
+        }
+        let mut strat_actions_generated_from_event = self.state_machine.strat_actions_generated_from_event.lock().await;
+        std::mem::replace(&mut *strat_actions_generated_from_event, Vec::new())
+    }
+
+    async fn update_config(&mut self, config: serde_json::Value) -> Result<()> {
+        let config: VolumeStrategyConfigArgs = serde_json::from_value(config)?;
+        if config.target_pool.is_some() || config.user_id.is_some() {
+            bail!("Target pool and owner can't be changed on a running strategy");
+        }
+        // the config is applied by the running superstate only, idle and failed strategies would drop it
+        if matches!(self.state_machine.state(), State::Idle {} | State::Error { .. }) {
+            bail!("Strategy {} isn't running, its config can't be changed", self.state_machine.instance.id);
+        }
+        self.state_machine
+            .handle(&SolanaStrategyEvent::ForStrategy(StrategyControlEvent::UpdateVolumeConfig(config)))
+            .await;
+
+        use crate::schema::volumestrategyinstances::dsl::*;
+        let instance = &self.state_machine.instance;
+        let mut conn = self.state_machine.context.db_pool.get().await?;
+        diesel::update(volumestrategyinstances.filter(id.eq(instance.id)))
+            .set((
+                tranche_size_sol.eq(instance.tranche_size_sol),
+                tranche_frequency_hbs.eq(instance.tranche_frequency_hbs),
+                tranche_length_hbs.eq(instance.tranche_length_hbs),
+                agents_buying_in_tranche.eq(instance.agents_buying_in_tranche),
+                agents_selling_in_tranche.eq(instance.agents_selling_in_tranche),
+                agents_keep_tokens_lamports.eq(instance.agents_keep_tokens_lamports),
+                daily_volume_sol.eq(instance.daily_volume_sol),
+                budget_days.eq(instance.budget_days),
+                max_inventory_sol.eq(instance.max_inventory_sol),
+                max_price_drift_pct.eq(instance.max_price_drift_pct),
+            ))
+            .execute(&mut conn)
+            .await?;
+        Ok(())
     }
 }
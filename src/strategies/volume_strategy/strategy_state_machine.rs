@@ -7,14 +7,14 @@ use crate::schema::users::dsl::users;
 use crate::schema::users::{chat_id, id};
 use crate::types::actions::{Amount, Asset, SolanaAction, SolanaActionPayload, SolanaTransferActionPayload};
 use crate::types::events::BotEvent::HeartBeat;
-use crate::types::events::{BotEvent, TickSizeMs};
+use crate::types::events::{BlockchainEvent, BotEvent, TickSizeMs};
 use crate::types::keys::KeypairClonable;
-use crate::types::pool::RaydiumPool;
+use crate::types::pool::{RaydiumPool, RaydiumPoolPriceUpdate};
 use crate::types::bot_user::{BotUser, Trader};
-use crate::types::volume_strategy::VolumeStrategyInstance;
-use crate::{solana, utils};
+use crate::types::volume_strategy::{AgentSeed, VolumeStrategyInstance};
+use crate::{analytics, solana, storage, utils};
 use anyhow::Result;
-use chrono::{DateTime, Utc};
+use chrono::Utc;
 use diesel::prelude::*;
 use diesel_async::RunQueryDsl;
 use futures::executor;
@@ -30,7 +30,9 @@ use tokio::sync::{Mutex as TokioMutex, Mutex};
 use tokio::time::sleep;
 use tracing::{info, trace, warn};
 use crate::strategies::volume_strategy::agent::{self, AgentState};
-use crate::strategies::events::{AgentEvent, SolanaStrategyEvent};
+use crate::strategies::volume_strategy::inventory::{self, InventoryBands, TrancheStart};
+use crate::strategies::events::{AgentEvent, SolanaStrategyEvent, StrategyControlEvent};
+use crate::tg_bot::volume_strategy_config_args::VolumeStrategyConfigArgs;
 use crate::utils::Stopwatch;
 use crate::utils::math;
 
@@ -99,6 +101,8 @@ pub struct VolumeStrategyStateMachine {
     pub strat_actions_generated_from_event: Arc<Mutex<Vec<Arc<Mutex<SolanaAction>>>>>,
     pub stopwatch: Stopwatch,
     pub agents: Vec<Arc<Mutex<StateMachine<AgentState>>>>,
+    pub agent_seed: AgentSeed,
+    pub tranche_start: Option<TrancheStart>,
 }
 
 impl Debug for VolumeStrategyStateMachine {
@@ -127,6 +131,10 @@ impl VolumeStrategyStateMachine {
                 .get_pool_details(&instance.target_pool)
                 .await?,
         );
+        // the pools traded at startup are already cached, the ones started later need to be added
+        if let Err(e) = solana::start_monitoring_pool(context, &pool, instance.id).await {
+            warn!("Error monitoring pool {}: {:?}", pool.id, e);
+        }
         let stopwatch = Stopwatch::new(instance.tranche_frequency_hbs as u64);
 
         let mut conn = context.db_pool.get().await?;
@@ -143,6 +151,7 @@ impl VolumeStrategyStateMachine {
                 pool.clone(),
                 KeypairClonable::new_from_privkey(&user.wallet_private_key).unwrap(),
                 None,
+                None,
                 strat_actions_generated_from_event.clone(),
                 None,
             )
@@ -150,6 +159,7 @@ impl VolumeStrategyStateMachine {
                 .state_machine(),
         ));
         let main_wallet_clone = main_wallet.clone();
+        let agent_seed = storage::agent_wallets::get_or_create_agent_seed(&context.db_pool, instance.id).await?;
         let mut strategy = VolumeStrategyStateMachine {
             context: context.clone(),
             instance: instance.clone(),
@@ -158,6 +168,8 @@ impl VolumeStrategyStateMachine {
             strat_actions_generated_from_event,
             pool,
             stopwatch,
+            agent_seed,
+            tranche_start: None,
         };
         info!("Strategy {} created with instance {:?}", instance.id, instance);
         Ok(strategy)
@@ -298,7 +310,14 @@ impl VolumeStrategyStateMachine {
     #[state(superstate = "running", entry_action = "offload_inventory")]
     async fn offloading_inventory(&mut self, event: &SolanaStrategyEvent) -> Response<State> {
         match self.main_wallet.lock().await.state() {
-            agent::State::Success { .. } | agent::State::Error { .. } | agent::State::Deactivated {} | agent::State::Idle {} => Transition(State::fund()),
+            agent::State::Success { .. } | agent::State::Error { .. } | agent::State::Deactivated {} | agent::State::Idle {} => {
+                if self.instance.is_budget_complete(Utc::now().naive_utc()) {
+                    self.finish_budget().await;
+                    Transition(State::idle())
+                } else {
+                    Transition(State::fund())
+                }
+            }
             _ => Super
         }
     }
@@ -312,6 +331,8 @@ impl VolumeStrategyStateMachine {
             "ENTER initialize_agents_with_sol, main wallet state: {:?}",
             self.main_wallet.lock().await.state()
         );
+        self.plan_budget_tranche().await;
+        self.record_tranche_start().await;
         let agents_num = self.instance.agents_buying_in_tranche;
         // next step: buying tokens for SOL by these agents
         let instance = &self.instance;
@@ -338,10 +359,14 @@ impl VolumeStrategyStateMachine {
             .iter()
             .enumerate()
         {
+            let Ok((agent_key, derivation_index)) = self.next_agent_key().await else {
+                continue;
+            };
             if let Ok(agent) = AgentState::new(
                 &self.context,
                 self.pool.clone(),
-                KeypairClonable::new(),
+                agent_key,
+                Some(derivation_index),
                 Some(self.instance.id),
                 self.strat_actions_generated_from_event.clone(),
                 Some(self.main_wallet.lock().await.agent_key.clone()),
@@ -488,7 +513,15 @@ impl VolumeStrategyStateMachine {
     )]
     async fn collecting_tokens(&mut self, event: &SolanaStrategyEvent) -> Response<State> {
         match self.get_execution_status().await {
-            ExecutionStatus::Done => Transition(State::transferring_token_to_sellers()),
+            ExecutionStatus::Done => {
+                let token_balance = self.main_wallet.lock().await.get_token_balance().await;
+                if self.tokens_to_sell(token_balance).await == 0 {
+                    info!("Strategy {}: nothing to sell after the tranche, skipping the sell", self.instance.id);
+                    Transition(State::sleeping())
+                } else {
+                    Transition(State::transferring_token_to_sellers())
+                }
+            }
             ExecutionStatus::Error => Transition(State::sweeping()),
             // ExecutionStatus::Error => Transition(State::error("Error collecting tokens".to_string())),
             _ => Super,
@@ -498,6 +531,7 @@ impl VolumeStrategyStateMachine {
     #[action]
     async fn transfer_token_to_sellers(&mut self) {
         let token_balance = self.main_wallet.lock().await.get_token_balance().await;
+        let token_balance = self.tokens_to_sell(token_balance).await;
         debug!(
             "ENTER TRANSFER TOKEN Main wallet state: {:?}",
             self.main_wallet.lock().await.state()
@@ -652,6 +686,10 @@ impl VolumeStrategyStateMachine {
 
     #[action]
     async fn set_sleep_timer_before_selling(&mut self) {
+        if self.instance.is_budgeted() {
+            self.record_tranche_volume().await;
+            self.plan_budget_tranche().await;
+        }
         debug!(
             "Setting sleep timer before selling for {} ticks",
             self.instance.tranche_frequency_hbs
@@ -676,6 +714,10 @@ impl VolumeStrategyStateMachine {
                 //timeout
                 if sw.is_time_elapsed(*tick_ms) {
                     self.stopwatch.turn_off();
+                    if self.instance.is_budget_complete(Utc::now().naive_utc()) {
+                        self.finish_budget().await;
+                        return Transition(State::idle());
+                    }
                     Transition(State::fund())
                 } else {
                     Super
@@ -695,6 +737,8 @@ impl VolumeStrategyStateMachine {
         let error_text = format!("Strategy stopped, error: {:?}", msg);
         self.drop();
         self.instance.completed_at = Some(Utc::now().naive_utc());
+        // a completed strategy isn't stopped by the manager, it releases the pool itself
+        solana::stop_monitoring_pool(&self.context, &self.pool.id, self.instance.id).await;
         error!("{:?}", error_text);
         let mut conn = self
             .context
@@ -709,10 +753,9 @@ impl VolumeStrategyStateMachine {
             .await
             .map_err(anyhow::Error::new)
             .unwrap();
-        self.context.tg_bot
-            .as_ref().unwrap()
-            .send_message(ChatId(user.chat_id), error_text)
-            .await;
+        if let Some(user_chat_id) = user.get_chat_id() && let Some(bot) = self.context.tg_bot.as_ref() {
+            bot.send_message(user_chat_id, error_text).await;
+        }
     }
 
     #[state(entry_action = "cleanup")]
@@ -722,7 +765,19 @@ impl VolumeStrategyStateMachine {
 
     #[superstate]
     async fn running(&mut self, event: &SolanaStrategyEvent) -> Response<State> {
-        Handled
+        match event {
+            SolanaStrategyEvent::ForStrategy(StrategyControlEvent::UpdateVolumeConfig(config)) => {
+                self.apply_config(config);
+                Handled
+            }
+            SolanaStrategyEvent::Original(BotEvent::BlockchainEvent(BlockchainEvent::PoolEventsRolledBack(pool, dropped_slots)))
+                if *pool == self.pool.id =>
+            {
+                self.invalidate_tranche_start(dropped_slots);
+                Handled
+            }
+            _ => Handled,
+        }
     }
 
     fn on_transition(&mut self, source: &State, target: &State) {
@@ -773,15 +828,23 @@ impl VolumeStrategyStateMachine {
         }
     }
 
+    /// A fresh agent wallet derived from the strategy's seed, with its derivation index.
+    async fn next_agent_key(&self) -> Result<(KeypairClonable, i32)> {
+        let index = storage::agent_wallets::next_agent_index(&self.context.db_pool, self.instance.id).await?;
+        Ok((self.agent_seed.derive(index)?, index))
+    }
+
     pub async fn create_agents(&mut self, number_of_agents: i32) {
         let agent_futures = futures::stream::iter(0..number_of_agents)
             .map(|_| {
                 let parent_strategy = self.clone();
                 async move {
+                    let (agent_key, derivation_index) = parent_strategy.next_agent_key().await.ok()?;
                     AgentState::new(
                         &parent_strategy.context,
                         parent_strategy.pool.clone(),
-                        KeypairClonable::new(),
+                        agent_key,
+                        Some(derivation_index),
                         Some(parent_strategy.instance.id),
                         parent_strategy.strat_actions_generated_from_event.clone(),
                         Some(parent_strategy.main_wallet.lock().await.agent_key.clone()),
@@ -798,6 +861,168 @@ impl VolumeStrategyStateMachine {
         self.agents = agents;
     }
 
+    fn inventory_bands(&self) -> InventoryBands {
+        InventoryBands {
+            max_inventory_sol: self.instance.max_inventory_sol,
+            max_price_drift_pct: self.instance.max_price_drift_pct,
+        }
+    }
+
+    async fn live_price(&self) -> Option<RaydiumPoolPriceUpdate> {
+        self.context.cache.target_pools_prices.lock().await.get(&self.pool.id).cloned()
+    }
+
+    /// Inventory bands: the inventory and the price the tranche starts from, the first tranche sets the reference price.
+    async fn record_tranche_start(&mut self) {
+        self.tranche_start = None;
+        if !self.inventory_bands().is_set() {
+            return;
+        }
+        let Some(pool_price) = self.live_price().await else {
+            warn!("Strategy {}: no price of pool {}, the tranche ignores the inventory bands", self.instance.id, self.pool.id);
+            return;
+        };
+        if self.instance.reference_price.is_none() {
+            self.instance.reference_price = Some(pool_price.price);
+            if let Err(e) = storage::volume_strategies::store_reference_price(&self.context.db_pool, &self.instance).await {
+                warn!("Can't store the reference price of strategy {}: {:?}", self.instance.id, e);
+            }
+        }
+        let token_balance = self.main_wallet.lock().await.get_token_balance().await;
+        self.tranche_start = Some(TrancheStart {
+            inventory: utils::decimals::tokens_to_ui_amount_with_decimals_f64(token_balance, self.pool.base_decimals),
+            price: pool_price.price,
+            slot: pool_price.slot.map(|slot| slot as u64),
+        });
+    }
+
+    // a tranche started on a price from a dropped fork ignores the inventory bands, as without a price
+    fn invalidate_tranche_start(&mut self, dropped_slots: &[u64]) {
+        if self
+            .tranche_start
+            .is_some_and(|start| start.slot.is_some_and(|slot| dropped_slots.contains(&slot)))
+        {
+            warn!("Strategy {}: the tranche start price was rolled back, the tranche ignores the inventory bands", self.instance.id);
+            self.tranche_start = None;
+        }
+    }
+
+    /// The tokens of the main wallet handed to the sellers, all of them without inventory bands, only the excess
+    /// above the band when the tranche bought nothing.
+    async fn tokens_to_sell(&self, token_balance: u64) -> u64 {
+        let (Some(start), Some(reference_price), Some(pool_price)) =
+            (self.tranche_start, self.instance.reference_price, self.live_price().await)
+        else {
+            return token_balance;
+        };
+        let decimals = self.pool.base_decimals;
+        let balance = utils::decimals::tokens_to_ui_amount_with_decimals_f64(token_balance, decimals);
+        let sell = inventory::tokens_to_sell(&self.inventory_bands(), reference_price, &start, balance, &pool_price);
+        info!(
+            "Strategy {}: selling {:.3} of {:.3} tokens, the tranche started {:.2}% from the reference price",
+            self.instance.id,
+            sell,
+            balance,
+            100.0 * (start.price / reference_price - 1.0),
+        );
+        utils::decimals::ui_amount_with_decimals_to_tokens(sell, decimals).min(token_balance)
+    }
+
+    /// Budget mode: sizes and spaces the next tranche after the volume traded so far.
+    async fn plan_budget_tranche(&mut self) {
+        let heartbeat_ms = self.context.get_settings().await.collector.heartbeat_frequency_ms;
+        let Some(plan) = self.instance.plan_tranche(Utc::now().naive_utc(), heartbeat_ms) else {
+            return;
+        };
+        debug!("Strategy {} next tranche: {:?}", self.instance.id, plan);
+        self.instance.tranche_size_sol = plan.tranche_size_sol;
+        self.instance.tranche_frequency_hbs = plan.tranche_frequency_hbs;
+        if let Err(e) = storage::volume_strategies::store_tranche_plan(&self.context.db_pool, &self.instance).await {
+            warn!("Can't store the tranche plan of strategy {}: {:?}", self.instance.id, e);
+        }
+    }
+
+    // the swaps recorded for the agents since the last count, a failed query is counted with the next tranche
+    async fn record_tranche_volume(&mut self) {
+        let now = Utc::now();
+        let from = self.instance.volume_counted_until.unwrap_or(self.instance.started_at).and_utc();
+        match analytics::queries::get_volume_traded(&self.context.db_pool, self.instance.id, from, now).await {
+            Ok(volume_sol) => {
+                self.instance.volume_traded_sol += volume_sol;
+                self.instance.volume_counted_until = Some(now.naive_utc());
+            }
+            Err(e) => {
+                warn!("Can't count the volume traded by strategy {}: {:?}", self.instance.id, e);
+                return;
+            }
+        }
+        if let Some(progress) = self.instance.budget_progress(Utc::now().naive_utc()) {
+            info!("Strategy {} volume budget: {}", self.instance.id, progress);
+        }
+        if let Err(e) = storage::volume_strategies::store_volume_traded(&self.context.db_pool, &self.instance).await {
+            warn!("Can't store the volume traded by strategy {}: {:?}", self.instance.id, e);
+        }
+    }
+
+    async fn finish_budget(&mut self) {
+        let text = format!(
+            "Strategy {} traded its volume budget, {:.3} SOL",
+            self.instance.id, self.instance.volume_traded_sol
+        );
+        info!("{}", text);
+        let Ok(mut conn) = self.context.db_pool.get().await else {
+            return;
+        };
+        if let Ok(user) = users.filter(id.eq(&self.instance.user_id)).first::<BotUser>(&mut conn).await
+            && let Some(user_chat_id) = user.get_chat_id()
+            && let Some(bot) = self.context.tg_bot.as_ref()
+        {
+            let _ = bot.send_message(user_chat_id, text).await;
+        }
+    }
+
+    /// Tranche parameters take effect from the next tranche, the pool and the owner can't be changed.
+    fn apply_config(&mut self, config: &VolumeStrategyConfigArgs) {
+        if let Some(tranche_size_sol) = config.tranche_size_sol {
+            self.instance.tranche_size_sol = tranche_size_sol;
+        }
+        if let Some(tranche_frequency_hbs) = config.tranche_frequency_hbs {
+            self.instance.tranche_frequency_hbs = tranche_frequency_hbs;
+        }
+        if let Some(tranche_length_hbs) = config.tranche_length_hbs {
+            self.instance.tranche_length_hbs = tranche_length_hbs;
+        }
+        if let Some(agents_buying_in_tranche) = config.agents_buying_in_tranche {
+            self.instance.agents_buying_in_tranche = agents_buying_in_tranche;
+        }
+        if let Some(agents_selling_in_tranche) = config.agents_selling_in_tranche {
+            self.instance.agents_selling_in_tranche = agents_selling_in_tranche;
+        }
+        if let Some(agents_keep_tokens_lamports) = config.agents_keep_tokens_lamports {
+            self.instance.agents_keep_tokens_lamports = agents_keep_tokens_lamports;
+        }
+        if config.clear_unset {
+            self.instance.daily_volume_sol = config.daily_volume_sol;
+            self.instance.budget_days = config.budget_days;
+            self.instance.max_inventory_sol = config.max_inventory_sol;
+            self.instance.max_price_drift_pct = config.max_price_drift_pct;
+        } else {
+            if let Some(daily_volume_sol) = config.daily_volume_sol {
+                self.instance.daily_volume_sol = Some(daily_volume_sol);
+            }
+            if let Some(budget_days) = config.budget_days {
+                self.instance.budget_days = Some(budget_days);
+            }
+            if let Some(max_inventory_sol) = config.max_inventory_sol {
+                self.instance.max_inventory_sol = Some(max_inventory_sol);
+            }
+            if let Some(max_price_drift_pct) = config.max_price_drift_pct {
+                self.instance.max_price_drift_pct = Some(max_price_drift_pct);
+            }
+        }
+        info!("Strategy {} config updated: {:?}", self.instance.id, self.instance);
+    }
+
     // todo sweep everything
     pub async fn drop(&mut self) {
         let deactivate_futures = self.agents.iter_mut().map(|agent| async {
@@ -0,0 +1,250 @@
+use crate::config::constants::{COLLECTOR_RESTART_BACKOFF_MAX_MS, COLLECTOR_RESTART_BACKOFF_MIN_MS};
+use crate::types::engine::Collector;
+use crate::types::events::SystemEvent;
+use chrono::{NaiveDateTime, Utc};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tokio_stream::StreamExt;
+use tracing::{error, info, warn};
+
+/// Exponential backoff between restarts, reset once the restarted component works again.
+#[derive(Debug, Clone)]
+pub struct Backoff {
+    min: Duration,
+    max: Duration,
+    current: Duration,
+    attempt: u32,
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self::new(
+            Duration::from_millis(COLLECTOR_RESTART_BACKOFF_MIN_MS),
+            Duration::from_millis(COLLECTOR_RESTART_BACKOFF_MAX_MS),
+        )
+    }
+}
+
+impl Backoff {
+    pub fn new(min: Duration, max: Duration) -> Self {
+        Self {
+            min,
+            max,
+            current: min,
+            attempt: 0,
+        }
+    }
+
+    /// The delay before the next attempt, doubling up to the max.
+    pub fn next_delay(&mut self) -> Duration {
+        let delay = self.current;
+        self.current = (self.current * 2).min(self.max);
+        self.attempt += 1;
+        delay
+    }
+
+    pub fn attempt(&self) -> u32 {
+        self.attempt
+    }
+
+    pub fn reset(&mut self) {
+        self.current = self.min;
+        self.attempt = 0;
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub enum CollectorHealth {
+    Starting,
+    Healthy,
+    Restarting { attempt: u32, error: String },
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CollectorHealthState {
+    pub health: CollectorHealth,
+    pub restarts: u64,
+    pub last_event_at: Option<NaiveDateTime>,
+    pub since: NaiveDateTime,
+}
+
+/// Health of the running collectors, for the status screens.
+#[derive(Debug, Clone, Default)]
+pub struct CollectorHealthRegistry(Arc<RwLock<BTreeMap<String, CollectorHealthState>>>);
+
+impl CollectorHealthRegistry {
+    pub fn set_health(&self, name: &str, health: CollectorHealth) {
+        let now = Utc::now().naive_utc();
+        let mut states = self.0.write().unwrap();
+        let state = states.entry(name.to_string()).or_insert_with(|| CollectorHealthState {
+            health: CollectorHealth::Starting,
+            restarts: 0,
+            last_event_at: None,
+            since: now,
+        });
+        if let CollectorHealth::Restarting { .. } = health {
+            state.restarts += 1;
+        }
+        if state.health != health {
+            state.since = now;
+        }
+        state.health = health;
+    }
+
+    pub fn mark_event(&self, name: &str) {
+        if let Some(state) = self.0.write().unwrap().get_mut(name) {
+            state.last_event_at = Some(Utc::now().naive_utc());
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<CollectorHealthState> {
+        self.0.read().unwrap().get(name).cloned()
+    }
+
+    pub fn snapshot(&self) -> BTreeMap<String, CollectorHealthState> {
+        self.0.read().unwrap().clone()
+    }
+
+    pub fn is_healthy(&self) -> bool {
+        self.0
+            .read()
+            .unwrap()
+            .values()
+            .all(|state| state.health == CollectorHealth::Healthy)
+    }
+}
+
+/// Runs a collector forever, restarting it with backoff when its stream fails or ends.
+/// `FeedDown` is published once per outage and `FeedUp` with the first event after it.
+pub async fn supervise_collector<E, F>(collector: Box<dyn Collector<E>>, health: CollectorHealthRegistry, publish: F)
+where
+    E: From<SystemEvent>,
+    F: Fn(E),
+{
+    let name = collector.name();
+    let mut backoff = Backoff::default();
+    let mut feed_down = false;
+    let mut healthy = false;
+    health.set_health(name, CollectorHealth::Starting);
+    loop {
+        info!("starting collector {}... ", name);
+        let error = match collector.get_event_stream().await {
+            Ok(mut event_stream) => {
+                while let Some(event) = event_stream.next().await {
+                    if !healthy {
+                        healthy = true;
+                        health.set_health(name, CollectorHealth::Healthy);
+                        backoff.reset();
+                        if feed_down {
+                            info!("Collector {} is up again", name);
+                            publish(SystemEvent::FeedUp(name.to_string()).into());
+                            feed_down = false;
+                        }
+                    }
+                    health.mark_event(name);
+                    publish(event);
+                }
+                "event stream ended".to_string()
+            }
+            Err(e) => format!("{:?}", e),
+        };
+
+        healthy = false;
+        let delay = backoff.next_delay();
+        warn!("Collector {} is down ({}), restarting in {:?}", name, error, delay);
+        health.set_health(name, CollectorHealth::Restarting {
+            attempt: backoff.attempt(),
+            error,
+        });
+        if !feed_down {
+            publish(SystemEvent::FeedDown(name.to_string()).into());
+            feed_down = true;
+        }
+        tokio::time::sleep(delay).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::engine::EventStream;
+    use anyhow::{anyhow, Result};
+    use async_trait::async_trait;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Mutex;
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum TestEvent {
+        Item(u32),
+        Down,
+        Up,
+    }
+
+    impl From<SystemEvent> for TestEvent {
+        fn from(event: SystemEvent) -> Self {
+            match event {
+                SystemEvent::FeedUp(_) => TestEvent::Up,
+                _ => TestEvent::Down,
+            }
+        }
+    }
+
+    // ends after an event, fails to reconnect once, then works
+    #[derive(Default)]
+    struct FlakyCollector {
+        calls: AtomicU32,
+    }
+
+    #[async_trait]
+    impl Collector<TestEvent> for FlakyCollector {
+        async fn get_event_stream(&self) -> Result<EventStream<'_, TestEvent>> {
+            match self.calls.fetch_add(1, Ordering::Relaxed) {
+                0 => Ok(Box::pin(tokio_stream::iter(vec![TestEvent::Item(1)]))),
+                1 => Err(anyhow!("connection refused")),
+                _ => Ok(Box::pin(tokio_stream::iter(vec![TestEvent::Item(2)]).chain(tokio_stream::pending()))),
+            }
+        }
+
+        fn name(&self) -> &'static str {
+            "flaky"
+        }
+    }
+
+    #[test]
+    fn test_backoff_doubles_up_to_the_max() {
+        let mut backoff = Backoff::new(Duration::from_millis(100), Duration::from_millis(300));
+        let delays: Vec<_> = (0..4).map(|_| backoff.next_delay().as_millis()).collect();
+        assert_eq!(delays, vec![100, 200, 300, 300]);
+        assert_eq!(backoff.attempt(), 4);
+        backoff.reset();
+        assert_eq!((backoff.next_delay().as_millis(), backoff.attempt()), (100, 1));
+    }
+
+    #[tokio::test]
+    async fn test_feed_down_once_per_outage() {
+        let events = Arc::new(Mutex::new(vec![]));
+        let health = CollectorHealthRegistry::default();
+        let published = events.clone();
+        let supervisor = tokio::spawn(supervise_collector(
+            Box::new(FlakyCollector::default()),
+            health.clone(),
+            move |event| published.lock().unwrap().push(event),
+        ));
+        // two restarts with the default backoff
+        for _ in 0..100 {
+            if events.lock().unwrap().len() >= 4 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        supervisor.abort();
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![TestEvent::Item(1), TestEvent::Down, TestEvent::Up, TestEvent::Item(2)]
+        );
+        let state = health.get("flaky").unwrap();
+        assert_eq!((state.health, state.restarts), (CollectorHealth::Healthy, 2));
+    }
+}
@@ -1,4 +1,10 @@
+use crate::analytics::StrategyStats;
+use crate::config::settings::ProviderName;
+use crate::solana::geyser_stats::ProviderFeedStats;
+use crate::supervisor::{CollectorHealth, CollectorHealthState};
+use crate::utils::bounded_queue::QueueStatsSnapshot;
 use solana_sdk::signature::Signature;
+use std::collections::BTreeMap;
 
 pub fn create_solscan_link(tx_hash: &Signature) -> String {
     let withdrawal_link = format!("https://solscan.io/tx/{}", tx_hash.to_string());
@@ -8,3 +14,58 @@ pub fn create_solscan_link(tx_hash: &Signature) -> String {
 pub fn format_curr(amount: f64) -> String {
     format!("{:.3} {}", amount, "SOL")
 }
+
+pub fn format_feed_stats(provider: &ProviderName, stats: &ProviderFeedStats, total_first_seen: u64) -> String {
+    format!(
+        "{}{}: first {:.0}%, lag {} slots, {:.0} msg/s",
+        provider,
+        if stats.demoted { " (demoted)" } else { "" },
+        stats.first_seen_ratio(total_first_seen) * 100.0,
+        stats.slot_lag,
+        stats.messages_per_sec,
+    )
+}
+
+pub fn format_operational_status(
+    feeds: &BTreeMap<ProviderName, ProviderFeedStats>,
+    total_first_seen: u64,
+    collectors: &BTreeMap<String, CollectorHealthState>,
+    queues: &BTreeMap<String, QueueStatsSnapshot>,
+) -> String {
+    let mut lines = vec!["Geyser feeds".to_string()];
+    lines.extend(feeds.iter().map(|(provider, stats)| format_feed_stats(provider, stats, total_first_seen)));
+    lines.push("\nCollectors".to_string());
+    lines.extend(collectors.iter().map(|(name, state)| match &state.health {
+        CollectorHealth::Restarting { attempt, error } => {
+            format!("{}: restarting (attempt {}), {}", name, attempt, error)
+        }
+        health => format!("{}: {:?}, {} restarts", name, health, state.restarts),
+    }));
+    lines.push("\nQueues".to_string());
+    lines.extend(queues.iter().map(|(name, stats)| {
+        let line = format!("{}: {} queued (max {}), {} dropped", name, stats.len, stats.max_len, stats.dropped);
+        match stats.high_water_alerts {
+            0 => line,
+            alerts => format!("{}, ⚠️ {} times over the high-water mark", line, alerts),
+        }
+    }));
+    lines.join("\n")
+}
+
+pub fn format_strategy_stats(strategy_id: i32, stats: &StrategyStats) -> String {
+    let percent = |value: Option<f64>| value.map_or("n/a".to_string(), |value| format!("{:.2}%", value));
+    let sol = |value: Option<f64>| value.map_or("n/a".to_string(), format_curr);
+    [
+        format!("Strategy {}", strategy_id),
+        format!("Trades: {}, average {}", stats.trade_count, format_curr(stats.average_trade_size)),
+        format!("Volume: {}", format_curr(stats.volume_generated)),
+        format!("PnL: {}", sol(stats.pnl)),
+        format!("Cost of trades: {}", format_curr(stats.cost_of_trades)),
+        format!("Win rate: {}", percent(stats.win_rate)),
+        format!("Profit factor: {}", stats.profit_factor.map_or("n/a".to_string(), |factor| format!("{:.2}", factor))),
+        format!("Spread: {}, market impact: {}", percent(stats.spread), percent(stats.market_impact)),
+        format!("Execution efficiency: {}", percent(stats.execution_efficiency)),
+        format!("Pool liquidity: {}", sol(stats.liquidity)),
+    ]
+    .join("\n")
+}
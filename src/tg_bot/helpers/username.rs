@@ -61,7 +61,7 @@ pub async fn get_user_from_button_press(
                 .clone()
             {
                 Some(whitelisted) => {
-                    if whitelisted.contains(&user.chat_id) {
+                    if user.chat_id.is_some_and(|user_chat_id| whitelisted.contains(&user_chat_id)) {
                         user.clone()
                     } else {
                         bail!("The system is currently only available to whitelisted users.");
@@ -2,10 +2,12 @@ use crate::tg_bot::bot_config::BotConfig;
 use crate::tg_bot::notifications::invalid_state;
 use crate::tg_bot::state::State;
 use crate::tg_bot::user_menu::command::BCommand;
+use crate::tg_bot::user_menu::kinds::handler as kinds_handler;
 use crate::tg_bot::user_menu::strategies::handler;
 use crate::tg_bot::user_menu::strategies::handler::select_strategy_handler;
 use crate::tg_bot::user_menu::top::endpoints;
 use crate::tg_bot::user_menu::top::handler::top_menu_callback_handler;
+use crate::tg_bot::user_menu::withdraw::handler as withdraw_handler;
 use dptree::case;
 use serde::ser::StdError;
 use std::sync::Arc;
@@ -59,7 +61,8 @@ pub async fn build_dispatcher(
                         admin_chat_ids.contains(&msg.chat.id.0)
                     })
                 })
-                .branch(case![BCommand::Collect].endpoint(endpoints::collect)),
+                .branch(case![BCommand::Collect].endpoint(endpoints::collect))
+                .branch(case![BCommand::Status].endpoint(endpoints::status)),
             );
 
         // Expecting input from the user
@@ -114,6 +117,21 @@ pub async fn build_dispatcher(
                 }]
                 .endpoint(handler::receive_button_agents_keep_tokens_lamports_handler),
             )
+            .branch(
+                case![State::ReceiveStrategyKindConfig {
+                    strategy_in_progress,
+                    kind,
+                    kind_config_message
+                }]
+                .endpoint(kinds_handler::receive_strategy_kind_config_handler),
+            )
+            .branch(
+                case![State::ReceiveWithdrawal {
+                    strategy_in_progress,
+                    withdrawal_message
+                }]
+                .endpoint(withdraw_handler::receive_withdrawal_handler),
+            )
             .branch(dptree::endpoint(invalid_state));
 
         // Handling button presses
@@ -125,6 +143,31 @@ pub async fn build_dispatcher(
                 }]
                 .endpoint(select_strategy_handler),
             )
+            // config prompt of the strategy kinds
+            .branch(
+                case![State::ReceiveStrategyKindConfig {
+                    strategy_in_progress,
+                    kind,
+                    kind_config_message
+                }]
+                .endpoint(kinds_handler::strategy_kind_callback_handler),
+            )
+            // withdrawal prompt and confirmation
+            .branch(
+                case![State::ReceiveWithdrawal {
+                    strategy_in_progress,
+                    withdrawal_message
+                }]
+                .endpoint(withdraw_handler::withdrawal_callback_handler),
+            )
+            .branch(
+                case![State::ConfirmWithdrawal {
+                    strategy_in_progress,
+                    withdrawal,
+                    withdrawal_message
+                }]
+                .endpoint(withdraw_handler::withdrawal_callback_handler),
+            )
             .branch(dptree::endpoint(top_menu_callback_handler));
 
         dialogue::enter::<Update, RedisStorage<Json>, State, _>()
@@ -50,7 +50,9 @@ pub async fn inform_about_successful_deposit(
         "💸 Successfully deposited {} 💸 {threshold_note}\n",
         format_curr(deposit_amount),
     ) + &*create_solscan_link(&tx_hash);
-    notify_user(bot, user.chat_id, &text).await;
+    if let Some(user_chat_id) = user.chat_id {
+        notify_user(bot, user_chat_id, &text).await;
+    }
     Ok(())
 }
 
@@ -1,5 +1,6 @@
 use std::fmt::Debug;
 use crate::types::sniping_strategy::{NewSnipingStrategyInstance, SnipingStrategyInstance};
+use crate::utils::crypto::decrypt_private_key;
 use solana_sdk::pubkey::Pubkey;
 use std::str::FromStr;
 
@@ -82,7 +83,8 @@ impl TryFrom<&SnipingStrategyConfigArgs> for NewSnipingStrategyInstance {
             user_id: value.user_id.ok_or("user_id is None")?,
             started_at: chrono::Utc::now().naive_utc(),
             completed_at: None,
-            sniper_private_key: value.sniper_privkey.clone().ok_or("sniper_privkey is None")?,
+            sniper_private_key: decrypt_private_key(value.sniper_privkey.as_deref().ok_or("sniper_privkey is None")?)
+                .map_err(|_| "sniper_privkey can't be decrypted")?,
             size_sol: value.size_sol.ok_or("size_sol is None")?,
             stop_loss_percent_move_down: value
                 .stop_loss_percent_move_down
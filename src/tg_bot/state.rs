@@ -1,5 +1,6 @@
 use crate::tg_bot::volume_strategy_config_args::{UpdateConfig, VolumeStrategyConfigArgs};
 use crate::types::engine::Strategy;
+use crate::types::withdrawal::WithdrawalRequest;
 use std::fmt::{Display, Formatter};
 use strum_macros::Display;
 use teloxide::dispatching::dialogue::serializer::Json;
@@ -58,6 +59,23 @@ pub enum State {
         strategy_in_progress: Option<VolumeStrategyConfigArgs>,
         selected_strategy_id: Option<i32>,
     },
+    // Awaiting the config of a strategy of the kind, prompted from its config schema
+    ReceiveStrategyKindConfig {
+        strategy_in_progress: Option<VolumeStrategyConfigArgs>,
+        kind: String,
+        kind_config_message: DialogueMessages,
+    },
+    // Awaiting the destination and the amount of a withdrawal
+    ReceiveWithdrawal {
+        strategy_in_progress: Option<VolumeStrategyConfigArgs>,
+        withdrawal_message: DialogueMessages,
+    },
+    // The withdrawal waits for the second confirmation
+    ConfirmWithdrawal {
+        strategy_in_progress: Option<VolumeStrategyConfigArgs>,
+        withdrawal: WithdrawalRequest,
+        withdrawal_message: DialogueMessages,
+    },
 }
 
 // Transition functions
@@ -123,6 +141,29 @@ impl State {
         }
     }
 
+    pub fn to_receive_strategy_kind_config(&self, kind: &str, message: DialogueMessages) -> Self {
+        State::ReceiveStrategyKindConfig {
+            strategy_in_progress: self.get_strategy_in_progress_in_any(),
+            kind: kind.to_string(),
+            kind_config_message: message,
+        }
+    }
+
+    pub fn to_receive_withdrawal(&self, message: DialogueMessages) -> Self {
+        State::ReceiveWithdrawal {
+            strategy_in_progress: self.get_strategy_in_progress_in_any(),
+            withdrawal_message: message,
+        }
+    }
+
+    pub fn to_confirm_withdrawal(&self, withdrawal: WithdrawalRequest, message: DialogueMessages) -> Self {
+        State::ConfirmWithdrawal {
+            strategy_in_progress: self.get_strategy_in_progress_in_any(),
+            withdrawal,
+            withdrawal_message: message,
+        }
+    }
+
     pub fn to_receive_strategy(&self) -> Self {
         State::ReceiveStrategy {
             strategy_in_progress: self.get_strategy_in_progress_in_any(),
@@ -182,6 +223,18 @@ impl State {
                 strategy_in_progress,
                 ..
             } => strategy_in_progress.clone(),
+            State::ReceiveStrategyKindConfig {
+                strategy_in_progress,
+                ..
+            } => strategy_in_progress.clone(),
+            State::ReceiveWithdrawal {
+                strategy_in_progress,
+                ..
+            } => strategy_in_progress.clone(),
+            State::ConfirmWithdrawal {
+                strategy_in_progress,
+                ..
+            } => strategy_in_progress.clone(),
             _ => None,
         }
     }
@@ -262,6 +315,8 @@ impl State {
             State::ReceiveAgentsBuyingInTranche { .. } => true,
             State::ReceiveAgentsSellingInTranche { .. } => true,
             State::ReceiveButtonAgentsKeepTokensLamports { .. } => true,
+            State::ReceiveStrategyKindConfig { .. } => true,
+            State::ReceiveWithdrawal { .. } => true,
             _ => false,
         }
     }
@@ -314,6 +369,18 @@ impl State {
                 strategy_menu_message,
                 ..
             } => Some(strategy_menu_message.message_to_delete.clone()),
+            State::ReceiveStrategyKindConfig {
+                kind_config_message,
+                ..
+            } => Some(kind_config_message.message_to_delete.clone()),
+            State::ReceiveWithdrawal {
+                withdrawal_message,
+                ..
+            } => Some(withdrawal_message.message_to_delete.clone()),
+            State::ConfirmWithdrawal {
+                withdrawal_message,
+                ..
+            } => Some(withdrawal_message.message_to_delete.clone()),
             _ => None,
         }
     }
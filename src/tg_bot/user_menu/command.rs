@@ -16,6 +16,8 @@ pub enum BCommand {
         description = "Collect all SOL and SPL tokens from the strategies wallets and send them to the main wallet"
     )]
     Collect,
+    #[command(description = "Operational stats: geyser feeds, collectors health and queues lag")]
+    Status,
     // #[command(description = "Usage information")]
     // Help,
     // #[command(description = "Pause bot")]
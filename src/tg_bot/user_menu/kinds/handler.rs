@@ -0,0 +1,145 @@
+use crate::strategies::{new_instance_row, parse_config_text};
+use crate::tg_bot::bot_config::{BotConfig, HandlerResult};
+use crate::tg_bot::helpers::buttons::make_keyboard;
+use crate::tg_bot::helpers::get_user_from_button_press;
+use crate::tg_bot::state::{DialogueMessages, MyDialogue, State};
+use crate::tg_bot::user_menu::top::handler::top_menu_callback_handler;
+use crate::tg_bot::user_menu::withdraw::handler::{back_to_main_menu, send_fading};
+use crate::tg_bot::volume_strategy_config_args::VolumeStrategyConfigArgs;
+use crate::utils::formatters::format_sol;
+use anyhow::anyhow;
+use teloxide::prelude::*;
+use teloxide::types::Message;
+use teloxide::Bot;
+
+// Strategy kind button names, the start button is followed by the kind name
+pub const BUTTON_START_STRATEGY_KIND: &str = "StartKind:";
+pub const BUTTON_CANCEL_STRATEGY_KIND: &str = "CancelStrategyKind";
+
+// one line per field of the config schema
+fn describe_schema(schema: &serde_json::Value) -> String {
+    let Some(fields) = schema.as_object() else {
+        return String::new();
+    };
+    fields
+        .iter()
+        .map(|(field, spec)| {
+            let description = spec["description"].as_str().unwrap_or_default();
+            match spec.get("default") {
+                Some(default) => format!("• {field}: {description}, {default} by default"),
+                None => format!("• {field}: {description}"),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Asks for the config of a strategy of the kind, from the main menu.
+pub async fn prompt_strategy_kind(
+    bot: &Bot,
+    dialogue: &MyDialogue,
+    config: &BotConfig,
+    message: &Message,
+    current_state: &State,
+    kind: &str,
+) -> HandlerResult {
+    let kind = config.strategy_manager.get_strategy_kind(kind).ok_or(anyhow!("Unknown strategy kind {}", kind))?;
+    let message_to_delete = bot
+        .send_message(
+            message.chat.id,
+            format!(
+                "📝 Enter the config as field=value separated by spaces:\n{}",
+                describe_schema(&kind.config_schema())
+            ),
+        )
+        .reply_markup(make_keyboard(&vec![vec![(
+            "❌Cancel".to_string(),
+            BUTTON_CANCEL_STRATEGY_KIND.to_string(),
+        )]]))
+        .await?;
+    dialogue
+        .update(current_state.to_receive_strategy_kind_config(kind.name(), DialogueMessages {
+            message_to_edit: message.clone(),
+            message_to_delete,
+        }))
+        .await?;
+    Ok(())
+}
+
+pub async fn receive_strategy_kind_config_handler(
+    bot: Bot,
+    dialogue: MyDialogue,
+    params: (Option<VolumeStrategyConfigArgs>, String, DialogueMessages),
+    msg: Message,
+    config: BotConfig,
+) -> HandlerResult {
+    let current_state = dialogue.get_or_default().await?;
+    let (_, kind, kind_config_message) = params;
+    let user = get_user_from_button_press(&config.context, &msg).await?;
+    let strategy_kind = config.strategy_manager.get_strategy_kind(&kind).ok_or(anyhow!("Unknown strategy kind {}", kind))?;
+    let row = parse_config_text(msg.text().unwrap_or_default())
+        .and_then(|fields| new_instance_row(&strategy_kind.config_schema(), user.id, &fields));
+    let row = match row {
+        Ok(row) => row,
+        Err(e) => {
+            // the prompt stays for another try
+            send_fading(&bot, &msg, &format!("Error: {}", e)).await?;
+            bot.delete_message(msg.chat.id, msg.id).await?;
+            return Ok(());
+        }
+    };
+    bot.delete_message(msg.chat.id, msg.id).await?;
+    bot.delete_message(msg.chat.id, kind_config_message.message_to_delete.id).await?;
+    let min_deposit = config
+        .context
+        .get_settings()
+        .await
+        .tgbot
+        .as_ref()
+        .map_or(0.0, |tgbot| tgbot.minimum_deposit_sol);
+    let user_sol_balance = config.context.rpc_pool.get_balance_ui(&user.wallet_address).await?;
+    let text = if user_sol_balance < min_deposit {
+        format!(
+            "You have {} and need at least {} SOL to start a strategy",
+            format_sol(user_sol_balance),
+            format_sol(min_deposit)
+        )
+    } else {
+        match config.strategy_manager.start_strategy_of_kind(&kind, row).await {
+            Ok(strategy_id) => format!("Strategy id {strategy_id} started 🔥"),
+            Err(e) => format!("❌ Failed to start a strategy: {}", e),
+        }
+    };
+    back_to_main_menu(&bot, &dialogue, &config, &user, &kind_config_message.message_to_edit, &current_state, &text).await
+}
+
+/// The cancel button of the prompt, the others are handled by the top menu.
+pub async fn strategy_kind_callback_handler(
+    bot: Bot,
+    dialogue: MyDialogue,
+    q: CallbackQuery,
+    config: BotConfig,
+) -> HandlerResult {
+    let Some(message) = q.message.clone() else {
+        return Ok(());
+    };
+    let current_state = dialogue.get_or_default().await?;
+    let State::ReceiveStrategyKindConfig { kind_config_message, .. } = &current_state else {
+        return Ok(());
+    };
+    let kind_config_message = kind_config_message.clone();
+    match q.data.as_deref() {
+        Some(BUTTON_CANCEL_STRATEGY_KIND) => {
+            let user = get_user_from_button_press(&config.context, &message).await?;
+            bot.delete_message(message.chat.id, kind_config_message.message_to_delete.id).await?;
+            back_to_main_menu(&bot, &dialogue, &config, &user, &kind_config_message.message_to_edit, &current_state, "Strategy cancelled").await?;
+        }
+        _ => {
+            // leaving the prompt for another menu
+            let _ = bot.delete_message(message.chat.id, kind_config_message.message_to_delete.id).await;
+            dialogue.update(current_state.to_main_menu()).await?;
+            top_menu_callback_handler(bot, dialogue, q, config).await?;
+        }
+    }
+    Ok(())
+}
@@ -1,3 +1,5 @@
 pub mod command;
+pub mod kinds;
 pub mod strategies;
 pub mod top;
+pub mod withdraw;
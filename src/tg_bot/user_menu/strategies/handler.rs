@@ -1,4 +1,4 @@
-use crate::strategies::VolumeStrategy;
+use crate::strategies::VOLUME_STRATEGY_KIND;
 use crate::tg_bot::bot_config::{BotConfig, HandlerResult};
 use crate::tg_bot::helpers::get_user_from_button_press;
 use crate::tg_bot::notifications::{notify_user, notify_with_fading_message, TimeToShow};
@@ -171,18 +171,9 @@ pub async fn select_strategy_handler(
                                 return Ok(());
                             }
                         };
-                        let strategy =
-                            match VolumeStrategy::new(&config.context.clone(), &volume_strategy).await {
-                                Ok(strategy) => strategy,
-                                Err(e) => {
-                                    bot.send_message(chat_id, &format!("Failed to create a strategy: {:?}", e)).await;
-                                    return Ok(());
-                                }
-                            };
-                            
                         match config
                             .strategy_manager
-                            .start_strategy(Box::new(strategy))
+                            .start_strategy_of_kind(VOLUME_STRATEGY_KIND, serde_json::to_value(&volume_strategy)?)
                             .await
                         {
                             Ok(strategy_id) => {
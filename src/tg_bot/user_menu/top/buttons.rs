@@ -1,47 +1,69 @@
 use crate::config::app_context::AppContext;
-use crate::strategies::VolumeStrategy;
 use crate::tg_bot::bot_config::BotConfig;
 use crate::tg_bot::helpers::buttons::ButtonMenu;
 use crate::tg_bot::user_menu::top::handler::{
-    BUTTON_BACK_TO_THE_MAIN_MENU, BUTTON_CONFIGURE_STRATEGY, BUTTON_STOP_STRATEGIES,
+    BUTTON_BACK_TO_THE_MAIN_MENU, BUTTON_CONFIGURE_STRATEGY, BUTTON_PAUSE_STRATEGIES,
+    BUTTON_RESUME_STRATEGIES, BUTTON_STOP_STRATEGIES, BUTTON_STRATEGY_STATS,
 };
-use crate::types::engine::StrategyManager;
+use crate::strategies::menu_kinds;
+use crate::tg_bot::user_menu::kinds::handler::BUTTON_START_STRATEGY_KIND;
+use crate::tg_bot::user_menu::withdraw::handler::BUTTON_WITHDRAW;
+use crate::types::engine::{StrategyManager, StrategyStatus};
 use crate::types::bot_user::BotUser;
 use futures::stream::{self, StreamExt};
 use once_cell::sync::Lazy;
 
 pub async fn get_menu_top(context: &BotConfig, user: &BotUser) -> ButtonMenu {
-    let user_strategies =
-        stream::iter(context.strategy_manager.get_active_strategies().await.values())
-            .filter_map(|strategy_mutex| async move {
-                let strategy = strategy_mutex.lock().await;
-                if let Some(volume_strategy) = strategy.as_any().downcast_ref::<VolumeStrategy>() {
-                    if volume_strategy.get_user_id() == user.id {
-                        Some(volume_strategy.clone())
-                    } else {
-                        None
-                    }
-                } else {
-                    None
-                }
-            })
+    let user_strategies_statuses =
+        stream::iter(context.strategy_manager.get_user_strategies(user.id).await.into_values())
+            .then(|strategy_mutex| async move { strategy_mutex.lock().await.get_status().await })
             .collect::<Vec<_>>()
             .await;
 
-    let running_strategies = user_strategies.len();
+    let running_strategies = user_strategies_statuses
+        .iter()
+        .filter(|status| matches!(status, StrategyStatus::Running(..)))
+        .count();
+    let paused_strategies = user_strategies_statuses
+        .iter()
+        .filter(|status| matches!(status, StrategyStatus::Paused(..)))
+        .count();
 
     let mut top_menu = vec![];
     top_menu.push(vec![(
         "📈 Configure Strategy".to_string(),
         BUTTON_CONFIGURE_STRATEGY.to_string(),
     )]);
+    for (title, kind) in menu_kinds(context.strategy_manager.as_ref()) {
+        top_menu.push(vec![(title.to_string(), format!("{}{}", BUTTON_START_STRATEGY_KIND, kind.name()))]);
+    }
 
     if running_strategies > 0 {
         top_menu.push(vec![(
-            format!("🔴 Stop All Running Strategies ({running_strategies})"),
+            format!("⏸ Pause Running Strategies ({running_strategies})"),
+            BUTTON_PAUSE_STRATEGIES.to_string(),
+        )]);
+    }
+    if paused_strategies > 0 {
+        top_menu.push(vec![(
+            format!("▶️ Resume Paused Strategies ({paused_strategies})"),
+            BUTTON_RESUME_STRATEGIES.to_string(),
+        )]);
+    }
+    if running_strategies + paused_strategies > 0 {
+        top_menu.push(vec![(
+            "📊 Strategy Stats".to_string(),
+            BUTTON_STRATEGY_STATS.to_string(),
+        )]);
+        top_menu.push(vec![(
+            format!("🔴 Stop All Strategies ({})", running_strategies + paused_strategies),
             BUTTON_STOP_STRATEGIES.to_string(),
         )]);
     }
+    top_menu.push(vec![(
+        "💸 Withdraw".to_string(),
+        BUTTON_WITHDRAW.to_string(),
+    )]);
     top_menu.push(vec![(
         "🔃".to_string(),
         BUTTON_BACK_TO_THE_MAIN_MENU.to_string(),
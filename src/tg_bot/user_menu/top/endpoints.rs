@@ -10,6 +10,7 @@ use crate::schema::volumestrategyinstances;
 use crate::schema::volumestrategyinstances::dsl::volumestrategyinstances as volumestrategyinstances_dsl;
 use crate::schema::volumestrategyinstances::id;
 use crate::tg_bot::bot_config::{BotConfig, HandlerResult};
+use crate::tg_bot::helpers::formatters::format_operational_status;
 use crate::tg_bot::helpers::get_user_from_user_message;
 use crate::tg_bot::state::MyDialogue;
 use crate::tg_bot::user_menu::top::screen::render_main_menu;
@@ -44,7 +45,7 @@ use tokio::sync::Mutex;
 use tokio::time::sleep;
 use tracing::{debug, error, info, trace};
 use uuid::Uuid;
-use crate::strategies::sweeper_strategy::SweeperStrategy;
+use crate::strategies::sweeper_strategy::SWEEPER_STRATEGY_KIND;
 use crate::strategies::SweeperStrategyStateMachine;
 use crate::types::actions::Amount::{Max, MaxAndClose};
 use crate::types::engine::Executor;
@@ -71,6 +72,24 @@ pub async fn start(
 }
 
 
+pub async fn status(
+    bot: Bot,
+    dialogue: MyDialogue,
+    message: Message,
+    config: BotConfig,
+) -> HandlerResult {
+    let context = &config.context;
+    let feeds = context.geyser_pool.stats.snapshot();
+    let text = format_operational_status(
+        &feeds,
+        context.geyser_pool.stats.total_first_seen(),
+        &context.collector_health.snapshot(),
+        &context.queue_stats.snapshot(),
+    );
+    bot.send_message(message.chat.id, text).await?;
+    Ok(())
+}
+
 pub async fn collect(
     bot: Bot,
     dialogue: MyDialogue,
@@ -100,10 +119,9 @@ pub async fn collect(
                     volume_strat.id,
                 );
 
-                let strategy = SweeperStrategy::new(&config.context, &volume_strat).await?;
                 let cfg_clone = config.clone();
                 cfg_clone.strategy_manager
-                    .start_strategy(Box::new(strategy))
+                    .start_strategy_of_kind(SWEEPER_STRATEGY_KIND, serde_json::to_value(volume_strat)?)
                     .await;
             }
 
@@ -1,11 +1,15 @@
-use crate::strategies::VolumeStrategy;
+use crate::analytics;
 use crate::tg_bot::bot_config::{BotConfig, HandlerResult};
+use crate::tg_bot::helpers::formatters::format_strategy_stats;
 use crate::tg_bot::helpers::get_user_from_button_press;
 use crate::tg_bot::notifications::{notify_user, notify_with_fading_message, TimeToShow};
 use crate::tg_bot::state::MyDialogue;
+use crate::tg_bot::user_menu::kinds::handler::{prompt_strategy_kind, BUTTON_START_STRATEGY_KIND};
 use crate::tg_bot::user_menu::strategies;
 use crate::tg_bot::user_menu::strategies::screen::render_strategies_menu;
 use crate::tg_bot::user_menu::top::screen::render_main_menu;
+use crate::tg_bot::user_menu::withdraw::handler::{prompt_withdrawal, BUTTON_WITHDRAW};
+use crate::types::engine::{StrategyId, StrategyManager, StrategyStatus};
 use chrono::{NaiveDate, TimeZone, Utc};
 use futures::stream::{self, StreamExt};
 use log::warn;
@@ -19,7 +23,10 @@ pub const BUTTON_CANCEL: &str = "❌Cancel";
 pub const SYSTEM: &str = "System Stats";
 // Main menu button names (NOT THE TEXT DISPLAYED
 pub const BUTTON_STOP_STRATEGIES: &str = "Strategies";
+pub const BUTTON_PAUSE_STRATEGIES: &str = "PauseStrategies";
+pub const BUTTON_RESUME_STRATEGIES: &str = "ResumeStrategies";
 pub const BUTTON_CONFIGURE_STRATEGY: &str = "SelectStrategy";
+pub const BUTTON_STRATEGY_STATS: &str = "StrategyStats";
 pub const BUTTON_ACCOUNT: &str = "Account";
 
 pub async fn top_menu_callback_handler(
@@ -62,35 +69,15 @@ pub async fn top_menu_callback_handler(
                                 .await?;
                         }
                         BUTTON_STOP_STRATEGIES => {
-                            let user_strategies = stream::iter(
-                                config.strategy_manager.get_active_strategies().await.values(),
-                            )
-                            .filter_map(|strategy_mutex| async move {
-                                let strategy = strategy_mutex.lock().await;
-                                if let Some(volume_strategy) =
-                                    strategy.as_any().downcast_ref::<VolumeStrategy>()
-                                {
-                                    if volume_strategy.state_machine.instance.user_id == user.id {
-                                        Some(volume_strategy.clone())
-                                    } else {
-                                        None
-                                    }
-                                } else {
-                                    None
-                                }
-                            })
-                            .collect::<Vec<_>>()
-                            .await;
-
                             let mut dropped_strats = "".to_string();
-                            for strategy in user_strategies {
+                            for strategy_id in get_user_strategies_ids(&config, user.id).await {
                                 config
                                     .strategy_manager
-                                    .drop_strategy(strategy.state_machine.instance.id)
+                                    .drop_strategy(strategy_id)
                                     .await?;
                                 dropped_strats += &format!(
-                                    "Strategy id {} stopped \n",
-                                    strategy.state_machine.instance.id
+                                    "Strategy id {} is stopping, collecting funds \n",
+                                    strategy_id
                                 );
                             }
                             notify_user(&bot, message.chat.id.0, &dropped_strats).await;
@@ -107,6 +94,44 @@ pub async fn top_menu_callback_handler(
                             //     }
                             // }
                         }
+                        BUTTON_PAUSE_STRATEGIES | BUTTON_RESUME_STRATEGIES => {
+                            let pause = button.as_str() == BUTTON_PAUSE_STRATEGIES;
+                            let mut report = "".to_string();
+                            for strategy_id in get_user_strategies_ids(&config, user.id).await {
+                                let res = if pause {
+                                    config.strategy_manager.pause_strategy(strategy_id).await
+                                } else {
+                                    config.strategy_manager.resume_strategy(strategy_id).await
+                                };
+                                report += &match res {
+                                    Ok(_) if pause => format!("Strategy id {} paused \n", strategy_id),
+                                    Ok(_) => format!("Strategy id {} resumed \n", strategy_id),
+                                    Err(e) => format!("Strategy id {}: {} \n", strategy_id, e),
+                                };
+                            }
+                            notify_user(&bot, message.chat.id.0, &report).await;
+                            render_main_menu(&config, &user, Some(&message), &current_state)
+                                .await?;
+                        }
+                        BUTTON_STRATEGY_STATS => {
+                            let mut reports = vec![];
+                            for strategy_id in get_user_strategies_ids(&config, user.id).await {
+                                reports.push(
+                                    match analytics::get_strategy_stats(&config.context.db_pool, strategy_id, None, None).await {
+                                        Ok(stats) => format_strategy_stats(strategy_id, &stats),
+                                        Err(e) => format!("Strategy {}: no stats, {}", strategy_id, e),
+                                    },
+                                );
+                            }
+                            bot.send_message(message.chat.id, reports.join("\n\n")).await?;
+                        }
+                        button if button.starts_with(BUTTON_START_STRATEGY_KIND) => {
+                            let kind = &button[BUTTON_START_STRATEGY_KIND.len()..];
+                            prompt_strategy_kind(&bot, &dialogue, &config, &message, &current_state, kind).await?;
+                        }
+                        BUTTON_WITHDRAW => {
+                            prompt_withdrawal(&bot, &dialogue, &message, &current_state).await?;
+                        }
                         BUTTON_CONFIGURE_STRATEGY => {
                             let mut state = current_state.to_receive_strategy();
                             if state.get_strategy_in_progress_in_any().is_none() {
@@ -142,3 +167,16 @@ pub async fn top_menu_callback_handler(
     }
     Ok(())
 }
+
+// strategies being wound down are not listed, they can't be paused or stopped again
+async fn get_user_strategies_ids(config: &BotConfig, user_id: i32) -> Vec<StrategyId> {
+    stream::iter(config.strategy_manager.get_user_strategies(user_id).await.into_iter())
+        .filter_map(|(strategy_id, strategy_mutex)| async move {
+            match strategy_mutex.lock().await.get_status().await {
+                StrategyStatus::Running(..) | StrategyStatus::Paused(..) => Some(strategy_id),
+                _ => None,
+            }
+        })
+        .collect::<Vec<_>>()
+        .await
+}
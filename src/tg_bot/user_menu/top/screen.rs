@@ -1,4 +1,5 @@
 use crate::config::app_context::AppContext;
+use crate::storage::deposits;
 use crate::tg_bot::bot_config::BotConfig;
 use crate::tg_bot::helpers::buttons::make_keyboard;
 use crate::tg_bot::state::State;
@@ -53,17 +54,21 @@ pub async fn render_main_menu(
             _ => "Select a target token, a strategy and start the bot".to_string(),
         }
     };
+    let deposit_wallet = deposits::get_or_create_deposit_wallet(&config.context.db_pool, user.id).await?;
     let header = format!("Welcome to the industry leader in volume bot services, designed to elevate your project intuitively with just a few clicks\\.\n\
     \nCurrent balance: `{}` SOL\\.\n\n\
     Your personal SOL deposit address on Solana mainnet, click to copy:\n`{}`\n\n\
-    {deposit_message}", utils::formatters::format_sol(current_balance), user.wallet_address);
+    {deposit_message}", utils::formatters::format_sol(current_balance), deposit_wallet.wallet);
 
+    let user_chat_id = user
+        .get_chat_id()
+        .ok_or_else(|| anyhow::anyhow!("User {} has no telegram chat", user.id))?;
     if let Some(message) = message {
         config
             .context
             .tg_bot
             .as_ref().unwrap()
-            .edit_message_text(ChatId(user.chat_id), message.id, header)
+            .edit_message_text(user_chat_id, message.id, header)
             .parse_mode(teloxide::types::ParseMode::MarkdownV2)
             .disable_web_page_preview(true)
             .reply_markup(keyboard)
@@ -74,7 +79,7 @@ pub async fn render_main_menu(
             .context
             .tg_bot
             .as_ref().unwrap()
-            .send_message(ChatId(user.chat_id), header)
+            .send_message(user_chat_id, header)
             .parse_mode(teloxide::types::ParseMode::MarkdownV2)
             .disable_web_page_preview(true)
             .reply_markup(keyboard)
@@ -0,0 +1,182 @@
+use crate::strategies::withdrawal;
+use crate::tg_bot::bot_config::{BotConfig, HandlerResult};
+use crate::tg_bot::helpers::buttons::make_keyboard;
+use crate::tg_bot::helpers::get_user_from_button_press;
+use crate::tg_bot::state::{DialogueMessages, MyDialogue, State};
+use crate::tg_bot::user_menu::top::handler::top_menu_callback_handler;
+use crate::tg_bot::user_menu::top::screen::render_main_menu;
+use crate::tg_bot::volume_strategy_config_args::VolumeStrategyConfigArgs;
+use crate::types::bot_user::BotUser;
+use crate::types::withdrawal::WithdrawalRequest;
+use crate::utils::formatters::format_sol;
+use teloxide::prelude::*;
+use teloxide::types::Message;
+use teloxide::Bot;
+
+// Withdrawal button names
+pub const BUTTON_WITHDRAW: &str = "Withdraw";
+pub const BUTTON_CONFIRM_WITHDRAWAL: &str = "ConfirmWithdrawal";
+pub const BUTTON_CANCEL_WITHDRAWAL: &str = "CancelWithdrawal";
+
+/// Asks for the destination and the amount, from the main menu.
+pub async fn prompt_withdrawal(bot: &Bot, dialogue: &MyDialogue, message: &Message, current_state: &State) -> HandlerResult {
+    let message_to_delete = bot
+        .send_message(
+            message.chat.id,
+            "📝 Enter the wallet to withdraw to and the amount in SOL, or \"all\" to withdraw everything, e.g. <wallet> 0.5. \
+            The strategies must be stopped first:",
+        )
+        .reply_markup(make_keyboard(&vec![vec![(
+            "❌Cancel".to_string(),
+            BUTTON_CANCEL_WITHDRAWAL.to_string(),
+        )]]))
+        .await?;
+    dialogue
+        .update(current_state.to_receive_withdrawal(DialogueMessages {
+            message_to_edit: message.clone(),
+            message_to_delete,
+        }))
+        .await?;
+    Ok(())
+}
+
+pub async fn receive_withdrawal_handler(
+    bot: Bot,
+    dialogue: MyDialogue,
+    params: (Option<VolumeStrategyConfigArgs>, DialogueMessages),
+    msg: Message,
+    config: BotConfig,
+) -> HandlerResult {
+    let current_state = dialogue.get_or_default().await?;
+    let withdrawal_message = params.1;
+    let user = get_user_from_button_press(&config.context, &msg).await?;
+    let request = match WithdrawalRequest::parse(msg.text().unwrap_or_default()) {
+        Ok(request) => request,
+        Err(e) => {
+            // the prompt stays for another try
+            send_fading(&bot, &msg, &format!("Error: {}", e)).await?;
+            bot.delete_message(msg.chat.id, msg.id).await?;
+            return Ok(());
+        }
+    };
+    bot.delete_message(msg.chat.id, msg.id).await?;
+    bot.delete_message(msg.chat.id, withdrawal_message.message_to_delete.id).await?;
+    if let Err(e) = withdrawal::check_withdrawal(&config.context.db_pool, config.strategy_manager.as_ref(), &user, &request).await {
+        return back_to_main_menu(&bot, &dialogue, &config, &user, &withdrawal_message.message_to_edit, &current_state, &format!("❌ {}", e)).await;
+    }
+    let require_confirmation = config
+        .context
+        .get_settings()
+        .await
+        .withdrawal
+        .clone()
+        .unwrap_or_default()
+        .require_confirmation;
+    if !require_confirmation {
+        let text = queue_withdrawal(&config, &user, &request).await;
+        return back_to_main_menu(&bot, &dialogue, &config, &user, &withdrawal_message.message_to_edit, &current_state, &text).await;
+    }
+    let confirmation_message = bot
+        .send_message(
+            msg.chat.id,
+            format!("Withdraw {} to {}?", describe_amount(&request), request.destination),
+        )
+        .reply_markup(make_keyboard(&vec![vec![
+            ("✅ Confirm".to_string(), BUTTON_CONFIRM_WITHDRAWAL.to_string()),
+            ("❌Cancel".to_string(), BUTTON_CANCEL_WITHDRAWAL.to_string()),
+        ]]))
+        .await?;
+    dialogue
+        .update(current_state.to_confirm_withdrawal(request, DialogueMessages {
+            message_to_edit: withdrawal_message.message_to_edit,
+            message_to_delete: confirmation_message,
+        }))
+        .await?;
+    Ok(())
+}
+
+/// The buttons of the prompt and of the confirmation, the others are handled by the top menu.
+pub async fn withdrawal_callback_handler(
+    bot: Bot,
+    dialogue: MyDialogue,
+    q: CallbackQuery,
+    config: BotConfig,
+) -> HandlerResult {
+    let Some(message) = q.message.clone() else {
+        return Ok(());
+    };
+    let current_state = dialogue.get_or_default().await?;
+    let (withdrawal, withdrawal_message) = match &current_state {
+        State::ReceiveWithdrawal { withdrawal_message, .. } => (None, withdrawal_message.clone()),
+        State::ConfirmWithdrawal { withdrawal, withdrawal_message, .. } => {
+            (Some(withdrawal.clone()), withdrawal_message.clone())
+        }
+        _ => return Ok(()),
+    };
+    let user = get_user_from_button_press(&config.context, &message).await?;
+    match q.data.as_deref() {
+        Some(BUTTON_CANCEL_WITHDRAWAL) => {
+            bot.delete_message(message.chat.id, withdrawal_message.message_to_delete.id).await?;
+            back_to_main_menu(&bot, &dialogue, &config, &user, &withdrawal_message.message_to_edit, &current_state, "Withdrawal cancelled").await?;
+        }
+        Some(BUTTON_CONFIRM_WITHDRAWAL) => {
+            let Some(request) = withdrawal else {
+                return Ok(());
+            };
+            bot.delete_message(message.chat.id, withdrawal_message.message_to_delete.id).await?;
+            let text = queue_withdrawal(&config, &user, &request).await;
+            back_to_main_menu(&bot, &dialogue, &config, &user, &withdrawal_message.message_to_edit, &current_state, &text).await?;
+        }
+        _ => {
+            // leaving the withdrawal for another menu
+            let _ = bot.delete_message(message.chat.id, withdrawal_message.message_to_delete.id).await;
+            dialogue.update(current_state.to_main_menu()).await?;
+            top_menu_callback_handler(bot, dialogue, q, config).await?;
+        }
+    }
+    Ok(())
+}
+
+// the checks are run again, a strategy may have been started in the meantime
+async fn queue_withdrawal(config: &BotConfig, user: &BotUser, request: &WithdrawalRequest) -> String {
+    match withdrawal::request_withdrawal(&config.context.db_pool, config.strategy_manager.as_ref(), user, request).await {
+        Ok(_) => format!(
+            "💸 Withdrawal of {} to {} queued, you'll be notified once it's confirmed",
+            describe_amount(request),
+            request.destination
+        ),
+        Err(e) => format!("❌ {}", e),
+    }
+}
+
+fn describe_amount(request: &WithdrawalRequest) -> String {
+    request
+        .amount_sol
+        .map_or("all the SOL".to_string(), |amount_sol| format!("{} SOL", format_sol(amount_sol)))
+}
+
+pub(crate) async fn back_to_main_menu(
+    bot: &Bot,
+    dialogue: &MyDialogue,
+    config: &BotConfig,
+    user: &BotUser,
+    menu_message: &Message,
+    current_state: &State,
+    text: &str,
+) -> HandlerResult {
+    bot.send_message(menu_message.chat.id, text).await?;
+    let updated_state = current_state.to_main_menu();
+    dialogue.update(updated_state.clone()).await?;
+    render_main_menu(config, user, Some(menu_message), &updated_state).await?;
+    Ok(())
+}
+
+pub(crate) async fn send_fading(bot: &Bot, msg: &Message, text: &str) -> HandlerResult {
+    let info_message = bot.send_message(msg.chat.id, text).await?;
+    let bot = bot.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(std::time::Duration::from_secs(3)).await;
+        let _ = bot.delete_message(info_message.chat.id, info_message.id).await;
+    });
+    Ok(())
+}
@@ -15,6 +15,13 @@ pub struct VolumeStrategyConfigArgs {
     pub agents_buying_in_tranche: Option<i32>,
     pub agents_selling_in_tranche: Option<i32>,
     pub agents_keep_tokens_lamports: Option<i64>,
+    pub daily_volume_sol: Option<f64>,
+    pub budget_days: Option<i32>,
+    pub max_inventory_sol: Option<f64>,
+    pub max_price_drift_pct: Option<f64>,
+    /// On a running strategy, the budget and the inventory bands left out are cleared instead of kept
+    #[serde(default)]
+    pub clear_unset: bool,
 }
 
 impl VolumeStrategyConfigArgs {
@@ -78,6 +85,13 @@ impl TryFrom<&VolumeStrategyConfigArgs> for VolumeStrategyInstance {
             agents_keep_tokens_lamports: value
                 .agents_keep_tokens_lamports
                 .ok_or("agents_keep_tokens_lamports is None")?,
+            daily_volume_sol: value.daily_volume_sol,
+            budget_days: value.budget_days,
+            volume_traded_sol: 0.0,
+            max_inventory_sol: value.max_inventory_sol,
+            max_price_drift_pct: value.max_price_drift_pct,
+            reference_price: None,
+            volume_counted_until: None,
         })
     }
 }
@@ -108,5 +122,17 @@ impl UpdateConfig for VolumeStrategyConfigArgs {
         if let Some(agents_keep_tokens_lamports) = new_config.agents_keep_tokens_lamports {
             self.agents_keep_tokens_lamports = Some(agents_keep_tokens_lamports);
         }
+        if let Some(daily_volume_sol) = new_config.daily_volume_sol {
+            self.daily_volume_sol = Some(daily_volume_sol);
+        }
+        if let Some(budget_days) = new_config.budget_days {
+            self.budget_days = Some(budget_days);
+        }
+        if let Some(max_inventory_sol) = new_config.max_inventory_sol {
+            self.max_inventory_sol = Some(max_inventory_sol);
+        }
+        if let Some(max_price_drift_pct) = new_config.max_price_drift_pct {
+            self.max_price_drift_pct = Some(max_price_drift_pct);
+        }
     }
 }
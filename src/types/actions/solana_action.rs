@@ -1,5 +1,5 @@
 use std::collections::BTreeMap;
-use crate::types::keys::KeypairClonable;
+use crate::types::keys::ActionWallet;
 use crate::types::pool::RaydiumPool;
 use crate::schema::*;
 use crate::utils;
@@ -38,6 +38,7 @@ impl ToSql<diesel::sql_types::Jsonb, Pg> for SolanaActionPayload {
     fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
         let json = serde_json::to_string(self)
             .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
+        out.write_all(&[1])?; // Write JSONB version (always 1 for now)
         out.write_all(json.as_bytes())?;
         Ok(IsNull::No)
     }
@@ -64,6 +65,8 @@ impl ToSql<sql_types::Text, Pg> for ActionExecutionStatus {
 #[sql_type = "diesel::sql_types::Jsonb"]
 pub struct Balance {
     pub sol: u64,
+    // json keys must be strings
+    #[serde(serialize_with = "crate::utils::serdealizers::serialize_pubkey_map")]
     pub token: BTreeMap<Pubkey, u64>,
 }
 
@@ -71,6 +74,7 @@ impl ToSql<Jsonb, Pg> for Balance {
     fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
         let json = serde_json::to_string(self)
             .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
+        out.write_all(&[1])?; // Write JSONB version (always 1 for now)
         out.write_all(json.as_bytes())?;
         Ok(IsNull::No)
     }
@@ -82,10 +86,10 @@ pub struct SolanaAction {
     #[diesel(serialize_as = String)]
     pub uuid: Uuid,
     #[diesel(serialize_as = String)]
-    pub sniper: KeypairClonable,
+    pub sniper: ActionWallet,
     // main_wallet is used for fees for Max transfers
     #[diesel(serialize_as = String)]
-    pub fee_payer: KeypairClonable,
+    pub fee_payer: ActionWallet,
     pub created_at: DateTime<Utc>,
     #[diesel(serialize_as = JsonbVec<SolanaActionPayload>)]
     pub action_payload: Vec<SolanaActionPayload>,
@@ -99,6 +103,25 @@ pub struct SolanaAction {
     pub confirmed_at: Option<DateTime<Utc>>,
 }
 
+/// A stored action as read back from the db, the keypairs are stored as their public keys.
+#[derive(Debug, Clone, Queryable, Selectable)]
+#[diesel(table_name = solana_actions)]
+#[diesel(check_for_backend(Pg))]
+pub struct SolanaActionRecord {
+    pub uuid: String,
+    pub sniper: String,
+    pub fee_payer: String,
+    pub created_at: DateTime<Utc>,
+    pub action_payload: JsonbWrapper<JsonValue>,
+    pub status: Option<String>,
+    pub tx_hash: Option<String>,
+    pub balance_before: Option<JsonbWrapper<JsonValue>>,
+    pub balance_after: Option<JsonbWrapper<JsonValue>>,
+    pub fee: Option<i64>,
+    pub sent_at: Option<DateTime<Utc>>,
+    pub confirmed_at: Option<DateTime<Utc>>,
+}
+
 impl Display for SolanaAction {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         for action in &self.action_payload {
@@ -128,22 +151,24 @@ impl Display for SolanaAction {
 }
 
 impl SolanaAction {
+    /// The wallets are keypairs, or pubkeys of wallets signed for by the signing service.
     pub fn new(
-        sniper: KeypairClonable,
+        sniper: impl Into<ActionWallet>,
         action_payload: Vec<SolanaActionPayload>,
     ) -> Self {
+        let sniper = sniper.into();
         SolanaAction::new_with_feepayer(sniper.clone(), sniper, action_payload)
     }
 
     pub fn new_with_feepayer(
-        sniper: KeypairClonable,
-        fee_payer: KeypairClonable,
+        sniper: impl Into<ActionWallet>,
+        fee_payer: impl Into<ActionWallet>,
         action_payload: Vec<SolanaActionPayload>,
     ) -> Self {
         Self {
             uuid: Uuid::new_v4(),
-            sniper,
-            fee_payer,
+            sniper: sniper.into(),
+            fee_payer: fee_payer.into(),
             created_at: Utc::now(),
             action_payload,
             status: ActionExecutionStatus::NotSent,
@@ -8,10 +8,12 @@ use diesel::sql_types::*;
 use diesel::Insertable;
 use serde_derive::{Deserialize, Serialize};
 use solana_sdk::pubkey::Pubkey;
-use solana_sdk::signature::{Keypair, Signature, Signer};
+use solana_sdk::signature::{Keypair, Signer};
 use teloxide::prelude::ChatId;
 
-#[derive(Debug, Clone, Serialize, Deserialize, Insertable, Default, Queryable, Identifiable)]
+/// A row of the deposits and withdrawals ledger, a deposit is successful once swept to the trading wallet,
+/// a withdrawal once its transfer is confirmed.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, Queryable, Selectable, Identifiable)]
 #[diesel(table_name = depositswithdrawals)]
 pub struct DepositsWithdrawals {
     pub id: i32,
@@ -20,11 +22,81 @@ pub struct DepositsWithdrawals {
     pub is_deposit: bool,
     pub amount_sol: Option<f64>,
     pub is_success: bool,
-    #[diesel(serialize_as = crate::utils::serdealizers::SignatureString)]
-    pub signature: Signature,
+    // the transaction of the deposit or of the withdrawal, none until a withdrawal is sent
+    pub signature: Option<String>,
+    // the transaction of the sweep, which takes the fee
     pub signature_fee: Option<String>,
     pub fee_taken_sol: Option<f64>,
+    // why it failed
     pub description: Option<String>,
+    pub action_uuid: Option<String>,
+    pub destination: Option<String>,
+}
+
+impl DepositsWithdrawals {
+    pub fn status(&self) -> &'static str {
+        if self.is_success {
+            "success"
+        } else if self.description.is_some() {
+            "failed"
+        } else if self.action_uuid.is_some() {
+            "sent"
+        } else {
+            "pending"
+        }
+    }
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = depositswithdrawals)]
+pub struct NewDepositsWithdrawals {
+    pub user_id: Option<i32>,
+    pub time: chrono::NaiveDateTime,
+    pub is_deposit: bool,
+    pub amount_sol: Option<f64>,
+    pub is_success: bool,
+    pub signature: Option<String>,
+    pub description: Option<String>,
+    pub destination: Option<String>,
+}
+
+#[derive(Debug, Clone, Selectable, Queryable, Identifiable)]
+#[diesel(table_name = deposit_wallets)]
+pub struct DepositWallet {
+    pub id: i32,
+    pub user_id: i32,
+    #[diesel(
+        serialize_as = crate::utils::serdealizers::PubkeyString,
+        deserialize_as = crate::utils::serdealizers::PubkeyString,
+    )]
+    pub wallet: Pubkey,
+    #[diesel(
+        serialize_as = crate::utils::serdealizers::PrivateKeyString,
+        deserialize_as = crate::utils::serdealizers::PrivateKeyString,
+    )]
+    pub private_key: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = deposit_wallets)]
+pub struct NewDepositWallet {
+    pub user_id: i32,
+    #[diesel(serialize_as = crate::utils::serdealizers::PubkeyString)]
+    pub wallet: Pubkey,
+    #[diesel(serialize_as = crate::utils::serdealizers::PrivateKeyString)]
+    pub private_key: String,
+}
+
+impl NewDepositWallet {
+    pub fn generate(user_id: i32) -> Self {
+        let keypair = Keypair::new();
+        NewDepositWallet {
+            user_id,
+            wallet: keypair.pubkey(),
+            private_key: private_key_string_base58(&keypair),
+        }
+    }
 }
 
 #[derive(Default, Debug, Clone, Selectable, Queryable, Identifiable, Associations)]
@@ -38,9 +110,15 @@ pub struct Trader {
         deserialize_as = crate::utils::serdealizers::PubkeyString,
     )]
     pub wallet: Pubkey,
-    pub private_key: String,
+    /// None for the wallets derived from the strategy's agent seed
+    #[diesel(
+        serialize_as = crate::utils::serdealizers::OptionalPrivateKeyString,
+        deserialize_as = crate::utils::serdealizers::OptionalPrivateKeyString,
+    )]
+    pub private_key: Option<String>,
     pub created: chrono::NaiveDateTime,
     pub is_active: bool,
+    pub derivation_index: Option<i32>,
 }
 
 #[derive(Clone, Insertable, Associations, Debug)]
@@ -53,9 +131,15 @@ pub struct NewTrader {
         deserialize_as = crate::utils::serdealizers::PubkeyString,
     )]
     pub wallet: Pubkey,
-    pub private_key: String,
+    /// None for the wallets derived from the strategy's agent seed
+    #[diesel(
+        serialize_as = crate::utils::serdealizers::OptionalPrivateKeyString,
+        deserialize_as = crate::utils::serdealizers::OptionalPrivateKeyString,
+    )]
+    pub private_key: Option<String>,
     pub created: chrono::NaiveDateTime,
     pub is_active: bool,
+    pub derivation_index: Option<i32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Queryable, Identifiable, Selectable)]
@@ -63,13 +147,17 @@ pub struct NewTrader {
 #[table_name = "users"]
 pub struct BotUser {
     pub id: i32,
-    pub chat_id: i64,
-    pub tg_name: String,
+    pub chat_id: Option<i64>,
+    pub tg_name: Option<String>,
     #[diesel(
         serialize_as = crate::utils::serdealizers::PubkeyString,
         deserialize_as = crate::utils::serdealizers::PubkeyString,
     )]
     pub wallet_address: Pubkey,
+    #[diesel(
+        serialize_as = crate::utils::serdealizers::PrivateKeyString,
+        deserialize_as = crate::utils::serdealizers::PrivateKeyString,
+    )]
     pub wallet_private_key: String,
     pub created: chrono::NaiveDateTime,
     pub last_login: chrono::NaiveDateTime,
@@ -77,19 +165,25 @@ pub struct BotUser {
     pub last_name: Option<String>,
     pub is_active: bool,
     pub is_superuser: bool,
+    // the wallet an API user signs in with, telegram users have none
+    pub client_wallet: Option<String>,
 }
 
 #[derive(Debug, Clone, Insertable)]
 #[diesel(check_for_backend(Pg))]
 #[table_name = "users"]
 pub struct NewBotUser {
-    pub chat_id: i64,
-    pub tg_name: String,
+    pub chat_id: Option<i64>,
+    pub tg_name: Option<String>,
     #[diesel(
         serialize_as = crate::utils::serdealizers::PubkeyString,
         deserialize_as = crate::utils::serdealizers::PubkeyString,
     )]
     pub wallet_address: Pubkey,
+    #[diesel(
+        serialize_as = crate::utils::serdealizers::PrivateKeyString,
+        deserialize_as = crate::utils::serdealizers::PrivateKeyString,
+    )]
     pub wallet_private_key: String,
     pub created: chrono::NaiveDateTime,
     pub last_login: chrono::NaiveDateTime,
@@ -97,14 +191,16 @@ pub struct NewBotUser {
     pub last_name: Option<String>,
     pub is_active: bool,
     pub is_superuser: bool,
+    // the wallet an API user signs in with, telegram users have none
+    pub client_wallet: Option<String>,
 }
 
 impl NewBotUser {
     pub(crate) fn new_from_tg_user(user: &teloxide::types::User) -> Self {
         let keypair = Keypair::new();
         NewBotUser {
-            chat_id: user.id.0 as i64,
-            tg_name: user.username.clone().unwrap_or("".to_string()),
+            chat_id: Some(user.id.0 as i64),
+            tg_name: Some(user.username.clone().unwrap_or("".to_string())),
             wallet_address: keypair.pubkey(),
             wallet_private_key: private_key_string_base58(&keypair),
             created: chrono::Utc::now().naive_utc(),
@@ -113,9 +209,36 @@ impl NewBotUser {
             last_name: user.last_name.clone(),
             is_active: true,
             is_superuser: false,
+            client_wallet: None,
         }
     }
-    pub fn get_chat_id(&self) -> ChatId {
-        ChatId(self.chat_id)
+
+    /// A user signing in with their own wallet through the API, a new trading wallet is generated for them.
+    pub(crate) fn new_from_client_wallet(client_wallet: &Pubkey) -> Self {
+        let keypair = Keypair::new();
+        NewBotUser {
+            chat_id: None,
+            tg_name: None,
+            wallet_address: keypair.pubkey(),
+            wallet_private_key: private_key_string_base58(&keypair),
+            created: chrono::Utc::now().naive_utc(),
+            last_login: chrono::Utc::now().naive_utc(),
+            first_name: None,
+            last_name: None,
+            is_active: true,
+            is_superuser: false,
+            client_wallet: Some(client_wallet.to_string()),
+        }
+    }
+
+    pub fn get_chat_id(&self) -> Option<ChatId> {
+        self.chat_id.map(ChatId)
+    }
+}
+
+impl BotUser {
+    /// `None` for the users registered through the API.
+    pub fn get_chat_id(&self) -> Option<ChatId> {
+        self.chat_id.map(ChatId)
     }
 }
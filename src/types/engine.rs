@@ -1,4 +1,4 @@
-use anyhow::Result;
+use anyhow::{bail, Result};
 use async_trait::async_trait;
 use futures::Stream;
 use std::any::Any;
@@ -9,14 +9,23 @@ use std::sync::Arc;
 use tokio::sync::Mutex;
 use tokio::task::JoinHandle;
 use tokio_stream::StreamExt;
+use crate::dispatcher::EventDispatcher;
+use crate::types::routing::EventFilter;
+use crate::utils::bounded_queue::BoundedQueue;
 
 /// A stream of events emitted by a [Collector](Collector).
 pub type EventStream<'a, E> = Pin<Box<dyn Stream<Item=E> + Send + 'a>>;
 pub type StrategyId = i32;
+/// Actions shared by the executors, each action is popped by exactly one of them.
+pub type ActionQueue<A> = Arc<BoundedQueue<A>>;
 
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub enum StrategyStatus {
     Running(HashMap<String, String>),
+    /// Not starting new work, in-flight actions are still being tracked.
+    Paused(HashMap<String, String>),
+    /// `on_stop` was called, the strategy is winding down and will report `Stopped` once done.
+    Stopping,
     Stopped,
 }
 /// Collector trait, which defines a source of raw events, like a swap on a dex or a tick on a cex.
@@ -24,6 +33,11 @@ pub enum StrategyStatus {
 pub trait Collector<E>: Send + Sync {
     /// Returns the core event stream for the collector.
     async fn get_event_stream(&self) -> Result<EventStream<'_, E>>;
+
+    /// Name used in logs, health state and feed notifications.
+    fn name(&self) -> &'static str {
+        "collector"
+    }
 }
 
 /// Aggregator trait, which defines a higher level event stream by aggregating raw events like indicators, candles, time series transformations, statistical computations, analytics, ML preprocessors
@@ -46,9 +60,87 @@ pub trait Strategy<E, A>: Any + Send + Sync + Debug {
 
     async fn get_status(&self) -> StrategyStatus;
 
+    /// Stop starting new work, events keep flowing so that in-flight actions can settle.
+    async fn pause(&mut self) -> Result<()> {
+        bail!("Pausing is not supported by {:?}", self)
+    }
+
+    /// Continue after `pause`.
+    async fn resume(&mut self) -> Result<()> {
+        bail!("Resuming is not supported by {:?}", self)
+    }
+
+    /// Called once when the strategy is being dropped, returns the final actions to execute,
+    /// e.g. collecting funds back to the main wallet.
+    async fn on_stop(&mut self) -> Vec<A> {
+        vec![]
+    }
+
+    /// Apply a partial config update to the running strategy.
+    async fn update_config(&mut self, config: serde_json::Value) -> Result<()> {
+        bail!("Config update is not supported by {:?}", self)
+    }
+
+    /// Events the strategy wants to receive, re-read after every processed event so it can change over time.
+    async fn subscriptions(&self) -> Vec<EventFilter> {
+        vec![EventFilter::all()]
+    }
+
+    /// Name of the [StrategyKind](StrategyKind) the strategy belongs to, `None` for system strategies.
+    fn kind(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// Bot user owning the strategy, `None` for system strategies.
+    fn owner_user_id(&self) -> Option<i32> {
+        None
+    }
+
+    /// The instance (db row) the strategy was built from, handed over to its kind for persistence.
+    fn instance(&self) -> serde_json::Value {
+        serde_json::Value::Null
+    }
+
+    /// Whether the instance was marked completed, e.g. after a failure, the strategy is no longer active.
+    fn is_completed(&self) -> bool {
+        self.instance().get("completed_at").is_some_and(|completed_at| !completed_at.is_null())
+    }
+
     fn as_any(&self) -> &dyn Any;
 }
 
+/// Descriptor of a family of strategies, lets the manager and the menus handle strategies without knowing their types.
+#[async_trait]
+pub trait StrategyKind<E, A>: Send + Sync {
+    /// Unique name, matches [Strategy::kind](Strategy::kind).
+    fn name(&self) -> &'static str;
+
+    /// Config fields with their types, descriptions and optional defaults, the start menus are built from it.
+    fn config_schema(&self) -> serde_json::Value;
+
+    /// Label of the kind in the telegram and API start menus, `None` for kinds started by the bot
+    /// or with menus of their own.
+    fn title(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// Whether instances are stored in the db and resumed after a restart.
+    fn is_persistent(&self) -> bool;
+
+    /// Stores a new instance (id 0) or reuses the stored one, returns the strategy rebuilt with its id. The id of a
+    /// kind that isn't persistent is ignored, the manager gives the strategy a transient one.
+    async fn persist(
+        &self,
+        row: serde_json::Value,
+    ) -> Result<(StrategyId, Box<dyn Strategy<E, A> + Send + Sync>)>;
+
+    /// Marks the stored instance as completed.
+    async fn complete(&self, id: StrategyId) -> Result<()>;
+
+    /// Strategies to resume on startup.
+    async fn load_active(&self) -> Result<Vec<(StrategyId, Box<dyn Strategy<E, A> + Send + Sync>)>>;
+}
+
 #[async_trait]
 pub trait StrategyManager<E, A>: Send + Sync {
     async fn sync_state(&self) -> Result<()>;
@@ -57,16 +149,50 @@ pub trait StrategyManager<E, A>: Send + Sync {
         strategy: Box<dyn Strategy<E, A> + Send + Sync>,
     ) -> Result<StrategyId>;
 
+    /// Builds and starts a strategy of a registered kind from its instance row.
+    async fn start_strategy_of_kind(&self, kind: &str, row: serde_json::Value) -> Result<StrategyId>;
+
     async fn drop_strategy(&self, id: StrategyId) -> Result<()>;
 
+    async fn pause_strategy(&self, id: StrategyId) -> Result<()>;
+
+    async fn resume_strategy(&self, id: StrategyId) -> Result<()>;
+
+    async fn update_strategy_config(&self, id: StrategyId, config: serde_json::Value) -> Result<()>;
+
     async fn get_active_strategies(
         &self,
     ) -> HashMap<StrategyId, Arc<Mutex<Box<dyn Strategy<E, A> + Send + Sync>>>>;
 
+    async fn get_user_strategies(
+        &self,
+        user_id: i32,
+    ) -> HashMap<StrategyId, Arc<Mutex<Box<dyn Strategy<E, A> + Send + Sync>>>> {
+        let mut user_strategies = HashMap::new();
+        for (id, strategy) in self.get_active_strategies().await {
+            if strategy.lock().await.owner_user_id() == Some(user_id) {
+                user_strategies.insert(id, strategy);
+            }
+        }
+        user_strategies
+    }
+
+    /// Status of a running strategy, managers can enrich it with their own counters like the queue lag.
+    async fn get_strategy_status(&self, id: StrategyId) -> Option<StrategyStatus> {
+        match self.get_active_strategies().await.get(&id) {
+            Some(strategy) => Some(strategy.lock().await.get_status().await),
+            None => None,
+        }
+    }
+
+    fn get_strategy_kind(&self, name: &str) -> Option<Arc<dyn StrategyKind<E, A>>>;
+
+    fn get_strategy_kinds(&self) -> Vec<Arc<dyn StrategyKind<E, A>>>;
+
     async fn run_strategy_manager(
         &self,
-        event_sender: tokio::sync::broadcast::Sender<E>,
-        action_sender: tokio::sync::broadcast::Sender<A>,
+        dispatcher: Arc<EventDispatcher<E>>,
+        action_queue: ActionQueue<A>,
     ) -> Result<()>;
 }
 
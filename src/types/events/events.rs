@@ -3,6 +3,7 @@ use crate::aggregators::period_indicators::{TickBarValue, TickBarWithPeriod};
 use crate::schema::*;
 use crate::types::actions::SolanaAction;
 use crate::types::pool::{RaydiumPool, RaydiumPoolPriceUpdate, RaydiumSwapEvent};
+use crate::types::routing::Routable;
 use crate::collectors::tx_stream::types::AccountPretty;
 use crate::utils::serdealizers::JsonbWrapper;
 use chrono::{DateTime, Utc};
@@ -45,6 +46,8 @@ pub enum BlockchainEvent {
     RaydiumHeartbeatPriceUpdate(RaydiumPoolPriceUpdate),
     RaydiumSwapEvent(RaydiumSwapEvent),
     RaydiumNewPoolEvent(RaydiumPool, RaydiumPoolPriceUpdate),
+    /// The pool events of these processed slots were on a dropped fork, a corrected price follows.
+    PoolEventsRolledBack(Pubkey, Vec<u64>),
     //below not implemented
     PumpFunCurveUpdate(RaydiumSwapEvent),
     PumpFunSwapDetails(RaydiumSwapEvent),
@@ -99,6 +102,15 @@ pub enum ExecutionResult {
 pub enum SystemEvent {
     DestroyStrategy(i32),
     Stop,
+    /// A collector failed and is being restarted, its events are missing until `FeedUp`.
+    FeedDown(String),
+    FeedUp(String),
+}
+
+impl From<SystemEvent> for BotEvent {
+    fn from(event: SystemEvent) -> Self {
+        BotEvent::SystemEvent(event)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -153,18 +165,37 @@ pub struct BotEventModel {
     pub event_type: String,
     #[diesel(serialize_as = JsonbWrapper<BotEvent>)]
     pub event_data: BotEvent,
+    // routing keys, the events of a strategy are looked up by them
+    pub pool: Option<String>,
+    pub wallets: Vec<String>,
 }
 
 impl From<BotEvent> for BotEventModel {
     fn from(event: BotEvent) -> Self {
+        let route = event.route();
         Self {
             timestamp: Utc::now(),
-            event_type: format!("{:?}", event),
+            event_type: route.event_type.to_string(),
             event_data: event,
+            pool: route.pool.map(|pool| pool.to_string()),
+            wallets: route.wallets.iter().map(|wallet| wallet.to_string()).collect(),
         }
     }
 }
 
+/// A stored event as read back from the db.
+#[derive(Debug, Clone, Queryable, Selectable)]
+#[diesel(table_name = bot_events)]
+#[diesel(check_for_backend(Pg))]
+pub struct BotEventRecord {
+    pub id: i64,
+    pub timestamp: DateTime<Utc>,
+    pub event_type: String,
+    pub event_data: JsonbWrapper<serde_json::Value>,
+    pub pool: Option<String>,
+    pub wallets: Vec<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExecutionReceipt {
     //todo mov all that to Arc<Mutex<SolanaAction>>
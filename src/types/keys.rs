@@ -1,3 +1,4 @@
+use crate::utils::crypto::decrypt_private_key;
 use crate::utils::keys::clone_keypair;
 use anyhow::{bail, Result};
 use solana_sdk::bs58;
@@ -11,6 +12,7 @@ use diesel::serialize::{IsNull, Output, ToSql};
 use diesel::{serialize, sql_types};
 use serde::{Serialize, Serializer};
 use tracing::error;
+use zeroize::Zeroizing;
 
 pub struct KeypairClonable(Keypair);
 
@@ -38,6 +40,12 @@ impl Debug for KeypairClonable {
     }
 }
 
+impl From<Keypair> for KeypairClonable {
+    fn from(keypair: Keypair) -> Self {
+        KeypairClonable(keypair)
+    }
+}
+
 impl From<KeypairClonable> for Keypair {
     fn from(keypair_clonable: KeypairClonable) -> Keypair {
         keypair_clonable.0
@@ -67,8 +75,10 @@ impl KeypairClonable {
         KeypairClonable(Keypair::new())
     }
 
+    /// Accepts a base58 private key or one encrypted with the master key.
     pub fn new_from_privkey(privkey: &str) -> Result<Self> {
-        let keypair = keypair_from_base58_string(privkey)?;
+        let privkey = Zeroizing::new(decrypt_private_key(privkey)?);
+        let keypair = keypair_from_base58_string(&privkey)?;
         Ok(KeypairClonable(keypair))
         // KeypairClonable(Keypair::from_base58_string(privkey))
     }
@@ -82,6 +92,62 @@ impl KeypairClonable {
     }
 }
 
+/// The wallet of an action: its in-memory key, or its pubkey alone when the signing service holds the key.
+#[derive(Clone)]
+pub enum ActionWallet {
+    Keypair(KeypairClonable),
+    Pubkey(Pubkey),
+}
+
+impl ActionWallet {
+    pub fn pubkey(&self) -> Pubkey {
+        match self {
+            ActionWallet::Keypair(keypair) => keypair.pubkey(),
+            ActionWallet::Pubkey(pubkey) => *pubkey,
+        }
+    }
+
+    pub fn keypair(&self) -> Option<&KeypairClonable> {
+        match self {
+            ActionWallet::Keypair(keypair) => Some(keypair),
+            ActionWallet::Pubkey(_) => None,
+        }
+    }
+}
+
+impl From<KeypairClonable> for ActionWallet {
+    fn from(keypair: KeypairClonable) -> Self {
+        ActionWallet::Keypair(keypair)
+    }
+}
+
+impl From<Pubkey> for ActionWallet {
+    fn from(pubkey: Pubkey) -> Self {
+        ActionWallet::Pubkey(pubkey)
+    }
+}
+
+impl From<ActionWallet> for String {
+    fn from(wallet: ActionWallet) -> Self {
+        wallet.pubkey().to_string()
+    }
+}
+
+impl Debug for ActionWallet {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.pubkey())
+    }
+}
+
+impl Serialize for ActionWallet {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.pubkey().to_string())
+    }
+}
+
 fn keypair_from_base58_string(privkey: &str) -> Result<Keypair> {
     // Attempt to decode the base58 string
     let secret_key_bytes = match bs58::decode(privkey).into_vec() {
@@ -0,0 +1,137 @@
+use crate::schema::*;
+use crate::types::bot_user::BotUser;
+use crate::types::engine::StrategyId;
+use anyhow::{bail, Result};
+use diesel::pg::Pg;
+use diesel::prelude::*;
+use diesel::sql_types::*;
+use diesel_derives::{Associations, Insertable, Queryable, Selectable};
+use serde_derive::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+
+#[derive(
+    Default,
+    Debug,
+    Clone,
+    Serialize,
+    Deserialize,
+    Queryable,
+    Selectable,
+    Associations,
+)]
+#[diesel(check_for_backend(Pg))]
+#[serde(rename_all = "lowercase")]
+#[belongs_to(BotUser, foreign_key = "user_id")]
+#[table_name = "marketmakingstrategyinstances"]
+pub struct MarketMakingStrategyInstance {
+    pub id: StrategyId,
+    pub user_id: i32,
+    #[diesel(
+        sql_type = Nullable < Text >,
+        serialize_as = crate::utils::serdealizers::PubkeyString,
+        deserialize_as = crate::utils::serdealizers::PubkeyString
+    )]
+    pub target_pool: Pubkey,
+    pub started_at: chrono::NaiveDateTime,
+    pub completed_at: Option<chrono::NaiveDateTime>,
+    /// Length of the EMA giving the fair price and of the Bollinger band, in ticks
+    pub indicator_period: i32,
+    /// Distance from the fair price to the first level of each ladder, in percent
+    pub spread_pct: f64,
+    /// Levels of each ladder, an order is sent for every level the price crosses
+    pub ladder_levels: i32,
+    pub order_size_sol: f64,
+    /// Most the tokens held by the strategy are worth, the buys stop there
+    pub max_inventory_sol: f64,
+    #[serde(default)]
+    pub bought_sol: f64,
+    #[serde(default)]
+    pub sold_sol: f64,
+}
+
+#[derive(Debug, Clone, Insertable, Associations)]
+#[diesel(check_for_backend(Pg))]
+#[belongs_to(BotUser, foreign_key = "user_id")]
+#[table_name = "marketmakingstrategyinstances"]
+pub struct NewMarketMakingStrategyInstance {
+    pub user_id: i32,
+    #[diesel(
+        sql_type = Nullable < Text >,
+        serialize_as = crate::utils::serdealizers::PubkeyString,
+        deserialize_as = crate::utils::serdealizers::PubkeyString
+    )]
+    pub target_pool: Pubkey,
+    pub started_at: chrono::NaiveDateTime,
+    pub completed_at: Option<chrono::NaiveDateTime>,
+    pub indicator_period: i32,
+    pub spread_pct: f64,
+    pub ladder_levels: i32,
+    pub order_size_sol: f64,
+    pub max_inventory_sol: f64,
+}
+
+impl From<&MarketMakingStrategyInstance> for NewMarketMakingStrategyInstance {
+    fn from(new: &MarketMakingStrategyInstance) -> Self {
+        NewMarketMakingStrategyInstance {
+            user_id: new.user_id,
+            target_pool: new.target_pool,
+            started_at: chrono::Utc::now().naive_utc(),
+            completed_at: new.completed_at,
+            indicator_period: new.indicator_period,
+            spread_pct: new.spread_pct,
+            ladder_levels: new.ladder_levels,
+            order_size_sol: new.order_size_sol,
+            max_inventory_sol: new.max_inventory_sol,
+        }
+    }
+}
+
+impl MarketMakingStrategyInstance {
+    pub fn validate(&self) -> Result<()> {
+        if !(self.order_size_sol > 0.0) || !self.order_size_sol.is_finite() {
+            bail!("The order size must be a positive number of SOL");
+        }
+        if !(self.spread_pct > 0.0 && self.spread_pct < 100.0) {
+            bail!("The spread must be between 0 and 100 percent");
+        }
+        if !(self.max_inventory_sol >= 0.0) || !self.max_inventory_sol.is_finite() {
+            bail!("The max inventory must be a number of SOL");
+        }
+        if self.ladder_levels < 1 {
+            bail!("The ladders need at least one level");
+        }
+        if self.indicator_period < 1 {
+            bail!("The indicator period must be positive");
+        }
+        Ok(())
+    }
+
+    /// Applies the fields set in the update, the pool and the owner are kept.
+    pub fn update(&mut self, config: &MarketMakingConfigArgs) -> Result<()> {
+        let mut updated = self.clone();
+        if let Some(spread_pct) = config.spread_pct {
+            updated.spread_pct = spread_pct;
+        }
+        if let Some(ladder_levels) = config.ladder_levels {
+            updated.ladder_levels = ladder_levels;
+        }
+        if let Some(order_size_sol) = config.order_size_sol {
+            updated.order_size_sol = order_size_sol;
+        }
+        if let Some(max_inventory_sol) = config.max_inventory_sol {
+            updated.max_inventory_sol = max_inventory_sol;
+        }
+        updated.validate()?;
+        *self = updated;
+        Ok(())
+    }
+}
+
+/// Changes to a running market making strategy.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MarketMakingConfigArgs {
+    pub spread_pct: Option<f64>,
+    pub ladder_levels: Option<i32>,
+    pub order_size_sol: Option<f64>,
+    pub max_inventory_sol: Option<f64>,
+}
@@ -2,7 +2,11 @@ pub mod actions;
 pub mod engine;
 pub mod events;
 pub mod keys;
+pub mod market_making_strategy;
 pub mod pool;
+pub mod routing;
 pub mod bot_user;
 pub mod sniping_strategy;
+pub mod subscription;
 pub mod volume_strategy;
+pub mod withdrawal;
@@ -1,11 +1,11 @@
-use crate::schema::prices;
+use crate::schema::{prices, swaps};
 use crate::solana::tx_parser::Swap;
 use chrono::{DateTime, NaiveDateTime, Utc};
 use diesel::backend::Backend;
 use diesel::deserialize::FromSql;
 use diesel::pg::{Pg, PgValue};
 use diesel::serialize::{IsNull, Output, ToSql};
-use diesel::{deserialize, serialize, sql_types, Insertable};
+use diesel::{deserialize, serialize, sql_types, Insertable, Queryable, Selectable};
 use diesel_derives::{AsExpression, FromSqlRow};
 use serde_derive::{Deserialize, Serialize};
 use solana_farm_client::raydium_sdk::{get_associated_authority, LiquidityPoolKeys};
@@ -33,9 +33,82 @@ pub struct RaydiumSwapEvent {
     pub price: f64,
     //todo currently just a quote_amount but should be denominated in USD
     pub volume: f64,
+    pub slot: u64,
+    // index in the block, unknown for the transactions fetched over RPC
+    pub tx_index: Option<u64>,
     pub created_at: chrono::DateTime<Utc>,
 }
 
+/// A swap as stored in the `swaps` hypertable.
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = swaps)]
+pub struct NewSwap {
+    pub created_at: DateTime<Utc>,
+    pub pool: String,
+    pub signature: String,
+    pub is_buy: bool,
+    pub base_amount: f64,
+    pub quote_amount: f64,
+    pub price: f64,
+    pub base_reserve: f64,
+    pub quote_reserve: f64,
+    pub slot: i64,
+    pub tx_index: Option<i64>,
+}
+
+impl From<&RaydiumSwapEvent> for NewSwap {
+    fn from(swap: &RaydiumSwapEvent) -> Self {
+        Self {
+            created_at: swap.created_at,
+            pool: swap.pool.to_string(),
+            signature: swap.signature.to_string(),
+            is_buy: matches!(swap.trade_direction, TradeDirection::Buy),
+            base_amount: swap.base_amount,
+            quote_amount: swap.quote_amount,
+            price: swap.price,
+            base_reserve: swap.price_update.base_reserve,
+            quote_reserve: swap.price_update.quote_reserve,
+            slot: swap.slot as i64,
+            tx_index: swap.tx_index.map(|index| index as i64),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Queryable, Selectable)]
+#[diesel(table_name = swaps)]
+#[diesel(check_for_backend(Pg))]
+pub struct SwapRecord {
+    pub id: i64,
+    pub created_at: DateTime<Utc>,
+    pub pool: String,
+    pub signature: String,
+    pub is_buy: bool,
+    /// Tokens and SOL traded, in ui amounts.
+    pub base_amount: f64,
+    pub quote_amount: f64,
+    pub price: f64,
+    pub base_reserve: f64,
+    pub quote_reserve: f64,
+    pub slot: i64,
+    pub tx_index: Option<i64>,
+}
+
+impl SwapRecord {
+    /// SOL per token before the swap, the reserves are from after it.
+    pub fn price_before(&self) -> Option<f64> {
+        let (base_reserve, quote_reserve) = if self.is_buy {
+            (self.base_reserve + self.base_amount, self.quote_reserve - self.quote_amount)
+        } else {
+            (self.base_reserve - self.base_amount, self.quote_reserve + self.quote_amount)
+        };
+        (base_reserve > 0.0 && quote_reserve > 0.0).then(|| quote_reserve / base_reserve)
+    }
+
+    pub fn price_after(&self) -> Option<f64> {
+        (self.base_reserve > 0.0).then(|| self.quote_reserve / self.base_reserve)
+    }
+}
+
 /// A new block event, containing the block number and hash.
 #[derive(Debug, Clone, Serialize, Deserialize, Insertable, Default)]
 #[diesel(table_name = prices)]
@@ -50,6 +123,21 @@ pub struct RaydiumPoolPriceUpdate {
     pub base_reserve: f64,
     pub quote_reserve: f64,
     pub created_at: NaiveDateTime,
+    // chain position, none for the RPC polled prices; signed for the db
+    pub slot: Option<i64>,
+    pub tx_index: Option<i64>,
+}
+
+impl RaydiumPoolPriceUpdate {
+    pub fn with_position(mut self, slot: u64, tx_index: Option<u64>) -> Self {
+        self.slot = Some(slot as i64);
+        self.tx_index = tx_index.map(|index| index as i64);
+        self
+    }
+
+    pub fn position(&self) -> Option<(u64, Option<u64>)> {
+        self.slot.map(|slot| (slot as u64, self.tx_index.map(|index| index as u64)))
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default, Hash, PartialEq, Eq)]
@@ -0,0 +1,230 @@
+use crate::config::constants::DEFAULT_DROP_OLDEST_EVENT_TYPES;
+use crate::types::events::{BlockchainEvent, BotEvent, DerivedEvent};
+use crate::utils::bounded_queue::DeliveryPolicy;
+use serde::Deserialize;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashSet;
+use strum_macros::Display;
+
+/// Event types the dispatcher indexes subscriptions by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Display, Deserialize)]
+pub enum EventType {
+    HeartBeat,
+    AccountUpdate,
+    Deposit,
+    Withdrawal,
+    ExecutionReceipt,
+    PoolPriceUpdate,
+    Swap,
+    NewPool,
+    Rollback,
+    Indicator,
+    Bar,
+    ExecutionResult,
+    System,
+    Other,
+}
+
+impl EventType {
+    pub const ALL: [EventType; 14] = [
+        EventType::HeartBeat,
+        EventType::AccountUpdate,
+        EventType::Deposit,
+        EventType::Withdrawal,
+        EventType::ExecutionReceipt,
+        EventType::PoolPriceUpdate,
+        EventType::Swap,
+        EventType::NewPool,
+        EventType::Rollback,
+        EventType::Indicator,
+        EventType::Bar,
+        EventType::ExecutionResult,
+        EventType::System,
+        EventType::Other,
+    ];
+}
+
+/// Routing keys of an event, a missing key means the event is not specific to that dimension.
+#[derive(Debug, Clone)]
+pub struct EventRoute {
+    pub event_type: EventType,
+    pub pool: Option<Pubkey>,
+    pub wallets: Vec<Pubkey>,
+    pub mint: Option<Pubkey>,
+}
+
+impl EventRoute {
+    pub fn new(event_type: EventType) -> Self {
+        Self {
+            event_type,
+            pool: None,
+            wallets: vec![],
+            mint: None,
+        }
+    }
+
+    pub fn with_pool(mut self, pool: Pubkey) -> Self {
+        self.pool = Some(pool);
+        self
+    }
+
+    pub fn with_wallet(mut self, wallet: Pubkey) -> Self {
+        self.wallets.push(wallet);
+        self
+    }
+
+    pub fn with_mint(mut self, mint: Pubkey) -> Self {
+        self.mint = Some(mint);
+        self
+    }
+}
+
+/// Events that can be routed by the [EventDispatcher](crate::dispatcher::EventDispatcher).
+pub trait Routable {
+    fn route(&self) -> EventRoute;
+}
+
+/// A subscription filter, `None` matches anything in that dimension.
+/// Events that don't carry a key (like heartbeats for pools) pass the key filter.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EventFilter {
+    pub event_types: Option<HashSet<EventType>>,
+    pub pools: Option<HashSet<Pubkey>>,
+    pub wallets: Option<HashSet<Pubkey>>,
+    pub mints: Option<HashSet<Pubkey>>,
+}
+
+impl EventFilter {
+    /// Matches every event.
+    pub fn all() -> Self {
+        Self::default()
+    }
+
+    pub fn of_types(event_types: &[EventType]) -> Self {
+        Self {
+            event_types: Some(event_types.iter().cloned().collect()),
+            ..Self::default()
+        }
+    }
+
+    pub fn with_pools(mut self, pools: impl IntoIterator<Item=Pubkey>) -> Self {
+        self.pools = Some(pools.into_iter().collect());
+        self
+    }
+
+    pub fn with_wallets(mut self, wallets: impl IntoIterator<Item=Pubkey>) -> Self {
+        self.wallets = Some(wallets.into_iter().collect());
+        self
+    }
+
+    pub fn with_mints(mut self, mints: impl IntoIterator<Item=Pubkey>) -> Self {
+        self.mints = Some(mints.into_iter().collect());
+        self
+    }
+
+    pub fn matches_type(&self, event_type: &EventType) -> bool {
+        self.event_types.as_ref().map_or(true, |types| types.contains(event_type))
+    }
+
+    pub fn matches(&self, route: &EventRoute) -> bool {
+        self.matches_type(&route.event_type)
+            && match (&self.pools, &route.pool) {
+            (Some(pools), Some(pool)) => pools.contains(pool),
+            _ => true,
+        }
+            && match (&self.mints, &route.mint) {
+            (Some(mints), Some(mint)) => mints.contains(mint),
+            _ => true,
+        }
+            && match &self.wallets {
+            Some(wallets) if !route.wallets.is_empty() => route.wallets.iter().any(|w| wallets.contains(w)),
+            _ => true,
+        }
+    }
+}
+
+/// Delivery policy of the event types when a queue is full, stale ticks can go, actions' receipts can't.
+#[derive(Debug, Clone)]
+pub struct QueuePolicy {
+    drop_oldest: HashSet<EventType>,
+}
+
+impl Default for QueuePolicy {
+    fn default() -> Self {
+        Self::new(DEFAULT_DROP_OLDEST_EVENT_TYPES)
+    }
+}
+
+impl QueuePolicy {
+    pub fn new(drop_oldest: impl IntoIterator<Item=EventType>) -> Self {
+        Self {
+            drop_oldest: drop_oldest.into_iter().collect(),
+        }
+    }
+
+    pub fn policy_of(&self, route: &EventRoute) -> DeliveryPolicy {
+        if self.drop_oldest.contains(&route.event_type) {
+            DeliveryPolicy::DropOldest
+        } else {
+            DeliveryPolicy::NeverDrop
+        }
+    }
+}
+
+impl Routable for BotEvent {
+    fn route(&self) -> EventRoute {
+        match self {
+            BotEvent::HeartBeat(..) => EventRoute::new(EventType::HeartBeat),
+            BotEvent::BlockchainEvent(event) => match event {
+                BlockchainEvent::AccountUpdate(account) => {
+                    let route = EventRoute::new(EventType::AccountUpdate).with_wallet(account.pubkey);
+                    match &account.token_unpacked_data {
+                        Some(token_account) => route.with_wallet(token_account.owner).with_mint(token_account.mint),
+                        None => route,
+                    }
+                }
+                BlockchainEvent::Deposit(_, wallet, _) => EventRoute::new(EventType::Deposit).with_wallet(*wallet),
+                BlockchainEvent::Withdrawal(_, wallet, _) => EventRoute::new(EventType::Withdrawal).with_wallet(*wallet),
+                BlockchainEvent::ExecutionReceipt(..) => EventRoute::new(EventType::ExecutionReceipt),
+                BlockchainEvent::RaydiumHeartbeatPriceUpdate(price_update) => {
+                    EventRoute::new(EventType::PoolPriceUpdate).with_pool(price_update.pool)
+                }
+                BlockchainEvent::RaydiumSwapEvent(swap) => EventRoute::new(EventType::Swap).with_pool(swap.pool),
+                BlockchainEvent::RaydiumNewPoolEvent(pool, _) => {
+                    EventRoute::new(EventType::NewPool).with_pool(pool.id).with_mint(pool.base_mint)
+                }
+                BlockchainEvent::PoolEventsRolledBack(pool, _) => EventRoute::new(EventType::Rollback).with_pool(*pool),
+                _ => EventRoute::new(EventType::Other),
+            },
+            BotEvent::DerivedEvent(event) => match event {
+                DerivedEvent::TickIndicatorEvent(pool, _) => EventRoute::new(EventType::Indicator).with_pool(*pool),
+                DerivedEvent::TickBarEvent(pool, _) => EventRoute::new(EventType::Bar).with_pool(*pool),
+            },
+            BotEvent::ExecutionResult(..) => EventRoute::new(EventType::ExecutionResult),
+            BotEvent::SystemEvent(..) => EventRoute::new(EventType::System),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pool_filter_passes_non_pool_events() {
+        let pool = Pubkey::new_unique();
+        let filter = EventFilter::all().with_pools([pool]);
+        assert!(filter.matches(&EventRoute::new(EventType::HeartBeat)));
+        assert!(filter.matches(&EventRoute::new(EventType::Swap).with_pool(pool)));
+        assert!(!filter.matches(&EventRoute::new(EventType::Swap).with_pool(Pubkey::new_unique())));
+    }
+
+    #[test]
+    fn test_type_and_wallet_filter() {
+        let wallet = Pubkey::new_unique();
+        let filter = EventFilter::of_types(&[EventType::Deposit]).with_wallets([wallet]);
+        assert!(filter.matches(&EventRoute::new(EventType::Deposit).with_wallet(wallet)));
+        assert!(!filter.matches(&EventRoute::new(EventType::Deposit).with_wallet(Pubkey::new_unique())));
+        assert!(!filter.matches(&EventRoute::new(EventType::Withdrawal).with_wallet(wallet)));
+    }
+}
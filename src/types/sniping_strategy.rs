@@ -7,6 +7,7 @@ use diesel_derives::{Associations, Identifiable, Insertable, Queryable, Selectab
 use solana_sdk::signature::{Keypair, Signer};
 use crate::schema::*;
 use crate::types::engine::StrategyId;
+use crate::types::keys::KeypairClonable;
 use crate::types::bot_user::{BotUser};
 use crate::types::volume_strategy::{NewVolumeStrategyInstance, VolumeStrategyInstance};
 
@@ -28,6 +29,10 @@ pub struct SnipingStrategyInstance {
     pub user_id: i32,
     pub started_at: chrono::NaiveDateTime,
     pub completed_at: Option<chrono::NaiveDateTime>,
+    #[diesel(
+        serialize_as = crate::utils::serdealizers::PrivateKeyString,
+        deserialize_as = crate::utils::serdealizers::PrivateKeyString,
+    )]
     pub sniper_private_key: String,
     pub size_sol: f64,
     pub stop_loss_percent_move_down: f64,
@@ -47,7 +52,7 @@ impl Debug for SnipingStrategyInstance {
                self.user_id,
                self.started_at,
                self.completed_at,
-               Keypair::from_base58_string(&self.sniper_private_key).pubkey(),
+               KeypairClonable::new_from_privkey(&self.sniper_private_key).map(|keypair| keypair.pubkey()).ok(),
                self.size_sol,
                self.stop_loss_percent_move_down,
                self.take_profit_percent_move_up,
@@ -68,6 +73,10 @@ pub struct NewSnipingStrategyInstance {
     pub user_id: i32,
     pub started_at: chrono::NaiveDateTime,
     pub completed_at: Option<chrono::NaiveDateTime>,
+    #[diesel(
+        serialize_as = crate::utils::serdealizers::PrivateKeyString,
+        deserialize_as = crate::utils::serdealizers::PrivateKeyString,
+    )]
     pub sniper_private_key: String,
     pub size_sol: f64,
     pub stop_loss_percent_move_down: f64,
@@ -103,3 +112,24 @@ impl From<&NewSnipingStrategyInstance> for SnipingStrategyInstance {
         }
     }
 }
+
+impl From<&SnipingStrategyInstance> for NewSnipingStrategyInstance {
+    fn from(instance: &SnipingStrategyInstance) -> Self {
+        NewSnipingStrategyInstance {
+            user_id: instance.user_id,
+            started_at: chrono::Utc::now().naive_utc(),
+            completed_at: instance.completed_at,
+            sniper_private_key: instance.sniper_private_key.clone(),
+            size_sol: instance.size_sol,
+            stop_loss_percent_move_down: instance.stop_loss_percent_move_down,
+            take_profit_percent_move_up: instance.take_profit_percent_move_up,
+            force_exit_horizon_s: instance.force_exit_horizon_s,
+            max_simultaneous_snipes: instance.max_simultaneous_snipes,
+            min_pool_liquidity_sol: instance.min_pool_liquidity_sol,
+            skip_pump_fun: instance.skip_pump_fun,
+            skip_mintable: instance.skip_mintable,
+            buy_delay_ms: instance.buy_delay_ms,
+            skip_if_price_drops_percent: instance.skip_if_price_drops_percent,
+        }
+    }
+}
@@ -0,0 +1,290 @@
+use crate::config::settings::SubscriptionConfig;
+use crate::schema::*;
+use crate::strategies::{MARKET_MAKING_STRATEGY_KIND, VOLUME_STRATEGY_KIND};
+use chrono::{DateTime, Duration, Utc};
+use diesel::pg::Pg;
+use diesel::prelude::*;
+use serde_derive::{Deserialize, Serialize};
+use strum_macros::{Display, EnumString};
+
+/// Plans as numbered in api.md.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum SubscriptionPlan {
+    Free = 0,
+    VolumeOnly = 1,
+    MarketMakingOnly = 2,
+    Premium = 3,
+}
+
+impl TryFrom<i32> for SubscriptionPlan {
+    type Error = anyhow::Error;
+
+    fn try_from(plan: i32) -> Result<Self, Self::Error> {
+        match plan {
+            0 => Ok(SubscriptionPlan::Free),
+            1 => Ok(SubscriptionPlan::VolumeOnly),
+            2 => Ok(SubscriptionPlan::MarketMakingOnly),
+            3 => Ok(SubscriptionPlan::Premium),
+            _ => Err(anyhow::anyhow!("Unknown subscription plan {}", plan)),
+        }
+    }
+}
+
+impl SubscriptionPlan {
+    /// Kinds of strategies that need a plan, the others are free.
+    pub fn is_paid_kind(kind: &str) -> bool {
+        kind == VOLUME_STRATEGY_KIND || kind == MARKET_MAKING_STRATEGY_KIND
+    }
+
+    pub fn allows_kind(&self, kind: &str) -> bool {
+        match kind {
+            VOLUME_STRATEGY_KIND => matches!(self, SubscriptionPlan::VolumeOnly | SubscriptionPlan::Premium),
+            MARKET_MAKING_STRATEGY_KIND => matches!(self, SubscriptionPlan::MarketMakingOnly | SubscriptionPlan::Premium),
+            _ => true,
+        }
+    }
+
+    /// SOL charged every period.
+    pub fn price_sol(&self, config: &SubscriptionConfig) -> f64 {
+        match self {
+            SubscriptionPlan::Free => 0.0,
+            SubscriptionPlan::VolumeOnly => config.volume_plan_price_sol,
+            SubscriptionPlan::MarketMakingOnly => config.market_making_plan_price_sol,
+            SubscriptionPlan::Premium => config.premium_plan_price_sol,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, EnumString)]
+#[strum(serialize_all = "lowercase")]
+pub enum SubscriptionStatus {
+    Active,
+    /// A payment was missed, the strategies keep running until `grace_until`.
+    Grace,
+    /// Not paid after the grace period, the plan is back to free.
+    Suspended,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, EnumString)]
+#[strum(serialize_all = "lowercase")]
+pub enum PaymentStatus {
+    Pending,
+    Paid,
+    Failed,
+}
+
+/// What the billing has to do with a subscription.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BillingDecision {
+    NotDue,
+    Charge,
+    /// Can't be paid now, retried until the given time.
+    EnterGrace(DateTime<Utc>),
+    Suspend,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(check_for_backend(Pg))]
+#[diesel(table_name = subscriptions)]
+pub struct Subscription {
+    pub id: i32,
+    pub user_id: i32,
+    pub token_mint: String,
+    pub plan: i32,
+    pub status: String,
+    pub subscribed_at: DateTime<Utc>,
+    // none until the first payment
+    pub paid_until: Option<DateTime<Utc>>,
+    pub grace_until: Option<DateTime<Utc>>,
+}
+
+impl Subscription {
+    pub fn get_plan(&self) -> SubscriptionPlan {
+        SubscriptionPlan::try_from(self.plan).unwrap_or(SubscriptionPlan::Free)
+    }
+
+    pub fn get_status(&self) -> SubscriptionStatus {
+        self.status.parse().unwrap_or(SubscriptionStatus::Active)
+    }
+
+    /// The plan the user can use now: a paid period or the grace period after it, nothing before the first
+    /// payment is settled or once suspended.
+    pub fn effective_plan(&self, now: DateTime<Utc>) -> SubscriptionPlan {
+        match self.get_status() {
+            SubscriptionStatus::Suspended => SubscriptionPlan::Free,
+            // a plan never paid has no grace period to use
+            SubscriptionStatus::Grace if self.paid_until.is_some() => self.get_plan(),
+            SubscriptionStatus::Grace => SubscriptionPlan::Free,
+            SubscriptionStatus::Active if self.paid_until.is_some_and(|paid_until| paid_until > now) => self.get_plan(),
+            SubscriptionStatus::Active => SubscriptionPlan::Free,
+        }
+    }
+
+    /// The status, paid period and grace period of this subscription subscribed again to `plan`: the paid period
+    /// carries over to a cheaper plan, a better or a reactivated plan is charged right away. The grace period is
+    /// kept, running or over, subscribing again doesn't start another one.
+    pub fn resubscribe(
+        &self,
+        plan: SubscriptionPlan,
+    ) -> (SubscriptionStatus, Option<DateTime<Utc>>, Option<DateTime<Utc>>) {
+        let status = match self.get_status() {
+            SubscriptionStatus::Grace => SubscriptionStatus::Grace,
+            SubscriptionStatus::Active | SubscriptionStatus::Suspended => SubscriptionStatus::Active,
+        };
+        let paid_until = self
+            .paid_until
+            .filter(|_| self.get_status() != SubscriptionStatus::Suspended && plan <= self.get_plan());
+        (status, paid_until, self.grace_until)
+    }
+
+    pub fn billing_decision(&self, now: DateTime<Utc>, can_pay: bool, grace_period: Duration) -> BillingDecision {
+        if self.get_plan() == SubscriptionPlan::Free
+            || self.get_status() == SubscriptionStatus::Suspended
+            || self.paid_until.is_some_and(|paid_until| paid_until > now)
+        {
+            return BillingDecision::NotDue;
+        }
+        match self.grace_until {
+            _ if can_pay => BillingDecision::Charge,
+            Some(grace_until) if now >= grace_until => BillingDecision::Suspend,
+            Some(_) => BillingDecision::NotDue,
+            None => BillingDecision::EnterGrace(now + grace_period),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[diesel(check_for_backend(Pg))]
+#[diesel(table_name = subscriptions)]
+pub struct NewSubscription {
+    pub user_id: i32,
+    pub token_mint: String,
+    pub plan: i32,
+    pub status: String,
+    pub subscribed_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(check_for_backend(Pg))]
+#[diesel(table_name = subscription_payments)]
+pub struct SubscriptionPayment {
+    pub id: i32,
+    pub subscription_id: i32,
+    pub action_uuid: String,
+    pub plan: i32,
+    pub amount_sol: f64,
+    pub status: String,
+    pub tx_hash: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub paid_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[diesel(check_for_backend(Pg))]
+#[diesel(table_name = subscription_payments)]
+pub struct NewSubscriptionPayment {
+    pub subscription_id: i32,
+    pub action_uuid: String,
+    pub plan: i32,
+    pub amount_sol: f64,
+    pub status: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn subscription(plan: SubscriptionPlan, paid_until: Option<DateTime<Utc>>, grace_until: Option<DateTime<Utc>>) -> Subscription {
+        Subscription {
+            id: 1,
+            user_id: 1,
+            token_mint: "mint".to_string(),
+            plan: plan as i32,
+            status: SubscriptionStatus::Active.to_string(),
+            subscribed_at: Utc::now(),
+            paid_until,
+            grace_until,
+        }
+    }
+
+    #[test]
+    fn test_billing_decision() {
+        let now = Utc::now();
+        let grace = Duration::days(3);
+        let free = subscription(SubscriptionPlan::Free, None, None);
+        assert_eq!(free.billing_decision(now, true, grace), BillingDecision::NotDue);
+
+        let new = subscription(SubscriptionPlan::VolumeOnly, None, None);
+        assert_eq!(new.billing_decision(now, true, grace), BillingDecision::Charge);
+        assert_eq!(new.billing_decision(now, false, grace), BillingDecision::EnterGrace(now + grace));
+
+        let paid = subscription(SubscriptionPlan::Premium, Some(now + Duration::days(1)), None);
+        assert_eq!(paid.billing_decision(now, false, grace), BillingDecision::NotDue);
+
+        let in_grace = subscription(SubscriptionPlan::Premium, Some(now - Duration::days(1)), Some(now + Duration::days(2)));
+        assert_eq!(in_grace.billing_decision(now, false, grace), BillingDecision::NotDue);
+        assert_eq!(in_grace.billing_decision(now, true, grace), BillingDecision::Charge);
+        assert_eq!(in_grace.billing_decision(now + Duration::days(2), false, grace), BillingDecision::Suspend);
+        assert_eq!(in_grace.billing_decision(now + Duration::days(2), true, grace), BillingDecision::Charge);
+
+        let mut suspended = in_grace.clone();
+        suspended.status = SubscriptionStatus::Suspended.to_string();
+        assert_eq!(suspended.billing_decision(now, true, grace), BillingDecision::NotDue);
+        assert_eq!(suspended.effective_plan(now), SubscriptionPlan::Free);
+
+        // subscribed again, the grace period is over already
+        let mut reactivated = suspended.clone();
+        reactivated.status = SubscriptionStatus::Active.to_string();
+        assert_eq!(reactivated.billing_decision(now + Duration::days(2), false, grace), BillingDecision::Suspend);
+    }
+
+    #[test]
+    fn test_plan_needs_a_settled_payment() {
+        let now = Utc::now();
+        assert_eq!(subscription(SubscriptionPlan::Premium, None, None).effective_plan(now), SubscriptionPlan::Free);
+        let paid = subscription(SubscriptionPlan::Premium, Some(now + Duration::days(1)), None);
+        assert_eq!(paid.effective_plan(now), SubscriptionPlan::Premium);
+        assert_eq!(paid.effective_plan(now + Duration::days(2)), SubscriptionPlan::Free);
+        let mut in_grace = subscription(SubscriptionPlan::Premium, Some(now - Duration::days(1)), Some(now + Duration::days(2)));
+        in_grace.status = SubscriptionStatus::Grace.to_string();
+        assert_eq!(in_grace.effective_plan(now), SubscriptionPlan::Premium);
+        // never paid, the grace period grants nothing
+        in_grace.paid_until = None;
+        assert_eq!(in_grace.effective_plan(now), SubscriptionPlan::Free);
+    }
+
+    #[test]
+    fn test_resubscribe_during_grace() {
+        let now = Utc::now();
+        let grace = Duration::days(3);
+        let mut in_grace = subscription(SubscriptionPlan::VolumeOnly, Some(now - Duration::days(1)), Some(now + Duration::days(2)));
+        in_grace.status = SubscriptionStatus::Grace.to_string();
+
+        // the same plan again is still in the same grace period, suspended once it's over
+        let (status, paid_until, grace_until) = in_grace.resubscribe(SubscriptionPlan::VolumeOnly);
+        assert_eq!(status, SubscriptionStatus::Grace);
+        assert_eq!(grace_until, in_grace.grace_until);
+        let mut again = in_grace.clone();
+        again.paid_until = paid_until;
+        assert_eq!(again.effective_plan(now), SubscriptionPlan::VolumeOnly);
+        assert_eq!(again.billing_decision(now + Duration::days(2), false, grace), BillingDecision::Suspend);
+
+        // a better plan isn't granted before it's paid
+        let (status, paid_until, grace_until) = in_grace.resubscribe(SubscriptionPlan::Premium);
+        let mut upgraded = subscription(SubscriptionPlan::Premium, paid_until, grace_until);
+        upgraded.status = status.to_string();
+        assert_eq!(upgraded.effective_plan(now), SubscriptionPlan::Free);
+        assert_eq!(upgraded.billing_decision(now + Duration::days(2), false, grace), BillingDecision::Suspend);
+    }
+
+    #[test]
+    fn test_plan_allows_kind() {
+        assert!(!SubscriptionPlan::Free.allows_kind(VOLUME_STRATEGY_KIND));
+        assert!(!SubscriptionPlan::MarketMakingOnly.allows_kind(VOLUME_STRATEGY_KIND));
+        assert!(SubscriptionPlan::VolumeOnly.allows_kind(VOLUME_STRATEGY_KIND));
+        assert!(SubscriptionPlan::Premium.allows_kind(VOLUME_STRATEGY_KIND));
+        assert!(!SubscriptionPlan::VolumeOnly.allows_kind(MARKET_MAKING_STRATEGY_KIND));
+        assert!(SubscriptionPlan::MarketMakingOnly.allows_kind(MARKET_MAKING_STRATEGY_KIND));
+        assert!(SubscriptionPlan::Free.allows_kind("sweeper"));
+    }
+}
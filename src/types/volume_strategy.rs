@@ -1,6 +1,10 @@
 use crate::schema::*;
 use crate::types::bot_user::BotUser;
 use crate::types::engine::StrategyId;
+use crate::types::keys::KeypairClonable;
+use crate::config::constants::{BUDGET_TRANCHE_INTERVAL_S, MAX_BUDGET_CATCH_UP_FACTOR, MIN_BUDGET_TRANCHE_SOL};
+use crate::utils::keys::{derive_keypair, generate_seed};
+use chrono::{Duration, NaiveDateTime};
 use diesel::pg::Pg;
 use diesel::prelude::*;
 use diesel::sql_types::*;
@@ -39,6 +43,27 @@ pub struct VolumeStrategyInstance {
     pub agents_buying_in_tranche: i32,
     pub agents_selling_in_tranche: i32,
     pub agents_keep_tokens_lamports: i64,
+    /// Budget mode when set, the tranche size and frequency are derived from it
+    #[serde(default)]
+    pub daily_volume_sol: Option<f64>,
+    /// Days the budget runs for, until stopped when not set
+    #[serde(default)]
+    pub budget_days: Option<i32>,
+    /// Volume of the tranches completed since the start, buys and sells
+    #[serde(default)]
+    pub volume_traded_sol: f64,
+    /// Inventory band, the tokens kept by the tranches are worth at most that
+    #[serde(default)]
+    pub max_inventory_sol: Option<f64>,
+    /// Price band around `reference_price`, in percent
+    #[serde(default)]
+    pub max_price_drift_pct: Option<f64>,
+    /// The pool price at the first tranche since the start
+    #[serde(default)]
+    pub reference_price: Option<f64>,
+    /// The swaps recorded before it are in `volume_traded_sol`, `started_at` when not set
+    #[serde(default)]
+    pub volume_counted_until: Option<NaiveDateTime>,
 }
 #[derive(Debug, Clone, Insertable, Associations)]
 #[diesel(check_for_backend(Pg))]
@@ -60,6 +85,10 @@ pub struct NewVolumeStrategyInstance {
     pub agents_buying_in_tranche: i32,
     pub agents_selling_in_tranche: i32,
     pub agents_keep_tokens_lamports: i64,
+    pub daily_volume_sol: Option<f64>,
+    pub budget_days: Option<i32>,
+    pub max_inventory_sol: Option<f64>,
+    pub max_price_drift_pct: Option<f64>,
 }
 
 
@@ -76,6 +105,203 @@ impl From<&VolumeStrategyInstance> for NewVolumeStrategyInstance {
             agents_buying_in_tranche: new.agents_buying_in_tranche,
             agents_selling_in_tranche: new.agents_selling_in_tranche,
             agents_keep_tokens_lamports: new.agents_keep_tokens_lamports,
+            daily_volume_sol: new.daily_volume_sol,
+            budget_days: new.budget_days,
+            max_inventory_sol: new.max_inventory_sol,
+            max_price_drift_pct: new.max_price_drift_pct,
         }
     }
 }
+
+/// The next tranche of the budget mode.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TranchePlan {
+    pub tranche_size_sol: f64,
+    pub tranche_frequency_hbs: i64,
+}
+
+impl VolumeStrategyInstance {
+    pub fn is_budgeted(&self) -> bool {
+        self.daily_volume_sol.is_some()
+    }
+
+    pub fn budget_end(&self) -> Option<NaiveDateTime> {
+        self.budget_days.map(|days| self.started_at + Duration::days(days as i64))
+    }
+
+    pub fn is_budget_complete(&self, now: NaiveDateTime) -> bool {
+        self.is_budgeted() && self.budget_end().is_some_and(|end| now >= end)
+    }
+
+    // the budget day `now` is in, the end of it and the volume due by then
+    fn budget_day(&self, now: NaiveDateTime, daily_volume_sol: f64) -> (i64, NaiveDateTime, f64) {
+        let day = (now - self.started_at).num_days().max(0);
+        let mut day_end = self.started_at + Duration::days(day + 1);
+        if let Some(end) = self.budget_end() {
+            day_end = day_end.min(end);
+        }
+        (day, day_end, daily_volume_sol * (day + 1) as f64)
+    }
+
+    /// Sizes and spaces the next tranche to trade what's due by the end of the budget day: a shortfall
+    /// of the previous days is caught up, with tranches up to `MAX_BUDGET_CATCH_UP_FACTOR` times the regular size
+    /// coming closer, a surplus slows the tranches down.
+    /// A tranche trades about twice its size, bought then sold. `tranche_length_hbs` is its expected duration.
+    pub fn plan_tranche(&self, now: NaiveDateTime, heartbeat_ms: u64) -> Option<TranchePlan> {
+        let daily_volume_sol = self.daily_volume_sol?;
+        if self.is_budget_complete(now) {
+            return None;
+        }
+        let (_, day_end, volume_due_sol) = self.budget_day(now, daily_volume_sol);
+        let time_left_ms = (day_end - now).num_milliseconds().max(heartbeat_ms as i64);
+        let volume_left_sol = volume_due_sol - self.volume_traded_sol;
+
+        let (tranche_size_sol, gap_ms) = if volume_left_sol <= 0.0 {
+            // ahead of the budget, the next tranche counts for the next day
+            (MIN_BUDGET_TRANCHE_SOL, time_left_ms)
+        } else {
+            let tranches_left = (time_left_ms / (BUDGET_TRANCHE_INTERVAL_S * 1000)).max(1);
+            let tranches_a_day = (24 * 3600 / BUDGET_TRANCHE_INTERVAL_S) as f64;
+            let max_tranche_size_sol = MAX_BUDGET_CATCH_UP_FACTOR * (daily_volume_sol / 2.0 / tranches_a_day);
+            let tranche_size_sol = (volume_left_sol / 2.0 / tranches_left as f64)
+                .min(max_tranche_size_sol)
+                .max(MIN_BUDGET_TRANCHE_SOL);
+            let gap_ms = (time_left_ms as f64 * 2.0 * tranche_size_sol / volume_left_sol).min(time_left_ms as f64);
+            (tranche_size_sol, gap_ms as i64)
+        };
+        let tranche_frequency_hbs = (gap_ms / heartbeat_ms as i64 - self.tranche_length_hbs).max(1);
+        Some(TranchePlan {
+            tranche_size_sol,
+            tranche_frequency_hbs,
+        })
+    }
+
+    /// Volume traded against the volume due by the end of the budget day.
+    pub fn budget_progress(&self, now: NaiveDateTime) -> Option<String> {
+        let daily_volume_sol = self.daily_volume_sol?;
+        if self.is_budget_complete(now) {
+            return Some(format!("complete, {:.3} SOL traded", self.volume_traded_sol));
+        }
+        let (day, _, volume_due_sol) = self.budget_day(now, daily_volume_sol);
+        let days = self.budget_days.map(|days| format!("/{}", days)).unwrap_or_default();
+        Some(format!(
+            "day {}{}, {:.3} of {:.3} SOL traded ({:.0}%)",
+            day + 1,
+            days,
+            self.volume_traded_sol,
+            volume_due_sol,
+            100.0 * self.volume_traded_sol / volume_due_sol,
+        ))
+    }
+}
+
+/// The seed the agent wallets of a strategy are derived from, `next_index` is the next unused path.
+#[derive(Clone, Queryable, Selectable, Identifiable)]
+#[diesel(check_for_backend(Pg))]
+#[diesel(table_name = agent_seeds, primary_key(strategy_instance_id))]
+pub struct AgentSeed {
+    pub strategy_instance_id: StrategyId,
+    #[diesel(deserialize_as = crate::utils::serdealizers::PrivateKeyString)]
+    pub seed: String,
+    pub next_index: i32,
+    pub created: chrono::DateTime<chrono::Utc>,
+}
+
+impl std::fmt::Debug for AgentSeed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AgentSeed")
+            .field("strategy_instance_id", &self.strategy_instance_id)
+            .field("seed", &"<hidden>")
+            .field("next_index", &self.next_index)
+            .finish()
+    }
+}
+
+impl AgentSeed {
+    pub fn derive(&self, index: i32) -> anyhow::Result<KeypairClonable> {
+        Ok(derive_keypair(&self.seed, index as u32)?.into())
+    }
+
+    /// Every wallet derived so far, to find the funds left on them.
+    pub fn derived_wallets(&self) -> anyhow::Result<Vec<(i32, KeypairClonable)>> {
+        (0..self.next_index)
+            .map(|index| Ok((index, self.derive(index)?)))
+            .collect()
+    }
+}
+
+#[derive(Clone, Insertable)]
+#[diesel(table_name = agent_seeds)]
+pub struct NewAgentSeed {
+    pub strategy_instance_id: StrategyId,
+    #[diesel(serialize_as = crate::utils::serdealizers::PrivateKeyString)]
+    pub seed: String,
+}
+
+impl NewAgentSeed {
+    pub fn generate(strategy_instance_id: StrategyId) -> Self {
+        NewAgentSeed {
+            strategy_instance_id,
+            seed: generate_seed(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn budgeted(daily_volume_sol: f64, budget_days: Option<i32>) -> VolumeStrategyInstance {
+        VolumeStrategyInstance {
+            started_at: NaiveDate::from_ymd_opt(2026, 10, 1).unwrap().and_hms_opt(0, 0, 0).unwrap(),
+            tranche_length_hbs: 10,
+            daily_volume_sol: Some(daily_volume_sol),
+            budget_days,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_plan_tranche() {
+        let mut instance = budgeted(96.0, Some(2));
+        let start = instance.started_at;
+        // 96 tranches of 15 minutes a day, each trading 1 SOL
+        let plan = instance.plan_tranche(start, 1000).unwrap();
+        assert_eq!(plan.tranche_size_sol, 0.5);
+        assert_eq!(plan.tranche_frequency_hbs, 890);
+
+        // half a day left and nothing traded yet, the tranches get bigger
+        let plan = instance.plan_tranche(start + Duration::hours(12), 1000).unwrap();
+        assert_eq!(plan.tranche_size_sol, 1.0);
+
+        // the first day traded, the second one starts from scratch
+        instance.volume_traded_sol = 96.0;
+        let plan = instance.plan_tranche(start + Duration::hours(24), 1000).unwrap();
+        assert_eq!(plan.tranche_size_sol, 0.5);
+        assert!(instance.budget_progress(start + Duration::hours(24)).unwrap().starts_with("day 2/2"));
+
+        assert!(instance.plan_tranche(start + Duration::hours(48), 1000).is_none());
+        assert!(instance.is_budget_complete(start + Duration::hours(48)));
+    }
+
+    #[test]
+    fn test_catch_up_is_capped() {
+        let instance = budgeted(96.0, None);
+        // nothing traded with half an hour of the day left, 48 SOL tranches would be due
+        let plan = instance.plan_tranche(instance.started_at + Duration::minutes(23 * 60 + 30), 1000).unwrap();
+        assert_eq!(plan.tranche_size_sol, 2.0);
+        // 24 of them spread over the half hour
+        assert_eq!(plan.tranche_frequency_hbs, 75 - 10);
+    }
+
+    #[test]
+    fn test_small_budget_spaces_tranches_out() {
+        let instance = budgeted(1.0, None);
+        let plan = instance.plan_tranche(instance.started_at, 1000).unwrap();
+        // 10 tranches of the minimum size a day
+        assert_eq!(plan.tranche_size_sol, MIN_BUDGET_TRANCHE_SOL);
+        assert_eq!(plan.tranche_frequency_hbs, 8640 - 10);
+        assert!(!instance.is_budget_complete(instance.started_at + Duration::days(365)));
+    }
+}
@@ -0,0 +1,130 @@
+use crate::config::constants::{BASE_TX_FEE_SOL, NEW_ACCOUNT_THRESHOLD_SOL, TRANSFER_PRIORITY_FEE_SOL};
+use crate::types::actions::Amount;
+use serde_derive::{Deserialize, Serialize};
+use solana_sdk::native_token::sol_to_lamports;
+use solana_sdk::pubkey::Pubkey;
+use std::str::FromStr;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum WithdrawalError {
+    #[error("The destination is not a valid wallet address")]
+    InvalidDestination,
+    #[error("The destination is a wallet of the bot")]
+    OwnWallet,
+    #[error("The amount must be a positive number of SOL")]
+    InvalidAmount,
+    #[error("{0} strategies are using the funds, stop them first")]
+    StrategiesRunning(usize),
+    #[error("A withdrawal is in progress already")]
+    InProgress,
+    #[error("The withdrawal is not found or expired")]
+    NotFound,
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
+
+/// SOL sent from the trading wallet of the user to their own wallet.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WithdrawalRequest {
+    pub destination: String,
+    // everything when `None`
+    pub amount_sol: Option<f64>,
+}
+
+impl WithdrawalRequest {
+    pub fn new(destination: &str, amount_sol: Option<f64>) -> Result<Self, WithdrawalError> {
+        let destination = Pubkey::from_str(destination.trim()).map_err(|_| WithdrawalError::InvalidDestination)?;
+        // PDAs can't sign, the funds would be lost
+        if !destination.is_on_curve() {
+            return Err(WithdrawalError::InvalidDestination);
+        }
+        if amount_sol.is_some_and(|amount| !amount.is_finite() || amount <= 0.0) {
+            return Err(WithdrawalError::InvalidAmount);
+        }
+        Ok(Self { destination: destination.to_string(), amount_sol })
+    }
+
+    /// `<destination> [amount|all]`, as typed in telegram.
+    pub fn parse(text: &str) -> Result<Self, WithdrawalError> {
+        let mut words = text.split_whitespace();
+        let destination = words.next().ok_or(WithdrawalError::InvalidDestination)?;
+        let amount_sol = match words.next() {
+            None => None,
+            Some(amount) if amount.eq_ignore_ascii_case("all") => None,
+            Some(amount) => Some(amount.parse::<f64>().map_err(|_| WithdrawalError::InvalidAmount)?),
+        };
+        if words.next().is_some() {
+            return Err(WithdrawalError::InvalidAmount);
+        }
+        Self::new(destination, amount_sol)
+    }
+
+    pub fn destination(&self) -> Pubkey {
+        Pubkey::from_str(&self.destination).unwrap_or_default()
+    }
+
+    pub fn amount(&self) -> Amount {
+        match self.amount_sol {
+            Some(amount_sol) => Amount::Exact(sol_to_lamports(amount_sol)),
+            None => Amount::MaxButLeaveForTransfer,
+        }
+    }
+}
+
+/// Checks the transfer against the balance of the trading wallet paying for it, a new destination
+/// account must be created rent exempt.
+pub fn check_withdrawal_amount(balance: u64, amount: &Amount, destination_exists: bool) -> Result<(), String> {
+    let fee = BASE_TX_FEE_SOL + TRANSFER_PRIORITY_FEE_SOL;
+    let transferred = match amount {
+        Amount::Exact(amount) => {
+            if amount + fee > balance {
+                return Err("Not enough SOL on the trading wallet".to_string());
+            }
+            *amount
+        }
+        _ => balance.saturating_sub(2 * fee),
+    };
+    if transferred == 0 {
+        return Err("Nothing to withdraw".to_string());
+    }
+    if !destination_exists && transferred < NEW_ACCOUNT_THRESHOLD_SOL {
+        return Err(format!(
+            "A new wallet needs at least {} SOL",
+            crate::utils::decimals::lamports_to_sol(NEW_ACCOUNT_THRESHOLD_SOL)
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::signature::{Keypair, Signer};
+
+    #[test]
+    fn test_parse_withdrawal() {
+        let wallet = Keypair::new().pubkey();
+        let request = WithdrawalRequest::parse(&format!("{} 1.5", wallet)).unwrap();
+        assert_eq!(request.destination(), wallet);
+        assert_eq!(request.amount(), Amount::Exact(1_500_000_000));
+        assert_eq!(WithdrawalRequest::parse(&format!("{} all", wallet)).unwrap().amount(), Amount::MaxButLeaveForTransfer);
+        assert_eq!(WithdrawalRequest::parse(&wallet.to_string()).unwrap().amount_sol, None);
+        assert!(matches!(WithdrawalRequest::parse(&format!("{} -1", wallet)), Err(WithdrawalError::InvalidAmount)));
+        assert!(matches!(WithdrawalRequest::parse("not-a-wallet 1"), Err(WithdrawalError::InvalidDestination)));
+        // a program derived address
+        let (pda, _) = Pubkey::find_program_address(&[b"vault"], &wallet);
+        assert!(matches!(WithdrawalRequest::parse(&pda.to_string()), Err(WithdrawalError::InvalidDestination)));
+    }
+
+    #[test]
+    fn test_check_withdrawal_amount() {
+        let balance = 1_000_000_000;
+        assert!(check_withdrawal_amount(balance, &Amount::Exact(500_000_000), true).is_ok());
+        assert!(check_withdrawal_amount(balance, &Amount::Exact(balance), true).is_err());
+        assert!(check_withdrawal_amount(balance, &Amount::MaxButLeaveForTransfer, false).is_ok());
+        assert!(check_withdrawal_amount(100_000, &Amount::MaxButLeaveForTransfer, false).is_err());
+        assert!(check_withdrawal_amount(balance, &Amount::Exact(1000), true).is_ok());
+        assert!(check_withdrawal_amount(balance, &Amount::Exact(1000), false).is_err());
+    }
+}
@@ -0,0 +1,221 @@
+use crate::config::constants::QUEUE_HIGH_WATER_MARK_FACTOR;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use tokio::sync::Notify;
+use tracing::error;
+
+/// What happens to an item pushed into a full queue.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeliveryPolicy {
+    /// The oldest droppable item is evicted, for data superseded by the next update like price ticks.
+    DropOldest,
+    /// The queue grows past its capacity, for actions and receipts. Reaching the high-water mark raises an alert.
+    NeverDrop,
+}
+
+#[derive(Debug, Default)]
+pub struct QueueStats {
+    len: AtomicUsize,
+    max_len: AtomicUsize,
+    delivered: AtomicU64,
+    dropped: AtomicU64,
+    overflowed: AtomicU64,
+    high_water_alerts: AtomicU64,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct QueueStatsSnapshot {
+    pub len: usize,
+    pub max_len: usize,
+    pub delivered: u64,
+    pub dropped: u64,
+    pub overflowed: u64,
+    pub high_water_alerts: u64,
+}
+
+impl QueueStats {
+    pub fn snapshot(&self) -> QueueStatsSnapshot {
+        QueueStatsSnapshot {
+            len: self.len.load(Ordering::Relaxed),
+            max_len: self.max_len.load(Ordering::Relaxed),
+            delivered: self.delivered.load(Ordering::Relaxed),
+            dropped: self.dropped.load(Ordering::Relaxed),
+            overflowed: self.overflowed.load(Ordering::Relaxed),
+            high_water_alerts: self.high_water_alerts.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Multi-producer multi-consumer queue, every item is popped by exactly one consumer.
+pub struct BoundedQueue<T> {
+    items: Mutex<VecDeque<(T, DeliveryPolicy)>>,
+    notify: Notify,
+    capacity: usize,
+    high_water_mark: usize,
+    name: String,
+    closed: AtomicBool,
+    stats: Arc<QueueStats>,
+}
+
+impl<T> BoundedQueue<T> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            items: Mutex::new(VecDeque::with_capacity(capacity)),
+            notify: Notify::new(),
+            capacity,
+            high_water_mark: capacity.saturating_mul(QUEUE_HIGH_WATER_MARK_FACTOR).max(1),
+            name: "queue".to_string(),
+            closed: AtomicBool::new(false),
+            stats: Arc::new(QueueStats::default()),
+        }
+    }
+
+    /// Named in the alerts.
+    pub fn with_name(mut self, name: &str) -> Self {
+        self.name = name.to_string();
+        self
+    }
+
+    pub fn with_high_water_mark(mut self, high_water_mark: usize) -> Self {
+        self.high_water_mark = high_water_mark.max(1);
+        self
+    }
+
+    pub fn push(&self, item: T, policy: DeliveryPolicy) {
+        let mut items = self.items.lock().unwrap();
+        if items.len() >= self.capacity {
+            match policy {
+                DeliveryPolicy::DropOldest => {
+                    self.stats.dropped.fetch_add(1, Ordering::Relaxed);
+                    match items.iter().position(|(_, p)| *p == DeliveryPolicy::DropOldest) {
+                        Some(oldest) => {
+                            items.remove(oldest);
+                        }
+                        // only never-drop items are queued, the incoming one is the oldest droppable
+                        None => return,
+                    }
+                }
+                DeliveryPolicy::NeverDrop => {
+                    self.stats.overflowed.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+        }
+        items.push_back((item, policy));
+        // alerted once per crossing, the queue has to drain below the mark to alert again
+        if items.len() == self.high_water_mark {
+            self.stats.high_water_alerts.fetch_add(1, Ordering::Relaxed);
+            error!(
+                "{} reached its high-water mark of {} items, capacity {}, its consumer is stuck or too slow",
+                self.name, self.high_water_mark, self.capacity
+            );
+        }
+        self.stats.len.store(items.len(), Ordering::Relaxed);
+        self.stats.max_len.fetch_max(items.len(), Ordering::Relaxed);
+        drop(items);
+        self.notify.notify_one();
+    }
+
+    /// Waits for the next item, `None` once the queue is closed and drained.
+    pub async fn pop(&self) -> Option<T> {
+        loop {
+            let notified = self.notify.notified();
+            {
+                let mut items = self.items.lock().unwrap();
+                if let Some((item, _)) = items.pop_front() {
+                    self.stats.len.store(items.len(), Ordering::Relaxed);
+                    self.stats.delivered.fetch_add(1, Ordering::Relaxed);
+                    // pass the wake-up on in case several items were pushed for several consumers
+                    if !items.is_empty() {
+                        self.notify.notify_one();
+                    }
+                    return Some(item);
+                }
+                if self.closed.load(Ordering::Relaxed) {
+                    return None;
+                }
+            }
+            notified.await;
+        }
+    }
+
+    pub fn close(&self) {
+        self.closed.store(true, Ordering::Relaxed);
+        self.notify.notify_waiters();
+    }
+
+    pub fn stats(&self) -> Arc<QueueStats> {
+        self.stats.clone()
+    }
+}
+
+/// Named queue counters of the running components, for the status screens.
+#[derive(Debug, Clone, Default)]
+pub struct QueueStatsRegistry(Arc<RwLock<BTreeMap<String, Arc<QueueStats>>>>);
+
+impl QueueStatsRegistry {
+    pub fn register(&self, name: &str, stats: Arc<QueueStats>) {
+        self.0.write().unwrap().insert(name.to_string(), stats);
+    }
+
+    pub fn unregister(&self, name: &str) {
+        self.0.write().unwrap().remove(name);
+    }
+
+    pub fn get(&self, name: &str) -> Option<QueueStatsSnapshot> {
+        self.0.read().unwrap().get(name).map(|stats| stats.snapshot())
+    }
+
+    pub fn snapshot(&self) -> BTreeMap<String, QueueStatsSnapshot> {
+        self.0
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(name, stats)| (name.clone(), stats.snapshot()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_drop_oldest_keeps_never_drop_items() {
+        let queue = BoundedQueue::new(2);
+        queue.push(1, DeliveryPolicy::NeverDrop);
+        queue.push(2, DeliveryPolicy::DropOldest);
+        queue.push(3, DeliveryPolicy::DropOldest);
+        queue.push(4, DeliveryPolicy::NeverDrop);
+        assert_eq!(queue.pop().await, Some(1));
+        assert_eq!(queue.pop().await, Some(3));
+        assert_eq!(queue.pop().await, Some(4));
+        let stats = queue.stats().snapshot();
+        assert_eq!(stats.dropped, 1);
+        assert_eq!(stats.overflowed, 1);
+    }
+
+    #[tokio::test]
+    async fn test_high_water_mark_alerts_once_per_crossing() {
+        let queue = BoundedQueue::new(2).with_high_water_mark(3);
+        for item in 0..4 {
+            queue.push(item, DeliveryPolicy::NeverDrop);
+        }
+        assert_eq!(queue.stats().snapshot().high_water_alerts, 1);
+        queue.pop().await;
+        queue.pop().await;
+        queue.push(4, DeliveryPolicy::NeverDrop);
+        assert_eq!(queue.stats().snapshot().high_water_alerts, 2);
+    }
+
+    #[tokio::test]
+    async fn test_closed_queue_is_drained() {
+        let queue = BoundedQueue::new(2);
+        queue.push(1, DeliveryPolicy::NeverDrop);
+        queue.close();
+        assert_eq!(queue.pop().await, Some(1));
+        assert_eq!(queue.pop().await, None);
+    }
+}
@@ -1,15 +1,122 @@
-use std::hash::{DefaultHasher, Hash, Hasher};
+use crate::config::constants::{ENCRYPTED_KEY_PREFIX, MASTER_KEY_ENV, MASTER_KEY_FILE_ENV};
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Nonce};
+use anyhow::{anyhow, bail, Context, Result};
+use base64::{engine::general_purpose, Engine};
+use once_cell::sync::OnceCell;
+use zeroize::Zeroizing;
 
-pub fn hash_i32_to_i32(value: i32) -> i32 {
-    // Create a hasher
-    let mut hasher = DefaultHasher::new();
+const NONCE_LEN: usize = 12;
+// a 32 bytes data key and its 16 bytes tag
+const WRAPPED_KEY_LEN: usize = 48;
 
-    // Hash the i32 value
-    value.hash(&mut hasher);
+static MASTER_KEY: OnceCell<Option<Aes256Gcm>> = OnceCell::new();
 
-    // Get the hash as a u64
-    let hash = hasher.finish();
+/// The master key is read once, from `MASTER_KEY` or else from the file of `MASTER_KEY_FILE`.
+/// Without either the keys are stored in clear.
+pub fn master_key() -> Result<Option<&'static Aes256Gcm>> {
+    MASTER_KEY.get_or_try_init(load_master_key).map(Option::as_ref)
+}
+
+fn load_master_key() -> Result<Option<Aes256Gcm>> {
+    let encoded = match (std::env::var(MASTER_KEY_ENV), std::env::var(MASTER_KEY_FILE_ENV)) {
+        (Ok(key), _) => Zeroizing::new(key),
+        (_, Ok(path)) => Zeroizing::new(
+            std::fs::read_to_string(&path).with_context(|| format!("Failed to read the master key file {}", path))?,
+        ),
+        _ => return Ok(None),
+    };
+    let key = Zeroizing::new(
+        general_purpose::STANDARD
+            .decode(encoded.trim())
+            .context("The master key is not base64")?,
+    );
+    let cipher = Aes256Gcm::new_from_slice(&key).map_err(|_| anyhow!("The master key must be 32 bytes"))?;
+    Ok(Some(cipher))
+}
+
+pub fn is_encrypted(stored: &str) -> bool {
+    stored.starts_with(ENCRYPTED_KEY_PREFIX)
+}
+
+/// Encrypts the private key with the master key, an encrypted one is returned as is.
+pub fn encrypt_private_key(private_key: &str) -> Result<String> {
+    if is_encrypted(private_key) {
+        return Ok(private_key.to_string());
+    }
+    let master_key = master_key()?.ok_or_else(|| anyhow!("No master key, set {} or {}", MASTER_KEY_ENV, MASTER_KEY_FILE_ENV))?;
+    seal(master_key, private_key)
+}
+
+/// The private key in clear, the keys stored before the encryption are returned as is.
+pub fn decrypt_private_key(stored: &str) -> Result<String> {
+    if !is_encrypted(stored) {
+        return Ok(stored.to_string());
+    }
+    let master_key = master_key()?.ok_or_else(|| anyhow!("The private key is encrypted and there is no master key"))?;
+    open(master_key, stored)
+}
+
+// envelope: a data key per secret, wrapped by the master key,
+// prefix + base64(master nonce | wrapped data key | data nonce | ciphertext)
+fn seal(master_key: &Aes256Gcm, plaintext: &str) -> Result<String> {
+    let data_key = Zeroizing::new(rand::random::<[u8; 32]>());
+    let data_cipher = Aes256Gcm::new_from_slice(data_key.as_slice()).map_err(|_| anyhow!("Invalid data key"))?;
+    let data_nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ciphertext = data_cipher
+        .encrypt(&data_nonce, plaintext.as_bytes())
+        .map_err(|_| anyhow!("Failed to encrypt the private key"))?;
+    let master_nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let wrapped_key = master_key
+        .encrypt(&master_nonce, data_key.as_slice())
+        .map_err(|_| anyhow!("Failed to wrap the data key"))?;
+    let envelope = [master_nonce.as_slice(), &wrapped_key, data_nonce.as_slice(), &ciphertext].concat();
+    Ok(format!("{}{}", ENCRYPTED_KEY_PREFIX, general_purpose::STANDARD.encode(envelope)))
+}
+
+fn open(master_key: &Aes256Gcm, stored: &str) -> Result<String> {
+    let envelope = general_purpose::STANDARD
+        .decode(&stored[ENCRYPTED_KEY_PREFIX.len()..])
+        .context("The encrypted private key is not base64")?;
+    if envelope.len() <= 2 * NONCE_LEN + WRAPPED_KEY_LEN {
+        bail!("The encrypted private key is truncated");
+    }
+    let (master_nonce, rest) = envelope.split_at(NONCE_LEN);
+    let (wrapped_key, rest) = rest.split_at(WRAPPED_KEY_LEN);
+    let (data_nonce, ciphertext) = rest.split_at(NONCE_LEN);
+    let data_key = Zeroizing::new(
+        master_key
+            .decrypt(Nonce::from_slice(master_nonce), wrapped_key)
+            .map_err(|_| anyhow!("Failed to unwrap the data key, wrong master key?"))?,
+    );
+    let data_cipher = Aes256Gcm::new_from_slice(&data_key).map_err(|_| anyhow!("Invalid data key"))?;
+    let plaintext = Zeroizing::new(
+        data_cipher
+            .decrypt(Nonce::from_slice(data_nonce), ciphertext)
+            .map_err(|_| anyhow!("Failed to decrypt the private key"))?,
+    );
+    Ok(String::from_utf8(plaintext.to_vec())?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seal_and_open() {
+        let master_key = Aes256Gcm::new(&Aes256Gcm::generate_key(&mut OsRng));
+        let private_key = "4Z7cXSyeFR8wNGMVXUE1TwtKn5D5Vu7FzEv69dokLv7KrQk7h6pu4LF8ZRR9yQBhc7uSM6RTTZtU1fmaxiNrxXrs";
+        let sealed = seal(&master_key, private_key).unwrap();
+        assert!(is_encrypted(&sealed));
+        assert!(!sealed.contains(private_key));
+        // a fresh data key and nonces every time
+        assert_ne!(sealed, seal(&master_key, private_key).unwrap());
+        assert_eq!(open(&master_key, &sealed).unwrap(), private_key);
 
-    // Convert to i32 by using modulus or casting
-    (hash as i32).abs() // Ensures the result is non-negative
+        let other_key = Aes256Gcm::new(&Aes256Gcm::generate_key(&mut OsRng));
+        assert!(open(&other_key, &sealed).is_err());
+        assert!(open(&master_key, &sealed[..sealed.len() - 8]).is_err());
+        // the keys stored in clear are read as is
+        assert_eq!(decrypt_private_key(private_key).unwrap(), private_key);
+    }
 }
@@ -1,5 +1,9 @@
+use anyhow::{anyhow, Result};
 use solana_sdk::bs58;
-use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::derivation_path::DerivationPath;
+use solana_sdk::signature::{keypair_from_seed_and_derivation_path, Keypair, Signer};
+
+const AGENT_SEED_LENGTH: usize = 64;
 
 pub fn clone_keypair(keypair: &Keypair) -> Keypair {
     let bytes = keypair.to_bytes();
@@ -31,6 +35,19 @@ pub fn from_base16_str_private_key(private_key: &str) -> Keypair {
     Keypair::from_bytes(&bytes).unwrap()
 }
 
+/// A random base58 seed to derive agent wallets from.
+pub fn generate_seed() -> String {
+    let seed: Vec<u8> = (0..AGENT_SEED_LENGTH).map(|_| rand::random::<u8>()).collect();
+    bs58::encode(seed).into_string()
+}
+
+/// The wallet at Solana's BIP44 path m/44'/501'/{index}'/0', derived with SLIP-0010 like the wallets do.
+pub fn derive_keypair(seed: &str, index: u32) -> Result<Keypair> {
+    let seed = bs58::decode(seed).into_vec()?;
+    keypair_from_seed_and_derivation_path(&seed, Some(DerivationPath::new_bip44(Some(index), Some(0))))
+        .map_err(|e| anyhow!("Failed to derive wallet {}: {}", index, e))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -64,6 +81,15 @@ mod tests {
         assert_eq!(keypair.to_bytes(), new_keypair.to_bytes());
     }
 
+    #[test]
+    fn test_derive_keypair() {
+        let seed = generate_seed();
+        let first = derive_keypair(&seed, 0).unwrap();
+        assert_eq!(first.to_bytes(), derive_keypair(&seed, 0).unwrap().to_bytes());
+        assert_ne!(first.pubkey(), derive_keypair(&seed, 1).unwrap().pubkey());
+        assert_ne!(first.pubkey(), derive_keypair(&generate_seed(), 0).unwrap().pubkey());
+    }
+
     #[test]
     fn test_to_from_str_private_key() {
         let keypair = Keypair::new();
@@ -1,4 +1,5 @@
 pub mod bloxroute_client;
+pub mod bounded_queue;
 pub mod circular_buffer_w_rev;
 pub mod decimals;
 mod fee_estimator;
@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use std::fmt::Debug;
 use diesel::deserialize::FromSql;
 use diesel::pg::{Pg, PgValue};
@@ -12,6 +13,7 @@ use solana_sdk::signature::Signature;
 use std::io::Write;
 use std::str::FromStr;
 use diesel::sql_types::Jsonb;
+use crate::utils::crypto;
 
 #[derive(Debug, FromSqlRow, AsExpression)]
 #[diesel(check_for_backend(Pg))]
@@ -67,6 +69,103 @@ impl FromSql<sql_types::Text, Pg> for PubkeyString {
     }
 }
 
+/// A private key, kept in clear in memory and encrypted in the database when there is a master key.
+#[derive(FromSqlRow, AsExpression)]
+#[diesel(check_for_backend(Pg))]
+#[diesel(sql_type = sql_types::Text)]
+pub struct PrivateKeyString(pub String);
+
+impl Debug for PrivateKeyString {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<hidden>")
+    }
+}
+
+impl From<String> for PrivateKeyString {
+    fn from(value: String) -> Self {
+        PrivateKeyString(value)
+    }
+}
+
+impl From<PrivateKeyString> for String {
+    fn from(value: PrivateKeyString) -> Self {
+        value.0
+    }
+}
+
+impl ToSql<sql_types::Text, Pg> for PrivateKeyString {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+        let stored = match crypto::master_key()? {
+            Some(_) => crypto::encrypt_private_key(&self.0)?,
+            None => self.0.clone(),
+        };
+        out.write_all(stored.as_bytes())?;
+        Ok(IsNull::No)
+    }
+}
+
+impl FromSql<sql_types::Text, Pg> for PrivateKeyString {
+    fn from_sql(bytes: PgValue<'_>) -> deserialize::Result<Self> {
+        let stored = std::str::from_utf8(bytes.as_bytes())?;
+        Ok(PrivateKeyString(crypto::decrypt_private_key(stored)?))
+    }
+}
+
+/// A private key that isn't stored for every row, e.g. the derived agent wallets.
+#[derive(FromSqlRow, AsExpression)]
+#[diesel(check_for_backend(Pg))]
+#[diesel(sql_type = sql_types::Text)]
+pub struct OptionalPrivateKeyString(pub Option<String>);
+
+impl Debug for OptionalPrivateKeyString {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            Some(_) => write!(f, "Some(<hidden>)"),
+            None => write!(f, "None"),
+        }
+    }
+}
+
+impl From<Option<String>> for OptionalPrivateKeyString {
+    fn from(value: Option<String>) -> Self {
+        OptionalPrivateKeyString(value)
+    }
+}
+
+impl From<OptionalPrivateKeyString> for Option<String> {
+    fn from(value: OptionalPrivateKeyString) -> Self {
+        value.0
+    }
+}
+
+impl ToSql<sql_types::Text, Pg> for OptionalPrivateKeyString {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+        let Some(private_key) = &self.0 else {
+            return Ok(IsNull::Yes);
+        };
+        let stored = match crypto::master_key()? {
+            Some(_) => crypto::encrypt_private_key(private_key)?,
+            None => private_key.clone(),
+        };
+        out.write_all(stored.as_bytes())?;
+        Ok(IsNull::No)
+    }
+}
+
+impl FromSql<sql_types::Nullable<sql_types::Text>, Pg> for OptionalPrivateKeyString {
+    fn from_sql(bytes: PgValue<'_>) -> deserialize::Result<Self> {
+        let stored = std::str::from_utf8(bytes.as_bytes())?;
+        Ok(OptionalPrivateKeyString(Some(crypto::decrypt_private_key(stored)?)))
+    }
+
+    fn from_nullable_sql(bytes: Option<PgValue<'_>>) -> deserialize::Result<Self> {
+        match bytes {
+            Some(bytes) => Self::from_sql(bytes),
+            None => Ok(OptionalPrivateKeyString(None)),
+        }
+    }
+}
+
 impl Into<SignatureString> for Signature {
     fn into(self) -> SignatureString {
         SignatureString(self)
@@ -132,6 +231,13 @@ where
     }
 }
 
+pub fn serialize_pubkey_map<S, V>(map: &BTreeMap<Pubkey, V>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+    V: Serialize,
+{
+    serializer.collect_map(map.iter().map(|(key, value)| (key.to_string(), value)))
+}
 
 #[derive(Debug, Clone, FromSqlRow, AsExpression)]
 #[diesel(sql_type = Jsonb)]
@@ -143,8 +249,8 @@ where
 {
     fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
         let value = serde_json::to_value(&self.0)?;
-        let json_string = serde_json::to_string(&value)?;
-        out.write_all(json_string.as_bytes())?;
+        out.write_all(&[1])?; // Write JSONB version (always 1 for now)
+        serde_json::to_writer(out, &value)?;
         Ok(IsNull::No)
     }
 }
@@ -177,3 +283,16 @@ where
         Ok(IsNull::No)
     }
 }
+
+impl<T> FromSql<Jsonb, Pg> for JsonbWrapper<T>
+where
+    T: serde::de::DeserializeOwned,
+{
+    fn from_sql(bytes: PgValue<'_>) -> deserialize::Result<Self> {
+        let bytes = bytes.as_bytes();
+        if bytes.first() != Some(&1) {
+            return Err("Unsupported JSONB encoding version".into());
+        }
+        Ok(JsonbWrapper(serde_json::from_slice(&bytes[1..])?))
+    }
+}